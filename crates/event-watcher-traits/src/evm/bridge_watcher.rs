@@ -14,13 +14,19 @@
 
 use super::{event_watcher::EventWatcher, *};
 
+use webb_relayer_store::wait_for_items;
+
+/// How long a bridge watcher waits for new commands before polling its
+/// queue anyway, for the commands enqueued by another process.
+const COMMANDS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 /// A Bridge Watcher is a trait for Bridge contracts that not specific for watching events from that contract,
 /// instead it watches for commands sent from other event watchers or services, it helps decouple the event watchers
 /// from the actual action that should be taken depending on the event.
 #[async_trait::async_trait]
 pub trait BridgeWatcher: EventWatcher
 where
-    Self::Store: ProposalStore<Proposal = ()>
+    Self::Store: ProposalStore<Proposal = SubmittedProposal>
         + QueueStore<transaction::eip2718::TypedTransaction, Key = SledQueueKey>
         + QueueStore<BridgeCommand, Key = SledQueueKey>,
 {
@@ -62,18 +68,17 @@ where
                 .await?;
             let bridge_key = BridgeKey::new(my_chain_id);
             let key = SledQueueKey::from_bridge_key(bridge_key);
+            let notify =
+                QueueStore::<BridgeCommand>::queue_notifier(&store, key);
             loop {
                 let result = match store.dequeue_item(key)? {
                     Some(cmd) => {
                         self.handle_cmd(store.clone(), &contract, cmd).await
                     }
                     None => {
-                        // yeild back to the runtime, to allow for other tasks
-                        // to make progress, instead of this busy loop.
-                        tokio::task::yield_now().await;
-                        // the small sleep here just in case the runtime decides to
-                        // run this task again immediately.
-                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        // sleep until a command is enqueued, instead of
+                        // busy polling the queue.
+                        wait_for_items(&notify, COMMANDS_POLL_INTERVAL).await;
                         continue;
                     }
                 };
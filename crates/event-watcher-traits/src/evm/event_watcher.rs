@@ -53,7 +53,7 @@ pub trait EventWatcher {
     /// The Events that this event watcher is interested in.
     type Events: contract::EthLogDecode + Clone;
     /// The Storage backend that will be used to store the required state for this event watcher
-    type Store: HistoryStore + EventHashStore;
+    type Store: HistoryStore + EventHashStore + BatchStore;
     /// Returns a task that should be running in the background
     /// that will watch events
     #[tracing::instrument(
@@ -102,6 +102,18 @@ pub trait EventWatcher {
                 let src_typed_chain_id = TypedChainId::Evm(chain_id);
                 let history_store_key =
                     ResourceId::new(src_target_system, src_typed_chain_id);
+                // the watcher was paused from the admin API, wait until
+                // it gets resumed. Otherwise its cursor can not be reset
+                // until the range of blocks is synced.
+                let syncing =
+                    match ctx.controls.start_watcher_sync(history_store_key) {
+                        Some(syncing) => syncing,
+                        None => {
+                            tokio::time::sleep(contract.polling_interval())
+                                .await;
+                            continue;
+                        }
+                    };
                 let block = store.get_last_block_number(
                     history_store_key,
                     contract.deployed_at().as_u64(),
@@ -125,6 +137,9 @@ pub trait EventWatcher {
                 tracing::trace!("Reading from #{} to #{}", block, dest_block);
                 // Only handle events from found blocks if they are new
                 if dest_block != block {
+                    // the writes of the handlers for this range of blocks are
+                    // committed at once, together with the last block number.
+                    let batch = StoreBatch::new();
                     let events_filter = contract
                         .event_with_filter::<Self::Events>(Default::default())
                         .from_block(block + 1)
@@ -150,6 +165,7 @@ pub trait EventWatcher {
                             );
                             handler.handle_event_with_retry(
                                 store.clone(),
+                                &batch,
                                 &contract,
                                 (event.clone(), log.clone()),
                                 backoff,
@@ -169,16 +185,23 @@ pub trait EventWatcher {
                             }
                         });
                         if mark_as_handled {
-                            store.set_last_block_number(
-                                history_store_key,
-                                log.block_number.as_u64(),
-                            )?;
                             tracing::trace!(
                                 "event handled successfully. at #{}",
                                 log.block_number
                             );
                         } else {
                             tracing::error!("Error while handling event, all handlers failed.");
+                            // keep what was handled in the previous blocks, this block
+                            // will be handled again after the restart.
+                            let handled_block = cmp::max(
+                                log.block_number.as_u64().saturating_sub(1),
+                                block,
+                            );
+                            batch.set_last_block_number(
+                                history_store_key,
+                                handled_block,
+                            );
+                            store.commit_batch(&batch)?;
                             tracing::warn!("Restarting event watcher ...");
                             // this a transient error, so we will retry again.
                             return Err(backoff::Error::transient(
@@ -187,10 +210,11 @@ pub trait EventWatcher {
                         }
                     }
                     // move forward.
-                    store
-                        .set_last_block_number(history_store_key, dest_block)?;
+                    batch.set_last_block_number(history_store_key, dest_block);
+                    store.commit_batch(&batch)?;
                     tracing::trace!("Last saved block number: #{}", dest_block);
                 }
+                drop(syncing);
                 tracing::trace!("Polled from #{} to #{}", block, dest_block);
                 if should_cooldown {
                     let duration = contract.polling_interval();
@@ -255,6 +279,10 @@ pub trait EventHandler {
     /// a method to be called with the event information,
     /// it is up to the handler to decide what to do with the event.
     ///
+    /// The leaves, encrypted outputs and event hashes are written to the `batch`,
+    /// which the watcher commits together with its last block number once every
+    /// event of the range of blocks is handled.
+    ///
     /// If this method returned an error, the handler will be considered as failed and will
     /// be discarded. to have a retry mechanism, use the [`EventHandlerWithRetry::handle_event_with_retry`] method
     /// which does exactly what it says.
@@ -263,6 +291,7 @@ pub trait EventHandler {
     async fn handle_event(
         &self,
         store: Arc<Self::Store>,
+        batch: &StoreBatch,
         contract: &Self::Contract,
         (event, log): (Self::Events, contract::LogMeta),
         metrics: Arc<metric::Metrics>,
@@ -283,23 +312,32 @@ pub trait EventHandlerWithRetry: EventHandler {
     ///
     /// If this method returns Ok(true), the event will be marked as handled.
     ///
+    /// Every attempt writes to its own batch, only the writes of the attempt that
+    /// succeeded are added to the `batch`.
+    ///
     /// **Note**: this method is automatically implemented for all the event handlers.
     async fn handle_event_with_retry(
         &self,
         store: Arc<Self::Store>,
+        batch: &StoreBatch,
         contract: &Self::Contract,
         (event, log): (Self::Events, contract::LogMeta),
         backoff: impl backoff::backoff::Backoff + Send + Sync + 'static,
         metrics: Arc<metric::Metrics>,
     ) -> webb_relayer_utils::Result<()> {
-        let wrapped_task = || {
+        let wrapped_task = || async {
+            let attempt = StoreBatch::new();
             self.handle_event(
                 store.clone(),
+                &attempt,
                 contract,
                 (event.clone(), log.clone()),
                 metrics.clone(),
             )
             .map_err(backoff::Error::transient)
+            .await?;
+            batch.append(&attempt);
+            Ok::<_, backoff::Error<webb_relayer_utils::Error>>(())
         };
         backoff::future::retry(backoff, wrapped_task).await?;
         Ok(())
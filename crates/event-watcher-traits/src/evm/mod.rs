@@ -31,8 +31,8 @@ use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
 use webb_relayer_context::RelayerContext;
 use webb_relayer_store::sled::SledQueueKey;
 use webb_relayer_store::{
-    BridgeCommand, BridgeKey, EventHashStore, HistoryStore, ProposalStore,
-    QueueStore,
+    BatchStore, BridgeCommand, BridgeKey, EventHashStore, HistoryStore,
+    ProposalStore, QueueStore, StoreBatch, SubmittedProposal,
 };
 use webb_relayer_utils::metric;
 
@@ -14,13 +14,19 @@
 
 use super::{event_watcher::SubstrateEventWatcher, *};
 
+use webb_relayer_store::wait_for_items;
+
+/// How long a bridge watcher waits for new commands before polling its
+/// queue anyway, for the commands enqueued by another process.
+const COMMANDS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 // A Substrate Bridge Watcher is a trait for Signature Bridge Pallet that is not specific for watching events from that pallet,
 /// instead it watches for commands sent from other event watchers or services, it helps decouple the event watchers
 /// from the actual action that should be taken depending on the event.
 #[async_trait::async_trait]
 pub trait SubstrateBridgeWatcher: SubstrateEventWatcher
 where
-    Self::Store: ProposalStore<Proposal = ()>
+    Self::Store: ProposalStore<Proposal = SubmittedProposal>
         + QueueStore<BridgeCommand, Key = SledQueueKey>,
 {
     /// A method that is called when a command is received that needs to be
@@ -33,6 +39,14 @@ where
         cmd: BridgeCommand,
     ) -> webb_relayer_utils::Result<()>;
 
+    /// Returns the typed chain id this bridge is known by, which is used to
+    /// pick the bridge commands queue this watcher drains.
+    ///
+    /// Defaults to a standalone Substrate chain.
+    fn typed_chain_id(&self, chain_id: u32) -> TypedChainId {
+        TypedChainId::Substrate(chain_id)
+    }
+
     /// Returns a task that should be running in the background
     /// that will watch events
     #[tracing::instrument(
@@ -51,9 +65,11 @@ where
         let backoff = backoff::backoff::Constant::new(Duration::from_secs(1));
 
         let task = || async {
-            let my_chain_id = webb_proposals::TypedChainId::Substrate(chain_id);
+            let my_chain_id = self.typed_chain_id(chain_id);
             let bridge_key = BridgeKey::new(my_chain_id);
             let key = SledQueueKey::from_bridge_key(bridge_key);
+            let notify =
+                QueueStore::<BridgeCommand>::queue_notifier(&store, key);
             loop {
                 let result = match store.dequeue_item(key)? {
                     Some(cmd) => {
@@ -66,12 +82,9 @@ where
                         .await
                     }
                     None => {
-                        // yeild back to the runtime, to allow for other tasks
-                        // to make progress, instead of this busy loop.
-                        tokio::task::yield_now().await;
-                        // the small sleep here just in case the runtime decides to
-                        // run this task again immediately.
-                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        // sleep until a command is enqueued, instead of
+                        // busy polling the queue.
+                        wait_for_items(&notify, COMMANDS_POLL_INTERVAL).await;
                         continue;
                     }
                 };
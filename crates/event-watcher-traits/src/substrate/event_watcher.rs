@@ -14,6 +14,19 @@
 
 use super::*;
 
+/// Returns the key the event watchers of the given Substrate chain keep the
+/// last block number they synced under.
+pub fn history_store_key(chain_id: u32) -> ResourceId {
+    let target = SubstrateTargetSystem::builder()
+        .pallet_index(chain_id as u8)
+        .tree_id(chain_id)
+        .build();
+    ResourceId::new(
+        TargetSystem::Substrate(target),
+        TypedChainId::Substrate(chain_id),
+    )
+}
+
 /// Represents a Substrate event watcher.
 #[async_trait::async_trait]
 pub trait SubstrateEventWatcher {
@@ -58,9 +71,10 @@ pub trait SubstrateEventWatcher {
         chain_id: u32,
         client: Arc<Self::Client>,
         store: Arc<Self::Store>,
-        metrics: Arc<webb_relayer_utils::metric::Metrics>,
+        ctx: &RelayerContext,
     ) -> webb_relayer_utils::Result<()> {
         let backoff = backoff::backoff::Constant::new(Duration::from_secs(1));
+        let metrics = &ctx.metrics;
 
         let task = || async {
             let mut instant = std::time::Instant::now();
@@ -71,15 +85,18 @@ pub trait SubstrateEventWatcher {
                 // get the latest seen block number.
 
                 // create history store key
-                let src_typed_chain_id = TypedChainId::Substrate(chain_id);
-                let target = SubstrateTargetSystem::builder()
-                    .pallet_index(chain_id as u8)
-                    .tree_id(chain_id)
-                    .build();
-                let src_target_system = TargetSystem::Substrate(target);
-                let history_store_key =
-                    ResourceId::new(src_target_system, src_typed_chain_id);
-
+                let history_store_key = history_store_key(chain_id);
+                // the watchers of this chain were paused from the admin API,
+                // wait until they get resumed. Otherwise their cursor can not
+                // be reset until the range of blocks is synced.
+                let syncing =
+                    match ctx.controls.start_watcher_sync(history_store_key) {
+                        Some(syncing) => syncing,
+                        None => {
+                            tokio::time::sleep(Duration::from_secs(6)).await;
+                            continue;
+                        }
+                    };
                 let block = store
                     .get_last_block_number(history_store_key, 1u64)
                     .map_err(Into::into)
@@ -193,6 +210,7 @@ pub trait SubstrateEventWatcher {
                         .set_last_block_number(history_store_key, dest_block)?;
                     tracing::trace!("Last saved block number: #{}", dest_block);
                 }
+                drop(syncing);
                 tracing::trace!("Polled from #{} to #{}", block, dest_block);
                 if should_cooldown {
                     let duration = Duration::from_secs(6);
@@ -29,9 +29,11 @@ use webb::substrate::{
 use webb_proposals::{
     ResourceId, SubstrateTargetSystem, TargetSystem, TypedChainId,
 };
+use webb_relayer_context::RelayerContext;
 use webb_relayer_store::sled::SledQueueKey;
 use webb_relayer_store::{
     BridgeCommand, BridgeKey, HistoryStore, ProposalStore, QueueStore,
+    SubmittedProposal,
 };
 
 /// Event watching traits
@@ -66,9 +66,8 @@ async fn substrate_event_watcher_should_work() -> webb_relayer_utils::Result<()>
     let watcher = RemarkedEventWatcher::default();
     let config = webb_relayer_config::WebbRelayerConfig::default();
     let ctx = RelayerContext::new(config);
-    let metrics = ctx.metrics.clone();
     watcher
-        .run(node_name, chain_id, client.into(), store, metrics)
+        .run(node_name, chain_id, client.into(), store, &ctx)
         .await?;
     Ok(())
 }
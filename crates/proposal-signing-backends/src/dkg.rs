@@ -1,32 +1,55 @@
+use std::borrow::Cow;
 use std::sync::Arc;
-use futures::StreamExt;
+use webb::evm::ethers::utils;
 use webb::substrate::dkg_runtime::api::runtime_types::webb_proposals::header::{TypedChainId, ResourceId};
 use webb::substrate::dkg_runtime::api::runtime_types::webb_proposals::nonce::Nonce;
 use webb::substrate::subxt::{OnlineClient, PolkadotConfig};
-use webb::substrate::subxt::ext::sp_core::sr25519::Pair as Sr25519Pair;
+use webb::substrate::subxt::dynamic::Value;
 use webb_proposals::{ProposalTrait};
 use webb::substrate::scale::{Encode, Decode};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::{
+    ProposalStatus, ProposalStore, QueueStore, SubmittedProposal,
+};
+use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
 use webb_relayer_utils::metric;
-use webb::substrate::subxt::tx::{PairSigner, TxStatus as TransactionStatus};
 use webb::substrate::dkg_runtime::api as RuntimeApi;
+use std::time::Duration;
 type DkgConfig = PolkadotConfig;
 type DkgClient = OnlineClient<DkgConfig>;
 /// A ProposalSigningBackend that uses the DKG System for Signing Proposals.
-pub struct DkgProposalSigningBackend {
+///
+/// Proposals are not submitted directly, instead the `acknowledge_proposal` extrinsics
+/// are enqueued in the DKG node substrate transaction queue, and every submitted proposal
+/// is recorded in the [`ProposalStore`] until the DKG signs it.
+pub struct DkgProposalSigningBackend<S>
+where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + ProposalStore<Proposal = SubmittedProposal>,
+{
     client: DkgClient,
-    pair: PairSigner<PolkadotConfig, Sr25519Pair>,
+    /// Something that implements the QueueStore and ProposalStore traits.
+    store: Arc<S>,
+    /// The chain id of the DKG node, used as the transaction queue key.
+    dkg_chain_id: u32,
     typed_chain_id: webb_proposals::TypedChainId,
 }
 
-impl DkgProposalSigningBackend {
+impl<S> DkgProposalSigningBackend<S>
+where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + ProposalStore<Proposal = SubmittedProposal>,
+{
     pub fn new(
         client: OnlineClient<PolkadotConfig>,
-        pair: PairSigner<PolkadotConfig, Sr25519Pair>,
+        store: Arc<S>,
+        dkg_chain_id: u32,
         typed_chain_id: webb_proposals::TypedChainId,
     ) -> Self {
         Self {
             client,
-            pair,
+            store,
+            dkg_chain_id,
             typed_chain_id,
         }
     }
@@ -34,7 +57,14 @@ impl DkgProposalSigningBackend {
 
 //AnchorUpdateProposal for evm
 #[async_trait::async_trait]
-impl super::ProposalSigningBackend for DkgProposalSigningBackend {
+impl<S> super::ProposalSigningBackend for DkgProposalSigningBackend<S>
+where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + ProposalStore<Proposal = SubmittedProposal>
+        + Send
+        + Sync
+        + 'static,
+{
     async fn can_handle_proposal(
         &self,
         proposal: &(impl ProposalTrait + Sync + Send + 'static),
@@ -59,7 +89,7 @@ impl super::ProposalSigningBackend for DkgProposalSigningBackend {
         }
         let resource_id_addrs = RuntimeApi::storage()
             .dkg_proposals()
-            .resources(&ResourceId(resource_id.into_bytes()));
+            .resources(ResourceId(resource_id.into_bytes()));
         let maybe_resource_id = self
             .client
             .storage()
@@ -81,84 +111,301 @@ impl super::ProposalSigningBackend for DkgProposalSigningBackend {
         proposal: &(impl ProposalTrait + Sync + Send + 'static),
         metrics: Arc<metric::Metrics>,
     ) -> webb_relayer_utils::Result<()> {
-        let tx_api = RuntimeApi::tx().dkg_proposals();
         let resource_id = proposal.header().resource_id();
         let nonce = proposal.header().nonce();
-        let src_chain_id =
-            webb_proposals_typed_chain_converter(self.typed_chain_id);
         let nonce = Nonce::decode(&mut nonce.encode().as_slice())?;
-        tracing::debug!(
-            nonce = %hex::encode(nonce.encode()),
-            resource_id = %hex::encode(resource_id.into_bytes()),
-            src_chain_id = ?src_chain_id,
-            proposal = %hex::encode(proposal.to_vec()),
-            "sending proposal to DKG runtime"
-        );
-        let xt = tx_api.acknowledge_proposal(
-            nonce,
-            src_chain_id,
-            ResourceId(resource_id.into_bytes()),
-            proposal.to_vec(),
+        let proposal_bytes = proposal.to_vec();
+        let submitted = SubmittedProposal::new(
+            nonce.0,
+            self.typed_chain_id,
+            resource_id,
+            proposal_bytes,
+            webb_relayer_utils::unix_timestamp(),
         );
+        submit_proposal(&self.store, self.dkg_chain_id, submitted, &metrics)
+    }
+}
 
-        // TODO: here we should have a substrate based tx queue in the background
-        // where just send the raw xt bytes and let it handle the work for us.
-        // but this here for now.
-        let signer = &self.pair;
-        let mut progress = self
-            .client
-            .tx()
-            .sign_and_submit_then_watch_default(&xt, signer)
-            .await?;
+/// Sends a proposal to the DKG for signing, by enqueueing its
+/// `acknowledge_proposal` call, and records it in the store.
+///
+/// A proposal that is still waiting to be signed (or already signed) does not
+/// need to be sent again, timed out proposals are resubmitted by the proposals
+/// lifecycle watcher.
+fn submit_proposal<S>(
+    store: &Arc<S>,
+    dkg_chain_id: u32,
+    submitted: SubmittedProposal,
+    metrics: &metric::Metrics,
+) -> webb_relayer_utils::Result<()>
+where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + ProposalStore<Proposal = SubmittedProposal>,
+{
+    let maybe_seen = store.get_proposal(&submitted.data_hash)?;
+    if let Some(seen) = maybe_seen {
+        if seen.status != ProposalStatus::Expired {
+            tracing::debug!(
+                data_hash = %hex::encode(seen.data_hash),
+                status = ?seen.status,
+                "proposal is already submitted to the DKG, skipping",
+            );
+            metrics.proposals_deduplicated.inc();
+            return Ok(());
+        }
+    }
+    if enqueue_acknowledge_proposal(store, dkg_chain_id, &submitted)? {
+        metrics.proposals_sent_to_dkg.inc();
+    } else {
+        metrics.proposals_deduplicated.inc();
+    }
+    store.insert_proposal(submitted)?;
+    Ok(())
+}
+
+/// Watches the proposals submitted to the DKG for signing, and submits again the ones
+/// that were not signed after `timeout` seconds. A proposal submitted more than
+/// `max_resubmissions` times without being signed is marked as expired, and the
+/// signed or expired proposals are removed after `retention` seconds.
+///
+/// Errors are logged and the proposals checked again later, so this never returns.
+#[tracing::instrument(skip(store))]
+pub async fn watch_submitted_proposals<S>(
+    store: Arc<S>,
+    dkg_chain_id: u32,
+    timeout: u64,
+    max_resubmissions: u32,
+    retention: u64,
+) where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + ProposalStore<Proposal = SubmittedProposal>,
+{
+    // check a few times per timeout period, so that timed out proposals
+    // do not wait for too long before they get submitted again.
+    let interval = Duration::from_secs((timeout / 4).clamp(1, 30));
+    let lifecycle = ProposalLifecycle {
+        dkg_chain_id,
+        timeout,
+        max_resubmissions,
+        retention,
+    };
+    loop {
+        tokio::time::sleep(interval).await;
+        let now = webb_relayer_utils::unix_timestamp();
+        if let Err(e) = lifecycle.check(&store, now) {
+            tracing::warn!(
+                error = %e,
+                "failed to check the proposals submitted to the DKG",
+            );
+        }
+    }
+}
+
+/// The lifecycle of the proposals submitted to the DKG, see
+/// [`watch_submitted_proposals`].
+#[derive(Debug, Clone, Copy)]
+struct ProposalLifecycle {
+    dkg_chain_id: u32,
+    timeout: u64,
+    max_resubmissions: u32,
+    retention: u64,
+}
 
-        while let Some(event) = progress.next().await {
-            let e = match event {
-                Ok(e) => e,
-                Err(err) => {
-                    tracing::error!(error = %err, "failed to watch for tx events");
-                    return Err(err.into());
-                }
-            };
-
-            match e {
-                TransactionStatus::Future => {}
-                TransactionStatus::Ready => {
-                    tracing::trace!("tx ready");
-                }
-                TransactionStatus::Broadcast(_) => {}
-                TransactionStatus::InBlock(_) => {
-                    tracing::trace!("tx in block");
-                }
-                TransactionStatus::Retracted(_) => {
-                    tracing::warn!("tx retracted");
-                }
-                TransactionStatus::FinalityTimeout(_) => {
-                    tracing::warn!("tx timeout");
-                }
-                TransactionStatus::Finalized(v) => {
-                    let maybe_success = v.wait_for_success().await;
-                    match maybe_success {
-                        Ok(_events) => {
-                            tracing::debug!("tx finalized");
-                            metrics.proposals_signed.inc();
-                        }
-                        Err(err) => {
-                            tracing::error!(error = %err, "tx failed");
-                            return Err(err.into());
-                        }
-                    }
-                }
-                TransactionStatus::Usurped(_) => {}
-                TransactionStatus::Dropped => {
-                    tracing::warn!("tx dropped");
-                }
-                TransactionStatus::Invalid => {
-                    tracing::warn!("tx invalid");
-                }
+impl ProposalLifecycle {
+    /// Checks every submitted proposal at `now`, a proposal that fails to be
+    /// checked does not stop the others from being checked.
+    fn check<S>(
+        &self,
+        store: &Arc<S>,
+        now: u64,
+    ) -> webb_relayer_utils::Result<()>
+    where
+        S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+            + ProposalStore<Proposal = SubmittedProposal>,
+    {
+        for proposal in store.get_proposals()? {
+            let data_hash = proposal.data_hash;
+            if let Err(e) = self.check_proposal(store, proposal, now) {
+                tracing::warn!(
+                    error = %e,
+                    data_hash = %hex::encode(data_hash),
+                    "failed to check the submitted proposal",
+                );
             }
         }
         Ok(())
     }
+
+    fn check_proposal<S>(
+        &self,
+        store: &Arc<S>,
+        mut proposal: SubmittedProposal,
+        now: u64,
+    ) -> webb_relayer_utils::Result<()>
+    where
+        S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+            + ProposalStore<Proposal = SubmittedProposal>,
+    {
+        if proposal.is_prunable(now, self.retention) {
+            store.remove_proposal(&proposal.data_hash)?;
+            tracing::event!(
+                target: webb_relayer_utils::probe::TARGET,
+                tracing::Level::DEBUG,
+                kind = %webb_relayer_utils::probe::Kind::SigningBackend,
+                backend = "DKG",
+                ty = "ProposalPruned",
+                data_hash = %hex::encode(proposal.data_hash),
+                status = ?proposal.status,
+            );
+            return Ok(());
+        }
+        if !proposal.is_timed_out(now, self.timeout) {
+            return Ok(());
+        }
+        if proposal.attempts > self.max_resubmissions {
+            proposal.mark_expired();
+            tracing::event!(
+                target: webb_relayer_utils::probe::TARGET,
+                tracing::Level::DEBUG,
+                kind = %webb_relayer_utils::probe::Kind::SigningBackend,
+                backend = "DKG",
+                ty = "ProposalExpired",
+                data_hash = %hex::encode(proposal.data_hash),
+                attempts = %proposal.attempts,
+            );
+            tracing::warn!(
+                data_hash = %hex::encode(proposal.data_hash),
+                nonce = %proposal.nonce,
+                attempts = %proposal.attempts,
+                "proposal was not signed by the DKG, giving up",
+            );
+        } else if enqueue_acknowledge_proposal(
+            store,
+            self.dkg_chain_id,
+            &proposal,
+        )? {
+            proposal.mark_resubmitted(now);
+            tracing::event!(
+                target: webb_relayer_utils::probe::TARGET,
+                tracing::Level::DEBUG,
+                kind = %webb_relayer_utils::probe::Kind::SigningBackend,
+                backend = "DKG",
+                ty = "ProposalResubmitted",
+                data_hash = %hex::encode(proposal.data_hash),
+                attempts = %proposal.attempts,
+            );
+        } else {
+            // still waiting in the tx queue, check again later.
+            return Ok(());
+        }
+        store.insert_proposal(proposal)
+    }
+}
+
+/// Enqueues the `acknowledge_proposal` call of the given proposal in the DKG transaction queue.
+///
+/// Returns `false` if the same call is already waiting in the queue.
+fn enqueue_acknowledge_proposal<S>(
+    store: &Arc<S>,
+    dkg_chain_id: u32,
+    submitted: &SubmittedProposal,
+) -> webb_relayer_utils::Result<bool>
+where
+    S: QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>,
+{
+    let resource_id = submitted.target_resource_id();
+    let typed_chain_id = submitted.src_typed_chain_id();
+    let nonce = Nonce(submitted.nonce);
+    let src_chain_id = webb_proposals_typed_chain_converter(typed_chain_id);
+    let data_hash = utils::keccak256(
+        (
+            &nonce,
+            &src_chain_id,
+            ResourceId(resource_id.into_bytes()),
+            &submitted.proposal,
+        )
+            .encode(),
+    );
+    let tx_key = SledQueueKey::from_substrate_with_custom_key(
+        dkg_chain_id,
+        make_acknowledge_proposal_key(data_hash),
+    );
+    // the same unsigned proposal could be produced more than once
+    // (for example, on a re-sync), no need to send it again while
+    // it is still waiting in the queue.
+    let already_queued =
+        QueueStore::<WebbDynamicTxPayload>::has_item(store, tx_key)?;
+    if already_queued {
+        tracing::debug!(
+            data_hash = %hex::encode(data_hash),
+            resource_id = %hex::encode(resource_id.into_bytes()),
+            "proposal is already in the DKG tx queue, skipping",
+        );
+        return Ok(false);
+    }
+    tracing::debug!(
+        nonce = %hex::encode(nonce.encode()),
+        resource_id = %hex::encode(resource_id.into_bytes()),
+        src_chain_id = ?src_chain_id,
+        proposal = %hex::encode(&submitted.proposal),
+        "sending proposal to DKG runtime"
+    );
+    let acknowledge_proposal_tx = WebbDynamicTxPayload {
+        pallet_name: Cow::Borrowed("DKGProposals"),
+        call_name: Cow::Borrowed("acknowledge_proposal"),
+        fields: vec![
+            Value::unnamed_composite(vec![Value::u128(nonce.0 as u128)]),
+            typed_chain_id_to_value(typed_chain_id),
+            Value::unnamed_composite(vec![Value::from_bytes(
+                resource_id.into_bytes(),
+            )]),
+            Value::from_bytes(&submitted.proposal),
+        ],
+    };
+    // Enqueue WebbDynamicTxPayload in dkg-substrate transaction queue
+    QueueStore::<WebbDynamicTxPayload>::enqueue_item(
+        store,
+        tx_key,
+        acknowledge_proposal_tx,
+    )?;
+    tracing::debug!(
+        data_hash = %hex::encode(data_hash),
+        "Enqueued acknowledge-proposal call for execution through dkg-substrate tx queue",
+    );
+    Ok(true)
+}
+
+/// Returns the key used to find the `acknowledge_proposal` call of a proposal in the queue.
+fn make_acknowledge_proposal_key(data_hash: [u8; 32]) -> [u8; 64] {
+    let mut result = [0u8; 64];
+    let prefix = b"dkg_proposals_acknowledge_prop__";
+    result[0..32].copy_from_slice(prefix);
+    result[32..64].copy_from_slice(&data_hash);
+    result
+}
+
+/// Converts the given typed chain id to the dynamic value of the DKG runtime `TypedChainId`.
+fn typed_chain_id_to_value(v: webb_proposals::TypedChainId) -> Value {
+    let (variant, id) = match v {
+        webb_proposals::TypedChainId::None => {
+            return Value::unnamed_variant("None", vec![])
+        }
+        webb_proposals::TypedChainId::Evm(id) => ("Evm", id),
+        webb_proposals::TypedChainId::Substrate(id) => ("Substrate", id),
+        webb_proposals::TypedChainId::PolkadotParachain(id) => {
+            ("PolkadotParachain", id)
+        }
+        webb_proposals::TypedChainId::KusamaParachain(id) => {
+            ("KusamaParachain", id)
+        }
+        webb_proposals::TypedChainId::RococoParachain(id) => {
+            ("RococoParachain", id)
+        }
+        webb_proposals::TypedChainId::Cosmos(id) => ("Cosmos", id),
+        webb_proposals::TypedChainId::Solana(id) => ("Solana", id),
+        webb_proposals::TypedChainId::Ink(id) => ("Ink", id),
+    };
+    Value::unnamed_variant(variant, vec![Value::u128(id as u128)])
 }
 
 fn webb_proposals_typed_chain_converter(
@@ -184,3 +431,179 @@ fn webb_proposals_typed_chain_converter(
         webb_proposals::TypedChainId::Ink(id) => TypedChainId::Ink(id),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use webb_relayer_store::InMemoryStore;
+
+    use super::*;
+
+    const DKG_CHAIN_ID: u32 = 1080;
+
+    const LIFECYCLE: ProposalLifecycle = ProposalLifecycle {
+        dkg_chain_id: DKG_CHAIN_ID,
+        timeout: 60,
+        max_resubmissions: 2,
+        retention: 100,
+    };
+
+    fn submitted(now: u64) -> SubmittedProposal {
+        let target = webb_proposals::ResourceId::new(
+            webb_proposals::TargetSystem::new_contract_address([1u8; 20]),
+            webb_proposals::TypedChainId::Evm(5001),
+        );
+        SubmittedProposal::new(
+            1,
+            webb_proposals::TypedChainId::Evm(5002),
+            target,
+            vec![1, 2, 3],
+            now,
+        )
+    }
+
+    fn queued(store: &Arc<InMemoryStore>) -> usize {
+        QueueStore::<WebbDynamicTxPayload>::queue_items(
+            store,
+            SledQueueKey::from_substrate_chain_id(DKG_CHAIN_ID),
+        )
+        .unwrap()
+        .len()
+    }
+
+    fn dequeue(store: &Arc<InMemoryStore>) {
+        QueueStore::<WebbDynamicTxPayload>::dequeue_item(
+            store,
+            SledQueueKey::from_substrate_chain_id(DKG_CHAIN_ID),
+        )
+        .unwrap()
+        .unwrap();
+    }
+
+    fn stored(
+        store: &Arc<InMemoryStore>,
+        proposal: &SubmittedProposal,
+    ) -> Option<SubmittedProposal> {
+        store.get_proposal(&proposal.data_hash).unwrap()
+    }
+
+    /// The metrics can only be registered once per process.
+    fn metrics() -> &'static metric::Metrics {
+        static METRICS: OnceLock<metric::Metrics> = OnceLock::new();
+        METRICS.get_or_init(metric::Metrics::new)
+    }
+
+    #[test]
+    fn proposals_should_be_sent_to_the_dkg_once() {
+        let store = Arc::new(InMemoryStore::default());
+        let sent = metrics().proposals_sent_to_dkg.get();
+        let deduplicated = metrics().proposals_deduplicated.get();
+        let submit = |now| {
+            submit_proposal(&store, DKG_CHAIN_ID, submitted(now), metrics())
+                .unwrap()
+        };
+
+        submit(100);
+        submit(110);
+        assert_eq!(queued(&store), 1);
+        assert_eq!(metrics().proposals_sent_to_dkg.get() - sent, 1.0);
+        assert_eq!(metrics().proposals_deduplicated.get() - deduplicated, 1.0);
+
+        // an expired proposal is sent again, unless its previous call is
+        // still waiting in the queue.
+        let expire = || {
+            let mut expired = stored(&store, &submitted(100)).unwrap();
+            expired.status = ProposalStatus::Expired;
+            store.insert_proposal(expired).unwrap();
+        };
+        expire();
+        submit(120);
+        assert_eq!(queued(&store), 1);
+        assert_eq!(metrics().proposals_deduplicated.get() - deduplicated, 2.0);
+        dequeue(&store);
+        expire();
+        submit(130);
+        assert_eq!(queued(&store), 1);
+        assert_eq!(metrics().proposals_sent_to_dkg.get() - sent, 2.0);
+        assert_eq!(
+            stored(&store, &submitted(100)).unwrap().status,
+            ProposalStatus::Pending
+        );
+    }
+
+    #[test]
+    fn timed_out_proposals_should_be_submitted_again() {
+        let store = Arc::new(InMemoryStore::default());
+        let proposal = submitted(100);
+        store.insert_proposal(proposal.clone()).unwrap();
+
+        LIFECYCLE.check(&store, 159).unwrap();
+        assert_eq!(queued(&store), 0);
+        assert_eq!(stored(&store, &proposal), Some(proposal.clone()));
+
+        LIFECYCLE.check(&store, 160).unwrap();
+        assert_eq!(queued(&store), 1);
+        let resubmitted = stored(&store, &proposal).unwrap();
+        assert_eq!(resubmitted.attempts, 2);
+        assert_eq!(resubmitted.submitted_at, 160);
+        assert_eq!(resubmitted.status, ProposalStatus::Pending);
+
+        // not sent again while the previous submission is still queued.
+        LIFECYCLE.check(&store, 220).unwrap();
+        assert_eq!(queued(&store), 1);
+        assert_eq!(stored(&store, &proposal).unwrap().attempts, 2);
+
+        dequeue(&store);
+        LIFECYCLE.check(&store, 220).unwrap();
+        assert_eq!(queued(&store), 1);
+        assert_eq!(stored(&store, &proposal).unwrap().attempts, 3);
+    }
+
+    #[test]
+    fn proposals_should_expire_after_the_last_resubmission() {
+        let store = Arc::new(InMemoryStore::default());
+        let mut proposal = submitted(100);
+        proposal.attempts = LIFECYCLE.max_resubmissions + 1;
+        store.insert_proposal(proposal.clone()).unwrap();
+
+        LIFECYCLE.check(&store, 160).unwrap();
+        assert_eq!(queued(&store), 0);
+        let expired = stored(&store, &proposal).unwrap();
+        assert_eq!(expired.status, ProposalStatus::Expired);
+        assert_eq!(expired.attempts, proposal.attempts);
+    }
+
+    #[test]
+    fn finished_proposals_should_be_pruned_after_the_retention() {
+        let store = Arc::new(InMemoryStore::default());
+        let mut signed = submitted(100);
+        signed.mark_signed(150);
+        let mut expired = submitted(120);
+        expired.proposal = vec![4, 5, 6];
+        expired.data_hash = utils::keccak256(&expired.proposal);
+        expired.mark_expired();
+        let pending = {
+            let mut pending = submitted(100);
+            pending.proposal = vec![7, 8, 9];
+            pending.data_hash = utils::keccak256(&pending.proposal);
+            pending
+        };
+        for proposal in [&signed, &expired, &pending] {
+            store.insert_proposal(proposal.clone()).unwrap();
+        }
+
+        // the pending proposal gets submitted again, it is never pruned.
+        LIFECYCLE.check(&store, 219).unwrap();
+        assert!(stored(&store, &signed).is_some());
+        assert!(stored(&store, &expired).is_some());
+
+        LIFECYCLE.check(&store, 220).unwrap();
+        assert!(stored(&store, &signed).is_some());
+        assert!(stored(&store, &expired).is_none());
+
+        LIFECYCLE.check(&store, 250).unwrap();
+        assert!(stored(&store, &signed).is_none());
+        assert!(stored(&store, &pending).is_some());
+    }
+}
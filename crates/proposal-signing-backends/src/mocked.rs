@@ -7,7 +7,9 @@ use webb::evm::ethers::prelude::*;
 use webb::evm::ethers::utils::keccak256;
 use webb_proposals::{ProposalTrait, ResourceId, TypedChainId};
 use webb_relayer_store::sled::SledQueueKey;
-use webb_relayer_store::{BridgeCommand, BridgeKey, QueueStore};
+use webb_relayer_store::{
+    BridgeCommand, BridgeKey, EnqueueOptions, QueueStore,
+};
 use webb_relayer_types::private_key::PrivateKey;
 use webb_relayer_utils::metric;
 
@@ -88,12 +90,15 @@ where
         // Proposal signed metric
         metrics.proposals_signed.inc();
         // now all we have to do is to send the data and the signature to the signature bridge.
-        self.store.enqueue_item(
+        let cmd = BridgeCommand::ExecuteProposalWithSignature {
+            data: proposal_bytes.to_vec(),
+            signature: signature_bytes,
+        };
+        let options = EnqueueOptions::with_priority(cmd.priority());
+        self.store.enqueue_item_with_options(
             SledQueueKey::from_bridge_key(bridge_key),
-            BridgeCommand::ExecuteProposalWithSignature {
-                data: proposal_bytes.to_vec(),
-                signature: signature_bytes,
-            },
+            cmd,
+            options,
         )?;
 
         Ok(())
@@ -14,8 +14,12 @@
 
 use crate::ProposalSigningBackend;
 use std::sync::Arc;
-use webb::evm::contract::protocol_solidity::v_anchor_contract;
+use webb::evm::contract::protocol_solidity::{
+    governed_token_wrapper_contract, signature_bridge_contract,
+    v_anchor_contract,
+};
 use webb::evm::ethers::prelude::EthCall;
+use webb::evm::ethers::types::{Address, U256};
 use webb_proposals::ProposalTrait;
 use webb_relayer_utils::metric;
 
@@ -37,7 +41,7 @@ where
     } else {
         tracing::warn!(
             proposal = ?hex::encode(proposal.to_vec()),
-            "Anchor update proposal is not supported by the signing backend"
+            "Proposal is not supported by the signing backend"
         );
     }
     Ok(())
@@ -90,3 +94,215 @@ pub fn substrate_anchor_update_proposal(
         .src_resource_id(src_resource_id)
         .build()
 }
+
+// create the proposal header of a proposal for Evm target system
+fn evm_proposal_header(
+    target_resource_id: webb_proposals::ResourceId,
+    function_signature_bytes: Vec<u8>,
+    nonce: u32,
+) -> webb_proposals::ProposalHeader {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&function_signature_bytes);
+    let function_signature = webb_proposals::FunctionSignature::from(buf);
+    webb_proposals::ProposalHeader::new(
+        target_resource_id,
+        function_signature,
+        nonce.into(),
+    )
+}
+
+// create token add proposal for Evm target system
+pub fn evm_token_add_proposal(
+    nonce: u32,
+    token: Address,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::TokenAddProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        governed_token_wrapper_contract::AddCall::selector().to_vec(),
+        nonce,
+    );
+    webb_proposals::evm::TokenAddProposal::new(header, token.to_fixed_bytes())
+}
+
+// create token remove proposal for Evm target system
+pub fn evm_token_remove_proposal(
+    nonce: u32,
+    token: Address,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::TokenRemoveProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        governed_token_wrapper_contract::RemoveCall::selector().to_vec(),
+        nonce,
+    );
+    webb_proposals::evm::TokenRemoveProposal::new(
+        header,
+        token.to_fixed_bytes(),
+    )
+}
+
+// create wrapping fee update proposal for Evm target system
+pub fn evm_wrapping_fee_update_proposal(
+    nonce: u32,
+    fee_percentage: u16,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::WrappingFeeUpdateProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        governed_token_wrapper_contract::SetFeeCall::selector().to_vec(),
+        nonce,
+    );
+    webb_proposals::evm::WrappingFeeUpdateProposal::new(header, fee_percentage)
+}
+
+// create max deposit limit proposal for Evm target system
+pub fn evm_max_deposit_limit_proposal(
+    nonce: u32,
+    max_deposit_limit: U256,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::MaxDepositLimitProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        v_anchor_contract::ConfigureMaximumDepositLimitCall::selector()
+            .to_vec(),
+        nonce,
+    );
+    let mut limit = [0u8; 32];
+    max_deposit_limit.to_big_endian(&mut limit);
+    webb_proposals::evm::MaxDepositLimitProposal::new(header, limit)
+}
+
+// create min withdrawal limit proposal for Evm target system
+pub fn evm_min_withdrawal_limit_proposal(
+    nonce: u32,
+    min_withdrawal_limit: U256,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::MinWithdrawalLimitProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        v_anchor_contract::ConfigureMinimalWithdrawalLimitCall::selector()
+            .to_vec(),
+        nonce,
+    );
+    let mut limit = [0u8; 32];
+    min_withdrawal_limit.to_big_endian(&mut limit);
+    webb_proposals::evm::MinWithdrawalLimitProposal::new(header, limit)
+}
+
+// create resource id update proposal for Evm target system
+pub fn evm_resource_id_update_proposal(
+    nonce: u32,
+    new_resource_id: webb_proposals::ResourceId,
+    handler_address: Address,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_proposals::evm::ResourceIdUpdateProposal {
+    let header = evm_proposal_header(
+        target_resource_id,
+        signature_bridge_contract::AdminSetResourceWithSignatureCall::selector(
+        )
+        .to_vec(),
+        nonce,
+    );
+    webb_proposals::evm::ResourceIdUpdateProposal::new(
+        header,
+        new_resource_id,
+        handler_address.to_fixed_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> webb_proposals::ResourceId {
+        webb_proposals::ResourceId::new(
+            webb_proposals::TargetSystem::new_contract_address([7u8; 20]),
+            webb_proposals::TypedChainId::Evm(5001),
+        )
+    }
+
+    fn assert_header(
+        header: webb_proposals::ProposalHeader,
+        selector: [u8; 4],
+        nonce: u32,
+    ) {
+        assert_eq!(header.resource_id(), target());
+        assert_eq!(header.function_signature().to_bytes(), selector);
+        assert_eq!(header.nonce().to_u32(), nonce);
+    }
+
+    #[test]
+    fn token_wrapper_proposals_should_call_the_token_wrapper() {
+        let token = Address::repeat_byte(2);
+        let proposal = evm_token_add_proposal(3, token, target());
+        assert_header(
+            proposal.header(),
+            governed_token_wrapper_contract::AddCall::selector(),
+            3,
+        );
+        assert_eq!(proposal.token_address(), token.to_fixed_bytes());
+
+        let proposal = evm_token_remove_proposal(4, token, target());
+        assert_header(
+            proposal.header(),
+            governed_token_wrapper_contract::RemoveCall::selector(),
+            4,
+        );
+        assert_eq!(proposal.token_address(), token.to_fixed_bytes());
+
+        let proposal = evm_wrapping_fee_update_proposal(5, 250, target());
+        assert_header(
+            proposal.header(),
+            governed_token_wrapper_contract::SetFeeCall::selector(),
+            5,
+        );
+        assert_eq!(proposal.wrapping_fee(), 250);
+    }
+
+    #[test]
+    fn limit_proposals_should_encode_the_limit_big_endian() {
+        let limit = U256::from(0x0102u64);
+        let mut expected = [0u8; 32];
+        expected[30] = 1;
+        expected[31] = 2;
+
+        let proposal = evm_max_deposit_limit_proposal(6, limit, target());
+        assert_header(
+            proposal.header(),
+            v_anchor_contract::ConfigureMaximumDepositLimitCall::selector(),
+            6,
+        );
+        assert_eq!(proposal.max_deposit_limit(), expected);
+
+        let proposal = evm_min_withdrawal_limit_proposal(7, limit, target());
+        assert_header(
+            proposal.header(),
+            v_anchor_contract::ConfigureMinimalWithdrawalLimitCall::selector(),
+            7,
+        );
+        assert_eq!(proposal.min_withdrawal_limit(), expected);
+    }
+
+    #[test]
+    fn resource_id_update_proposal_should_call_the_bridge() {
+        let new_resource_id = webb_proposals::ResourceId::new(
+            webb_proposals::TargetSystem::new_contract_address([9u8; 20]),
+            webb_proposals::TypedChainId::Evm(5002),
+        );
+        let handler = Address::repeat_byte(3);
+        let proposal = evm_resource_id_update_proposal(
+            8,
+            new_resource_id,
+            handler,
+            target(),
+        );
+        assert_header(
+            proposal.header(),
+            signature_bridge_contract::AdminSetResourceWithSignatureCall::selector(),
+            8,
+        );
+        assert_eq!(proposal.new_resource_id(), new_resource_id);
+        assert_eq!(proposal.handler_address(), handler.to_fixed_bytes());
+    }
+}
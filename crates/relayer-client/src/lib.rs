@@ -0,0 +1,444 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Relayer Client 🕸️
+//!
+//! A typed client for the API of the relayer.
+//!
+//! ## Overview
+//!
+//! The [`RelayerClient`] reads the data served by a relayer over HTTP, and
+//! relays transactions through it. A relayed transaction is submitted as a
+//! job, whose updates are then followed over the websocket until the relayer
+//! is done with it.
+//!
+//! ```no_run
+//! # use webb_relayer_client::{Command, Result};
+//! # async fn run(command: Command) -> Result<()> {
+//! use futures::prelude::*;
+//! use webb_relayer_client::RelayerClient;
+//!
+//! let client = RelayerClient::new("http://localhost:9955")?;
+//! let mut updates = client.relay(&command).await?;
+//! while let Some(update) = updates.try_next().await? {
+//!     println!("{:?}", update.status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use futures::prelude::*;
+use futures::stream::BoxStream;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+use webb::evm::ethers::types::{Address, U256};
+use webb_relayer_handler_utils::{
+    FeaturesConfig, IpInformationResponse, ProposalsResponse,
+    RelayJobSubmittedResponse, RelayerMetricResponse,
+};
+
+pub use webb_relayer_handler_utils::{
+    AnchorSubscription, CacheSubscription, CacheUpdate, CachedItemKind,
+    Command, CommandResponse, CommandResponseMessage, CommandType,
+    EncryptedOutputsCacheResponse, EvmCommand, JobSubscription,
+    LeavesCacheResponse, LeavesStatusResponse, LeavesSyncStatus, NetworkStatus,
+    ProposalInfo, ProposalStatus, RelayJob, SubstrateCommand, WebbI256,
+    WithdrawStatus,
+};
+pub use webb_relayer_tx_relay_utils::{
+    ExtData, MixerRelayTransaction, ProofData, VAnchorRelayTransaction,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An enum of all possible errors of the relayer client.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The relayer could not be reached, or its reply could not be read.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// Error on the websocket connection.
+    #[error(transparent)]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    /// JSON Error occurred.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Error while parsing a URL.
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    /// The relayer replied to a request with an error.
+    #[error("{message} ({code}, status: {status})")]
+    Api {
+        /// The HTTP status of the reply.
+        status: u16,
+        /// The machine readable code of the error, i.e. `UNSUPPORTED_CHAIN`.
+        code: String,
+        /// What went wrong, for humans to read.
+        message: String,
+    },
+    /// The relayer replied to a command with an error.
+    #[error("Relayer error: {}", _0)]
+    Relayer(String),
+    /// The connection was closed before the relayer was done with the job.
+    #[error("The connection was closed before the relayer was done")]
+    ConnectionClosed,
+}
+
+/// A type alias for the result of the relayer client.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The body of the errors replied by the relayer.
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    code: String,
+    message: String,
+}
+
+/// The configuration of a relayer, as shared by its `/info` route.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayerInfo {
+    /// The evm chains of the relayer, by chain id.
+    #[serde(default)]
+    pub evm: HashMap<String, EvmChainInfo>,
+    /// The substrate chains of the relayer, by chain id.
+    #[serde(default)]
+    pub substrate: HashMap<String, SubstrateChainInfo>,
+    /// The features enabled on the relayer.
+    pub features: FeaturesConfig,
+}
+
+/// An evm chain the relayer is configured for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmChainInfo {
+    /// The name of the chain.
+    pub name: String,
+    /// Whether the relayer serves the chain.
+    pub enabled: bool,
+    /// The chain id.
+    pub chain_id: u32,
+    /// The account the fees of the relayer are paid to.
+    pub beneficiary: Option<Address>,
+    /// The contracts of the chain the relayer knows about.
+    #[serde(default)]
+    pub contracts: Vec<EvmContractInfo>,
+}
+
+/// A contract the relayer is configured for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmContractInfo {
+    /// The kind of the contract, i.e. `VAnchor`.
+    pub contract: String,
+    /// The address of the contract.
+    pub address: Address,
+    /// The fees of the relayer for the withdrawals from the contract.
+    pub withdraw_config: Option<WithdrawInfo>,
+}
+
+/// The fees of the relayer for the withdrawals from a contract.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawInfo {
+    /// The fee taken from the withdrawn amount, as a fraction of it.
+    pub withdraw_fee_percentage: f64,
+    /// The gas limit of the withdrawals.
+    pub withdraw_gaslimit: U256,
+}
+
+/// A substrate chain the relayer is configured for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubstrateChainInfo {
+    /// The name of the chain.
+    pub name: String,
+    /// Whether the relayer serves the chain.
+    pub enabled: bool,
+    /// The chain id.
+    pub chain_id: u32,
+    /// The SS58 encoded account the fees of the relayer are paid to.
+    pub beneficiary: Option<String>,
+    /// The pallets of the chain the relayer knows about.
+    #[serde(default)]
+    pub pallets: Vec<SubstratePalletInfo>,
+}
+
+/// A pallet the relayer is configured for.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubstratePalletInfo {
+    /// The kind of the pallet, i.e. `VAnchorBn254`.
+    pub pallet: String,
+}
+
+/// An update of a transaction relayed as a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayUpdate {
+    /// The latest response of the relayer to the relayed command, if any.
+    pub status: Option<CommandResponse>,
+    /// The job relaying the transaction, the update is the last one once
+    /// it is finished.
+    pub job: RelayJob,
+}
+
+impl TryFrom<RelayJob> for RelayUpdate {
+    type Error = Error;
+
+    fn try_from(job: RelayJob) -> Result<Self> {
+        let status =
+            job.status.clone().map(serde_json::from_value).transpose()?;
+        Ok(Self { status, job })
+    }
+}
+
+/// A client of the HTTP and websocket API of a relayer.
+#[derive(Debug, Clone)]
+pub struct RelayerClient {
+    http: reqwest::Client,
+    /// The root url of the relayer, i.e. `http://localhost:9955`.
+    url: Url,
+}
+
+impl RelayerClient {
+    /// Creates a client of the relayer at the given root url.
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_http_client(url, reqwest::Client::new())
+    }
+
+    /// Creates a client of the relayer at the given root url, sending its
+    /// HTTP requests with the given client, i.e. to set their timeout.
+    pub fn with_http_client(url: &str, http: reqwest::Client) -> Result<Self> {
+        let mut url = Url::parse(url)?;
+        // the routes are joined to the root url, which would replace its
+        // last segment if it is not ended by a slash.
+        if !url.path().ends_with('/') {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+        Ok(Self { http, url })
+    }
+
+    /// The IP address of this client, as seen by the relayer.
+    pub async fn ip(&self) -> Result<String> {
+        let response: IpInformationResponse = self.get("ip", &()).await?;
+        Ok(response.ip)
+    }
+
+    /// The configuration of the relayer.
+    pub async fn info(&self) -> Result<RelayerInfo> {
+        self.get("info", &()).await
+    }
+
+    /// The leaves of the merkle tree of a VAnchor contract, cached by the
+    /// relayer.
+    pub async fn evm_leaves(
+        &self,
+        chain_id: u32,
+        contract: Address,
+    ) -> Result<LeavesCacheResponse> {
+        self.get(&format!("leaves/evm/{chain_id}/{contract:?}"), &())
+            .await
+    }
+
+    /// The leaves of a merkle tree of a substrate pallet, cached by the
+    /// relayer.
+    pub async fn substrate_leaves(
+        &self,
+        chain_id: u32,
+        tree_id: u32,
+        pallet_id: u8,
+    ) -> Result<LeavesCacheResponse> {
+        let path = format!("leaves/substrate/{chain_id}/{tree_id}/{pallet_id}");
+        self.get(&path, &()).await
+    }
+
+    /// The number of leaves of a VAnchor contract cached by the relayer and
+    /// their sync status, without the leaves themselves.
+    pub async fn evm_leaves_status(
+        &self,
+        chain_id: u32,
+        contract: Address,
+    ) -> Result<LeavesStatusResponse> {
+        let path = format!("leaves/evm/{chain_id}/{contract:?}/status");
+        self.get(&path, &()).await
+    }
+
+    /// The number of leaves of a merkle tree of a substrate pallet cached by
+    /// the relayer, without the leaves themselves.
+    pub async fn substrate_leaves_status(
+        &self,
+        chain_id: u32,
+        tree_id: u32,
+        pallet_id: u8,
+    ) -> Result<LeavesStatusResponse> {
+        let path =
+            format!("leaves/substrate/{chain_id}/{tree_id}/{pallet_id}/status");
+        self.get(&path, &()).await
+    }
+
+    /// The encrypted outputs of a VAnchor contract, cached by the relayer.
+    pub async fn evm_encrypted_outputs(
+        &self,
+        chain_id: u32,
+        contract: Address,
+    ) -> Result<EncryptedOutputsCacheResponse> {
+        let path = format!("encrypted_outputs/evm/{chain_id}/{contract:?}");
+        self.get(&path, &()).await
+    }
+
+    /// The metrics of the relayer, in the prometheus text format.
+    pub async fn metrics(&self) -> Result<String> {
+        let response: RelayerMetricResponse = self.get("metrics", &()).await?;
+        Ok(response.metrics)
+    }
+
+    /// The proposals the relayer submitted to the DKG for signing, only the
+    /// ones with the given status if there is one.
+    pub async fn proposals(
+        &self,
+        status: Option<ProposalStatus>,
+    ) -> Result<Vec<ProposalInfo>> {
+        #[derive(Serialize)]
+        struct ProposalsQuery {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            status: Option<ProposalStatus>,
+        }
+
+        let response: ProposalsResponse =
+            self.get("proposals", &ProposalsQuery { status }).await?;
+        Ok(response.proposals)
+    }
+
+    /// Submits a transaction to relay, it is relayed in the background.
+    ///
+    /// Returns the id of the job relaying it.
+    pub async fn submit(&self, command: &Command) -> Result<String> {
+        let request = self.http.post(self.api_url("send")?).json(command);
+        let response: RelayJobSubmittedResponse =
+            read_reply(request.send().await?).await?;
+        Ok(response.job_id)
+    }
+
+    /// The latest state of a relay job.
+    pub async fn job(&self, id: &str) -> Result<RelayJob> {
+        self.get(&format!("jobs/{id}"), &()).await
+    }
+
+    /// Relays a transaction, and follows it until the relayer is done with
+    /// it.
+    ///
+    /// Returns the updates of the job relaying it, see [`Self::follow_job`].
+    pub async fn relay(
+        &self,
+        command: &Command,
+    ) -> Result<BoxStream<'static, Result<RelayUpdate>>> {
+        let id = self.submit(command).await?;
+        self.follow_job(&id).await
+    }
+
+    /// Follows a relay job over the websocket.
+    ///
+    /// Returns its updates, from its current state until it is finished.
+    /// The stream ends with an error if the relayer could not read the job,
+    /// or if the connection was lost before the job was finished.
+    pub async fn follow_job(
+        &self,
+        id: &str,
+    ) -> Result<BoxStream<'static, Result<RelayUpdate>>> {
+        let mut url = self.url.join("ws")?;
+        // both are special schemes, so the scheme can always be swapped.
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        let _ = url.set_scheme(scheme);
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+        let subscription = Command::Job(JobSubscription { id: id.to_owned() });
+        socket
+            .send(Message::Text(serde_json::to_string(&subscription)?))
+            .await?;
+
+        let updates = stream::unfold(Some(socket), |socket| async move {
+            let mut socket = socket?;
+            loop {
+                let text = match socket.next().await {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Some((Err(Error::ConnectionClosed), None));
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Some((Err(e.into()), None)),
+                };
+                let update = serde_json::from_str(&text)
+                    .map_err(Error::from)
+                    .and_then(|message: CommandResponseMessage| match message
+                        .response
+                    {
+                        CommandResponse::Job(job) => RelayUpdate::try_from(job),
+                        CommandResponse::Error(e) => Err(Error::Relayer(e)),
+                        other => Err(Error::Relayer(format!(
+                            "Unexpected response: {other:?}"
+                        ))),
+                    });
+                return match update {
+                    Ok(update) if update.job.finished => {
+                        let _ = socket.close(None).await;
+                        Some((Ok(update), None))
+                    }
+                    Ok(update) => Some((Ok(update), Some(socket))),
+                    Err(e) => Some((Err(e), None)),
+                };
+            }
+        });
+        Ok(updates.boxed())
+    }
+
+    /// The url of a route of the HTTP API.
+    fn api_url(&self, path: &str) -> Result<Url> {
+        Ok(self.url.join("api/v1/")?.join(path)?)
+    }
+
+    /// Sends a `GET` request to a route of the HTTP API.
+    async fn get<T, Q>(&self, path: &str, query: &Q) -> Result<T>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        let request = self.http.get(self.api_url(path)?).query(query);
+        read_reply(request.send().await?).await
+    }
+}
+
+/// Reads the value of a reply, or the error the relayer replied with.
+async fn read_reply<T: DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json().await?);
+    }
+    let body = response.text().await?;
+    let (code, message) = match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(error) => (error.code, error.message),
+        Err(_) => (String::from("UNKNOWN"), body),
+    };
+    Err(Error::Api {
+        status: status.as_u16(),
+        code,
+        message,
+    })
+}
@@ -0,0 +1,185 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::TryStreamExt;
+use serde_json::json;
+use webb::evm::ethers::prelude::I256;
+use webb::evm::ethers::types::{Bytes, H256};
+use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+use webb_relayer_config::evm::EvmChainConfig;
+use webb_relayer_config::WebbRelayerConfig;
+use webb_relayer_context::RelayerContext;
+use webb_relayer_store::{
+    AnyStore, EncryptedOutputCacheStore, InMemoryStore, LeafCacheStore,
+};
+
+use super::*;
+
+const CONTRACT: &str = "0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df";
+
+fn config() -> WebbRelayerConfig {
+    let evm: EvmChainConfig = serde_json::from_value(json!({
+        "name": "hermes",
+        "enabled": true,
+        "http-endpoint": "http://localhost:5001",
+        "ws-endpoint": "ws://localhost:5001",
+        "block-confirmations": 1,
+        "chain-id": 5001,
+        "beneficiary": CONTRACT,
+        "contracts": [{
+            "contract": "VAnchor",
+            "address": CONTRACT,
+            "deployed-at": 1,
+            "events-watcher": { "polling-interval": 1000 },
+            "withdraw-config": {
+                "withdraw-fee-percentage": 0.05,
+                "withdraw-gaslimit": "0x5B8D80",
+            },
+        }],
+    }))
+    .unwrap();
+    let mut config = WebbRelayerConfig {
+        port: 0,
+        ..Default::default()
+    };
+    config.evm.insert(evm.chain_id.to_string(), evm);
+    config
+}
+
+fn store(contract: Address) -> AnyStore {
+    let store = AnyStore::from(InMemoryStore::default());
+    let key = ResourceId::new(
+        TargetSystem::new_contract_address(contract.to_fixed_bytes()),
+        TypedChainId::Evm(5001),
+    );
+    store
+        .insert_leaves(key, &[(0, vec![1; 32]), (1, vec![2; 32])])
+        .unwrap();
+    store.insert_last_deposit_block_number(key, 42).unwrap();
+    store
+        .insert_encrypted_output(key, &[(0, vec![3; 8])])
+        .unwrap();
+    store
+}
+
+fn vanchor_command(chain_id: u64, contract: Address) -> Command {
+    let element = H256::repeat_byte(1);
+    Command::Evm(EvmCommand::VAnchor(VAnchorRelayTransaction {
+        chain_id,
+        id: contract,
+        proof_data: ProofData {
+            proof: Bytes::from(vec![0; 32]),
+            public_amount: element,
+            roots: Bytes::from(vec![0; 64]),
+            input_nullifiers: vec![element],
+            output_commitments: vec![element, element],
+            ext_data_hash: element,
+        },
+        ext_data: ExtData {
+            recipient: Address::repeat_byte(2),
+            relayer: Address::repeat_byte(3),
+            ext_amount: WebbI256(I256::from(-10)),
+            fee: U256::from(1),
+            refund: U256::zero(),
+            token: Address::zero(),
+            encrypted_output1: Bytes::from(vec![4; 8]),
+            encrypted_output2: Bytes::from(vec![5; 8]),
+        },
+    }))
+}
+
+// the metrics of a context can only be registered once per process, so
+// everything is checked against the same relayer.
+#[tokio::test]
+async fn client_talks_to_the_relayer() {
+    let contract: Address = CONTRACT.parse().unwrap();
+    let ctx = RelayerContext::new(config());
+    let (addrs, server) =
+        webb_relayer::service::build_web_services(ctx, store(contract))
+            .unwrap();
+    tokio::spawn(server);
+    let url = format!("http://127.0.0.1:{}", addrs[0].port());
+    let client = RelayerClient::new(&url).unwrap();
+
+    assert_eq!(client.ip().await.unwrap(), "127.0.0.1");
+
+    let info = client.info().await.unwrap();
+    let chain = &info.evm["5001"];
+    assert_eq!(chain.chain_id, 5001);
+    assert_eq!(chain.beneficiary, Some(contract));
+    assert_eq!(chain.contracts[0].contract, "VAnchor");
+    assert_eq!(chain.contracts[0].address, contract);
+    let withdraw = chain.contracts[0].withdraw_config.as_ref().unwrap();
+    assert_eq!(withdraw.withdraw_fee_percentage, 0.05);
+    assert_eq!(withdraw.withdraw_gaslimit, U256::from(0x5B8D80));
+    assert!(info.features.private_tx_relay);
+
+    let leaves = client.evm_leaves(5001, contract).await.unwrap();
+    assert_eq!(leaves.leaves, vec![vec![1; 32], vec![2; 32]]);
+    assert_eq!(leaves.last_queried_block, 42);
+    let status = client.evm_leaves_status(5001, contract).await.unwrap();
+    assert_eq!(status.leaves_count, 2);
+    assert_eq!(status.last_queried_block, 42);
+    let outputs = client.evm_encrypted_outputs(5001, contract).await.unwrap();
+    assert_eq!(outputs.encrypted_outputs, vec![vec![3; 8]]);
+    assert_eq!(outputs.last_queried_block, 42);
+    match client.evm_leaves(1, contract).await {
+        Err(Error::Api { status, code, .. }) => {
+            assert_eq!(status, 400);
+            assert_eq!(code, "UNSUPPORTED_CHAIN");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+
+    assert!(!client.metrics().await.unwrap().is_empty());
+    assert!(client.proposals(None).await.unwrap().is_empty());
+
+    // the relayer is not configured for the chain, so the job fails
+    // without ever reaching a node.
+    let updates: Vec<RelayUpdate> = client
+        .relay(&vanchor_command(1, contract))
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let last = updates.last().unwrap();
+    assert!(last.job.finished);
+    assert!(updates.iter().any(|update| update.status
+        == Some(CommandResponse::Network(NetworkStatus::UnsupportedChain))));
+    assert!(client.job(&last.job.id).await.unwrap().finished);
+}
+
+#[test]
+fn routes_are_joined_below_the_root_url() {
+    for root in [
+        "http://localhost:9955/relayer",
+        "http://localhost:9955/relayer/",
+    ] {
+        let client = RelayerClient::new(root).unwrap();
+        assert_eq!(
+            client.api_url("info").unwrap().as_str(),
+            "http://localhost:9955/relayer/api/v1/info"
+        );
+        assert_eq!(
+            client.url.join("ws").unwrap().as_str(),
+            "http://localhost:9955/relayer/ws"
+        );
+    }
+    let client = RelayerClient::new("http://localhost:9955").unwrap();
+    assert_eq!(
+        client.api_url("ip").unwrap().as_str(),
+        "http://localhost:9955/api/v1/ip"
+    );
+}
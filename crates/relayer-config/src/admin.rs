@@ -0,0 +1,25 @@
+use super::*;
+
+/// AdminConfig is the configuration of the admin API of the relayer.
+///
+/// The admin API is only served when a token is configured, usually through
+/// the `WEBB_ADMIN_TOKEN` environment variable.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdminConfig {
+    /// The bearer token that every admin request must carry in its
+    /// `Authorization` header.
+    #[serde(default, skip_serializing)]
+    pub token: Option<String>,
+    /// The port the admin API listens on, if not set the admin API is served
+    /// on the main port under `/api/v1/admin`.
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+impl AdminConfig {
+    /// Returns `true` if the admin API should be served.
+    pub fn enabled(&self) -> bool {
+        matches!(self.token.as_deref(), Some(t) if !t.is_empty())
+    }
+}
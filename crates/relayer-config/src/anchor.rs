@@ -7,15 +7,16 @@ use crate::{
 use super::*;
 
 /// Linked anchor config for Evm based target system
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct RawResourceId {
     /// Raw resource Id
+    #[schemars(with = "String")]
     pub resource_id: H256,
 }
 
 /// LinkedAnchorConfig is configuration for the linked anchors. Linked anchor can be added in multiple ways
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum LinkedAnchorConfig {
     /// Linked anchor configuration for raw resource Id   
@@ -74,14 +75,17 @@ impl LinkedAnchorConfig {
 }
 
 /// VAnchorWithdrawConfig is the configuration for the VAnchor Withdraw.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct VAnchorWithdrawConfig {
     /// The fee percentage that your account will receive when you relay a transaction
     /// over this chain.
     #[serde(rename(serialize = "withdrawFeePercentage"))]
+    #[schemars(rename = "withdrawFeePercentage")]
     pub withdraw_fee_percentage: f64,
     /// A hex value of the gaslimit when doing a withdraw relay transaction on this chain.
     #[serde(rename(serialize = "withdrawGaslimit"))]
+    #[schemars(rename = "withdrawGaslimit")]
+    #[schemars(with = "String")]
     pub withdraw_gaslimit: U256,
 }
@@ -2,7 +2,7 @@ use super::*;
 use webb_relayer_types::rpc_url::RpcUrl;
 
 /// Block poller configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct BlockPollerConfig {
     /// The starting block to listen at.
@@ -10,6 +10,7 @@ pub struct BlockPollerConfig {
     pub start_block: Option<u64>,
     /// Polling interval in milliseconds
     #[serde(rename(serialize = "pollingInterval"))]
+    #[schemars(rename = "pollingInterval")]
     pub polling_interval: u64,
     /// The maximum blocks per step.
     ///
@@ -23,5 +24,6 @@ pub struct BlockPollerConfig {
     pub print_progress_interval: u64,
     /// Light client RPC url
     #[serde(default)]
+    #[schemars(with = "Option<String>")]
     pub light_client_rpc_url: Option<RpcUrl>,
 }
@@ -1,3 +1,4 @@
+use crate::store::{StoreBackend, StoreConfig};
 use crate::WebbRelayerConfig;
 use anyhow::Context;
 use directories_next::ProjectDirs;
@@ -33,6 +34,82 @@ pub struct Opts {
     /// and will be deleted when the process exits.
     #[structopt(long)]
     pub tmp: bool,
+    /// The store backend to use (sled, sqlite or memory), overrides the
+    /// backend set in the configuration.
+    #[structopt(long, value_name = "BACKEND")]
+    pub store: Option<StoreBackend>,
+    /// Run a maintenance command instead of starting the relayer.
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Maintenance commands of the relayer.
+#[derive(StructOpt)]
+pub enum Command {
+    /// Export or import snapshots of the leaves cache.
+    Snapshot(SnapshotCommand),
+    /// Manage the transactions and bridge commands that failed and were
+    /// taken out of their queues.
+    DeadLetters(DeadLetterCommand),
+}
+
+/// Dead letters commands.
+///
+/// Queues are named like `evm_tx_<chain id>`, `substrate_tx_<chain id>` or
+/// `bridge_cmd_<typed chain id>`:
+///
+/// $ webb-relayer -c <CONFIG_FILE_PATH> dead-letters list evm_tx_5001
+///
+/// $ webb-relayer -c <CONFIG_FILE_PATH> dead-letters retry evm_tx_5001 <ID>
+#[derive(StructOpt)]
+pub enum DeadLetterCommand {
+    /// List the dead letters of a queue, as JSON.
+    List {
+        /// The name of the queue.
+        queue: String,
+    },
+    /// Send a dead letter back to its queue, it is kept until processed successfully.
+    Retry {
+        /// The name of the queue.
+        queue: String,
+        /// The hex encoded id of the dead letter.
+        id: String,
+    },
+    /// Discard a dead letter for good.
+    Discard {
+        /// The name of the queue.
+        queue: String,
+        /// The hex encoded id of the dead letter.
+        id: String,
+    },
+}
+
+/// Leaves cache snapshot commands.
+///
+/// Export the cached data of some anchors from a synced relayer:
+///
+/// $ webb-relayer -c <CONFIG_FILE_PATH> snapshot export --resource-id <RESOURCE_ID> -o snapshot.json
+///
+/// Then import it into the empty store of a new relayer:
+///
+/// $ webb-relayer -c <CONFIG_FILE_PATH> snapshot import -i snapshot.json
+#[derive(StructOpt)]
+pub enum SnapshotCommand {
+    /// Export the leaves, encrypted outputs and last synced block of the given anchors.
+    Export {
+        /// The hex encoded resource ids of the anchors to export.
+        #[structopt(long = "resource-id", required = true)]
+        resource_ids: Vec<String>,
+        /// The snapshot file to write.
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Import a snapshot into an empty store, after verifying it against the contracts.
+    Import {
+        /// The snapshot file to read.
+        #[structopt(short, long, parse(from_os_str))]
+        input: PathBuf,
+    },
 }
 
 /// Loads the configuration from the given directory.
@@ -114,31 +191,67 @@ pub fn setup_logger(verbosity: i32, filter: &str) -> anyhow::Result<()> {
 
 /// Creates a database store for the relayer based on the configuration passed in.
 ///
-/// Returns `Ok(store::sled::SledStore)` on success, or `Err(anyhow::Error)` on failure.
+/// Returns `Ok(store::AnyStore)` on success, or `Err(anyhow::Error)` on failure.
 ///
 /// # Arguments
 ///
 /// * `opts` - The configuration options for the database store.
+/// * `config` - The store configuration, which selects the database backend.
 pub async fn create_store(
     opts: &Opts,
-) -> anyhow::Result<webb_relayer_store::SledStore> {
+    config: &StoreConfig,
+) -> anyhow::Result<webb_relayer_store::AnyStore> {
+    let backend = opts.store.unwrap_or(config.backend);
+    if backend == StoreBackend::Memory {
+        tracing::debug!("Using in-memory store");
+        return Ok(webb_relayer_store::InMemoryStore::default().into());
+    }
     // check if we shall use the temp dir.
     if opts.tmp {
         tracing::debug!("Using temp dir for store");
-        let store = webb_relayer_store::SledStore::temporary()?;
+        let store = match backend {
+            StoreBackend::Sled => {
+                webb_relayer_store::SledStore::temporary()?.into()
+            }
+            StoreBackend::Sqlite => {
+                webb_relayer_store::SqliteStore::temporary()?.into()
+            }
+            StoreBackend::Memory => unreachable!("handled above"),
+        };
         return Ok(store);
     }
-    let dirs = ProjectDirs::from(PACKAGE_ID[0], PACKAGE_ID[1], PACKAGE_ID[2])
-        .context("failed to get config")?;
-    let p = match opts.config_dir.as_ref() {
-        Some(p) => p.to_path_buf(),
-        None => dirs.data_local_dir().to_path_buf(),
+    let db_path = match config.path.as_ref() {
+        Some(path) => path.clone(),
+        None => {
+            let dirs =
+                ProjectDirs::from(PACKAGE_ID[0], PACKAGE_ID[1], PACKAGE_ID[2])
+                    .context("failed to get config")?;
+            let p = match opts.config_dir.as_ref() {
+                Some(p) => p.to_path_buf(),
+                None => dirs.data_local_dir().to_path_buf(),
+            };
+            let base = match opts.config_dir.as_ref().zip(p.parent()) {
+                Some((_, parent)) => parent.to_path_buf(),
+                None => p,
+            };
+            match backend {
+                StoreBackend::Sqlite => base.join("store.db"),
+                _ => base.join("store"),
+            }
+        }
     };
-    let db_path = match opts.config_dir.as_ref().zip(p.parent()) {
-        Some((_, parent)) => parent.join("store"),
-        None => p.join("store"),
+    tracing::debug!(backend = ?backend, path = ?db_path, "Opening store");
+    let store = match backend {
+        StoreBackend::Sled => {
+            webb_relayer_store::SledStore::open(db_path)?.into()
+        }
+        StoreBackend::Sqlite => {
+            if let Some(parent) = db_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            webb_relayer_store::SqliteStore::open(db_path)?.into()
+        }
+        StoreBackend::Memory => unreachable!("handled above"),
     };
-
-    let store = webb_relayer_store::SledStore::open(db_path)?;
     Ok(store)
 }
@@ -10,7 +10,7 @@ use webb_relayer_types::{
 };
 
 /// CosmwasmConfig is the configuration for the Cosmwasm based networks.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmConfig {
     /// String that groups configuration for this chain on a human-readable name.
@@ -20,18 +20,23 @@ pub struct CosmwasmConfig {
     pub enabled: bool,
     /// Http(s) Endpoint for quick Req/Res
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub http_endpoint: RpcUrl,
     /// Websocket Endpoint for long living connections
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub ws_endpoint: RpcUrl,
     /// Block Explorer for this chain.
     ///
     /// Optional, and only used for printing a clickable links
     /// for transactions and contracts.
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub explorer: Option<url::Url>,
     /// chain specific id (output of chainId opcode on Cosmwasm networks)
     #[serde(rename(serialize = "chainId"))]
+    #[schemars(rename = "chainId")]
+    #[schemars(with = "u32")]
     pub chain_id: CWChainId,
     /// The Mnemonic of this account on this network
     /// the format is more dynamic here:
@@ -47,32 +52,37 @@ pub struct CosmwasmConfig {
     /// 3. if it doesn't contains special characters and has 12 or 24 words in it
     ///   then we should process it as a mnemonic string: 'word two three four ...'
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub mnemonic: Mnemonic,
     /// Optionally, a user can specify an account to receive rewards for relaying
+    #[schemars(with = "Option<String>")]
     pub beneficiary: Option<Addr>,
     /// Supported contracts over this chain.
     #[serde(default)]
     pub contracts: Vec<CosmwasmContract>,
     /// TxQueue configuration
     #[serde(skip_serializing, default)]
+    #[schemars(skip)]
     pub tx_queue: TxQueueConfig,
 }
 
 /// CosmwasmVAnchorWithdrawConfig is the configuration for the Cosmwasm VAnchor Withdraw.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmVAnchorWithdrawConfig {
     /// The fee percentage that your account will receive when you relay a transaction
     /// over this chain.
     #[serde(rename(serialize = "withdrawFeePercentage"))]
+    #[schemars(rename = "withdrawFeePercentage")]
     pub withdraw_fee_percentage: u8,
     /// A stringified value of the limit(Uint128) when doing a withdraw relay transaction on this chain.
     #[serde(rename(serialize = "withdrawLimit"))]
+    #[schemars(rename = "withdrawLimit")]
     pub withdraw_limit: String,
 }
 
 /// CosmwasmLinkedVAnchorConfig is the configuration for the cosmwasm linked Vanchor.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmLinkedVAnchorConfig {
     /// The Chain name where this anchor belongs to.
@@ -82,7 +92,7 @@ pub struct CosmwasmLinkedVAnchorConfig {
 }
 
 /// Enumerates the supported cosmwasm-contract configurations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "contract")]
 pub enum CosmwasmContract {
     /// The VAnchor contract configuration.
@@ -92,18 +102,19 @@ pub enum CosmwasmContract {
 }
 
 /// CosmwasmCommonContractConfig represents the cosmwasm common configuration for contracts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmCommonContractConfig {
     /// The address of this contract on this chain.
     pub address: String,
     /// the block number where this contract got deployed at.
     #[serde(rename(serialize = "deployedAt"))]
+    #[schemars(rename = "deployedAt")]
     pub deployed_at: u64,
 }
 
 /// CosmwasmVAnchorContractConfig represents the configuration for the Cosmwasm VAnchor contract.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmVAnchorContractConfig {
     /// Common contract configuration.
@@ -111,20 +122,24 @@ pub struct CosmwasmVAnchorContractConfig {
     pub common: CosmwasmCommonContractConfig,
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
     /// Anchor withdraw configuration.
     #[serde(rename(serialize = "withdrawConfig"))]
+    #[schemars(rename = "withdrawConfig")]
     pub withdraw_config: Option<CosmwasmVAnchorWithdrawConfig>,
     /// The type of the optional signing backend used for signing proposals. It can be None for pure Tx relayers
     #[serde(rename(serialize = "proposalSigningBackend"))]
+    #[schemars(rename = "proposalSigningBackend")]
     pub proposal_signing_backend: Option<ProposalSigningBackendConfig>,
     /// A List of linked Anchor Contracts (on other chains) to this contract.
     #[serde(rename(serialize = "linkedAnchors"), default)]
+    #[schemars(rename = "linkedAnchors")]
     pub linked_anchors: Option<Vec<CosmwasmLinkedVAnchorConfig>>,
 }
 
 /// Cosmwasm Signature Bridge contract configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CosmwasmSignatureBridgeContractConfig {
     /// Common contract configuration.
@@ -132,5 +147,6 @@ pub struct CosmwasmSignatureBridgeContractConfig {
     pub common: CosmwasmCommonContractConfig,
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
 }
@@ -1,7 +1,7 @@
 use super::*;
 
 /// EventsWatchConfig is the configuration for the events watch.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct EventsWatcherConfig {
     /// A flag for enabling API endpoints for querying data from the relayer.
@@ -12,12 +12,21 @@ pub struct EventsWatcherConfig {
     pub enabled: bool,
     /// Polling interval in milliseconds
     #[serde(rename(serialize = "pollingInterval"))]
+    #[schemars(rename = "pollingInterval")]
     pub polling_interval: u64,
     /// The maximum number of events to fetch in one request.
     #[serde(skip_serializing, default = "max_blocks_per_step_default")]
+    #[schemars(skip)]
     pub max_blocks_per_step: u64,
     /// print sync progress frequency in milliseconds
     /// if it is zero, means no progress will be printed.
     #[serde(skip_serializing, default = "print_progress_interval_default")]
+    #[schemars(skip)]
     pub print_progress_interval: u64,
+    /// How often, in milliseconds, the cached leaves are checked against the
+    /// contract, and the missing ones fetched again.
+    /// if it is zero, the leaves are never checked.
+    #[serde(skip_serializing, default = "leaves_check_interval_default")]
+    #[schemars(skip)]
+    pub leaves_check_interval: u64,
 }
@@ -11,7 +11,7 @@ use crate::{
 use super::*;
 
 /// EvmChainConfig is the configuration for the EVM based networks.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct EvmChainConfig {
     /// String that groups configuration for this chain on a human-readable name.
@@ -21,21 +21,26 @@ pub struct EvmChainConfig {
     pub enabled: bool,
     /// Http(s) Endpoint for quick Req/Res
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub http_endpoint: RpcUrl,
     /// Websocket Endpoint for long living connections
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub ws_endpoint: RpcUrl,
     /// Block confirmations
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub block_confirmations: u32,
     /// Block Explorer for this chain.
     ///
     /// Optional, and only used for printing a clickable links
     /// for transactions and contracts.
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub explorer: Option<url::Url>,
     /// chain specific id (output of chainId opcode on EVM networks)
     #[serde(rename(serialize = "chainId"))]
+    #[schemars(rename = "chainId")]
     pub chain_id: u32,
     /// The Private Key of this account on this network
     /// the format is more dynamic here:
@@ -55,31 +60,35 @@ pub struct EvmChainConfig {
     /// 4. if it doesn't contains special characters and has 12 or 24 words in it
     ///   then we should process it as a mnemonic string: 'word two three four ...'
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub private_key: Option<PrivateKey>,
     /// Optionally, a user can specify an account to receive rewards for relaying
+    #[schemars(with = "Option<String>")]
     pub beneficiary: Option<Address>,
     /// Supported contracts over this chain.
     #[serde(default)]
     pub contracts: Vec<Contract>,
     /// TxQueue configuration
     #[serde(skip_serializing, default)]
+    #[schemars(skip)]
     pub tx_queue: TxQueueConfig,
     /// Block poller/listening configuration
     pub block_poller: Option<BlockPollerConfig>,
 }
 
 /// Linked anchor config for Evm based target system
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct EvmLinkedAnchorConfig {
     /// The chain Id
     pub chain_id: u32,
     /// The V-anchor Contract Address.
+    #[schemars(with = "String")]
     pub address: Address,
 }
 
 /// Enumerates the supported contract configurations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "contract")]
 pub enum Contract {
     /// The VAnchor contract configuration.
@@ -88,21 +97,27 @@ pub enum Contract {
     OpenVAnchor(VAnchorContractConfig),
     /// The Signature Bridge contract configuration.
     SignatureBridge(SignatureBridgeContractConfig),
+    /// The Fungible Token Wrapper contract configuration.
+    TokenWrapper(GovernanceContractConfig),
+    /// The Anchor Handler contract configuration.
+    AnchorHandler(GovernanceContractConfig),
 }
 
 /// CommonContractConfig represents the common configuration for contracts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct CommonContractConfig {
     /// The address of this contract on this chain.
+    #[schemars(with = "String")]
     pub address: Address,
     /// the block number where this contract got deployed at.
     #[serde(rename(serialize = "deployedAt"))]
+    #[schemars(rename = "deployedAt")]
     pub deployed_at: u64,
 }
 
 /// VAnchorContractConfig represents the configuration for the VAnchor contract.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct VAnchorContractConfig {
     /// Common contract configuration.
@@ -110,20 +125,68 @@ pub struct VAnchorContractConfig {
     pub common: CommonContractConfig,
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
     /// Anchor withdraw configuration.
     #[serde(rename(serialize = "withdrawConfig"))]
+    #[schemars(rename = "withdrawConfig")]
     pub withdraw_config: Option<VAnchorWithdrawConfig>,
     /// The type of the optional signing backend used for signing proposals. It can be None for pure Tx relayers
     #[serde(rename(serialize = "proposalSigningBackend"))]
+    #[schemars(rename = "proposalSigningBackend")]
     pub proposal_signing_backend: Option<ProposalSigningBackendConfig>,
     /// A List of linked Anchor Contracts (on other chains) to this contract.
     #[serde(rename(serialize = "linkedAnchors"), default)]
+    #[schemars(rename = "linkedAnchors")]
     pub linked_anchors: Option<Vec<LinkedAnchorConfig>>,
 }
 
+/// GovernanceContractConfig represents the configuration for contracts whose
+/// events are turned into governance proposals (Token Wrapper, Anchor Handler).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct GovernanceContractConfig {
+    /// Common contract configuration.
+    #[serde(flatten)]
+    pub common: CommonContractConfig,
+    /// Controls the events watcher
+    #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
+    pub events_watcher: EventsWatcherConfig,
+    /// The type of the optional signing backend used for signing proposals.
+    #[serde(rename(serialize = "proposalSigningBackend"))]
+    #[schemars(rename = "proposalSigningBackend")]
+    pub proposal_signing_backend: Option<ProposalSigningBackendConfig>,
+    /// A List of linked contracts (on other chains) that should receive
+    /// the proposals created from this contract's events.
+    #[serde(rename(serialize = "linkedContracts"), default)]
+    #[schemars(rename = "linkedContracts")]
+    pub linked_contracts: Option<Vec<LinkedAnchorConfig>>,
+    /// The addresses of the tokens of this contract on the linked chains,
+    /// token additions/removals are only proposed to the linked contracts
+    /// whose chain has the token listed here.
+    #[serde(rename(serialize = "linkedTokens"), default)]
+    #[schemars(rename = "linkedTokens")]
+    pub linked_tokens: Vec<LinkedTokenConfig>,
+}
+
+/// LinkedTokenConfig maps a token of this chain to the same token on a linked
+/// chain.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkedTokenConfig {
+    /// The address of the token on this chain.
+    #[schemars(with = "String")]
+    pub token: Address,
+    /// The chain Id of the linked chain.
+    pub chain_id: u32,
+    /// The address of the token on the linked chain.
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
 /// Signature Bridge contract configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SignatureBridgeContractConfig {
     /// Common contract configuration.
@@ -131,5 +194,6 @@ pub struct SignatureBridgeContractConfig {
     pub common: CommonContractConfig,
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
 }
@@ -28,6 +28,8 @@
 //! * `substrate`: Substrate based networks and the configuration. See [config/local-substrate](./config/local-substrate) for an example.
 //! * `cosmwasm`: Cosmos-SDK based networks and the configuration.
 
+/// Admin API configuration
+pub mod admin;
 /// Generic anchor configuration
 pub mod anchor;
 /// Block poller configuration
@@ -42,8 +44,14 @@ pub mod cosmwasm;
 pub mod event_watcher;
 /// EVM configuration
 pub mod evm;
+/// Rate limits configuration
+pub mod rate_limit;
+/// HTTP/websocket server configuration
+pub mod server;
 /// Signing backend configuration
 pub mod signing_backend;
+/// Store configuration
+pub mod store;
 /// Substrate configuration
 pub mod substrate;
 /// Utils for processing configuration
@@ -51,10 +59,14 @@ pub mod utils;
 
 use cosmwasm::CosmwasmConfig;
 use evm::EvmChainConfig;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use substrate::SubstrateConfig;
 
+/// The features of the relayer that can be turned off.
+pub use webb_relayer_handler_utils::FeaturesConfig;
+
 /// The default port the relayer will listen on. Defaults to 9955.
 const fn default_port() -> u16 {
     9955
@@ -75,15 +87,36 @@ const fn max_blocks_per_step_default() -> u64 {
 const fn print_progress_interval_default() -> u64 {
     7_000
 }
+/// The leaves cache is checked every `600_000` (10 minutes) by default.
+const fn leaves_check_interval_default() -> u64 {
+    600_000
+}
+/// The smart anchor updates time window is set to `10_000` by default.
+const fn smart_anchor_updates_window_default() -> u64 {
+    10_000
+}
+
+const fn dkg_proposal_timeout_default() -> u64 {
+    5 * 60
+}
+
+const fn dkg_proposal_max_resubmissions_default() -> u32 {
+    3
+}
+
+const fn dkg_proposal_retention_default() -> u64 {
+    7 * 24 * 60 * 60
+}
 
 /// WebbRelayerConfig is the configuration for the webb relayer.
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct WebbRelayerConfig {
     /// WebSocket Server Port number
     ///
     /// default to 9955
     #[serde(default = "default_port", skip_serializing)]
+    #[schemars(skip)]
     pub port: u16,
     /// EVM based networks and the configuration.
     ///
@@ -112,6 +145,34 @@ pub struct WebbRelayerConfig {
     /// 3. Private transaction relaying
     #[serde(default)]
     pub features: FeaturesConfig,
+    /// Configuration for the relayer database
+    ///
+    /// default to a sled database in the relayer data directory.
+    #[serde(default)]
+    pub store: store::StoreConfig,
+    /// Configuration for the admin API of the relayer
+    ///
+    /// disabled unless a token is set.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub admin: admin::AdminConfig,
+    /// Configuration for the HTTP/websocket server of the relayer
+    ///
+    /// listens on all the interfaces with the configured port, over plain HTTP, by default.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub server: server::ServerConfig,
+    /// Configuration for the websocket server of the relayer
+    #[serde(default)]
+    pub ws: WebSocketConfig,
+    /// Configuration for the private transactions relayed over HTTP
+    #[serde(default)]
+    pub jobs: RelayJobsConfig,
+    /// Configuration for the rate limits of the relayer API
+    ///
+    /// nothing is limited by default.
+    #[serde(default)]
+    pub rate_limit: rate_limit::RateLimitConfig,
 }
 
 impl WebbRelayerConfig {
@@ -146,7 +207,7 @@ impl WebbRelayerConfig {
 }
 
 /// ExperimentalConfig is the configuration for the Experimental Options.
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct ExperimentalConfig {
     /// Enable the Smart Anchor Updates when it comes to signaling
@@ -155,35 +216,104 @@ pub struct ExperimentalConfig {
     /// The number of retries to check if an anchor is updated before sending our update
     /// or not, before actually sending our update.
     pub smart_anchor_updates_retries: u32,
+    /// The time window (in milliseconds) in which deposits are coalesced into
+    /// a single anchor update, when smart anchor updates are enabled. The
+    /// deposit opening the window is only marked as handled once the update
+    /// is sent, at the end of the window.
+    ///
+    /// default to 10_000
+    #[serde(default = "smart_anchor_updates_window_default")]
+    pub smart_anchor_updates_window: u64,
+}
+
+impl Default for ExperimentalConfig {
+    fn default() -> Self {
+        Self {
+            smart_anchor_updates: false,
+            smart_anchor_updates_retries: 0,
+            smart_anchor_updates_window: smart_anchor_updates_window_default(),
+        }
+    }
 }
 
-/// FeaturesConfig is the configuration for running relayer with option.
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+/// WebSocketConfig is the configuration for the websocket server.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
-pub struct FeaturesConfig {
-    /// Enable data quering for leafs
-    pub data_query: bool,
-    /// Enable governance relaying
-    pub governance_relay: bool,
-    /// Enable private tx relaying
-    pub private_tx_relay: bool,
-}
-impl Default for FeaturesConfig {
+pub struct WebSocketConfig {
+    /// Maximum number of commands handled at the same time for a single
+    /// connection speaking the multiplexed (version 2) protocol, further
+    /// commands are not read until one of them is done.
+    ///
+    /// default to 8
+    #[serde(default = "ws_max_concurrent_commands_default")]
+    pub max_concurrent_commands: usize,
+}
+
+impl Default for WebSocketConfig {
     fn default() -> Self {
         Self {
-            data_query: true,
-            governance_relay: true,
-            private_tx_relay: true,
+            max_concurrent_commands: ws_max_concurrent_commands_default(),
         }
     }
 }
 
+const fn ws_max_concurrent_commands_default() -> usize {
+    8
+}
+
+/// RelayJobsConfig is the configuration for the private transactions
+/// submitted over HTTP, each one relayed as a job.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct RelayJobsConfig {
+    /// Maximum number of jobs relayed at the same time, further submissions
+    /// are refused until one of them is done.
+    ///
+    /// default to 64
+    #[serde(default = "relay_jobs_max_in_flight_default")]
+    pub max_in_flight: usize,
+    /// How long (in seconds) finished jobs are kept before being pruned.
+    ///
+    /// default to one day.
+    #[serde(default = "relay_jobs_retention_default")]
+    pub retention: u64,
+    /// How often (in seconds) the finished jobs are pruned.
+    ///
+    /// default to one hour.
+    #[serde(default = "relay_jobs_prune_interval_default")]
+    pub prune_interval: u64,
+}
+
+impl Default for RelayJobsConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: relay_jobs_max_in_flight_default(),
+            retention: relay_jobs_retention_default(),
+            prune_interval: relay_jobs_prune_interval_default(),
+        }
+    }
+}
+
+const fn relay_jobs_max_in_flight_default() -> usize {
+    64
+}
+
+const fn relay_jobs_retention_default() -> u64 {
+    24 * 60 * 60
+}
+
+const fn relay_jobs_prune_interval_default() -> u64 {
+    60 * 60
+}
+
 /// TxQueueConfig is the configuration for the TxQueue.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TxQueueConfig {
-    /// Maximum number of milliseconds to wait before dequeuing a transaction from
-    /// the queue.
+    /// Maximum number of milliseconds to wait between two transactions, and
+    /// before polling the queue again while it is empty, the queue is woken up
+    /// sooner by new transactions. The waits are random, from 1 second up to
+    /// this interval.
     pub max_sleep_interval: u64,
 }
 
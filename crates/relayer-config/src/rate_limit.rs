@@ -0,0 +1,41 @@
+use super::*;
+
+/// RateLimitConfig is the configuration of the rate limits of the relayer API.
+///
+/// Requests are limited per client IP address and per group of routes, a
+/// group without a limit is not limited at all.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitConfig {
+    /// Limit of the data querying routes (leaves and encrypted outputs).
+    #[serde(default)]
+    pub data: Option<TokenBucketConfig>,
+    /// Limit of the transaction relaying routes (websocket connections and
+    /// relay jobs).
+    #[serde(default)]
+    pub relay: Option<TokenBucketConfig>,
+    /// Limit of all the other routes of the API, except the admin API.
+    #[serde(default)]
+    pub general: Option<TokenBucketConfig>,
+    /// Maximum number of websocket connections open at the same time.
+    #[serde(default)]
+    pub max_ws_connections: Option<usize>,
+    /// Maximum number of websocket connections open at the same time from a
+    /// single client IP address.
+    #[serde(default)]
+    pub max_ws_connections_per_ip: Option<usize>,
+}
+
+/// TokenBucketConfig is the configuration of a token bucket.
+///
+/// Every request takes a token from the bucket of its client, and is rejected
+/// when the bucket is empty. The bucket is refilled at a constant rate.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenBucketConfig {
+    /// The number of tokens of a full bucket, i.e. the number of requests a
+    /// client can send at once.
+    pub burst: u32,
+    /// The number of tokens added to the bucket every second.
+    pub per_second: f64,
+}
@@ -0,0 +1,71 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use super::*;
+
+/// The certificate and key files are checked for changes every `60` seconds by default.
+const fn tls_reload_interval_default() -> u64 {
+    60
+}
+
+/// ServerConfig is the configuration of the HTTP/websocket server of the relayer.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ServerConfig {
+    /// The addresses the relayer listens on, i.e. `["127.0.0.1:9955", "[::1]:9955"]`.
+    ///
+    /// When empty, the relayer listens on all the interfaces with the
+    /// configured `port`.
+    #[serde(default)]
+    pub listen: Vec<SocketAddr>,
+    /// The CORS policy of the relayer API.
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Serve the relayer API over TLS, instead of plain HTTP.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// CorsConfig is the CORS policy of the relayer API.
+///
+/// Any origin is allowed by default.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CorsConfig {
+    /// The origins allowed to query the relayer, i.e. `["https://app.webb.tools"]`.
+    ///
+    /// When empty, or containing `*`, any origin is allowed.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// The methods allowed in the CORS requests, i.e. `["GET", "POST"]`.
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    /// The headers allowed in the CORS requests, i.e. `["content-type"]`.
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// Returns `true` if any origin is allowed.
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.is_empty()
+            || self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+/// TlsConfig is the configuration of the TLS certificate of the relayer.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TlsConfig {
+    /// The path of the certificate chain, in PEM format.
+    pub cert_path: PathBuf,
+    /// The path of the private key of the certificate, in PEM format.
+    pub key_path: PathBuf,
+    /// How often, in seconds, the certificate and key files are checked for
+    /// changes, the new connections are made with the new certificate once
+    /// they changed.
+    ///
+    /// default to 60
+    #[serde(default = "tls_reload_interval_default")]
+    pub reload_interval: u64,
+}
@@ -3,7 +3,7 @@ use webb_relayer_types::private_key::PrivateKey;
 use super::*;
 
 /// Enumerates the supported different signing backends configurations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum ProposalSigningBackendConfig {
     /// Uses an already running and configured DKG Node for signing proposals.
@@ -14,7 +14,7 @@ pub enum ProposalSigningBackendConfig {
 }
 
 /// DKGNodeSigningBackendConfig represents the configuration for the DKGNode signing backend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DkgNodeProposalSigningBackendConfig {
     /// The name of the DKG Node that this contract will use.
@@ -24,10 +24,11 @@ pub struct DkgNodeProposalSigningBackendConfig {
 }
 
 /// MockedSigningBackendConfig represents the configuration for the Mocked signing backend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct MockedProposalSigningBackendConfig {
     /// The private key of the current Governor.
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub private_key: PrivateKey,
 }
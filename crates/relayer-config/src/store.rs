@@ -0,0 +1,83 @@
+use super::*;
+
+/// The database backend used by the relayer to store its data.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum StoreBackend {
+    /// A [Sled](https://sled.rs)-based database.
+    #[default]
+    Sled,
+    /// A [SQLite](https://sqlite.org)-based database, which can be shared with
+    /// other relayer processes (e.g. an API only relayer).
+    Sqlite,
+    /// An in-memory store, nothing is persisted and the relayer starts
+    /// syncing from scratch on every restart.
+    Memory,
+}
+
+impl std::str::FromStr for StoreBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sled" => Ok(Self::Sled),
+            "sqlite" => Ok(Self::Sqlite),
+            "memory" => Ok(Self::Memory),
+            _ => Err(format!(
+                "unknown store backend `{s}`, expected one of: sled, sqlite, memory"
+            )),
+        }
+    }
+}
+
+/// StoreConfig is the configuration for the relayer database.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct StoreConfig {
+    /// The database backend to use.
+    ///
+    /// default to `sled`
+    #[serde(default)]
+    pub backend: StoreBackend,
+    /// The path of the database, if not set the default data directory
+    /// of the relayer is used.
+    #[serde(default)]
+    pub path: Option<std::path::PathBuf>,
+    /// How many blocks below the last synced block of their contract the
+    /// hashes of the handled events are kept, older ones are pruned in the
+    /// background.
+    ///
+    /// if not set, the event hashes are never pruned.
+    #[serde(default)]
+    pub event_hashes_depth: Option<u64>,
+    /// How often (in seconds) the old event hashes are pruned.
+    ///
+    /// default to one hour.
+    #[serde(default = "event_hashes_prune_interval_default")]
+    pub event_hashes_prune_interval: u64,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: StoreBackend::default(),
+            path: None,
+            event_hashes_depth: None,
+            event_hashes_prune_interval: event_hashes_prune_interval_default(),
+        }
+    }
+}
+
+const fn event_hashes_prune_interval_default() -> u64 {
+    60 * 60
+}
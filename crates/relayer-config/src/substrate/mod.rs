@@ -1,5 +1,6 @@
 use super::*;
 use webb::substrate::subxt::ext::sp_core::sr25519::Public;
+use webb::substrate::subxt::ext::sp_runtime::AccountId32;
 use webb_relayer_types::{rpc_url::RpcUrl, suri::Suri};
 
 use crate::{
@@ -8,7 +9,7 @@ use crate::{
 };
 
 /// SubstrateConfig is the relayer configuration for the Substrate based networks.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SubstrateConfig {
     /// String that groups configuration for this chain on a human-readable name.
@@ -18,19 +19,28 @@ pub struct SubstrateConfig {
     pub enabled: bool,
     /// Http(s) Endpoint for quick Req/Res
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub http_endpoint: RpcUrl,
     /// Websocket Endpoint for long living connections
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub ws_endpoint: RpcUrl,
     /// Block Explorer for this Substrate node.
     ///
     /// Optional, and only used for printing a clickable links
     /// for transactions and contracts.
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub explorer: Option<url::Url>,
     /// chain specific id (output of ChainIdentifier constant on LinkableTree Pallet)
     #[serde(rename(serialize = "chainId"))]
+    #[schemars(rename = "chainId")]
     pub chain_id: u32,
+    /// The kind of chain this node is, used to derive the typed chain id
+    /// of this node when talking to other chains (defaults to a standalone Substrate chain).
+    #[serde(default, rename(serialize = "chainType"))]
+    #[schemars(rename = "chainType")]
+    pub chain_type: SubstrateChainType,
     /// Interprets the string in order to generate a key Pair. in the
     /// case that the pair can be expressed as a direct derivation from a seed (some cases, such as Sr25519 derivations
     /// with path components, cannot).
@@ -61,8 +71,10 @@ pub struct SubstrateConfig {
     ///
     /// `None` is returned if no matches are found.
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub suri: Option<Suri>,
     /// Optionally, a user can specify an account to receive rewards for relaying
+    #[schemars(with = "Option<String>")]
     pub beneficiary: Option<Public>,
     /// Which Substrate Runtime to use?
     pub runtime: SubstrateRuntime,
@@ -71,11 +83,66 @@ pub struct SubstrateConfig {
     pub pallets: Vec<Pallet>,
     /// TxQueue configuration
     #[serde(skip_serializing, default)]
+    #[schemars(skip)]
     pub tx_queue: TxQueueConfig,
 }
 
+impl SubstrateConfig {
+    /// Returns the [`webb_proposals::TypedChainId`] of this node based on its configured chain type.
+    pub fn typed_chain_id(&self) -> webb_proposals::TypedChainId {
+        self.chain_type.typed_chain_id(self.chain_id)
+    }
+}
+
+/// Enumerates the supported kinds of Substrate based chains.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum SubstrateChainType {
+    /// A standalone Substrate chain.
+    #[default]
+    Substrate,
+    /// A parachain connected to the Polkadot relay chain.
+    PolkadotParachain,
+    /// A parachain connected to the Kusama relay chain.
+    KusamaParachain,
+    /// A parachain connected to the Rococo relay chain.
+    RococoParachain,
+}
+
+impl SubstrateChainType {
+    /// Returns true if this chain is a parachain.
+    pub fn is_parachain(&self) -> bool {
+        !matches!(self, Self::Substrate)
+    }
+
+    /// Wraps the given chain id into the matching [`webb_proposals::TypedChainId`] variant.
+    pub fn typed_chain_id(
+        &self,
+        chain_id: u32,
+    ) -> webb_proposals::TypedChainId {
+        use webb_proposals::TypedChainId;
+        match self {
+            Self::Substrate => TypedChainId::Substrate(chain_id),
+            Self::PolkadotParachain => {
+                TypedChainId::PolkadotParachain(chain_id)
+            }
+            Self::KusamaParachain => TypedChainId::KusamaParachain(chain_id),
+            Self::RococoParachain => TypedChainId::RococoParachain(chain_id),
+        }
+    }
+}
+
 /// Linked anchor config for Substrate based target system
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SubstrateLinkedAnchorConfig {
     /// chain Id
@@ -87,7 +154,7 @@ pub struct SubstrateLinkedAnchorConfig {
 }
 
 /// Enumerates the supported pallets configurations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "pallet")]
 pub enum Pallet {
     /// `dkg-metadata` or as named in the runtime as `DKG` pallet.
@@ -101,10 +168,12 @@ pub enum Pallet {
     SignatureBridge(SignatureBridgePalletConfig),
     /// `vanchor-bn256` or as named in the runtime as `VAnchorBn256` pallet.
     VAnchorBn254(VAnchorBn254PalletConfig),
+    /// An Ink! `SignatureBridge` contract deployed through the `Contracts` pallet.
+    InkSignatureBridge(InkSignatureBridgeContractConfig),
 }
 
 /// Enumerates the supported Substrate runtimes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum SubstrateRuntime {
     /// The DKG runtime. (dkg-substrate)
     #[serde(rename = "DKG")]
@@ -114,51 +183,100 @@ pub enum SubstrateRuntime {
 }
 
 /// DKGProposalsPalletConfig represents the configuration for the DKGProposals pallet.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DKGProposalsPalletConfig {
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
 }
 
 /// DKGPalletConfig represents the configuration for the DKG pallet (dkg-metadata).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DKGPalletConfig {
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
 }
 /// DKGProposalHandlerPalletConfig represents the configuration for the DKGProposalHandler pallet.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DKGProposalHandlerPalletConfig {
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
+    /// How long (in seconds) to wait for the DKG to sign a submitted proposal
+    /// before submitting it again.
+    ///
+    /// default to 300 seconds
+    #[serde(
+        default = "dkg_proposal_timeout_default",
+        rename(serialize = "proposalTimeout")
+    )]
+    #[schemars(rename = "proposalTimeout")]
+    pub proposal_timeout: u64,
+    /// How many times a proposal is submitted again before it is marked as expired.
+    ///
+    /// default to 3
+    #[serde(
+        default = "dkg_proposal_max_resubmissions_default",
+        rename(serialize = "proposalMaxResubmissions")
+    )]
+    #[schemars(rename = "proposalMaxResubmissions")]
+    pub proposal_max_resubmissions: u32,
+    /// How long (in seconds) a signed or expired proposal is kept, before it
+    /// is removed from the store.
+    ///
+    /// default to 604800 seconds (7 days)
+    #[serde(
+        default = "dkg_proposal_retention_default",
+        rename(serialize = "proposalRetention")
+    )]
+    #[schemars(rename = "proposalRetention")]
+    pub proposal_retention: u64,
 }
 
 /// SignatureBridgePalletConfig represents the configuration for the SignatureBridge pallet.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SignatureBridgePalletConfig {
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
+    pub events_watcher: EventsWatcherConfig,
+}
+
+/// InkSignatureBridgeContractConfig represents the configuration for an Ink! SignatureBridge contract.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct InkSignatureBridgeContractConfig {
+    /// The account id of the deployed contract.
+    #[schemars(with = "String")]
+    pub address: AccountId32,
+    /// Controls the events watcher
+    #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
 }
 
 /// VAnchorBn254PalletConfig represents the configuration for the VAnchorBn254 pallet.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct VAnchorBn254PalletConfig {
     /// Controls the events watcher
     #[serde(rename(serialize = "eventsWatcher"))]
+    #[schemars(rename = "eventsWatcher")]
     pub events_watcher: EventsWatcherConfig,
     /// The type of the optional signing backend used for signing proposals. It can be None for pure Tx relayers
     #[serde(rename(serialize = "proposalSigningBackend"))]
+    #[schemars(rename = "proposalSigningBackend")]
     pub proposal_signing_backend: Option<ProposalSigningBackendConfig>,
     /// A List of linked Anchor on this chain.
     #[serde(rename(serialize = "linkedAnchors"), default)]
+    #[schemars(rename = "linkedAnchors")]
     pub linked_anchors: Option<Vec<LinkedAnchorConfig>>,
 }
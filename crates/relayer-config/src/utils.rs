@@ -169,8 +169,8 @@ pub fn postloading_process(
         let typed_chain_id =
             webb_proposals::TypedChainId::Evm(network_chain.chain_id);
         chain_list.insert(typed_chain_id);
-        network_chain.contracts.iter_mut().for_each(|c| {
-            if let Contract::VAnchor(cfg) = c {
+        network_chain.contracts.iter_mut().for_each(|c| match c {
+            Contract::VAnchor(cfg) => {
                 let linked_anchors = cfg.linked_anchors.clone();
                 if let Some(linked_anchors) = linked_anchors {
                     let linked_anchors: Vec<LinkedAnchorConfig> =
@@ -181,12 +181,23 @@ pub fn postloading_process(
                     cfg.linked_anchors = Some(linked_anchors);
                 }
             }
+            Contract::TokenWrapper(cfg) | Contract::AnchorHandler(cfg) => {
+                let linked_contracts = cfg.linked_contracts.clone();
+                if let Some(linked_contracts) = linked_contracts {
+                    let linked_contracts: Vec<LinkedAnchorConfig> =
+                        linked_contracts
+                            .into_iter()
+                            .map(|contract| contract.into_raw_resource_id())
+                            .collect();
+                    cfg.linked_contracts = Some(linked_contracts);
+                }
+            }
+            _ => {}
         })
     }
     // Convert linked anchor to Raw ResourceId type for substrate chains
     for (_, network_chain) in config.substrate.iter_mut() {
-        let typed_chain_id =
-            webb_proposals::TypedChainId::Substrate(network_chain.chain_id);
+        let typed_chain_id = network_chain.typed_chain_id();
         chain_list.insert(typed_chain_id);
         network_chain.pallets.iter_mut().for_each(|c| {
             if let Pallet::VAnchorBn254(cfg) = c {
@@ -0,0 +1,164 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use webb_proposals::{ResourceId, TypedChainId};
+
+/// ServiceControls keeps track of the paused events watchers and
+/// transaction queues of the relayer.
+///
+/// Watchers are identified by the [`ResourceId`] they store their cursor
+/// under, queues by the chain they send transactions to.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceControls {
+    watchers: Arc<RwLock<Watchers>>,
+    paused_queues: Arc<RwLock<HashSet<TypedChainId>>>,
+}
+
+#[derive(Debug, Default)]
+struct Watchers {
+    paused: HashSet<[u8; 32]>,
+    /// The number of watchers syncing a range of blocks, for each resource.
+    syncing: HashMap<[u8; 32], usize>,
+}
+
+/// Held by a watcher while it syncs a range of blocks, see
+/// [`ServiceControls::start_watcher_sync`].
+#[derive(Debug)]
+pub struct WatcherSyncGuard {
+    watchers: Arc<RwLock<Watchers>>,
+    resource_id: [u8; 32],
+}
+
+impl Drop for WatcherSyncGuard {
+    fn drop(&mut self) {
+        let mut watchers =
+            self.watchers.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = watchers.syncing.get_mut(&self.resource_id) {
+            *count -= 1;
+            if *count == 0 {
+                watchers.syncing.remove(&self.resource_id);
+            }
+        }
+    }
+}
+
+impl ServiceControls {
+    /// Pauses the watcher of the given resource, returns `false` if it was
+    /// already paused.
+    pub fn pause_watcher(&self, resource_id: ResourceId) -> bool {
+        self.watchers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .paused
+            .insert(resource_id.into_bytes())
+    }
+
+    /// Resumes the watcher of the given resource, returns `false` if it was
+    /// not paused.
+    pub fn resume_watcher(&self, resource_id: ResourceId) -> bool {
+        self.watchers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .paused
+            .remove(&resource_id.into_bytes())
+    }
+
+    /// Returns `true` if the watcher of the given resource is paused.
+    pub fn is_watcher_paused(&self, resource_id: ResourceId) -> bool {
+        self.watchers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .paused
+            .contains(&resource_id.into_bytes())
+    }
+
+    /// Returns `true` if the watchers of the given resource are paused, and
+    /// none of them is still syncing the range of blocks it started before.
+    pub fn is_watcher_stopped(&self, resource_id: ResourceId) -> bool {
+        let watchers = self.watchers.read().unwrap_or_else(|e| e.into_inner());
+        let resource_id = resource_id.into_bytes();
+        watchers.paused.contains(&resource_id)
+            && !watchers.syncing.contains_key(&resource_id)
+    }
+
+    /// Called by a watcher before syncing a range of blocks, returns `None`
+    /// if it is paused. Otherwise the watcher counts as syncing until the
+    /// returned guard is dropped.
+    pub fn start_watcher_sync(
+        &self,
+        resource_id: ResourceId,
+    ) -> Option<WatcherSyncGuard> {
+        let mut watchers =
+            self.watchers.write().unwrap_or_else(|e| e.into_inner());
+        let resource_id = resource_id.into_bytes();
+        if watchers.paused.contains(&resource_id) {
+            return None;
+        }
+        *watchers.syncing.entry(resource_id).or_default() += 1;
+        Some(WatcherSyncGuard {
+            watchers: self.watchers.clone(),
+            resource_id,
+        })
+    }
+
+    /// Returns the resources of all the paused watchers.
+    pub fn paused_watchers(&self) -> Vec<ResourceId> {
+        self.watchers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .paused
+            .iter()
+            .map(|bytes| ResourceId::from(*bytes))
+            .collect()
+    }
+
+    /// Pauses the transaction queue of the given chain, returns `false` if
+    /// it was already paused.
+    pub fn pause_queue(&self, chain_id: TypedChainId) -> bool {
+        self.paused_queues
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(chain_id)
+    }
+
+    /// Resumes the transaction queue of the given chain, returns `false` if
+    /// it was not paused.
+    pub fn resume_queue(&self, chain_id: TypedChainId) -> bool {
+        self.paused_queues
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&chain_id)
+    }
+
+    /// Returns `true` if the transaction queue of the given chain is paused.
+    pub fn is_queue_paused(&self, chain_id: TypedChainId) -> bool {
+        self.paused_queues
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&chain_id)
+    }
+
+    /// Returns the chains of all the paused transaction queues.
+    pub fn paused_queues(&self) -> Vec<TypedChainId> {
+        self.paused_queues
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .copied()
+            .collect()
+    }
+}
@@ -20,7 +20,14 @@ use std::convert::TryFrom;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Semaphore};
+
+/// Runtime control of the watchers and transaction queues.
+pub mod control;
+pub use control::ServiceControls;
+/// Rate limiting of the relayer API.
+pub mod rate_limit;
+pub use rate_limit::RateLimiter;
 
 #[cfg(feature = "evm")]
 use webb::evm::ethers::core::k256::SecretKey;
@@ -32,6 +39,7 @@ use webb::substrate::subxt;
 #[cfg(feature = "substrate")]
 use webb::substrate::subxt::ext::sp_core::sr25519::Pair as Sr25519Pair;
 
+use webb_relayer_store::LeavesSyncStatuses;
 use webb_relayer_utils::metric;
 use webb_relayer_utils::metric::Metrics;
 
@@ -51,6 +59,17 @@ pub struct RelayerContext {
     notify_shutdown: broadcast::Sender<()>,
     /// Represents the metrics for the relayer
     pub metrics: Arc<metric::Metrics>,
+    /// The sync status of the leaves cache of the anchors, as reported by
+    /// their leaves checkers.
+    pub leaves_sync: LeavesSyncStatuses,
+    /// The paused watchers and transaction queues, controlled through the
+    /// admin API.
+    pub controls: ServiceControls,
+    /// The rate limits of the relayer API.
+    pub rate_limiter: RateLimiter,
+    /// The permits of the relay jobs in flight, one is held by every job
+    /// until the relayer is done with it.
+    pub relay_jobs: Arc<Semaphore>,
 }
 
 impl RelayerContext {
@@ -58,10 +77,16 @@ impl RelayerContext {
     pub fn new(config: webb_relayer_config::WebbRelayerConfig) -> Self {
         let (notify_shutdown, _) = broadcast::channel(2);
         let metrics = Arc::new(Metrics::new());
+        let rate_limiter = RateLimiter::new(config.rate_limit.clone());
+        let relay_jobs = Arc::new(Semaphore::new(config.jobs.max_in_flight));
         Self {
             config,
             notify_shutdown,
             metrics,
+            leaves_sync: LeavesSyncStatuses::default(),
+            controls: ServiceControls::default(),
+            rate_limiter,
+            relay_jobs,
         }
     }
     /// Returns a broadcast receiver handle for the shutdown signal.
@@ -0,0 +1,249 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use webb_relayer_config::rate_limit::{RateLimitConfig, TokenBucketConfig};
+
+/// How often the buckets that are full again are dropped.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The groups of routes that are rate limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    /// The data querying routes (leaves and encrypted outputs).
+    Data,
+    /// The transaction relaying routes (websocket connections and relay jobs).
+    Relay,
+    /// All the other routes of the API.
+    General,
+}
+
+impl RouteGroup {
+    /// The name of the group, as used in the metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteGroup::Data => "data",
+            RouteGroup::Relay => "relay",
+            RouteGroup::General => "general",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &TokenBucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * limit.per_second)
+            .min(f64::from(limit.burst));
+        self.updated_at = now;
+    }
+
+    fn is_full(&self, limit: &TokenBucketConfig) -> bool {
+        self.tokens >= f64::from(limit.burst)
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    entries: HashMap<(RouteGroup, IpAddr), TokenBucket>,
+    cleaned_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct WsConnections {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+/// RateLimiter keeps a token bucket for every client IP address of each
+/// [`RouteGroup`], and counts the open websocket connections.
+///
+/// Clients whose address is unknown share the same buckets.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<Buckets>>,
+    ws_connections: Arc<Mutex<WsConnections>>,
+}
+
+impl RateLimiter {
+    /// Creates a new RateLimiter with the given limits.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            buckets: Arc::new(Mutex::new(Buckets {
+                entries: HashMap::new(),
+                cleaned_at: Instant::now(),
+            })),
+            ws_connections: Arc::default(),
+        }
+    }
+
+    fn limit(&self, group: RouteGroup) -> Option<&TokenBucketConfig> {
+        match group {
+            RouteGroup::Data => self.config.data.as_ref(),
+            RouteGroup::Relay => self.config.relay.as_ref(),
+            RouteGroup::General => self.config.general.as_ref(),
+        }
+    }
+
+    /// Takes a token from the bucket of the client for the given group of
+    /// routes, returns `false` if the request should be rejected.
+    pub fn check(&self, group: RouteGroup, ip: Option<IpAddr>) -> bool {
+        let limit = match self.limit(group) {
+            Some(limit) => *limit,
+            None => return true,
+        };
+        let ip = ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let now = Instant::now();
+        let mut buckets =
+            self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if now.saturating_duration_since(buckets.cleaned_at) >= CLEANUP_INTERVAL
+        {
+            // a full bucket is the same as a missing one.
+            buckets.entries.retain(|(group, _), bucket| {
+                match self.limit(*group) {
+                    Some(limit) => {
+                        bucket.refill(limit, now);
+                        !bucket.is_full(limit)
+                    }
+                    None => false,
+                }
+            });
+            buckets.cleaned_at = now;
+        }
+        let bucket = buckets
+            .entries
+            .entry((group, ip))
+            .or_insert_with(|| TokenBucket::full(&limit, now));
+        bucket.refill(&limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Counts a new websocket connection of the client, returns `None` if
+    /// too many connections are already open.
+    ///
+    /// The connection is counted until the returned guard is dropped.
+    pub fn acquire_ws_connection(
+        &self,
+        ip: Option<IpAddr>,
+    ) -> Option<WsConnectionGuard> {
+        let ip = ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let mut connections = self
+            .ws_connections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let from_ip = connections.per_ip.get(&ip).copied().unwrap_or_default();
+        let too_many = matches!(
+            self.config.max_ws_connections,
+            Some(max) if connections.total >= max
+        ) || matches!(
+            self.config.max_ws_connections_per_ip,
+            Some(max) if from_ip >= max
+        );
+        if too_many {
+            return None;
+        }
+        connections.total += 1;
+        connections.per_ip.insert(ip, from_ip + 1);
+        Some(WsConnectionGuard {
+            ip,
+            connections: Arc::clone(&self.ws_connections),
+        })
+    }
+}
+
+/// A websocket connection counted by the [`RateLimiter`], until dropped.
+#[derive(Debug)]
+pub struct WsConnectionGuard {
+    ip: IpAddr,
+    connections: Arc<Mutex<WsConnections>>,
+}
+
+impl Drop for WsConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections =
+            self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        connections.total = connections.total.saturating_sub(1);
+        if let Some(count) = connections.per_ip.get_mut(&self.ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                connections.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_per_group_and_ip() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            data: Some(TokenBucketConfig {
+                burst: 2,
+                per_second: 0.0,
+            }),
+            ..Default::default()
+        });
+        let a = Some(IpAddr::from([10, 0, 0, 1]));
+        let b = Some(IpAddr::from([10, 0, 0, 2]));
+        assert!(limiter.check(RouteGroup::Data, a));
+        assert!(limiter.check(RouteGroup::Data, a));
+        assert!(!limiter.check(RouteGroup::Data, a));
+        assert!(limiter.check(RouteGroup::Data, b));
+        // groups without a limit are never limited.
+        assert!(limiter.check(RouteGroup::General, a));
+    }
+
+    #[test]
+    fn ws_connections_are_released_on_drop() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_ws_connections: Some(2),
+            max_ws_connections_per_ip: Some(1),
+            ..Default::default()
+        });
+        let a = Some(IpAddr::from([10, 0, 0, 1]));
+        let b = Some(IpAddr::from([10, 0, 0, 2]));
+        let c = Some(IpAddr::from([10, 0, 0, 3]));
+        let first = limiter.acquire_ws_connection(a).unwrap();
+        assert!(limiter.acquire_ws_connection(a).is_none());
+        let _second = limiter.acquire_ws_connection(b).unwrap();
+        assert!(limiter.acquire_ws_connection(c).is_none());
+        drop(first);
+        assert!(limiter.acquire_ws_connection(a).is_some());
+    }
+}
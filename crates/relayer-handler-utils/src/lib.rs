@@ -15,7 +15,11 @@
 #![allow(clippy::large_enum_variant)]
 #![allow(missing_docs)]
 
-use serde::{Deserialize, Deserializer, Serialize};
+use core::fmt;
+use schemars::JsonSchema;
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::sync::mpsc;
 use webb::evm::ethers::abi::Address;
 use webb::evm::ethers::prelude::{ContractError, I256};
@@ -27,34 +31,139 @@ use webb_relayer_tx_relay_utils::{
     MixerRelayTransaction, VAnchorRelayTransaction,
 };
 
+mod schema;
+mod shared;
+
+pub use shared::{
+    CachedItemKind, FeaturesConfig, LeavesConsistency, LeavesSyncStatus,
+    ProposalStatus, RelayJob,
+};
+
 /// Representation for IP address response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct IpInformationResponse {
     pub ip: String,
 }
 
+/// The cached leaves of a merkle tree.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeavesCacheResponse {
+    /// The leaves, in the order of their insertion in the tree.
+    pub leaves: Vec<Vec<u8>>,
+    /// The last block the leaves were fetched up to.
+    pub last_queried_block: u64,
+    /// The last check of the cached leaves against the contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_status: Option<LeavesSyncStatus>,
+}
+
+/// The state of the leaves cache of a merkle tree, without its leaves.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeavesStatusResponse {
+    /// The number of cached leaves.
+    pub leaves_count: u64,
+    /// The last block the leaves were fetched up to.
+    pub last_queried_block: u64,
+    /// The last check of the cached leaves against the contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_status: Option<LeavesSyncStatus>,
+}
+
+/// The cached encrypted outputs of a VAnchor contract.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedOutputsCacheResponse {
+    /// The encrypted outputs, in the order of their insertion in the tree.
+    pub encrypted_outputs: Vec<Vec<u8>>,
+    /// The last block the encrypted outputs were fetched up to.
+    pub last_queried_block: u64,
+}
+
+/// The metrics of the relayer, in the prometheus text format.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerMetricResponse {
+    pub metrics: String,
+}
+
+/// A proposal submitted by the relayer to the DKG for signing.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalInfo {
+    /// The hex encoded hash of the proposal data.
+    pub data_hash: String,
+    pub nonce: u32,
+    pub src_chain_id: u64,
+    /// The hex encoded resource id the proposal is for.
+    pub target: String,
+    /// The hex encoded proposal data.
+    pub proposal: String,
+    pub status: ProposalStatus,
+    /// How many times the proposal was submitted.
+    pub attempts: u32,
+    /// The unix timestamp (in seconds) of the first submission.
+    pub submitted_at: u64,
+    /// The unix timestamp (in seconds) the proposal was seen signed at.
+    pub signed_at: Option<u64>,
+}
+
+/// The proposals submitted by the relayer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalsResponse {
+    pub proposals: Vec<ProposalInfo>,
+}
+
+/// The reply to a relay transaction submitted over HTTP.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayJobSubmittedResponse {
+    /// The id of the job relaying the transaction.
+    pub job_id: String,
+}
+
 /// A wrapper type around [`I256`] that implements a correct way for [`Serialize`] and [`Deserialize`].
 ///
 /// This supports the signed integer hex values that are not originally supported by the [`I256`] type.
-#[derive(Debug, Clone, Serialize)]
-#[serde(transparent)]
+#[derive(Debug, Clone)]
 pub struct WebbI256(pub I256);
 
+impl Serialize for WebbI256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // written the same way it is read, i.e. `-0x2a`.
+        let sign = if self.0.is_negative() { "-" } else { "" };
+        let (_, abs) = self.0.into_sign_and_abs();
+        serializer.serialize_str(&format!("{sign}{abs:#x}"))
+    }
+}
+
 impl<'de> Deserialize<'de> for WebbI256 {
     fn deserialize<D>(deserializer: D) -> Result<WebbI256, D::Error>
     where
         D: Deserializer<'de>,
     {
         let i128_str = String::deserialize(deserializer)?;
-        let i128_val =
-            I256::from_hex_str(&i128_str).map_err(serde::de::Error::custom)?;
+        // `I256::from_hex_str` only reads the digits, so the `0x` prefix
+        // following the sign is optional.
+        let (sign, digits) = match i128_str.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", i128_str.as_str()),
+        };
+        let digits = digits.strip_prefix("0x").unwrap_or(digits);
+        let i128_val = I256::from_hex_str(&format!("{sign}{digits}"))
+            .map_err(serde::de::Error::custom)?;
         Ok(WebbI256(i128_val))
     }
 }
 
 /// Type of Command to use
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Command {
     /// Substrate specific subcommand.
@@ -63,10 +172,164 @@ pub enum Command {
     Evm(EvmCommand),
     /// Ping?
     Ping(),
+    /// Subscribes to the status of a relay job submitted over HTTP.
+    Job(JobSubscription),
+    /// Subscribes to the leaves, encrypted outputs and nullifiers stored for
+    /// some anchors.
+    Subscribe(CacheSubscription),
+}
+
+/// A subscription to the status updates of a relay job.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSubscription {
+    /// The id of the job, as returned when it was submitted.
+    pub id: String,
+}
+
+/// A subscription to the items cached for some anchors.
+///
+/// The items already stored from the requested indices on are sent first,
+/// then the new ones as they are stored, so a client can resume from the
+/// next index it has not seen without missing any item.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSubscription {
+    /// The anchors to follow.
+    pub anchors: Vec<AnchorSubscription>,
+}
+
+/// The anchor of a [`CacheSubscription`], with the index to start each kind
+/// of item from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorSubscription {
+    /// The resource id of the anchor, as a hex string.
+    pub resource_id: String,
+    /// The index of the first leaf to send.
+    #[serde(default)]
+    pub leaves_from: u32,
+    /// The index of the first encrypted output to send.
+    #[serde(default)]
+    pub encrypted_outputs_from: u32,
+    /// The index of the first nullifier to send.
+    #[serde(default)]
+    pub nullifiers_from: u32,
+}
+
+/// The names of the [`Command`] variants, as they appear on the wire.
+const COMMANDS: &[&str] = &["substrate", "evm", "ping", "job", "subscribe"];
+
+/// Versions of the websocket protocol, selected by the client with the
+/// `version` query parameter when connecting, i.e. `/ws?version=2`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema,
+)]
+pub enum ProtocolVersion {
+    /// The commands sent over a connection are handled one after the other.
+    #[default]
+    #[serde(rename = "1")]
+    V1,
+    /// The commands sent over a connection are handled concurrently, and the
+    /// responses are told apart by the request id of their command.
+    #[serde(rename = "2")]
+    V2,
+}
+
+/// The query parameters of the websocket endpoint.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct WebSocketQuery {
+    /// The version of the protocol spoken over the connection.
+    #[serde(default)]
+    pub version: ProtocolVersion,
+}
+
+/// An id chosen by the client for a command, echoed back in every response
+/// to that command.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(u64),
+    String(String),
+}
+
+/// A [`Command`] received over the websocket, with its optional request id.
+///
+/// The request id sits next to the command, i.e. `{"requestId": 1, "ping": []}`,
+/// so a command without one is read the same as before.
+#[derive(Debug, Clone)]
+pub struct CommandRequest {
+    /// The id of the request, if the client set one.
+    pub request_id: Option<RequestId>,
+    /// The command to handle.
+    pub command: Command,
+}
+
+impl<'de> Deserialize<'de> for CommandRequest {
+    fn deserialize<D>(deserializer: D) -> Result<CommandRequest, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CommandRequestVisitor;
+
+        impl<'de> Visitor<'de> for CommandRequestVisitor {
+            type Value = CommandRequest;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a command with an optional requestId")
+            }
+
+            // The command is read straight from the map rather than buffered,
+            // so that the `u128` amounts of substrate commands are kept intact.
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut request_id = None;
+                let mut command = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = match key.as_str() {
+                        "requestId" => {
+                            request_id = map.next_value()?;
+                            continue;
+                        }
+                        "substrate" => Command::Substrate(map.next_value()?),
+                        "evm" => Command::Evm(map.next_value()?),
+                        "ping" => {
+                            map.next_value::<IgnoredAny>()?;
+                            Command::Ping()
+                        }
+                        "job" => Command::Job(map.next_value()?),
+                        "subscribe" => Command::Subscribe(map.next_value()?),
+                        other => {
+                            return Err(de::Error::unknown_variant(
+                                other, COMMANDS,
+                            ))
+                        }
+                    };
+                    if command.replace(value).is_some() {
+                        return Err(de::Error::custom(
+                            "expected a single command",
+                        ));
+                    }
+                }
+                let command = command
+                    .ok_or_else(|| de::Error::custom("missing command"))?;
+                Ok(CommandRequest {
+                    request_id,
+                    command,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(CommandRequestVisitor)
+    }
 }
 
 /// Enumerates the supported protocols for relaying transactions
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum CommandType<Id, P, R, E, I, B, A, T> {
     /// Webb Mixer.
@@ -76,7 +339,7 @@ pub enum CommandType<Id, P, R, E, I, B, A, T> {
 }
 
 /// Enumerates the command responses
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum CommandResponse {
     /// Pong?
@@ -85,14 +348,157 @@ pub enum CommandResponse {
     Network(NetworkStatus),
     /// Withdrawal Status
     Withdraw(WithdrawStatus),
+    /// The latest status of a relay job.
+    Job(RelayJob),
+    /// Items newly stored for a subscribed anchor.
+    CacheUpdate(CacheUpdate),
     /// An error occurred
     Error(String),
     /// Unsupported feature or yet to be implemented.
     #[allow(unused)]
-    Unimplemented(&'static str),
+    Unimplemented(String),
+}
+
+/// Items of one kind stored for a subscribed anchor, with consecutive indices
+/// starting at `from`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUpdate {
+    /// The resource id of the anchor, as a hex string.
+    pub resource_id: String,
+    /// The kind of the items.
+    pub kind: CachedItemKind,
+    /// The index of the first item.
+    pub from: u32,
+    /// The items, in the order of their indices.
+    pub items: Vec<Vec<u8>>,
+}
+
+/// A [`CommandResponse`] sent over the websocket, with the request id of the
+/// command it answers.
+///
+/// The request id is left out when the command had none, so the response is
+/// the same as a bare [`CommandResponse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResponseMessage {
+    /// The id of the request this is a response to.
+    pub request_id: Option<RequestId>,
+    /// The response itself.
+    pub response: CommandResponse,
+}
+
+impl Serialize for CommandResponseMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(request_id) = &self.request_id {
+            map.serialize_entry("requestId", request_id)?;
+        }
+        match &self.response {
+            CommandResponse::Pong() => map.serialize_entry("pong", &[(); 0])?,
+            CommandResponse::Network(v) => map.serialize_entry("network", v)?,
+            CommandResponse::Withdraw(v) => {
+                map.serialize_entry("withdraw", v)?
+            }
+            CommandResponse::Job(v) => map.serialize_entry("job", v)?,
+            CommandResponse::CacheUpdate(v) => {
+                map.serialize_entry("cacheUpdate", v)?
+            }
+            CommandResponse::Error(v) => map.serialize_entry("error", v)?,
+            CommandResponse::Unimplemented(v) => {
+                map.serialize_entry("unimplemented", v)?
+            }
+        }
+        map.end()
+    }
+}
+
+/// The names of the [`CommandResponse`] variants, as they appear on the wire.
+const RESPONSES: &[&str] = &[
+    "pong",
+    "network",
+    "withdraw",
+    "job",
+    "cacheUpdate",
+    "error",
+    "unimplemented",
+];
+
+impl<'de> Deserialize<'de> for CommandResponseMessage {
+    fn deserialize<D>(
+        deserializer: D,
+    ) -> Result<CommandResponseMessage, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CommandResponseMessageVisitor;
+
+        impl<'de> Visitor<'de> for CommandResponseMessageVisitor {
+            type Value = CommandResponseMessage;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a response with an optional requestId")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut request_id = None;
+                let mut response = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = match key.as_str() {
+                        "requestId" => {
+                            request_id = map.next_value()?;
+                            continue;
+                        }
+                        "pong" => {
+                            map.next_value::<IgnoredAny>()?;
+                            CommandResponse::Pong()
+                        }
+                        "network" => {
+                            CommandResponse::Network(map.next_value()?)
+                        }
+                        "withdraw" => {
+                            CommandResponse::Withdraw(map.next_value()?)
+                        }
+                        "job" => CommandResponse::Job(map.next_value()?),
+                        "cacheUpdate" => {
+                            CommandResponse::CacheUpdate(map.next_value()?)
+                        }
+                        "error" => CommandResponse::Error(map.next_value()?),
+                        "unimplemented" => {
+                            CommandResponse::Unimplemented(map.next_value()?)
+                        }
+                        other => {
+                            return Err(de::Error::unknown_variant(
+                                other, RESPONSES,
+                            ))
+                        }
+                    };
+                    if response.replace(value).is_some() {
+                        return Err(de::Error::custom(
+                            "expected a single response",
+                        ));
+                    }
+                }
+                let response = response
+                    .ok_or_else(|| de::Error::custom("missing response"))?;
+                Ok(CommandResponseMessage {
+                    request_id,
+                    response,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(CommandResponseMessageVisitor)
+    }
 }
+
 /// Enumerates the network status response of the relayer
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum NetworkStatus {
     /// Relayer is connecting to the network.
@@ -114,7 +520,7 @@ pub enum NetworkStatus {
     InvalidRelayerAddress,
 }
 /// Enumerates the withdraw status response of the relayer
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum WithdrawStatus {
     /// The transaction is sent to the network.
@@ -123,12 +529,14 @@ pub enum WithdrawStatus {
     Submitted {
         /// The transaction hash.
         #[serde(rename = "txHash")]
+        #[schemars(with = "String")]
         tx_hash: H256,
     },
     /// The transaction is in the block.
     Finalized {
         /// The transaction hash.
         #[serde(rename = "txHash")]
+        #[schemars(with = "String")]
         tx_hash: H256,
     },
     /// Valid transaction.
@@ -205,3 +613,118 @@ pub fn into_withdraw_error<M: Middleware>(
 
     WithdrawStatus::Errored { reason, code }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn command_request_id_is_optional() {
+        let request: CommandRequest =
+            serde_json::from_str(r#"{"ping": []}"#).unwrap();
+        assert!(request.request_id.is_none());
+        assert!(matches!(request.command, Command::Ping()));
+
+        let request: CommandRequest =
+            serde_json::from_str(r#"{"requestId": "a", "job": {"id": "42"}}"#)
+                .unwrap();
+        assert_eq!(request.request_id, Some(RequestId::String("a".into())));
+        assert!(matches!(request.command, Command::Job(sub) if sub.id == "42"));
+
+        let request: CommandRequest = serde_json::from_str(
+            r#"{"subscribe": {"anchors": [
+                {"resourceId": "0x01", "leavesFrom": 3}
+            ]}}"#,
+        )
+        .unwrap();
+        match request.command {
+            Command::Subscribe(sub) => {
+                assert_eq!(sub.anchors[0].resource_id, "0x01");
+                assert_eq!(sub.anchors[0].leaves_from, 3);
+                assert_eq!(sub.anchors[0].nullifiers_from, 0);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        assert!(
+            serde_json::from_str::<CommandRequest>(r#"{"requestId": 1}"#)
+                .is_err()
+        );
+        assert!(serde_json::from_str::<CommandRequest>(
+            r#"{"ping": [], "job": {"id": "42"}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn command_response_echoes_request_id() {
+        let message = CommandResponseMessage {
+            request_id: Some(RequestId::Number(7)),
+            response: CommandResponse::Pong(),
+        };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"requestId":7,"pong":[]}"#
+        );
+
+        let message = CommandResponseMessage {
+            request_id: None,
+            response: CommandResponse::Error("oops".into()),
+        };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"error":"oops"}"#
+        );
+    }
+
+    #[test]
+    fn command_response_round_trips() {
+        let messages = [
+            CommandResponseMessage {
+                request_id: Some(RequestId::String("a".into())),
+                response: CommandResponse::Pong(),
+            },
+            CommandResponseMessage {
+                request_id: None,
+                response: CommandResponse::Withdraw(
+                    WithdrawStatus::Finalized {
+                        tx_hash: H256::repeat_byte(1),
+                    },
+                ),
+            },
+            CommandResponseMessage {
+                request_id: Some(RequestId::Number(2)),
+                response: CommandResponse::Network(NetworkStatus::Failed {
+                    reason: "oops".into(),
+                }),
+            },
+            CommandResponseMessage {
+                request_id: Some(RequestId::Number(3)),
+                response: CommandResponse::CacheUpdate(CacheUpdate {
+                    resource_id: "0x01".into(),
+                    kind: CachedItemKind::Nullifiers,
+                    from: 4,
+                    items: vec![vec![1; 32], vec![2; 32]],
+                }),
+            },
+        ];
+        for message in messages {
+            let json = serde_json::to_string(&message).unwrap();
+            let read: CommandResponseMessage =
+                serde_json::from_str(&json).unwrap();
+            assert_eq!(read, message);
+        }
+    }
+
+    #[test]
+    fn signed_amounts_round_trip() {
+        for amount in [-42i64, 0, 42] {
+            let value = WebbI256(I256::from(amount));
+            let json = serde_json::to_string(&value).unwrap();
+            let read: WebbI256 = serde_json::from_str(&json).unwrap();
+            assert_eq!(read.0, value.0);
+        }
+        let value = WebbI256(I256::from(-42));
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#""-0x2a""#);
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The JSON schemas of the websocket messages.
+//!
+//! The commands and their responses are read and written by hand, and the
+//! commands are generic over the types of each chain, so their schemas are
+//! written here instead of being derived.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+
+use crate::{
+    CacheSubscription, CacheUpdate, Command, CommandRequest,
+    CommandResponseMessage, CommandType, JobSubscription, NetworkStatus,
+    RelayJob, RequestId, WithdrawStatus,
+};
+
+/// A hex string, i.e. an address, a hash, an amount or raw bytes.
+struct Hex;
+
+impl JsonSchema for Hex {
+    fn schema_name() -> String {
+        "Hex".into()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("^(0x)?[0-9a-fA-F]*$")
+    }
+}
+
+/// A hex string of a signed amount, negative amounts start with a `-`.
+struct SignedHex;
+
+impl JsonSchema for SignedHex {
+    fn schema_name() -> String {
+        "SignedHex".into()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("^-?(0x)?[0-9a-fA-F]*$")
+    }
+}
+
+/// An SS58 encoded substrate account.
+struct Ss58;
+
+impl JsonSchema for Ss58 {
+    fn schema_name() -> String {
+        "Ss58".into()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// [`crate::EvmCommand`], with its ethereum types written as hex strings.
+type EvmCommandType = CommandType<Hex, Hex, Hex, Hex, Hex, Hex, SignedHex, Hex>;
+
+/// [`crate::SubstrateCommand`], with its accounts written in SS58.
+type SubstrateCommandType =
+    CommandType<u32, Vec<u8>, Vec<[u8; 32]>, [u8; 32], Ss58, u128, i128, u32>;
+
+/// The schema of an evm relay command.
+struct EvmCommandSchema;
+
+impl JsonSchema for EvmCommandSchema {
+    fn schema_name() -> String {
+        "EvmCommand".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        inline_schema::<EvmCommandType>(gen)
+    }
+}
+
+/// The schema of a substrate relay command.
+struct SubstrateCommandSchema;
+
+impl JsonSchema for SubstrateCommandSchema {
+    fn schema_name() -> String {
+        "SubstrateCommand".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        inline_schema::<SubstrateCommandType>(gen)
+    }
+}
+
+impl JsonSchema for Command {
+    fn schema_name() -> String {
+        "Command".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let variants = command_variants(gen);
+        message_schema(None, "A command to the relayer.", variants)
+    }
+}
+
+impl JsonSchema for CommandRequest {
+    fn schema_name() -> String {
+        "CommandRequest".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let variants = command_variants(gen);
+        message_schema(
+            Some(gen.subschema_for::<RequestId>()),
+            "A command sent over the websocket, with an optional request id.",
+            variants,
+        )
+    }
+}
+
+impl JsonSchema for CommandResponseMessage {
+    fn schema_name() -> String {
+        "CommandResponse".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let variants = vec![
+            ("pong", empty_array_schema()),
+            ("network", gen.subschema_for::<NetworkStatus>()),
+            ("withdraw", gen.subschema_for::<WithdrawStatus>()),
+            ("job", gen.subschema_for::<RelayJob>()),
+            ("cacheUpdate", gen.subschema_for::<CacheUpdate>()),
+            ("error", gen.subschema_for::<String>()),
+            ("unimplemented", gen.subschema_for::<String>()),
+        ];
+        message_schema(
+            Some(gen.subschema_for::<RequestId>()),
+            "A response sent over the websocket, with the request id of the \
+             command it answers, if it had one.",
+            variants,
+        )
+    }
+}
+
+/// Generates the schema of `T` with all of its subschemas inlined, so that
+/// the generic types it is made of do not end up in the definitions.
+fn inline_schema<T: JsonSchema>(gen: &SchemaGenerator) -> Schema {
+    let mut settings = gen.settings().clone();
+    settings.inline_subschemas = true;
+    T::json_schema(&mut settings.into_generator())
+}
+
+/// The variants of a [`Command`], named as they are on the wire.
+fn command_variants(gen: &mut SchemaGenerator) -> Vec<(&'static str, Schema)> {
+    vec![
+        ("substrate", gen.subschema_for::<SubstrateCommandSchema>()),
+        ("evm", gen.subschema_for::<EvmCommandSchema>()),
+        ("ping", empty_array_schema()),
+        ("job", gen.subschema_for::<JobSubscription>()),
+        ("subscribe", gen.subschema_for::<CacheSubscription>()),
+    ]
+}
+
+/// The schema of a message holding exactly one of the given variants, next
+/// to an optional `requestId` if there is one, i.e. `{"requestId": 1,
+/// "ping": []}` for the websocket commands.
+fn message_schema(
+    request_id: Option<Schema>,
+    description: &str,
+    variants: Vec<(&str, Schema)>,
+) -> Schema {
+    let one_of = variants
+        .into_iter()
+        .map(|(name, schema)| {
+            let mut variant = SchemaObject {
+                instance_type: Some(InstanceType::Object.into()),
+                ..Default::default()
+            };
+            let object = variant.object();
+            if let Some(request_id) = &request_id {
+                object
+                    .properties
+                    .insert("requestId".to_owned(), request_id.clone());
+            }
+            object.properties.insert(name.to_owned(), schema);
+            object.required.insert(name.to_owned());
+            object.additional_properties = Some(Box::new(false.into()));
+            variant.into()
+        })
+        .collect();
+    let mut schema = SchemaObject::default();
+    schema.metadata().description = Some(description.to_owned());
+    schema.subschemas().one_of = Some(one_of);
+    schema.into()
+}
+
+/// The schema of a string matching the given pattern.
+fn string_schema(pattern: &str) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    };
+    schema.string().pattern = Some(pattern.to_owned());
+    schema.into()
+}
+
+/// The schema of an empty array, which is how the `ping` and `pong` messages
+/// are written.
+fn empty_array_schema() -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        ..Default::default()
+    };
+    schema.array().max_items = Some(0);
+    schema.into()
+}
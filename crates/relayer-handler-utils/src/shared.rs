@@ -0,0 +1,192 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The types of the relayer API that the store and the configuration of the
+//! relayer use as well, so clients get them without depending on either.
+
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The state of a relay job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayJob {
+    /// The hex encoded id of the job.
+    pub id: String,
+    /// The unix timestamp (in seconds) the job was submitted at.
+    pub submitted_at: u64,
+    /// The unix timestamp (in seconds) of the last update of the job.
+    pub updated_at: u64,
+    /// Whether the relayer is done with the job, successfully or not.
+    pub finished: bool,
+    /// The latest response of the relayer to the job command.
+    pub status: Option<serde_json::Value>,
+    /// The hash of the relayed transaction, once it was submitted.
+    pub tx_hash: Option<String>,
+    /// Why the job failed, if it did.
+    pub error: Option<String>,
+}
+
+impl RelayJob {
+    /// A new job, submitted at the given unix timestamp (in seconds).
+    pub fn new(id: String, submitted_at: u64) -> Self {
+        Self {
+            id,
+            submitted_at,
+            updated_at: submitted_at,
+            finished: false,
+            status: None,
+            tx_hash: None,
+            error: None,
+        }
+    }
+}
+
+/// The lifecycle status of a proposal sent to the DKG for signing.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ProposalStatus {
+    /// Sent to the DKG, waiting for the `ProposalSigned` event.
+    Pending,
+    /// Signed by the DKG.
+    Signed,
+    /// Was not signed after all the resubmissions.
+    Expired,
+}
+
+/// The kinds of items cached for an anchor.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum CachedItemKind {
+    /// The leaves of its merkle tree, by leaf index.
+    Leaves,
+    /// The encrypted outputs of its commitments, by leaf index.
+    EncryptedOutputs,
+    /// The nullifiers spent on it, indexed in the order they were spent.
+    Nullifiers,
+}
+
+/// The result of checking the cached leaves of an anchor against its contract.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct LeavesConsistency {
+    /// The number of leaves in the cache.
+    pub cached: u32,
+    /// The number of leaves in the contract (its `nextIndex`).
+    pub expected: u32,
+    /// The (inclusive) ranges of leaf indices missing from the cache.
+    pub missing: Vec<(u32, u32)>,
+    /// The indices holding the same leaf as a lower index.
+    pub duplicates: Vec<u32>,
+    /// The cached indices the contract does not have (yet).
+    pub unexpected: Vec<u32>,
+}
+
+impl LeavesConsistency {
+    /// Checks the cached `leaves`, ordered by index, against the `expected`
+    /// number of leaves of the contract.
+    pub fn check(leaves: &[(u32, Vec<u8>)], expected: u32) -> Self {
+        let mut missing = Vec::new();
+        let mut duplicates = Vec::new();
+        let mut unexpected = Vec::new();
+        let mut seen = HashMap::with_capacity(leaves.len());
+        let mut next = 0u32;
+        for (index, leaf) in leaves {
+            if *index >= expected {
+                unexpected.push(*index);
+                continue;
+            }
+            if *index > next {
+                missing.push((next, index - 1));
+            }
+            next = index + 1;
+            if seen.insert(leaf.as_slice(), *index).is_some() {
+                duplicates.push(*index);
+            }
+        }
+        if next < expected {
+            missing.push((next, expected - 1));
+        }
+        Self {
+            cached: leaves.len() as u32,
+            expected,
+            missing,
+            duplicates,
+            unexpected,
+        }
+    }
+
+    /// Returns `true` if the cache has exactly the leaves of the contract.
+    pub fn is_consistent(&self) -> bool {
+        self.cached == self.expected
+            && self.missing.is_empty()
+            && self.duplicates.is_empty()
+            && self.unexpected.is_empty()
+    }
+
+    /// Returns the (inclusive) ranges of leaf indices that should be fetched
+    /// again from the contract, ordered by index.
+    ///
+    /// A duplicated leaf is fetched again as well, since there is no telling
+    /// which one of its indices is the right one.
+    pub fn indices_to_refetch(&self) -> Vec<(u32, u32)> {
+        let mut ranges = self.missing.clone();
+        ranges.extend(self.duplicates.iter().map(|index| (*index, *index)));
+        ranges.sort_unstable();
+        ranges
+    }
+}
+
+/// The sync status of the leaves cache of an anchor, as of its last check.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeavesSyncStatus {
+    /// The block the cache was checked at.
+    pub checked_at_block: u64,
+    /// The result of the check, before fetching the missing leaves again.
+    pub consistency: LeavesConsistency,
+    /// The number of leaves fetched again from the contract.
+    pub refetched: u32,
+    /// Whether the cache is consistent with the contract after the check.
+    pub healthy: bool,
+}
+
+/// FeaturesConfig is the configuration for running relayer with option.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct FeaturesConfig {
+    /// Enable data quering for leafs
+    pub data_query: bool,
+    /// Enable governance relaying
+    pub governance_relay: bool,
+    /// Enable private tx relaying
+    pub private_tx_relay: bool,
+}
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            data_query: true,
+            governance_relay: true,
+            private_tx_relay: true,
+        }
+    }
+}
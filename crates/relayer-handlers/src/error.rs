@@ -0,0 +1,175 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors of the relayer API.
+//!
+//! Every failed request is replied with the HTTP status of its [`ErrorCode`],
+//! and a JSON body such as `{"code": "UNSUPPORTED_CHAIN", "message": "Unsupported Chain: 1"}`.
+
+use std::convert::Infallible;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::Reply;
+
+/// The machine readable codes of the errors of the relayer API, they never
+/// change once released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The request is malformed, e.g. an invalid parameter or body.
+    InvalidRequest,
+    /// The chain is not configured on the relayer.
+    UnsupportedChain,
+    /// The contract is not configured on the relayer.
+    UnsupportedContract,
+    /// The feature is disabled on the relayer, or for the contract.
+    FeatureDisabled,
+    /// The admin token is missing or invalid.
+    Unauthorized,
+    /// The requested resource does not exist.
+    NotFound,
+    /// The route does not support the method of the request.
+    MethodNotAllowed,
+    /// The request conflicts with the current state of the resource.
+    Conflict,
+    /// The body of the request is too large.
+    PayloadTooLarge,
+    /// The client sent too many requests, it should slow down.
+    RateLimited,
+    /// The relayer failed to read from or write to its store.
+    StoreError,
+    /// The relayer failed for another reason.
+    InternalError,
+}
+
+impl ErrorCode {
+    /// The HTTP status the errors with this code are replied with.
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest
+            | ErrorCode::UnsupportedChain
+            | ErrorCode::UnsupportedContract => StatusCode::BAD_REQUEST,
+            ErrorCode::FeatureDisabled => StatusCode::FORBIDDEN,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::StoreError | ErrorCode::InternalError => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+/// The error of a relayer API request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    /// What went wrong, for clients to act on.
+    pub code: ErrorCode,
+    /// What went wrong, for humans to read.
+    pub message: String,
+}
+
+impl ApiError {
+    /// Creates a new error with the given code.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// An error of the store, it is logged since the request is not the
+    /// cause of it.
+    pub fn store(context: &str, error: webb_relayer_utils::Error) -> Self {
+        tracing::error!(error = %error, "{}", context);
+        Self::new(ErrorCode::StoreError, format!("{context}: {error}"))
+    }
+}
+
+impl Reply for ApiError {
+    fn into_response(self) -> warp::reply::Response {
+        let status = self.code.status();
+        warp::reply::with_status(warp::reply::json(&self), status)
+            .into_response()
+    }
+}
+
+/// The result of a relayer API request.
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Replies with the value of a request, or with its error.
+pub fn api_reply<T: Reply>(
+    result: ApiResult<T>,
+) -> Result<warp::reply::Response, Infallible> {
+    Ok(match result {
+        Ok(reply) => reply.into_response(),
+        Err(error) => error.into_response(),
+    })
+}
+
+/// Rejection of the requests over the rate limits of their client.
+#[derive(Debug)]
+pub struct RateLimited;
+
+impl warp::reject::Reject for RateLimited {}
+
+/// Rejection of the admin requests without a valid token.
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Turns the rejections of the requests that matched no route into errors.
+///
+/// It never rejects, the error type only keeps the filters it recovers
+/// servable the same way as the others.
+pub async fn handle_rejection(
+    err: warp::Rejection,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use warp::reject;
+
+    let error = if err.find::<RateLimited>().is_some() {
+        ApiError::new(ErrorCode::RateLimited, "Too many requests, slow down")
+    } else if err.find::<Unauthorized>().is_some() {
+        ApiError::new(ErrorCode::Unauthorized, "Missing or invalid admin token")
+    } else if err.is_not_found() {
+        ApiError::new(ErrorCode::NotFound, "Not found")
+    } else if let Some(e) = err.find::<reject::MethodNotAllowed>() {
+        ApiError::new(ErrorCode::MethodNotAllowed, e.to_string())
+    } else if let Some(e) = err.find::<reject::PayloadTooLarge>() {
+        ApiError::new(ErrorCode::PayloadTooLarge, e.to_string())
+    } else if let Some(e) = err.find::<warp::body::BodyDeserializeError>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else if let Some(e) = err.find::<reject::InvalidQuery>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else if let Some(e) = err.find::<reject::InvalidHeader>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else if let Some(e) = err.find::<reject::MissingHeader>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else if let Some(e) = err.find::<reject::LengthRequired>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else if let Some(e) = err.find::<reject::UnsupportedMediaType>() {
+        ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+    } else {
+        tracing::warn!("Unhandled rejection: {:?}", err);
+        ApiError::new(ErrorCode::InternalError, "Internal error")
+    };
+    Ok(error)
+}
@@ -16,16 +16,15 @@
 
 #![allow(clippy::large_enum_variant)]
 #![warn(missing_docs)]
-use std::collections::HashMap;
 use std::convert::Infallible;
-use std::error::Error;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use ethereum_types::Address;
 use futures::prelude::*;
-use serde::Serialize;
-use tokio::sync::mpsc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
 use tokio_stream::wrappers::ReceiverStream;
 use warp::ws::Message;
 use webb::evm::ethers::{
@@ -36,91 +35,160 @@ use webb::substrate::subxt::ext::sp_core::Pair;
 use webb_proposals::{
     ResourceId, SubstrateTargetSystem, TargetSystem, TypedChainId,
 };
+use webb_relayer_config::WebbRelayerConfig;
+use webb_relayer_context::control::ServiceControls;
+use webb_relayer_context::rate_limit::{RouteGroup, WsConnectionGuard};
 use webb_relayer_context::RelayerContext;
 use webb_relayer_handler_utils::{
-    Command, CommandResponse, CommandStream, CommandType, EvmCommand,
-    IpInformationResponse, SubstrateCommand,
+    CacheSubscription, CacheUpdate, Command, CommandRequest, CommandResponse,
+    CommandResponseMessage, CommandStream, CommandType,
+    EncryptedOutputsCacheResponse, EvmCommand, IpInformationResponse,
+    LeavesCacheResponse, LeavesStatusResponse, NetworkStatus, ProposalInfo,
+    ProposalsResponse, ProtocolVersion, RelayJobSubmittedResponse,
+    RelayerMetricResponse, SubstrateCommand, WithdrawStatus,
+};
+use webb_relayer_store::dead_letter::{
+    discard_dead_letter, list_dead_letters, retry_dead_letter,
+};
+use webb_relayer_store::queue_admin::{list_queue_items, purge_queue_items};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::{
+    CacheSubscriptionStore, CachedItemKind, EncryptedOutputCacheStore,
+    HistoryStore, JobStore, LeafCacheStore, LeavesSyncStatus,
+    LeavesSyncStatuses, ProposalStatus, ProposalStore, QueueKey, QueuedItem,
+    RelayJob, SubmittedProposal,
 };
-use webb_relayer_store::{EncryptedOutputCacheStore, LeafCacheStore};
 use webb_relayer_tx_relay::evm::vanchor::handle_vanchor_relay_tx;
 use webb_relayer_tx_relay::substrate::mixer::handle_substrate_mixer_relay_tx;
 use webb_relayer_tx_relay::substrate::vanchor::handle_substrate_vanchor_relay_tx;
 use webb_relayer_utils::metric::Metrics;
 
+pub mod error;
+pub mod openapi;
+#[cfg(test)]
+mod tests;
+
+pub use error::{
+    api_reply, handle_rejection, ApiError, ApiResult, ErrorCode, RateLimited,
+    Unauthorized,
+};
+
 /// Sets up a websocket connection.
 ///
+/// With the first version of the protocol the commands are handled one after
+/// the other, with the second one up to `ws.max-concurrent-commands` of them are
+/// handled at the same time and their responses are interleaved.
+///
 /// Returns `Ok(())` on success
 ///
 /// # Arguments
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
+/// * `version` - The version of the protocol spoken by the client
 /// * `stream` - Websocket stream
 pub async fn accept_connection(
     ctx: &RelayerContext,
+    store: Arc<webb_relayer_store::AnyStore>,
+    version: ProtocolVersion,
     stream: warp::ws::WebSocket,
 ) -> webb_relayer_utils::Result<()> {
-    let (mut tx, mut rx) = stream.split();
-
+    let (tx, mut rx) = stream.split();
+    let max_concurrent_commands = match version {
+        ProtocolVersion::V1 => 1,
+        ProtocolVersion::V2 => ctx.config.ws.max_concurrent_commands.max(1),
+    };
+    let permits = Arc::new(Semaphore::new(max_concurrent_commands));
+    // the responses to all the commands of this connection are sent through
+    // this channel, so that they can be interleaved over the websocket.
+    let (res_tx, res_rx) = mpsc::channel(50);
+    let responses = ReceiverStream::new(res_rx)
+        .map(|v: CommandResponseMessage| {
+            serde_json::to_string(&v).expect("bad value")
+        })
+        .inspect(|v| tracing::trace!("Sending: {}", v))
+        .map(Message::text)
+        .map(Result::Ok)
+        .forward(tx)
+        .map_err(|_| webb_relayer_utils::Error::FailedToSendResponse);
     // Wait for client to send over text (such as relay transaction requests)
-    while let Some(msg) = rx.try_next().await? {
-        if let Ok(text) = msg.to_str() {
-            handle_text(ctx, text, &mut tx).await?;
+    let requests = async move {
+        while let Some(msg) = rx.try_next().await? {
+            if let Ok(text) = msg.to_str() {
+                // a new command is only read once there is room for it, which
+                // keeps the commands of a version 1 client in order.
+                let permit = Arc::clone(&permits)
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                let ctx = ctx.clone();
+                let store = store.clone();
+                let text = text.to_owned();
+                let res_tx = res_tx.clone();
+                tokio::spawn(async move {
+                    handle_text(&ctx, store, &text, res_tx).await;
+                    drop(permit);
+                });
+            }
         }
-    }
-    Ok(())
+        webb_relayer_utils::Result::Ok(())
+    };
+    let (received, sent) = future::join(requests, responses).await;
+    received?;
+    sent
 }
-/// Sets up a websocket channels for message sending.
+/// Handles a single message received over the websocket.
 ///
 /// This is primarily used for transaction relaying. The intention is
 /// that a user will send formatted relay requests to the relayer using
 /// the websocket. The command will be extracted and sent to `handle_cmd`
-/// if successfully deserialized.
-///
-/// Returns `Ok(())` on success
+/// if successfully deserialized, and every response to it is tagged with the
+/// request id of the command, if it has one.
 ///
 /// # Arguments
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
 /// * `v` - The text (usually in a JSON form) message to be handled.
-/// * `tx` - A mutable Trait implementation of the `warp::ws::Sender` trait
-pub async fn handle_text<TX>(
+/// * `tx` - The channel the responses are sent to
+pub async fn handle_text(
     ctx: &RelayerContext,
+    store: Arc<webb_relayer_store::AnyStore>,
     v: &str,
-    tx: &mut TX,
-) -> webb_relayer_utils::Result<()>
-where
-    TX: Sink<Message> + Unpin,
-    TX::Error: Error + Send + Sync + 'static,
-{
-    // for every connection, we create a new channel, where we will use to send messages
-    // over it.
-    let (my_tx, my_rx) = mpsc::channel(50);
-    let res_stream = ReceiverStream::new(my_rx);
-    match serde_json::from_str(v) {
-        Ok(cmd) => {
-            handle_cmd(ctx.clone(), cmd, my_tx).await;
-            // Send back the response, usually a transaction hash
-            // from processing the transaction relaying command.
-            res_stream
-                .fuse()
-                .map(|v| serde_json::to_string(&v).expect("bad value"))
-                .inspect(|v| tracing::trace!("Sending: {}", v))
-                .map(Message::text)
-                .map(Result::Ok)
-                .forward(tx)
-                .map_err(|_| webb_relayer_utils::Error::FailedToSendResponse)
-                .await?;
+    tx: mpsc::Sender<CommandResponseMessage>,
+) {
+    match serde_json::from_str::<CommandRequest>(v) {
+        Ok(request) => {
+            let (my_tx, my_rx) = mpsc::channel(50);
+            let request_id = request.request_id;
+            // Send back the responses, usually a transaction hash
+            // from processing the transaction relaying command, while
+            // the command is being handled.
+            let responses = ReceiverStream::new(my_rx).for_each(|response| {
+                let message = CommandResponseMessage {
+                    request_id: request_id.clone(),
+                    response,
+                };
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(message).await;
+                }
+            });
+            future::join(
+                handle_cmd(ctx.clone(), store, request.command, my_tx),
+                responses,
+            )
+            .await;
         }
         Err(e) => {
             tracing::warn!("Got invalid payload: {:?}", e);
-            let error = CommandResponse::Error(e.to_string());
-            let value = serde_json::to_string(&error)?;
-            tx.send(Message::text(value))
-                .map_err(|_| webb_relayer_utils::Error::FailedToSendResponse)
-                .await?;
+            let message = CommandResponseMessage {
+                request_id: None,
+                response: CommandResponse::Error(e.to_string()),
+            };
+            let _ = tx.send(message).await;
         }
     };
-    Ok(())
 }
 
 /// Handles the `ip` address response
@@ -133,9 +201,7 @@ where
 pub async fn handle_ip_info(
     ip: Option<IpAddr>,
 ) -> Result<impl warp::Reply, Infallible> {
-    Ok(warp::reply::json(&IpInformationResponse {
-        ip: ip.unwrap().to_string(),
-    }))
+    api_reply(client_ip(ip))
 }
 /// Handles the socket address response
 ///
@@ -147,8 +213,19 @@ pub async fn handle_ip_info(
 pub async fn handle_socket_info(
     ip: Option<SocketAddr>,
 ) -> Result<impl warp::Reply, Infallible> {
+    api_reply(client_ip(ip.map(|addr| addr.ip())))
+}
+
+/// Replies with the IP address of the client, if it is known.
+fn client_ip(ip: Option<IpAddr>) -> ApiResult<impl warp::Reply> {
+    let ip = ip.ok_or_else(|| {
+        ApiError::new(
+            ErrorCode::InternalError,
+            "The address of the client is unknown",
+        )
+    })?;
     Ok(warp::reply::json(&IpInformationResponse {
-        ip: ip.unwrap().ip().to_string(),
+        ip: ip.to_string(),
     }))
 }
 /// Handles relayer configuration requests
@@ -161,12 +238,6 @@ pub async fn handle_socket_info(
 pub async fn handle_relayer_info(
     ctx: Arc<RelayerContext>,
 ) -> Result<impl warp::Reply, Infallible> {
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct RelayerInformationResponse {
-        #[serde(flatten)]
-        config: webb_relayer_config::WebbRelayerConfig,
-    }
     // clone the original config, to update it with accounts.
     let mut config = ctx.config.clone();
 
@@ -209,116 +280,179 @@ pub async fn handle_relayer_info(
 /// * `contract` - An address of the contract to query
 /// * `ctx` - RelayContext reference that holds the configuration
 pub async fn handle_leaves_cache_evm(
-    store: Arc<webb_relayer_store::sled::SledStore>,
+    store: Arc<webb_relayer_store::AnyStore>,
     chain_id: u32,
     contract: Address,
     ctx: Arc<RelayerContext>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let config = ctx.config.clone();
-
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct LeavesCacheResponse {
-        leaves: Vec<Vec<u8>>,
-        last_queried_block: u64,
-    }
-    // Unsupported feature response
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct UnsupportedFeature {
-        message: String,
-    }
-
-    // check if data query is enabled for relayer
-    if !config.features.data_query {
-        tracing::warn!("Data query is not enabled for relayer.");
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: "Data query is not enabled for relayer.".to_string(),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
-        ));
-    }
+    api_reply(leaves_cache_evm(
+        store.as_ref(),
+        &ctx.config,
+        &ctx.leaves_sync,
+        chain_id,
+        contract,
+    ))
+}
 
+/// Makes sure the data of the given evm contract can be queried.
+fn check_evm_data_query(
+    config: &WebbRelayerConfig,
+    chain_id: u32,
+    contract: Address,
+) -> ApiResult<()> {
+    check_data_query(config)?;
     // check if chain is supported
-    let chain = match ctx.config.evm.get(&chain_id.to_string()) {
-        Some(v) => v,
-        None => {
-            tracing::warn!("Unsupported Chain: {}", chain_id);
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!("Unsupported Chain: {chain_id}"),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
-        }
-    };
-
-    let supported_contracts: HashMap<_, _> = chain
+    let chain = config.evm.get(&chain_id.to_string()).ok_or_else(|| {
+        tracing::warn!("Unsupported Chain: {}", chain_id);
+        ApiError::new(
+            ErrorCode::UnsupportedChain,
+            format!("Unsupported Chain: {chain_id}"),
+        )
+    })?;
+    // check if contract is supported
+    let event_watcher_config = chain
         .contracts
         .iter()
-        .cloned()
-        .filter_map(|c| match c {
+        .find_map(|c| match c {
             webb_relayer_config::evm::Contract::VAnchor(c)
-            | webb_relayer_config::evm::Contract::OpenVAnchor(c) => {
-                Some((c.common.address, c.events_watcher))
+            | webb_relayer_config::evm::Contract::OpenVAnchor(c)
+                if c.common.address == contract =>
+            {
+                Some(&c.events_watcher)
             }
             _ => None,
         })
-        .collect();
-
-    // check if contract is supported
-    let event_watcher_config = match supported_contracts.get(&contract) {
-        Some(config) => config,
-        None => {
+        .ok_or_else(|| {
             tracing::warn!(
                 "Unsupported Contract: {:?} for chaind : {}",
                 contract,
                 chain_id
             );
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!(
-                        "Unsupported Contract: {} for chaind : {}",
-                        contract, chain_id
-                    ),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
-        }
-    };
+            ApiError::new(
+                ErrorCode::UnsupportedContract,
+                format!(
+                    "Unsupported Contract: {} for chaind : {}",
+                    contract, chain_id
+                ),
+            )
+        })?;
     // check if data query is enabled for contract
     if !event_watcher_config.enable_data_query {
         tracing::warn!("Enbable data query for contract : ({})", contract);
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: format!(
-                    "Enbable data query for contract : ({})",
-                    contract
-                ),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
+        return Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            format!("Enbable data query for contract : ({})", contract),
         ));
     }
+    Ok(())
+}
+
+/// Makes sure data querying is enabled on the relayer.
+fn check_data_query(config: &WebbRelayerConfig) -> ApiResult<()> {
+    if config.features.data_query {
+        Ok(())
+    } else {
+        tracing::warn!("Data query is not enabled for relayer.");
+        Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Data query is not enabled for relayer.",
+        ))
+    }
+}
+
+/// Reads the cached leaves of an evm contract.
+fn leaves_cache_evm<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    leaves_sync: &LeavesSyncStatuses,
+    chain_id: u32,
+    contract: Address,
+) -> ApiResult<impl warp::Reply>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_evm_data_query(config, chain_id, contract)?;
     // create history store key
     let src_target_system =
         TargetSystem::new_contract_address(contract.to_fixed_bytes());
     let src_typed_chain_id = TypedChainId::Evm(chain_id);
     let history_store_key =
         ResourceId::new(src_target_system, src_typed_chain_id);
-    let leaves = store.get_leaves(history_store_key).unwrap();
+    let leaves = store
+        .get_leaves(history_store_key)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
     let last_queried_block = store
         .get_last_deposit_block_number(history_store_key)
-        .unwrap();
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
+    let sync_status = leaves_sync.get(history_store_key);
 
-    Ok(warp::reply::with_status(
-        warp::reply::json(&LeavesCacheResponse {
-            leaves,
-            last_queried_block,
-        }),
-        warp::http::StatusCode::OK,
+    Ok(warp::reply::json(&LeavesCacheResponse {
+        leaves,
+        last_queried_block,
+        sync_status,
+    }))
+}
+
+/// Handles the requests for the state of the leaves cache of an evm
+/// contract, which is much lighter than reading all of its leaves.
+pub async fn handle_leaves_status_evm(
+    store: Arc<webb_relayer_store::AnyStore>,
+    chain_id: u32,
+    contract: Address,
+    ctx: Arc<RelayerContext>,
+) -> Result<impl warp::Reply, Infallible> {
+    api_reply(leaves_status_evm(
+        store.as_ref(),
+        &ctx.config,
+        &ctx.leaves_sync,
+        chain_id,
+        contract,
     ))
 }
+
+/// Reads the state of the leaves cache of an evm contract.
+fn leaves_status_evm<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    leaves_sync: &LeavesSyncStatuses,
+    chain_id: u32,
+    contract: Address,
+) -> ApiResult<impl warp::Reply>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_evm_data_query(config, chain_id, contract)?;
+    let history_store_key = ResourceId::new(
+        TargetSystem::new_contract_address(contract.to_fixed_bytes()),
+        TypedChainId::Evm(chain_id),
+    );
+    let sync_status = leaves_sync.get(history_store_key);
+    leaves_status(store, history_store_key, sync_status)
+}
+
+/// Reads the number of cached leaves of a merkle tree and the block they
+/// were fetched up to.
+fn leaves_status<S>(
+    store: &S,
+    history_store_key: ResourceId,
+    sync_status: Option<LeavesSyncStatus>,
+) -> ApiResult<warp::reply::Json>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    let leaves_count = store
+        .get_leaves_count(history_store_key)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
+    let last_queried_block = store
+        .get_last_deposit_block_number(history_store_key)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
+    Ok(warp::reply::json(&LeavesStatusResponse {
+        leaves_count,
+        last_queried_block,
+        sync_status,
+    }))
+}
+
 /// Handles leaf data requests for substrate
 ///
 /// Returns a Result with the `LeafDataResponse` on success
@@ -331,37 +465,33 @@ pub async fn handle_leaves_cache_evm(
 /// * `pallet_id` - Pallet id of the the source system to query
 /// * `ctx` - RelayContext reference that holds the configuration
 pub async fn handle_leaves_cache_substrate(
-    store: Arc<webb_relayer_store::sled::SledStore>,
+    store: Arc<webb_relayer_store::AnyStore>,
     chain_id: u32,
     tree_id: u32,
     pallet_id: u8,
     ctx: Arc<RelayerContext>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let config = ctx.config.clone();
-    // Leaves cache response
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct LeavesCacheResponse {
-        leaves: Vec<Vec<u8>>,
-        last_queried_block: u64,
-    }
-    // Unsupported feature response
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct UnsupportedFeature {
-        message: String,
-    }
-    // check if data querying is enabled
-    if !config.features.data_query {
-        tracing::warn!("Data query is not enabled for relayer.");
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: "Data query is not enabled for relayer.".to_string(),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
-        ));
-    }
+    api_reply(leaves_cache_substrate(
+        store.as_ref(),
+        &ctx.config,
+        chain_id,
+        tree_id,
+        pallet_id,
+    ))
+}
 
+/// Reads the cached leaves of a substrate tree.
+fn leaves_cache_substrate<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    chain_id: u32,
+    tree_id: u32,
+    pallet_id: u8,
+) -> ApiResult<impl warp::Reply>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_data_query(config)?;
     // create history store key
     let src_typed_chain_id = TypedChainId::Substrate(chain_id);
     let target = SubstrateTargetSystem::builder()
@@ -372,19 +502,61 @@ pub async fn handle_leaves_cache_substrate(
     let history_store_key =
         ResourceId::new(src_target_system, src_typed_chain_id);
 
-    let leaves = store.get_leaves(history_store_key).unwrap();
+    let leaves = store
+        .get_leaves(history_store_key)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
     let last_queried_block = store
         .get_last_deposit_block_number(history_store_key)
-        .unwrap();
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
 
-    Ok(warp::reply::with_status(
-        warp::reply::json(&LeavesCacheResponse {
-            leaves,
-            last_queried_block,
-        }),
-        warp::http::StatusCode::OK,
+    Ok(warp::reply::json(&LeavesCacheResponse {
+        leaves,
+        last_queried_block,
+        sync_status: None,
+    }))
+}
+
+/// Handles the requests for the state of the leaves cache of a substrate
+/// tree, which is much lighter than reading all of its leaves.
+pub async fn handle_leaves_status_substrate(
+    store: Arc<webb_relayer_store::AnyStore>,
+    chain_id: u32,
+    tree_id: u32,
+    pallet_id: u8,
+    ctx: Arc<RelayerContext>,
+) -> Result<impl warp::Reply, Infallible> {
+    api_reply(leaves_status_substrate(
+        store.as_ref(),
+        &ctx.config,
+        chain_id,
+        tree_id,
+        pallet_id,
     ))
 }
+
+/// Reads the state of the leaves cache of a substrate tree.
+fn leaves_status_substrate<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    chain_id: u32,
+    tree_id: u32,
+    pallet_id: u8,
+) -> ApiResult<impl warp::Reply>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_data_query(config)?;
+    let target = SubstrateTargetSystem::builder()
+        .pallet_index(pallet_id)
+        .tree_id(tree_id)
+        .build();
+    let history_store_key = ResourceId::new(
+        TargetSystem::Substrate(target),
+        TypedChainId::Substrate(chain_id),
+    );
+    leaves_status(store, history_store_key, None)
+}
+
 /// Handles leaf data requests for Cosmos-SDK chains(cosmwasm)
 ///
 /// Returns a Result with the `LeafDataResponse` on success
@@ -396,112 +568,86 @@ pub async fn handle_leaves_cache_substrate(
 /// * `contract` - An address of the contract to query
 /// * `ctx` - RelayContext reference that holds the configuration
 pub async fn handle_leaves_cache_cosmwasm(
-    store: Arc<webb_relayer_store::sled::SledStore>,
+    store: Arc<webb_relayer_store::AnyStore>,
     chain_id: u32,
     contract: String,
     ctx: Arc<RelayerContext>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let config = ctx.config.clone();
-
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct LeavesCacheResponse {
-        leaves: Vec<Vec<u8>>,
-        last_queried_block: u64,
-    }
-    // Unsupported feature response
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct UnsupportedFeature {
-        message: String,
-    }
-
-    // check if data query is enabled for relayer
-    if !config.features.data_query {
-        tracing::warn!("Data query is not enabled for relayer.");
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: "Data query is not enabled for relayer.".to_string(),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
-        ));
-    }
+    api_reply(leaves_cache_cosmwasm(
+        store.as_ref(),
+        &ctx.config,
+        chain_id,
+        contract,
+    ))
+}
 
+/// Reads the cached leaves of a cosmwasm contract.
+fn leaves_cache_cosmwasm<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    chain_id: u32,
+    contract: String,
+) -> ApiResult<impl warp::Reply>
+where
+    S: LeafCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_data_query(config)?;
     // check if chain is supported
-    let chain = match ctx.config.cosmwasm.get(&chain_id.to_string()) {
-        Some(v) => v,
-        None => {
+    let chain =
+        config.cosmwasm.get(&chain_id.to_string()).ok_or_else(|| {
             tracing::warn!("Unsupported Chain: {}", chain_id);
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!("Unsupported Chain: {chain_id}"),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
-        }
-    };
-
-    let supported_contracts: HashMap<_, _> = chain
+            ApiError::new(
+                ErrorCode::UnsupportedChain,
+                format!("Unsupported Chain: {chain_id}"),
+            )
+        })?;
+    // check if contract is supported
+    let event_watcher_config = chain
         .contracts
         .iter()
-        .cloned()
-        .filter_map(|c| match c {
-            webb_relayer_config::cosmwasm::CosmwasmContract::VAnchor(c) => {
-                Some((c.common.address, c.events_watcher))
+        .find_map(|c| match c {
+            webb_relayer_config::cosmwasm::CosmwasmContract::VAnchor(c)
+                if c.common.address == contract =>
+            {
+                Some(&c.events_watcher)
             }
             _ => None,
         })
-        .collect();
-
-    // check if contract is supported
-    let event_watcher_config = match supported_contracts.get(&contract) {
-        Some(config) => config,
-        None => {
+        .ok_or_else(|| {
             tracing::warn!(
                 "Unsupported Contract: {:?} for chaind : {}",
                 contract,
                 chain_id
             );
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!(
-                        "Unsupported Contract: {} for chaind : {}",
-                        contract, chain_id
-                    ),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
-        }
-    };
+            ApiError::new(
+                ErrorCode::UnsupportedContract,
+                format!(
+                    "Unsupported Contract: {} for chaind : {}",
+                    contract, chain_id
+                ),
+            )
+        })?;
     // check if data query is enabled for contract
     if !event_watcher_config.enable_data_query {
-        tracing::warn!(
-            "Enbable data query for contract : ({})",
-            contract.to_string()
-        );
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: format!(
-                    "Enbable data query for contract : ({})",
-                    contract
-                ),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
+        tracing::warn!("Enbable data query for contract : ({})", contract);
+        return Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            format!("Enbable data query for contract : ({})", contract),
         ));
     }
-    let leaves = store.get_leaves(chain_id).unwrap();
-    let last_queried_block =
-        store.get_last_deposit_block_number(chain_id).unwrap();
+    let leaves = store
+        .get_leaves(chain_id)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
+    let last_queried_block = store
+        .get_last_deposit_block_number(chain_id)
+        .map_err(|e| ApiError::store("Failed to read the leaves", e))?;
 
-    Ok(warp::reply::with_status(
-        warp::reply::json(&LeavesCacheResponse {
-            leaves,
-            last_queried_block,
-        }),
-        warp::http::StatusCode::OK,
-    ))
+    Ok(warp::reply::json(&LeavesCacheResponse {
+        leaves,
+        last_queried_block,
+        sync_status: None,
+    }))
 }
-
 /// Handles encrypted outputs data requests for evm
 ///
 /// Returns a Result with the `EncryptedOutputDataResponse` on success
@@ -513,128 +659,668 @@ pub async fn handle_leaves_cache_cosmwasm(
 /// * `contract` - An address of the contract to query
 /// * `ctx` - RelayContext reference that holds the configuration
 pub async fn handle_encrypted_outputs_cache_evm(
-    store: Arc<webb_relayer_store::sled::SledStore>,
+    store: Arc<webb_relayer_store::AnyStore>,
     chain_id: u32,
     contract: Address,
     ctx: Arc<RelayerContext>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let config = ctx.config.clone();
+    api_reply(encrypted_outputs_cache_evm(
+        store.as_ref(),
+        &ctx.config,
+        chain_id,
+        contract,
+    ))
+}
 
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct EncryptedOutputsCacheResponse {
-        encrypted_outputs: Vec<Vec<u8>>,
-        last_queried_block: u64,
-    }
-    // Unsupported feature response
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct UnsupportedFeature {
-        message: String,
-    }
+/// Reads the cached encrypted outputs of an evm contract.
+fn encrypted_outputs_cache_evm<S>(
+    store: &S,
+    config: &WebbRelayerConfig,
+    chain_id: u32,
+    contract: Address,
+) -> ApiResult<impl warp::Reply>
+where
+    S: EncryptedOutputCacheStore<Output = Vec<Vec<u8>>>,
+{
+    check_evm_data_query(config, chain_id, contract)?;
+    // create history store key
+    let src_target_system =
+        TargetSystem::new_contract_address(contract.to_fixed_bytes());
+    let src_typed_chain_id = TypedChainId::Evm(chain_id);
+    let history_store_key =
+        ResourceId::new(src_target_system, src_typed_chain_id);
+    let encrypted_output =
+        store.get_encrypted_output(history_store_key).map_err(|e| {
+            ApiError::store("Failed to read the encrypted outputs", e)
+        })?;
+    let last_queried_block = store
+        .get_last_deposit_block_number_for_encrypted_output(history_store_key)
+        .map_err(|e| {
+            ApiError::store("Failed to read the encrypted outputs", e)
+        })?;
 
-    // check if data query is enabled for relayer
-    if !config.features.data_query {
-        tracing::warn!("Data query is not enabled for relayer.");
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: "Data query is not enabled for relayer.".to_string(),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
-        ));
+    Ok(warp::reply::json(&EncryptedOutputsCacheResponse {
+        encrypted_outputs: encrypted_output,
+        last_queried_block,
+    }))
+}
+
+/// Query parameters of the submitted proposals requests.
+#[derive(Debug, Default, Deserialize)]
+pub struct ProposalsQuery {
+    /// Only return the proposals with this status.
+    pub status: Option<ProposalStatus>,
+}
+
+/// Handles submitted proposals requests
+///
+/// Returns a Result with the `ProposalsResponse` on success
+///
+/// # Arguments
+///
+/// * `store` - [Sled](https://sled.rs)-based database store
+/// * `query` - Optional filters for the returned proposals
+pub async fn handle_proposals(
+    store: Arc<webb_relayer_store::AnyStore>,
+    query: ProposalsQuery,
+) -> Result<impl warp::Reply, Infallible> {
+    api_reply(proposals(store.as_ref(), query))
+}
+
+/// Reads the submitted proposals matching the query.
+fn proposals<S>(store: &S, query: ProposalsQuery) -> ApiResult<impl warp::Reply>
+where
+    S: ProposalStore<Proposal = SubmittedProposal>,
+{
+    let proposals = store
+        .get_proposals()
+        .map_err(|e| ApiError::store("Failed to read the proposals", e))?;
+    let proposals = proposals
+        .into_iter()
+        .filter(|p| query.status.map(|s| s == p.status).unwrap_or(true))
+        .map(|p| ProposalInfo {
+            data_hash: format!("0x{}", hex::encode(p.data_hash)),
+            nonce: p.nonce,
+            src_chain_id: p.src_chain_id,
+            target: format!("0x{}", hex::encode(p.target)),
+            proposal: format!("0x{}", hex::encode(&p.proposal)),
+            status: p.status,
+            attempts: p.attempts,
+            submitted_at: p.submitted_at,
+            signed_at: p.signed_at,
+        })
+        .collect();
+    Ok(warp::reply::json(&ProposalsResponse { proposals }))
+}
+
+/// The configuration of the relayer, without its secrets.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerInformationResponse {
+    /// The configuration, flattened in the response.
+    #[serde(flatten)]
+    pub config: WebbRelayerConfig,
+}
+
+/// The response of the admin requests that only change the state of the
+/// relayer.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageResponse {
+    /// What was done, meant for humans.
+    pub message: String,
+}
+
+/// A dead letter, as listed by the admin API.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterInfo {
+    /// The hex encoded id of the dead letter.
+    pub id: String,
+    /// The transaction or bridge command that failed.
+    pub item: serde_json::Value,
+    /// Why it failed the last time.
+    pub reason: String,
+    /// How many times it was tried.
+    pub attempts: u32,
+    /// The unix timestamp (in seconds) of its first failure.
+    pub first_failed_at: u64,
+    /// The unix timestamp (in seconds) of its last failure.
+    pub last_failed_at: u64,
+    /// The unix timestamp (in seconds) it was last sent back to its queue.
+    pub retried_at: Option<u64>,
+}
+
+/// The dead letters of a queue.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLettersResponse {
+    /// The name of the queue.
+    pub queue: String,
+    /// Its dead letters.
+    pub dead_letters: Vec<DeadLetterInfo>,
+}
+
+/// The paused watchers and transaction queues.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminStatusResponse {
+    /// The hex encoded resource ids of the paused watchers.
+    pub paused_watchers: Vec<String>,
+    /// The names of the paused transaction queues.
+    pub paused_queues: Vec<String>,
+}
+
+/// The items waiting in a queue.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItemsResponse {
+    /// The name of the queue.
+    pub queue: String,
+    /// Its items, in the order they are dequeued.
+    pub items: Vec<QueuedItem<serde_json::Value>>,
+}
+
+fn message_reply(
+    message: String,
+    status: warp::http::StatusCode,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&MessageResponse { message }),
+        status,
+    )
+}
+
+/// Parses the queue name and the hex encoded id of a dead letter request.
+fn parse_dead_letter_params(
+    queue: &str,
+    id: Option<&str>,
+) -> ApiResult<(SledQueueKey, Vec<u8>)> {
+    let queue = parse_queue(queue)?;
+    let id = match id {
+        Some(id) => hex::decode(id.trim_start_matches("0x")).map_err(|e| {
+            ApiError::new(
+                ErrorCode::InvalidRequest,
+                format!("Invalid dead letter id: {e}"),
+            )
+        })?,
+        None => Vec::new(),
+    };
+    Ok((queue, id))
+}
+
+/// Returns the queue with the given name.
+fn parse_queue(queue: &str) -> ApiResult<SledQueueKey> {
+    SledQueueKey::from_queue_name(queue).ok_or_else(|| {
+        ApiError::new(
+            ErrorCode::InvalidRequest,
+            format!("Unknown queue: {queue}"),
+        )
+    })
+}
+
+/// Handles dead letters requests
+///
+/// Returns a Result with the `DeadLettersResponse` on success
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `queue` - The name of the queue (e.g. `evm_tx_5001` or `bridge_cmd_<typed chain id>`)
+pub async fn handle_dead_letters(
+    store: Arc<webb_relayer_store::AnyStore>,
+    queue: String,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = parse_dead_letter_params(&queue, None).and_then(|(key, _)| {
+        list_dead_letters(store.as_ref(), key)
+            .map_err(|e| ApiError::store("Failed to read the dead letters", e))
+    });
+    api_reply(result.map(|letters| {
+        let dead_letters = letters
+            .into_iter()
+            .map(|l| DeadLetterInfo {
+                id: format!("0x{}", hex::encode(l.id)),
+                item: l.item,
+                reason: l.reason,
+                attempts: l.attempts,
+                first_failed_at: l.first_failed_at,
+                last_failed_at: l.last_failed_at,
+                retried_at: l.retried_at,
+            })
+            .collect();
+        warp::reply::json(&DeadLettersResponse {
+            queue,
+            dead_letters,
+        })
+    }))
+}
+
+/// Handles requests to send a dead letter back to its queue
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `queue` - The name of the queue the dead letter belongs to
+/// * `id` - The hex encoded id of the dead letter
+pub async fn handle_retry_dead_letter(
+    store: Arc<webb_relayer_store::AnyStore>,
+    queue: String,
+    id: String,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = parse_dead_letter_params(&queue, Some(&id)).and_then(
+        |(queue_key, id)| match retry_dead_letter(
+            store.as_ref(),
+            queue_key,
+            &id,
+        ) {
+            Ok(true) => Ok(message_reply(
+                "Dead letter sent back to its queue".into(),
+                warp::http::StatusCode::OK,
+            )),
+            Ok(false) => {
+                Err(ApiError::new(ErrorCode::NotFound, "Dead letter not found"))
+            }
+            // the dead letter is already waiting to be retried.
+            Err(e @ webb_relayer_utils::Error::Generic(_)) => {
+                Err(ApiError::new(
+                    ErrorCode::Conflict,
+                    format!("Failed to retry the dead letter: {e}"),
+                ))
+            }
+            Err(e) => {
+                Err(ApiError::store("Failed to retry the dead letter", e))
+            }
+        },
+    );
+    api_reply(result)
+}
+
+/// Handles requests to discard a dead letter
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `queue` - The name of the queue the dead letter belongs to
+/// * `id` - The hex encoded id of the dead letter
+pub async fn handle_discard_dead_letter(
+    store: Arc<webb_relayer_store::AnyStore>,
+    queue: String,
+    id: String,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = parse_dead_letter_params(&queue, Some(&id)).and_then(
+        |(queue_key, id)| match discard_dead_letter(
+            store.as_ref(),
+            queue_key,
+            &id,
+        ) {
+            Ok(true) => Ok(message_reply(
+                "Dead letter discarded".into(),
+                warp::http::StatusCode::OK,
+            )),
+            Ok(false) => {
+                Err(ApiError::new(ErrorCode::NotFound, "Dead letter not found"))
+            }
+            Err(e) => {
+                Err(ApiError::store("Failed to discard the dead letter", e))
+            }
+        },
+    );
+    api_reply(result)
+}
+
+/// Takes a token from the rate limit bucket of the client for the given
+/// group of routes, the request is rejected if there is none left.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `group` - The group of routes the request belongs to
+/// * `ip` - The IP address of the client, if known
+pub async fn check_rate_limit(
+    ctx: Arc<RelayerContext>,
+    group: RouteGroup,
+    ip: Option<IpAddr>,
+) -> Result<(), warp::Rejection> {
+    if ctx.rate_limiter.check(group, ip) {
+        Ok(())
+    } else {
+        ctx.metrics
+            .rate_limited_requests
+            .with_label_values(&[group.as_str()])
+            .inc();
+        Err(warp::reject::custom(RateLimited))
     }
+}
 
-    // check if chain is supported
-    let chain = match ctx.config.evm.get(&chain_id.to_string()) {
-        Some(v) => v,
+/// Counts a new websocket connection of the client, the request is rejected
+/// if too many connections are already open.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `ip` - The IP address of the client, if known
+pub async fn check_ws_connection_limit(
+    ctx: Arc<RelayerContext>,
+    ip: Option<IpAddr>,
+) -> Result<WsConnectionGuard, warp::Rejection> {
+    match ctx.rate_limiter.acquire_ws_connection(ip) {
+        Some(guard) => Ok(guard),
         None => {
-            tracing::warn!("Unsupported Chain: {}", chain_id);
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!("Unsupported Chain: {chain_id}"),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
+            ctx.metrics
+                .rate_limited_requests
+                .with_label_values(&["ws-connections"])
+                .inc();
+            Err(warp::reject::custom(RateLimited))
         }
+    }
+}
+
+/// Compares the two tokens in constant time.
+fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Checks the `Authorization: Bearer <token>` header of an admin request
+/// against the admin token of the relayer.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `authorization` - The value of the `Authorization` header, if any
+pub async fn check_admin_token(
+    ctx: Arc<RelayerContext>,
+    authorization: Option<String>,
+) -> Result<(), warp::Rejection> {
+    let expected = match ctx.config.admin.token.as_deref() {
+        Some(token) if !token.is_empty() => token,
+        _ => return Err(warp::reject::custom(Unauthorized)),
     };
+    let given = authorization
+        .as_deref()
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    if tokens_match(given.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(warp::reject::custom(Unauthorized))
+    }
+}
 
-    let supported_contracts: HashMap<_, _> = chain
-        .contracts
-        .iter()
-        .cloned()
-        .filter_map(|c| match c {
-            webb_relayer_config::evm::Contract::VAnchor(c)
-            | webb_relayer_config::evm::Contract::OpenVAnchor(c) => {
-                Some((c.common.address, c.events_watcher))
+/// Turns the [`Unauthorized`] rejections of the admin API into replies,
+/// other rejections are left to the next routes.
+pub async fn handle_admin_rejection(
+    err: warp::Rejection,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if err.find::<Unauthorized>().is_some() {
+        Ok(ApiError::new(
+            ErrorCode::Unauthorized,
+            "Missing or invalid admin token",
+        ))
+    } else {
+        Err(err)
+    }
+}
+
+/// Returns the chain of the transaction queue with the given name.
+fn parse_tx_queue(queue: &str) -> ApiResult<TypedChainId> {
+    match parse_queue(queue)? {
+        SledQueueKey::EvmTx { chain_id, .. } => Ok(TypedChainId::Evm(chain_id)),
+        SledQueueKey::SubstrateTx { chain_id, .. } => {
+            Ok(TypedChainId::Substrate(chain_id))
+        }
+        _ => Err(ApiError::new(
+            ErrorCode::InvalidRequest,
+            format!("Only the transaction queues can be paused: {queue}"),
+        )),
+    }
+}
+
+/// Handles admin status requests
+///
+/// Returns the paused watchers and transaction queues.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+pub async fn handle_admin_status(
+    ctx: Arc<RelayerContext>,
+) -> Result<impl warp::Reply, Infallible> {
+    let paused_watchers = ctx
+        .controls
+        .paused_watchers()
+        .into_iter()
+        .map(|r| format!("0x{}", hex::encode(r.into_bytes())))
+        .collect();
+    let paused_queues = ctx
+        .controls
+        .paused_queues()
+        .into_iter()
+        .filter_map(|chain_id| match chain_id {
+            TypedChainId::Evm(id) => Some(SledQueueKey::from_evm_chain_id(id)),
+            TypedChainId::Substrate(id) => {
+                Some(SledQueueKey::from_substrate_chain_id(id))
             }
             _ => None,
         })
+        .map(|key| key.queue_name())
         .collect();
+    Ok(warp::reply::with_status(
+        warp::reply::json(&AdminStatusResponse {
+            paused_watchers,
+            paused_queues,
+        }),
+        warp::http::StatusCode::OK,
+    ))
+}
 
-    // check if contract is supported
-    let event_watcher_config = match supported_contracts.get(&contract) {
-        Some(config) => config,
-        None => {
-            tracing::warn!(
-                "Unsupported Contract: {:?} for chaind : {}",
-                contract,
-                chain_id
-            );
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&UnsupportedFeature {
-                    message: format!(
-                        "Unsupported Contract: {} for chaind : {}",
-                        contract, chain_id
-                    ),
-                }),
-                warp::http::StatusCode::BAD_REQUEST,
-            ));
-        }
+/// Handles requests to pause or resume an events watcher
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `resource_id` - The key the watcher stores its last block number under
+/// * `pause` - Whether to pause or resume the watcher
+pub async fn handle_admin_pause_watcher(
+    ctx: Arc<RelayerContext>,
+    resource_id: ResourceId,
+    pause: bool,
+) -> Result<impl warp::Reply, Infallible> {
+    let changed = if pause {
+        ctx.controls.pause_watcher(resource_id)
+    } else {
+        ctx.controls.resume_watcher(resource_id)
     };
-    // check if data query is enabled for contract
-    if !event_watcher_config.enable_data_query {
-        tracing::warn!("Enbable data query for contract : ({})", contract);
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&UnsupportedFeature {
-                message: format!(
-                    "Enbable data query for contract : ({})",
-                    contract
-                ),
-            }),
-            warp::http::StatusCode::FORBIDDEN,
+    let state = if pause { "paused" } else { "resumed" };
+    tracing::info!(
+        resource_id = %hex::encode(resource_id.into_bytes()),
+        "Watcher {state} from the admin API",
+    );
+    let message = if changed {
+        format!("Watcher {state}")
+    } else {
+        format!("Watcher already {state}")
+    };
+    Ok(message_reply(message, warp::http::StatusCode::OK))
+}
+
+/// The body of a watcher cursor reset request.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetCursorRequest {
+    /// The block the watcher should continue from, the events of the next
+    /// blocks are handled again.
+    pub block: u64,
+}
+
+/// Handles requests to reset the last synced block of an events watcher
+///
+/// The watcher must be paused first, and done with the range of blocks it was
+/// syncing, so it does not overwrite the new value.
+/// When the event hashes are pruned, the watcher can not be reset further
+/// than `event-hashes-depth` blocks back, since the events of the older
+/// blocks would not be known as handled anymore.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
+/// * `resource_id` - The key the watcher stores its last block number under
+/// * `request` - The block to reset the watcher to
+pub async fn handle_admin_reset_cursor(
+    ctx: Arc<RelayerContext>,
+    store: Arc<webb_relayer_store::AnyStore>,
+    resource_id: ResourceId,
+    request: ResetCursorRequest,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = reset_cursor(
+        &ctx.controls,
+        ctx.config.store.event_hashes_depth,
+        &*store,
+        resource_id,
+        request.block,
+    );
+    api_reply(result.map(|_| {
+        tracing::info!(
+            resource_id = %hex::encode(resource_id.into_bytes()),
+            block = request.block,
+            "Watcher cursor reset from the admin API",
+        );
+        message_reply(
+            format!("Watcher cursor reset to #{}", request.block),
+            warp::http::StatusCode::OK,
+        )
+    }))
+}
+
+/// Resets the last synced block of a paused watcher, once it stopped syncing,
+/// unless the event hashes of the blocks after it may have been pruned
+/// already, `event_hashes_depth` blocks behind the current one.
+pub fn reset_cursor<S: HistoryStore>(
+    controls: &ServiceControls,
+    event_hashes_depth: Option<u64>,
+    store: &S,
+    resource_id: ResourceId,
+    block: u64,
+) -> ApiResult<()> {
+    if !controls.is_watcher_paused(resource_id) {
+        return Err(ApiError::new(
+            ErrorCode::Conflict,
+            "The watcher must be paused before resetting its cursor",
         ));
     }
-    // create history store key
-    let src_target_system =
-        TargetSystem::new_contract_address(contract.to_fixed_bytes());
-    let src_typed_chain_id = TypedChainId::Evm(chain_id);
-    let history_store_key =
-        ResourceId::new(src_target_system, src_typed_chain_id);
-    let encrypted_output =
-        store.get_encrypted_output(history_store_key).unwrap();
-    let last_queried_block = store
-        .get_last_deposit_block_number_for_encrypted_output(history_store_key)
-        .unwrap();
+    // a range of blocks started before the pause would overwrite the cursor
+    // once synced.
+    if !controls.is_watcher_stopped(resource_id) {
+        return Err(ApiError::new(
+            ErrorCode::Conflict,
+            "The watcher is still syncing the blocks it started before the \
+             pause, try again later",
+        ));
+    }
+    // the hashes are pruned below the last synced block of the watcher.
+    if let Some(depth) = event_hashes_depth {
+        let last_block =
+            store.get_last_block_number(resource_id, 0).map_err(|e| {
+                ApiError::store("Failed to read the watcher cursor", e)
+            })?;
+        let horizon = last_block.saturating_sub(depth);
+        if block < horizon {
+            return Err(ApiError::new(
+                ErrorCode::InvalidRequest,
+                format!(
+                    "The event hashes before block #{horizon} may be pruned, \
+                     the cursor can not be reset to #{block}"
+                ),
+            ));
+        }
+    }
+    store
+        .set_last_block_number(resource_id, block)
+        .map_err(|e| {
+            ApiError::store("Failed to reset the watcher cursor", e)
+        })?;
+    Ok(())
+}
 
-    Ok(warp::reply::with_status(
-        warp::reply::json(&EncryptedOutputsCacheResponse {
-            encrypted_outputs: encrypted_output,
-            last_queried_block,
+/// Handles requests to pause or resume a transaction queue
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `queue` - The name of the queue (e.g. `evm_tx_5001`)
+/// * `pause` - Whether to pause or resume the queue
+pub async fn handle_admin_pause_queue(
+    ctx: Arc<RelayerContext>,
+    queue: String,
+    pause: bool,
+) -> Result<impl warp::Reply, Infallible> {
+    api_reply(parse_tx_queue(&queue).map(|chain_id| {
+        let changed = if pause {
+            ctx.controls.pause_queue(chain_id)
+        } else {
+            ctx.controls.resume_queue(chain_id)
+        };
+        let state = if pause { "paused" } else { "resumed" };
+        tracing::info!(%queue, "Queue {state} from the admin API");
+        let message = if changed {
+            format!("Queue {state}")
+        } else {
+            format!("Queue already {state}")
+        };
+        message_reply(message, warp::http::StatusCode::OK)
+    }))
+}
+
+/// Handles requests to list the items of a queue
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `queue` - The name of the queue (e.g. `evm_tx_5001` or `bridge_cmd_<typed chain id>`)
+pub async fn handle_admin_queue_items(
+    store: Arc<webb_relayer_store::AnyStore>,
+    queue: String,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = parse_queue(&queue).and_then(|queue_key| {
+        list_queue_items(store.as_ref(), queue_key)
+            .map_err(|e| ApiError::store("Failed to read the queue", e))
+    });
+    api_reply(
+        result.map(|items| {
+            warp::reply::json(&QueueItemsResponse { queue, items })
         }),
-        warp::http::StatusCode::OK,
-    ))
+    )
+}
+
+/// Handles requests to remove all the items of a queue
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `queue` - The name of the queue (e.g. `evm_tx_5001` or `bridge_cmd_<typed chain id>`)
+pub async fn handle_admin_purge_queue(
+    store: Arc<webb_relayer_store::AnyStore>,
+    queue: String,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = parse_queue(&queue).and_then(|queue_key| {
+        purge_queue_items(store.as_ref(), queue_key)
+            .map_err(|e| ApiError::store("Failed to purge the queue", e))
+    });
+    api_reply(result.map(|removed| {
+        tracing::warn!(%queue, removed, "Queue purged from the admin API");
+        message_reply(
+            format!("Removed {removed} items from the queue"),
+            warp::http::StatusCode::OK,
+        )
+    }))
 }
 
 /// Handles relayer metric requests
 ///
 /// Returns a Result with the `MetricResponse` on success
 pub async fn handle_metric_info() -> Result<impl warp::Reply, Infallible> {
-    #[derive(Debug, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct RelayerMetricResponse {
-        metrics: String,
-    }
-
     let metric_gathered = Metrics::gather_metrics();
     Ok(warp::reply::with_status(
         warp::reply::json(&RelayerMetricResponse {
@@ -644,33 +1330,53 @@ pub async fn handle_metric_info() -> Result<impl warp::Reply, Infallible> {
     ))
 }
 
+/// Handles OpenAPI document requests
+///
+/// Returns the OpenAPI document of the relayer API
+pub async fn handle_openapi() -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&openapi::openapi()))
+}
+
 /// Handles the command prompts for EVM and Substrate chains
 ///
 /// # Arguments
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
 /// * `cmd` - The command to execute
 /// * `stream` - The stream to write the response to
 pub async fn handle_cmd(
     ctx: RelayerContext,
+    store: Arc<webb_relayer_store::AnyStore>,
     cmd: Command,
     stream: CommandStream,
 ) {
     use CommandResponse::*;
-    if ctx.config.features.private_tx_relay {
+    // the subscriptions serve the cached data, the other commands relay
+    // transactions.
+    let (enabled, feature) = match cmd {
+        Command::Subscribe(_) => (ctx.config.features.data_query, "Data query"),
+        _ => (
+            ctx.config.features.private_tx_relay,
+            "Private transaction relaying",
+        ),
+    };
+    if enabled {
         match cmd {
             Command::Substrate(sub) => handle_substrate(ctx, sub, stream).await,
             Command::Evm(evm) => handle_evm(ctx, evm, stream).await,
             Command::Ping() => {
                 let _ = stream.send(Pong()).await;
             }
+            Command::Job(sub) => stream_relay_job(store, sub.id, stream).await,
+            Command::Subscribe(sub) => {
+                stream_cache_updates(store, sub, stream).await
+            }
         }
     } else {
-        tracing::error!("Private transaction relaying is not configured..!");
+        tracing::error!("{feature} is not configured..!");
         let _ = stream
-            .send(Error(
-                "Private transaction relaying is not enabled.".to_string(),
-            ))
+            .send(Error(format!("{feature} is not enabled.")))
             .await;
     }
 }
@@ -713,3 +1419,352 @@ pub async fn handle_substrate<'a>(
         }
     }
 }
+
+/// How often the subscriptions to a relay job check for its updates.
+const RELAY_JOB_POLL_INTERVAL: std::time::Duration =
+    std::time::Duration::from_millis(500);
+
+/// Sends the status of a relay job every time it changes, until the relayer
+/// is done with it.
+///
+/// The job is read from the store, so it can be followed from any relayer
+/// sharing the same database.
+async fn stream_relay_job(
+    store: Arc<webb_relayer_store::AnyStore>,
+    id: String,
+    stream: CommandStream,
+) {
+    let mut last_sent: Option<RelayJob> = None;
+    loop {
+        let job = match store.get_job(&id) {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                let _ = stream
+                    .send(CommandResponse::Error(format!("Unknown job: {id}")))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to read the relay job");
+                let _ = stream
+                    .send(CommandResponse::Error(format!(
+                        "Failed to read the job: {e}"
+                    )))
+                    .await;
+                return;
+            }
+        };
+        let finished = job.finished;
+        if last_sent.as_ref() != Some(&job) {
+            last_sent = Some(job.clone());
+            if stream.send(CommandResponse::Job(job)).await.is_err() {
+                // the client went away.
+                return;
+            }
+        }
+        if finished {
+            return;
+        }
+        tokio::time::sleep(RELAY_JOB_POLL_INTERVAL).await;
+    }
+}
+
+/// The most items of one kind sent in a single cache update.
+const MAX_CACHE_UPDATE_ITEMS: usize = 512;
+
+/// The most anchors a single cache subscription can follow.
+const MAX_SUBSCRIBED_ANCHORS: usize = 64;
+
+/// How often the cache subscriptions check for new items when they are not
+/// woken up, for the items stored by other relayers sharing the database.
+const CACHE_UPDATES_POLL_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(5);
+
+/// An anchor followed by a cache subscription, with the index of the next
+/// item of each kind to send.
+struct AnchorCursors {
+    name: String,
+    resource_id: ResourceId,
+    updates: watch::Receiver<u64>,
+    next: [(CachedItemKind, u32); 3],
+}
+
+/// Parses a hex encoded (optionally `0x` prefixed) resource id.
+fn parse_resource_id(value: &str) -> Result<ResourceId, &'static str> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|_| "invalid hex")?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "resource id must be 32 bytes")?;
+    Ok(ResourceId::from(bytes))
+}
+
+/// Sends the items cached for the subscribed anchors from the requested
+/// indices on, then the new ones as they are stored, until the client goes
+/// away.
+///
+/// The items of each kind are sent in order and without gaps, an item
+/// stored past a missing index is held back until the missing one is
+/// stored too.
+async fn stream_cache_updates(
+    store: Arc<webb_relayer_store::AnyStore>,
+    sub: CacheSubscription,
+    stream: CommandStream,
+) {
+    if sub.anchors.is_empty() {
+        let _ = stream
+            .send(CommandResponse::Error("No anchors to follow".to_string()))
+            .await;
+        return;
+    }
+    if sub.anchors.len() > MAX_SUBSCRIBED_ANCHORS {
+        let _ = stream
+            .send(CommandResponse::Error(format!(
+                "Too many anchors to follow, at most {MAX_SUBSCRIBED_ANCHORS} are allowed"
+            )))
+            .await;
+        return;
+    }
+    let mut anchors = Vec::with_capacity(sub.anchors.len());
+    for anchor in sub.anchors {
+        let resource_id = match parse_resource_id(&anchor.resource_id) {
+            Ok(resource_id) => resource_id,
+            Err(reason) => {
+                let _ = stream
+                    .send(CommandResponse::Error(format!(
+                        "Invalid resource id {}: {reason}",
+                        anchor.resource_id
+                    )))
+                    .await;
+                return;
+            }
+        };
+        anchors.push(AnchorCursors {
+            name: anchor.resource_id,
+            resource_id,
+            // subscribed before the first read, so that the items stored in
+            // between are not missed.
+            updates: store.cache_updates(resource_id),
+            next: [
+                (CachedItemKind::Leaves, anchor.leaves_from),
+                (
+                    CachedItemKind::EncryptedOutputs,
+                    anchor.encrypted_outputs_from,
+                ),
+                (CachedItemKind::Nullifiers, anchor.nullifiers_from),
+            ],
+        });
+    }
+    loop {
+        for anchor in &mut anchors {
+            for (kind, next) in &mut anchor.next {
+                loop {
+                    let items = match store.get_cached_items(
+                        *kind,
+                        anchor.resource_id,
+                        *next,
+                        MAX_CACHE_UPDATE_ITEMS,
+                    ) {
+                        Ok(items) => items,
+                        Err(e) => {
+                            tracing::error!(
+                                error = %e,
+                                "Failed to read the cached items",
+                            );
+                            let _ = stream
+                                .send(CommandResponse::Error(format!(
+                                    "Failed to read the cached items: {e}"
+                                )))
+                                .await;
+                            return;
+                        }
+                    };
+                    let read = items.len();
+                    let items: Vec<_> = items
+                        .into_iter()
+                        .zip(*next..)
+                        .take_while(|((index, _), expected)| index == expected)
+                        .map(|((_, item), _)| item)
+                        .collect();
+                    if items.is_empty() {
+                        break;
+                    }
+                    let from = *next;
+                    let sent = items.len();
+                    *next += sent as u32;
+                    let update = CacheUpdate {
+                        resource_id: anchor.name.clone(),
+                        kind: *kind,
+                        from,
+                        items,
+                    };
+                    let response = CommandResponse::CacheUpdate(update);
+                    if stream.send(response).await.is_err() {
+                        // the client went away.
+                        return;
+                    }
+                    if sent < MAX_CACHE_UPDATE_ITEMS || sent < read {
+                        break;
+                    }
+                }
+            }
+        }
+        let changes = anchors
+            .iter_mut()
+            .map(|anchor| Box::pin(anchor.updates.changed()));
+        tokio::select! {
+            _ = future::select_all(changes) => {}
+            _ = tokio::time::sleep(CACHE_UPDATES_POLL_INTERVAL) => {}
+            _ = stream.closed() => return,
+        }
+    }
+}
+
+/// Records a response of the relayer to the command of a job.
+fn update_relay_job(job: &mut RelayJob, response: &CommandResponse) {
+    use CommandResponse::*;
+    job.updated_at = webb_relayer_utils::unix_timestamp();
+    job.status = serde_json::to_value(response).ok();
+    match response {
+        Withdraw(WithdrawStatus::Submitted { tx_hash })
+        | Withdraw(WithdrawStatus::Finalized { tx_hash }) => {
+            job.tx_hash = Some(format!("{tx_hash:?}"));
+        }
+        Withdraw(WithdrawStatus::Errored { code, reason }) => {
+            job.error = Some(format!("{reason} (code: {code})"));
+        }
+        Withdraw(WithdrawStatus::InvalidMerkleRoots) => {
+            job.error = Some(String::from("Invalid merkle roots"));
+        }
+        Network(NetworkStatus::Failed { reason }) => {
+            job.error = Some(reason.clone());
+        }
+        Network(
+            status @ (NetworkStatus::UnsupportedContract
+            | NetworkStatus::UnsupportedChain
+            | NetworkStatus::InvalidRelayerAddress),
+        ) => {
+            job.error = Some(format!("{status:?}"));
+        }
+        Error(reason) => job.error = Some(reason.clone()),
+        Unimplemented(reason) => job.error = Some(reason.clone()),
+        _ => {}
+    }
+}
+
+/// Starts relaying the command of a new job in the background, the state
+/// of the job is kept up to date in the store until the relayer is done
+/// with it.
+///
+/// Returns the job, as stored before the command started.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
+/// * `cmd` - The command to relay
+/// * `permit` - The permit of the job, released once the relayer is done
+///   with it
+pub fn spawn_relay_job(
+    ctx: RelayerContext,
+    store: Arc<webb_relayer_store::AnyStore>,
+    cmd: Command,
+    permit: OwnedSemaphorePermit,
+) -> webb_relayer_utils::Result<RelayJob> {
+    let mut id = [0u8; 16];
+    rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut id);
+    let mut job =
+        RelayJob::new(hex::encode(id), webb_relayer_utils::unix_timestamp());
+    store.insert_job(&job)?;
+    let submitted = job.clone();
+    let (tx, mut rx) = mpsc::channel(50);
+    tokio::spawn(handle_cmd(ctx, store.clone(), cmd, tx));
+    tokio::spawn(async move {
+        while let Some(response) = rx.recv().await {
+            update_relay_job(&mut job, &response);
+            if let Err(e) = store.insert_job(&job) {
+                tracing::error!(id = %job.id, error = %e, "Failed to update the relay job");
+            }
+        }
+        // the command was handled, nothing else will be sent.
+        job.finished = true;
+        job.updated_at = webb_relayer_utils::unix_timestamp();
+        if let Err(e) = store.insert_job(&job) {
+            tracing::error!(id = %job.id, error = %e, "Failed to update the relay job");
+        }
+        drop(permit);
+    });
+    Ok(submitted)
+}
+
+/// Handles relay transaction requests submitted over HTTP
+///
+/// Returns the id of the job right away, its status can then be polled with
+/// [`handle_relay_job_status`] or followed over the websocket.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store
+/// * `cmd` - The command to relay, the same as the websocket ones
+pub async fn handle_relay_job_submission(
+    ctx: Arc<RelayerContext>,
+    store: Arc<webb_relayer_store::AnyStore>,
+    cmd: Command,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = if !matches!(cmd, Command::Evm(_) | Command::Substrate(_)) {
+        Err(ApiError::new(
+            ErrorCode::InvalidRequest,
+            "Only the evm and substrate commands can be submitted",
+        ))
+    } else if !ctx.config.features.private_tx_relay {
+        Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Private transaction relaying is not enabled.",
+        ))
+    } else if let Ok(permit) = ctx.relay_jobs.clone().try_acquire_owned() {
+        spawn_relay_job(ctx.as_ref().clone(), store, cmd, permit)
+            .map_err(|e| ApiError::store("Failed to create the relay job", e))
+    } else {
+        Err(ApiError::new(
+            ErrorCode::RateLimited,
+            "Too many relay jobs in flight, try again later",
+        ))
+    };
+    api_reply(result.map(|job| {
+        warp::reply::with_status(
+            warp::reply::json(&RelayJobSubmittedResponse { job_id: job.id }),
+            warp::http::StatusCode::ACCEPTED,
+        )
+    }))
+}
+
+/// Handles relay job status requests
+///
+/// Returns the latest state of the job
+///
+/// # Arguments
+///
+/// * `store` - database store
+/// * `id` - The id of the job, as returned when it was submitted
+pub async fn handle_relay_job_status(
+    store: Arc<webb_relayer_store::AnyStore>,
+    id: String,
+) -> Result<impl warp::Reply, Infallible> {
+    api_reply(relay_job_status(store.as_ref(), &id))
+}
+
+/// Reads the latest state of a relay job.
+fn relay_job_status<S: JobStore>(
+    store: &S,
+    id: &str,
+) -> ApiResult<impl warp::Reply> {
+    match store.get_job(id) {
+        Ok(Some(job)) => Ok(warp::reply::json(&job)),
+        Ok(None) => Err(ApiError::new(
+            ErrorCode::NotFound,
+            format!("Unknown job: {id}"),
+        )),
+        Err(e) => Err(ApiError::store("Failed to read the job", e)),
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The OpenAPI document of the relayer API.
+//!
+//! The schemas in the document are generated from the types the routes reply
+//! with, so it changes along with them. The admin routes are documented too,
+//! they require the `adminToken` security scheme.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+use webb_relayer_handler_utils::{
+    Command, CommandRequest, CommandResponseMessage,
+    EncryptedOutputsCacheResponse, IpInformationResponse, LeavesCacheResponse,
+    LeavesStatusResponse, ProposalsResponse, ProtocolVersion,
+    RelayJobSubmittedResponse, RelayerMetricResponse,
+};
+use webb_relayer_store::{ProposalStatus, RelayJob};
+
+use crate::{
+    AdminStatusResponse, ApiError, DeadLettersResponse, MessageResponse,
+    QueueItemsResponse, RelayerInformationResponse, ResetCursorRequest,
+};
+
+/// Builds the OpenAPI document of the relayer API.
+pub fn openapi() -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let chain_id = path_param::<u32>(&mut gen, "chainId", "The chain id.");
+    let contract = path_param::<String>(
+        &mut gen,
+        "contract",
+        "The address of the contract.",
+    );
+    let tree_id = path_param::<u32>(&mut gen, "treeId", "The id of the tree.");
+    let pallet_id =
+        path_param::<u8>(&mut gen, "palletId", "The index of the pallet.");
+    let job_id = path_param::<String>(&mut gen, "id", "The id of the job.");
+    let queue = path_param::<String>(
+        &mut gen,
+        "queue",
+        "The name of the queue (e.g. `evm_tx_5001` or \
+         `bridge_cmd_<typed chain id>`).",
+    );
+    let dead_letter_id = path_param::<String>(
+        &mut gen,
+        "id",
+        "The hex encoded id of the dead letter.",
+    );
+    let status = json!({
+        "name": "status",
+        "in": "query",
+        "description": "Only return the proposals with this status.",
+        "schema": gen.subschema_for::<ProposalStatus>(),
+    });
+
+    let paths = json!({
+        "/api/v1/ip": {
+            "get": operation::<IpInformationResponse>(
+                &mut gen,
+                "The IP address of the client, as seen by the relayer.",
+                vec![],
+            ),
+        },
+        "/api/v1/info": {
+            "get": operation::<RelayerInformationResponse>(
+                &mut gen,
+                "The configuration of the relayer, without its secrets.",
+                vec![],
+            ),
+        },
+        "/api/v1/leaves/evm/{chainId}/{contract}": {
+            "get": operation::<LeavesCacheResponse>(
+                &mut gen,
+                "The cached leaves of a VAnchor contract.",
+                vec![chain_id.clone(), contract.clone()],
+            ),
+        },
+        "/api/v1/leaves/evm/{chainId}/{contract}/status": {
+            "get": operation::<LeavesStatusResponse>(
+                &mut gen,
+                "The state of the leaves cache of a VAnchor contract, without its leaves.",
+                vec![chain_id.clone(), contract.clone()],
+            ),
+        },
+        "/api/v1/leaves/substrate/{chainId}/{treeId}/{palletId}": {
+            "get": operation::<LeavesCacheResponse>(
+                &mut gen,
+                "The cached leaves of a merkle tree pallet.",
+                vec![chain_id.clone(), tree_id.clone(), pallet_id.clone()],
+            ),
+        },
+        "/api/v1/leaves/substrate/{chainId}/{treeId}/{palletId}/status": {
+            "get": operation::<LeavesStatusResponse>(
+                &mut gen,
+                "The state of the leaves cache of a merkle tree pallet, without its leaves.",
+                vec![chain_id.clone(), tree_id, pallet_id],
+            ),
+        },
+        "/api/v1/encrypted_outputs/evm/{chainId}/{contract}": {
+            "get": operation::<EncryptedOutputsCacheResponse>(
+                &mut gen,
+                "The cached encrypted outputs of a VAnchor contract.",
+                vec![chain_id.clone(), contract.clone()],
+            ),
+        },
+        "/api/v1/metrics": {
+            "get": operation::<RelayerMetricResponse>(
+                &mut gen,
+                "The metrics of the relayer.",
+                vec![],
+            ),
+        },
+        "/api/v1/openapi.json": {
+            "get": operation::<Value>(
+                &mut gen,
+                "This OpenAPI document.",
+                vec![],
+            ),
+        },
+        "/api/v1/proposals": {
+            "get": operation::<ProposalsResponse>(
+                &mut gen,
+                "The proposals the relayer submitted to the DKG for signing.",
+                vec![status],
+            ),
+        },
+        "/api/v1/send": {
+            "post": submit_operation(&mut gen),
+        },
+        "/api/v1/jobs/{id}": {
+            "get": operation::<RelayJob>(
+                &mut gen,
+                "The latest state of a relay job.",
+                vec![job_id],
+            ),
+        },
+        "/ws": {
+            "get": websocket_operation(&mut gen),
+        },
+        "/api/v1/admin/status": {
+            "get": admin(operation::<AdminStatusResponse>(
+                &mut gen,
+                "The paused watchers and transaction queues.",
+                vec![],
+            )),
+        },
+        "/api/v1/admin/watchers/evm/{chainId}/{contract}/pause": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Pauses the events watcher of a contract.",
+                vec![chain_id.clone(), contract.clone()],
+            )),
+        },
+        "/api/v1/admin/watchers/evm/{chainId}/{contract}/resume": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Resumes the events watcher of a contract.",
+                vec![chain_id.clone(), contract.clone()],
+            )),
+        },
+        "/api/v1/admin/watchers/evm/{chainId}/{contract}/cursor": {
+            "post": admin(reset_cursor_operation(
+                &mut gen,
+                vec![chain_id.clone(), contract],
+            )),
+        },
+        "/api/v1/admin/watchers/substrate/{chainId}/pause": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Pauses the events watchers of a substrate chain.",
+                vec![chain_id.clone()],
+            )),
+        },
+        "/api/v1/admin/watchers/substrate/{chainId}/resume": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Resumes the events watchers of a substrate chain.",
+                vec![chain_id.clone()],
+            )),
+        },
+        "/api/v1/admin/watchers/substrate/{chainId}/cursor": {
+            "post": admin(reset_cursor_operation(&mut gen, vec![chain_id])),
+        },
+        "/api/v1/admin/queues/{queue}": {
+            "get": admin(operation::<QueueItemsResponse>(
+                &mut gen,
+                "The items waiting in a queue.",
+                vec![queue.clone()],
+            )),
+            "delete": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Removes all the items of a queue.",
+                vec![queue.clone()],
+            )),
+        },
+        "/api/v1/admin/queues/{queue}/pause": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Pauses a transaction queue.",
+                vec![queue.clone()],
+            )),
+        },
+        "/api/v1/admin/queues/{queue}/resume": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Resumes a transaction queue.",
+                vec![queue.clone()],
+            )),
+        },
+        "/api/v1/admin/dead-letters/{queue}": {
+            "get": admin(operation::<DeadLettersResponse>(
+                &mut gen,
+                "The transactions and bridge commands of a queue that failed.",
+                vec![queue.clone()],
+            )),
+        },
+        "/api/v1/admin/dead-letters/{queue}/{id}": {
+            "delete": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Discards a dead letter.",
+                vec![queue.clone(), dead_letter_id.clone()],
+            )),
+        },
+        "/api/v1/admin/dead-letters/{queue}/{id}/retry": {
+            "post": admin(operation::<MessageResponse>(
+                &mut gen,
+                "Sends a dead letter back to its queue.",
+                vec![queue, dead_letter_id],
+            )),
+        },
+    });
+
+    let mut schemas = gen.take_definitions();
+    for schema in schemas.values_mut() {
+        for visitor in gen.visitors_mut() {
+            visitor.visit_schema(schema);
+        }
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Webb Relayer API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "adminToken": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "The `admin.token` of the relayer.",
+                },
+            },
+        },
+    })
+}
+
+/// An operation replying with `T`, or with an [`ApiError`].
+fn operation<T: JsonSchema>(
+    gen: &mut SchemaGenerator,
+    summary: &str,
+    parameters: Vec<Value>,
+) -> Value {
+    json!({
+        "summary": summary,
+        "parameters": parameters,
+        "responses": {
+            "200": json_response::<T>(gen, "The requested data."),
+            "default": error_response(gen),
+        },
+    })
+}
+
+/// An operation of the admin API, only served when the relayer has an admin
+/// token, and to the requests bearing it.
+fn admin(mut operation: Value) -> Value {
+    operation["security"] = json!([{ "adminToken": [] }]);
+    operation
+}
+
+/// The operation resetting the last synced block of an events watcher.
+fn reset_cursor_operation(
+    gen: &mut SchemaGenerator,
+    parameters: Vec<Value>,
+) -> Value {
+    json!({
+        "summary": "Resets the last synced block of a paused events watcher.",
+        "parameters": parameters,
+        "requestBody": {
+            "required": true,
+            "content": {
+                "application/json": {
+                    "schema": gen.subschema_for::<ResetCursorRequest>(),
+                },
+            },
+        },
+        "responses": {
+            "200": json_response::<MessageResponse>(gen, "The cursor was reset."),
+            "default": error_response(gen),
+        },
+    })
+}
+
+/// The operation relaying a transaction over HTTP.
+fn submit_operation(gen: &mut SchemaGenerator) -> Value {
+    json!({
+        "summary": "Relays a transaction in the background, as a job.",
+        "description": "Only the `evm` and `substrate` commands are accepted.",
+        "requestBody": {
+            "required": true,
+            "content": {
+                "application/json": {
+                    "schema": gen.subschema_for::<Command>(),
+                },
+            },
+        },
+        "responses": {
+            "202": json_response::<RelayJobSubmittedResponse>(
+                gen,
+                "The job was created.",
+            ),
+            "default": error_response(gen),
+        },
+    })
+}
+
+/// The websocket endpoint, OpenAPI can not describe the messages sent over
+/// it, they are given in the `x-websocket` extension instead.
+fn websocket_operation(gen: &mut SchemaGenerator) -> Value {
+    json!({
+        "summary": "Relays transactions and follows them over a websocket.",
+        "parameters": [{
+            "name": "version",
+            "in": "query",
+            "description": "The version of the protocol of the connection.",
+            "schema": gen.subschema_for::<ProtocolVersion>(),
+        }],
+        "responses": {
+            "101": {
+                "description": "The connection is upgraded to a websocket.",
+            },
+            "default": error_response(gen),
+        },
+        "x-websocket": {
+            "send": gen.subschema_for::<CommandRequest>(),
+            "receive": gen.subschema_for::<CommandResponseMessage>(),
+        },
+    })
+}
+
+/// A JSON response holding a `T`.
+fn json_response<T: JsonSchema>(
+    gen: &mut SchemaGenerator,
+    description: &str,
+) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": {
+                "schema": gen.subschema_for::<T>(),
+            },
+        },
+    })
+}
+
+/// The response of a failed request.
+fn error_response(gen: &mut SchemaGenerator) -> Value {
+    json_response::<ApiError>(gen, "The error of the request.")
+}
+
+/// A required path parameter holding a `T`.
+fn path_param<T: JsonSchema>(
+    gen: &mut SchemaGenerator,
+    name: &str,
+    description: &str,
+) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": gen.subschema_for::<T>(),
+    })
+}
@@ -0,0 +1,838 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+use warp::http::StatusCode;
+use warp::Reply;
+use webb_relayer_config::evm::EvmChainConfig;
+use webb_relayer_config::substrate::SubstrateConfig;
+use webb_relayer_config::WebbRelayerConfig;
+use webb_relayer_handler_utils::{AnchorSubscription, RequestId};
+use webb_relayer_store::{
+    AnyStore, HistoryStore, HistoryStoreKey, InMemoryStore, JobStore,
+    LeafCacheStore, LeavesConsistency, LeavesSyncStatus, NullifierCacheStore,
+    ProposalStore, RelayJob, SubmittedProposal,
+};
+use webb_relayer_utils::Error;
+
+use super::*;
+
+/// A store failing every read and write.
+#[derive(Debug, Clone, Default)]
+struct FaultyStore;
+
+fn fault<T>() -> webb_relayer_utils::Result<T> {
+    Err(Error::Generic("faulty store"))
+}
+
+impl HistoryStore for FaultyStore {
+    fn set_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+        _block_number: u64,
+    ) -> webb_relayer_utils::Result<u64> {
+        fault()
+    }
+
+    fn get_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+        _default_block_number: u64,
+    ) -> webb_relayer_utils::Result<u64> {
+        fault()
+    }
+}
+
+impl LeafCacheStore for FaultyStore {
+    type Output = Vec<Vec<u8>>;
+
+    fn get_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+    ) -> webb_relayer_utils::Result<Self::Output> {
+        fault()
+    }
+
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+    ) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>> {
+        fault()
+    }
+
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+    ) -> webb_relayer_utils::Result<u64> {
+        fault()
+    }
+
+    fn insert_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+        _leaves: &[(u32, Vec<u8>)],
+    ) -> webb_relayer_utils::Result<()> {
+        fault()
+    }
+
+    fn get_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+    ) -> webb_relayer_utils::Result<u64> {
+        fault()
+    }
+
+    fn insert_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        _key: K,
+        _block_number: u64,
+    ) -> webb_relayer_utils::Result<u64> {
+        fault()
+    }
+}
+
+impl ProposalStore for FaultyStore {
+    type Proposal = SubmittedProposal;
+
+    fn insert_proposal(
+        &self,
+        _proposal: Self::Proposal,
+    ) -> webb_relayer_utils::Result<()> {
+        fault()
+    }
+
+    fn get_proposal(
+        &self,
+        _data_hash: &[u8],
+    ) -> webb_relayer_utils::Result<Option<Self::Proposal>> {
+        fault()
+    }
+
+    fn get_proposals(&self) -> webb_relayer_utils::Result<Vec<Self::Proposal>> {
+        fault()
+    }
+
+    fn remove_proposal(
+        &self,
+        _data_hash: &[u8],
+    ) -> webb_relayer_utils::Result<Option<Self::Proposal>> {
+        fault()
+    }
+}
+
+impl JobStore for FaultyStore {
+    fn get_jobs(&self) -> webb_relayer_utils::Result<Vec<RelayJob>> {
+        fault()
+    }
+
+    fn remove_job(
+        &self,
+        _id: &str,
+    ) -> webb_relayer_utils::Result<Option<RelayJob>> {
+        fault()
+    }
+
+    fn insert_job(&self, _job: &RelayJob) -> webb_relayer_utils::Result<()> {
+        fault()
+    }
+
+    fn get_job(
+        &self,
+        _id: &str,
+    ) -> webb_relayer_utils::Result<Option<RelayJob>> {
+        fault()
+    }
+}
+
+/// Reads the status and the error of a reply.
+async fn error_of(reply: impl Reply) -> (StatusCode, serde_json::Value) {
+    let response = reply.into_response();
+    let status = response.status();
+    let body = warp::hyper::body::to_bytes(response.into_body())
+        .await
+        .expect("failed to read the body");
+    let body = serde_json::from_slice(&body).expect("not a json body");
+    (status, body)
+}
+
+fn assert_error(
+    (status, body): (StatusCode, serde_json::Value),
+    expected: ErrorCode,
+) {
+    assert_eq!(status, expected.status());
+    assert_eq!(body["code"], serde_json::to_value(expected).unwrap());
+    assert!(body["message"].is_string());
+}
+
+#[tokio::test]
+async fn store_errors_are_replied_instead_of_panicking() {
+    let store = FaultyStore;
+    let config = WebbRelayerConfig::default();
+
+    let reply = api_reply(leaves_cache_substrate(&store, &config, 1, 0, 0));
+    assert_error(error_of(reply.unwrap()).await, ErrorCode::StoreError);
+
+    let query = ProposalsQuery { status: None };
+    let reply = api_reply(proposals(&store, query));
+    assert_error(error_of(reply.unwrap()).await, ErrorCode::StoreError);
+
+    let reply = api_reply(relay_job_status(&store, "0011"));
+    assert_error(error_of(reply.unwrap()).await, ErrorCode::StoreError);
+}
+
+#[tokio::test]
+async fn disabled_data_query_is_forbidden() {
+    let mut config = WebbRelayerConfig::default();
+    config.features.data_query = false;
+
+    let reply =
+        api_reply(leaves_cache_substrate(&FaultyStore, &config, 1, 0, 0));
+    assert_error(error_of(reply.unwrap()).await, ErrorCode::FeatureDisabled);
+}
+
+#[tokio::test]
+async fn relay_jobs_over_the_cap_are_rate_limited() {
+    let mut config = sample_config();
+    config.features.private_tx_relay = true;
+    config.jobs.max_in_flight = 0;
+    let ctx = Arc::new(RelayerContext::new(config));
+    let store = Arc::new(AnyStore::from(InMemoryStore::default()));
+    let element = format!("0x{}", "01".repeat(32));
+    let address = format!("0x{}", "02".repeat(20));
+    let cmd: Command = serde_json::from_value(json!({
+        "evm": {
+            "vAnchor": {
+                "chainId": 5001,
+                "id": address,
+                "proofData": {
+                    "proof": "0x00",
+                    "publicAmount": element,
+                    "roots": "0x00",
+                    "inputNullifiers": [element],
+                    "outputCommitments": [element, element],
+                    "extDataHash": element,
+                },
+                "extData": {
+                    "recipient": address,
+                    "relayer": address,
+                    "extAmount": "-0xa",
+                    "fee": "0x1",
+                    "refund": "0x0",
+                    "token": address,
+                    "encryptedOutput1": "0x04",
+                    "encryptedOutput2": "0x05",
+                },
+            },
+        },
+    }))
+    .unwrap();
+
+    let reply = handle_relay_job_submission(ctx, store.clone(), cmd)
+        .await
+        .unwrap();
+    assert_error(error_of(reply).await, ErrorCode::RateLimited);
+    // nothing was queued for the rejected submission.
+    assert!(store.get_jobs().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cursor_resets_before_the_pruned_events_are_refused() {
+    let controls = ServiceControls::default();
+    let store = InMemoryStore::default();
+    let resource_id = ResourceId::new(
+        TargetSystem::new_contract_address([1u8; 20]),
+        TypedChainId::Evm(1),
+    );
+    store.set_last_block_number(resource_id, 1_000).unwrap();
+
+    let error = reset_cursor(&controls, Some(100), &store, resource_id, 950)
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::Conflict);
+
+    // a range of blocks started before the pause is still being synced.
+    let syncing = controls.start_watcher_sync(resource_id).unwrap();
+    controls.pause_watcher(resource_id);
+    assert!(controls.start_watcher_sync(resource_id).is_none());
+    let error = reset_cursor(&controls, Some(100), &store, resource_id, 950)
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::Conflict);
+    drop(syncing);
+
+    let error = reset_cursor(&controls, Some(100), &store, resource_id, 899)
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidRequest);
+    assert_eq!(store.get_last_block_number(resource_id, 0).unwrap(), 1_000);
+
+    reset_cursor(&controls, Some(100), &store, resource_id, 900).unwrap();
+    assert_eq!(store.get_last_block_number(resource_id, 0).unwrap(), 900);
+}
+
+fn subscription(resource_id: &str, leaves_from: u32) -> CacheSubscription {
+    CacheSubscription {
+        anchors: vec![AnchorSubscription {
+            resource_id: resource_id.into(),
+            leaves_from,
+            encrypted_outputs_from: 0,
+            nullifiers_from: 0,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn cache_subscriptions_resume_without_gaps() {
+    let store = Arc::new(AnyStore::from(InMemoryStore::default()));
+    let key = ResourceId::new(
+        TargetSystem::new_contract_address([1; 20]),
+        TypedChainId::Evm(1),
+    );
+    let name = format!("0x{}", hex::encode(key.into_bytes()));
+    store
+        .insert_leaves(key, &[(0, vec![0]), (1, vec![1]), (3, vec![3])])
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::channel(8);
+    let task = tokio::spawn(stream_cache_updates(
+        store.clone(),
+        subscription(&name, 1),
+        tx,
+    ));
+    // the leaf after the gap is held back.
+    let update = CacheUpdate {
+        resource_id: name.clone(),
+        kind: CachedItemKind::Leaves,
+        from: 1,
+        items: vec![vec![1]],
+    };
+    assert_eq!(rx.recv().await, Some(CommandResponse::CacheUpdate(update)));
+
+    store.insert_leaves(key, &[(2, vec![2])]).unwrap();
+    let update = CacheUpdate {
+        resource_id: name.clone(),
+        kind: CachedItemKind::Leaves,
+        from: 2,
+        items: vec![vec![2], vec![3]],
+    };
+    assert_eq!(rx.recv().await, Some(CommandResponse::CacheUpdate(update)));
+
+    store.insert_nullifiers(key, &[vec![4]]).unwrap();
+    let update = CacheUpdate {
+        resource_id: name,
+        kind: CachedItemKind::Nullifiers,
+        from: 0,
+        items: vec![vec![4]],
+    };
+    assert_eq!(rx.recv().await, Some(CommandResponse::CacheUpdate(update)));
+
+    // the subscription ends with the connection.
+    drop(rx);
+    task.await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_cache_subscriptions_are_errors() {
+    let store = Arc::new(AnyStore::from(InMemoryStore::default()));
+    let (tx, mut rx) = mpsc::channel(8);
+    stream_cache_updates(store.clone(), subscription("0x0011", 0), tx).await;
+    assert!(matches!(rx.recv().await, Some(CommandResponse::Error(_))));
+
+    let name = format!("0x{}", hex::encode([1u8; 32]));
+    let mut sub = subscription(&name, 0);
+    sub.anchors = vec![sub.anchors[0].clone(); MAX_SUBSCRIBED_ANCHORS + 1];
+    let (tx, mut rx) = mpsc::channel(8);
+    stream_cache_updates(store, sub, tx).await;
+    assert!(matches!(rx.recv().await, Some(CommandResponse::Error(_))));
+}
+
+#[tokio::test]
+async fn unknown_chains_are_bad_requests() {
+    let config = WebbRelayerConfig::default();
+
+    let reply = api_reply(leaves_cache_evm(
+        &FaultyStore,
+        &config,
+        &LeavesSyncStatuses::default(),
+        5001,
+        Address::zero(),
+    ));
+    let (status, body) = error_of(reply.unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "UNSUPPORTED_CHAIN");
+}
+
+#[tokio::test]
+async fn rejections_are_replied_as_errors() {
+    let reply = handle_rejection(warp::reject::custom(RateLimited))
+        .await
+        .unwrap();
+    assert_error(error_of(reply).await, ErrorCode::RateLimited);
+
+    let reply = handle_rejection(warp::reject::not_found()).await.unwrap();
+    assert_error(error_of(reply).await, ErrorCode::NotFound);
+}
+
+/// Resolves a `$ref` of the OpenAPI document.
+fn resolve<'a>(doc: &'a Value, reference: &Value) -> Result<&'a Value, String> {
+    reference
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/components/schemas/"))
+        .and_then(|name| doc["components"]["schemas"].get(name))
+        .ok_or_else(|| format!("unresolved reference {reference}"))
+}
+
+/// The properties an object matching `schema` may hold, `None` if it may
+/// hold any.
+fn known_properties(doc: &Value, schema: &Value) -> Option<BTreeSet<String>> {
+    let mut known = BTreeSet::new();
+    let mut pending = vec![schema];
+    while let Some(schema) = pending.pop() {
+        match schema.get("additionalProperties") {
+            None | Some(Value::Bool(false)) => {}
+            Some(_) => return None,
+        }
+        if let Some(properties) = schema["properties"].as_object() {
+            known.extend(properties.keys().cloned());
+        }
+        if let Some(reference) = schema.get("$ref") {
+            pending.push(resolve(doc, reference).ok()?);
+        }
+        for key in ["allOf", "anyOf", "oneOf"] {
+            if let Some(schemas) = schema[key].as_array() {
+                pending.extend(schemas);
+            }
+        }
+    }
+    if known.is_empty() {
+        None
+    } else {
+        Some(known)
+    }
+}
+
+/// Checks a value against a schema of the OpenAPI document, objects may only
+/// hold the properties known to their schema, so that a field added to a
+/// response without its schema is caught.
+fn validate(
+    doc: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+) -> Result<(), String> {
+    check(doc, schema, value, path)?;
+    if let (Value::Object(object), Some(known)) =
+        (value, known_properties(doc, schema))
+    {
+        if let Some(key) = object.keys().find(|key| !known.contains(*key)) {
+            return Err(format!("{path}: unknown property `{key}`"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a value against the keywords of a schema.
+fn check(
+    doc: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Object(_) => schema,
+        _ => return Err(format!("{path}: rejected by {schema}")),
+    };
+    if value.is_null() && schema["nullable"] == true {
+        return Ok(());
+    }
+    if let Some(reference) = schema.get("$ref") {
+        check(doc, resolve(doc, reference)?, value, path)?;
+    }
+    if let Some(ty) = schema["type"].as_str() {
+        let matches = match ty {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            _ => false,
+        };
+        if !matches {
+            return Err(format!("{path}: expected {ty}, found {value}"));
+        }
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        if !values.contains(value) {
+            return Err(format!("{path}: {value} is not one of {values:?}"));
+        }
+    }
+    for schema in schema["allOf"].as_array().into_iter().flatten() {
+        check(doc, schema, value, path)?;
+    }
+    if let Some(schemas) = schema["anyOf"].as_array() {
+        if !schemas.iter().any(|s| check(doc, s, value, path).is_ok()) {
+            return Err(format!("{path}: {value} matches none of anyOf"));
+        }
+    }
+    if let Some(schemas) = schema["oneOf"].as_array() {
+        let matching = schemas
+            .iter()
+            .filter(|s| check(doc, s, value, path).is_ok())
+            .count();
+        if matching != 1 {
+            return Err(format!("{path}: {value} matches {matching} of oneOf"));
+        }
+    }
+    match value {
+        Value::Object(object) => {
+            for key in schema["required"].as_array().into_iter().flatten() {
+                let key = key.as_str().unwrap_or_default();
+                if !object.contains_key(key) {
+                    return Err(format!("{path}: missing property `{key}`"));
+                }
+            }
+            for (key, item) in object {
+                let item_path = format!("{path}.{key}");
+                if let Some(property) = schema["properties"].get(key) {
+                    validate(doc, property, item, &item_path)?;
+                } else if let Some(additional) =
+                    schema.get("additionalProperties").filter(|s| s.is_object())
+                {
+                    validate(doc, additional, item, &item_path)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(max) = schema["maxItems"].as_u64() {
+                if items.len() as u64 > max {
+                    return Err(format!("{path}: more than {max} items"));
+                }
+            }
+            match &schema["items"] {
+                Value::Array(schemas) if schemas.len() != items.len() => {
+                    return Err(format!(
+                        "{path}: expected a tuple of {}",
+                        schemas.len()
+                    ));
+                }
+                Value::Array(schemas) => {
+                    for (i, (schema, item)) in
+                        schemas.iter().zip(items).enumerate()
+                    {
+                        validate(doc, schema, item, &format!("{path}[{i}]"))?;
+                    }
+                }
+                Value::Null => {}
+                schema => {
+                    for (i, item) in items.iter().enumerate() {
+                        validate(doc, schema, item, &format!("{path}[{i}]"))?;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Asserts the serialized value matches the schema of its type in the
+/// OpenAPI document.
+#[track_caller]
+fn assert_matches_spec<T: Serialize + JsonSchema>(doc: &Value, value: &T) {
+    let name = T::schema_name();
+    let schema = json!({ "$ref": format!("#/components/schemas/{name}") });
+    let value = serde_json::to_value(value).unwrap();
+    if let Err(e) = validate(doc, &schema, &value, &name) {
+        panic!("{name} does not match the OpenAPI document: {e}");
+    }
+}
+
+fn sample_job() -> RelayJob {
+    RelayJob {
+        id: "0011".into(),
+        submitted_at: 1_670_000_000,
+        updated_at: 1_670_000_012,
+        finished: true,
+        status: Some(json!({ "withdraw": "finalized" })),
+        tx_hash: Some(format!("0x{}", "ab".repeat(32))),
+        error: None,
+    }
+}
+
+fn sample_config() -> WebbRelayerConfig {
+    let address = "0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df";
+    let evm: EvmChainConfig = serde_json::from_value(json!({
+        "name": "hermes",
+        "enabled": true,
+        "http-endpoint": "http://localhost:5001",
+        "ws-endpoint": "ws://localhost:5001",
+        "block-confirmations": 1,
+        "chain-id": 5001,
+        "beneficiary": address,
+        "contracts": [{
+            "contract": "VAnchor",
+            "address": address,
+            "deployed-at": 1,
+            "events-watcher": { "polling-interval": 1000 },
+            "withdraw-config": {
+                "withdraw-fee-percentage": 0.0,
+                "withdraw-gaslimit": "0x5B8D80",
+            },
+            "proposal-signing-backend": { "type": "DKGNode", "node": "dkg" },
+            "linked-anchors": [
+                { "type": "Evm", "chain-id": 5002, "address": address },
+                { "type": "Raw", "resource-id": format!("0x{:064x}", 1) },
+            ],
+        }],
+    }))
+    .unwrap();
+    let substrate: SubstrateConfig = serde_json::from_value(json!({
+        "name": "localnode",
+        "enabled": true,
+        "http-endpoint": "http://localhost:9933",
+        "ws-endpoint": "ws://localhost:9944",
+        "chain-id": 1080,
+        "runtime": "WebbProtocol",
+        "pallets": [{
+            "pallet": "VAnchorBn254",
+            "events-watcher": { "polling-interval": 10000 },
+            "linked-anchors": [
+                {
+                    "type": "Substrate",
+                    "chain-id": 1081,
+                    "pallet": 44,
+                    "tree-id": 5,
+                },
+            ],
+        }],
+    }))
+    .unwrap();
+    let mut config = WebbRelayerConfig::default();
+    config.evm.insert(evm.chain_id.to_string(), evm);
+    config
+        .substrate
+        .insert(substrate.chain_id.to_string(), substrate);
+    config
+}
+
+#[test]
+fn openapi_references_resolve() {
+    fn check_references(doc: &Value, value: &Value) {
+        match value {
+            Value::Object(object) => {
+                if let Some(reference) = object.get("$ref") {
+                    resolve(doc, reference).unwrap();
+                }
+                object.values().for_each(|v| check_references(doc, v));
+            }
+            Value::Array(items) => {
+                items.iter().for_each(|v| check_references(doc, v))
+            }
+            _ => {}
+        }
+    }
+
+    let doc = openapi::openapi();
+    assert_eq!(doc["openapi"], "3.0.3");
+    check_references(&doc, &doc);
+}
+
+#[test]
+fn responses_match_the_openapi_document() {
+    let doc = openapi::openapi();
+
+    assert_matches_spec(
+        &doc,
+        &IpInformationResponse {
+            ip: "127.0.0.1".into(),
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &RelayerInformationResponse {
+            config: sample_config(),
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &LeavesCacheResponse {
+            leaves: vec![vec![1; 32], vec![2; 32]],
+            last_queried_block: 42,
+            sync_status: None,
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &LeavesCacheResponse {
+            leaves: vec![],
+            last_queried_block: 42,
+            sync_status: Some(LeavesSyncStatus {
+                checked_at_block: 40,
+                consistency: LeavesConsistency {
+                    cached: 2,
+                    expected: 4,
+                    missing: vec![(2, 3)],
+                    duplicates: vec![1],
+                    unexpected: vec![],
+                },
+                refetched: 2,
+                healthy: true,
+            }),
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &EncryptedOutputsCacheResponse {
+            encrypted_outputs: vec![vec![3; 64]],
+            last_queried_block: 42,
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &RelayerMetricResponse {
+            metrics: "# HELP".into(),
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &ProposalsResponse {
+            proposals: vec![ProposalInfo {
+                data_hash: "0x01".into(),
+                nonce: 1,
+                src_chain_id: 5001,
+                target: "0x02".into(),
+                proposal: "0x03".into(),
+                status: ProposalStatus::Pending,
+                attempts: 1,
+                submitted_at: 1_670_000_000,
+                signed_at: None,
+            }],
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &RelayJobSubmittedResponse {
+            job_id: "0011".into(),
+        },
+    );
+    assert_matches_spec(&doc, &sample_job());
+    assert_matches_spec(
+        &doc,
+        &ApiError::new(ErrorCode::NotFound, "Unknown job: 0011"),
+    );
+}
+
+#[test]
+fn admin_responses_match_the_openapi_document() {
+    let doc = openapi::openapi();
+
+    assert_matches_spec(
+        &doc,
+        &AdminStatusResponse {
+            paused_watchers: vec![format!("0x{}", "01".repeat(32))],
+            paused_queues: vec!["evm_tx_5001".into()],
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &QueueItemsResponse {
+            queue: "evm_tx_5001".into(),
+            items: vec![QueuedItem {
+                priority: Default::default(),
+                not_before: Some(1_670_000_000),
+                item: json!({ "nonce": 1 }),
+            }],
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &DeadLettersResponse {
+            queue: "evm_tx_5001".into(),
+            dead_letters: vec![DeadLetterInfo {
+                id: "0x0011".into(),
+                item: json!({ "nonce": 1 }),
+                reason: "nonce too low".into(),
+                attempts: 3,
+                first_failed_at: 1_670_000_000,
+                last_failed_at: 1_670_000_012,
+                retried_at: None,
+            }],
+        },
+    );
+    assert_matches_spec(
+        &doc,
+        &MessageResponse {
+            message: "Dead letter discarded".into(),
+        },
+    );
+}
+
+#[test]
+fn websocket_messages_match_the_openapi_document() {
+    let doc = openapi::openapi();
+
+    let responses = vec![
+        CommandResponse::Pong(),
+        CommandResponse::Network(NetworkStatus::Connected),
+        CommandResponse::Network(NetworkStatus::Failed {
+            reason: "oops".into(),
+        }),
+        CommandResponse::Withdraw(WithdrawStatus::Submitted {
+            tx_hash: Default::default(),
+        }),
+        CommandResponse::Withdraw(WithdrawStatus::Errored {
+            code: -1,
+            reason: "oops".into(),
+        }),
+        CommandResponse::Job(sample_job()),
+        CommandResponse::CacheUpdate(CacheUpdate {
+            resource_id: format!("0x{}", "00".repeat(32)),
+            kind: CachedItemKind::Leaves,
+            from: 3,
+            items: vec![vec![1; 32]],
+        }),
+        CommandResponse::Error("oops".into()),
+        CommandResponse::Unimplemented("soon".into()),
+    ];
+    for (i, response) in responses.into_iter().enumerate() {
+        let request_id = (i % 2 == 0).then_some(RequestId::Number(i as u64));
+        assert_matches_spec(
+            &doc,
+            &CommandResponseMessage {
+                request_id,
+                response,
+            },
+        );
+    }
+
+    let schema = json!({ "$ref": "#/components/schemas/CommandRequest" });
+    for request in [
+        json!({ "requestId": 1, "ping": [] }),
+        json!({ "requestId": "a", "job": { "id": "0011" } }),
+        json!({
+            "requestId": 2,
+            "subscribe": {
+                "anchors": [{ "resourceId": "0x00", "nullifiersFrom": 4 }]
+            }
+        }),
+    ] {
+        serde_json::from_value::<CommandRequest>(request.clone()).unwrap();
+        validate(&doc, &schema, &request, "CommandRequest").unwrap();
+    }
+    let mixed = json!({ "ping": [], "job": { "id": "0011" } });
+    assert!(validate(&doc, &schema, &mixed, "CommandRequest").is_err());
+}
@@ -0,0 +1,382 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::{watch, Notify};
+use webb_proposals::ResourceId;
+
+use crate::sled::SledQueueKey;
+use crate::{
+    BatchStore, CacheSubscriptionStore, CachedItemKind, DeadLetter,
+    DeadLetterStore, EncryptedOutputCacheStore, EnqueueOptions, EventHashStore,
+    HistoryStore, HistoryStoreKey, InMemoryStore, JobStore, LeafCacheStore,
+    NullifierCacheStore, ProposalStore, QueueStore, QueuedItem, RelayJob,
+    SledStore, SqliteStore, StoreBatch, SubmittedProposal,
+};
+
+/// AnyStore is a store that forwards every call to the backend
+/// selected in the relayer configuration.
+#[derive(Clone, Debug)]
+pub enum AnyStore {
+    /// A [Sled](https://sled.rs)-based store.
+    Sled(SledStore),
+    /// A [SQLite](https://sqlite.org)-based store.
+    Sqlite(SqliteStore),
+    /// An in-memory store, that does not persist anything.
+    Memory(InMemoryStore),
+}
+
+/// Calls the same expression on whichever backend is in use.
+macro_rules! dispatch {
+    ($self:ident, $store:ident => $e:expr) => {
+        match $self {
+            AnyStore::Sled($store) => $e,
+            AnyStore::Sqlite($store) => $e,
+            AnyStore::Memory($store) => $e,
+        }
+    };
+}
+
+impl AnyStore {
+    /// Gets the total amount of data stored on disk
+    pub fn get_data_stored_size(&self) -> u64 {
+        dispatch!(self, store => store.get_data_stored_size())
+    }
+
+    /// Gets the number of entries of every tree (or table) of the store.
+    pub fn get_tree_sizes(&self) -> crate::Result<Vec<(String, u64)>> {
+        dispatch!(self, store => store.get_tree_sizes())
+    }
+}
+
+impl From<SledStore> for AnyStore {
+    fn from(store: SledStore) -> Self {
+        Self::Sled(store)
+    }
+}
+
+impl From<SqliteStore> for AnyStore {
+    fn from(store: SqliteStore) -> Self {
+        Self::Sqlite(store)
+    }
+}
+
+impl From<InMemoryStore> for AnyStore {
+    fn from(store: InMemoryStore) -> Self {
+        Self::Memory(store)
+    }
+}
+
+impl HistoryStore for AnyStore {
+    fn set_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.set_last_block_number(key, block_number))
+    }
+
+    fn get_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        default_block_number: u64,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.get_last_block_number(key, default_block_number))
+    }
+}
+
+impl LeafCacheStore for AnyStore {
+    type Output = Vec<Vec<u8>>;
+
+    fn get_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        dispatch!(self, store => store.get_leaves(key))
+    }
+
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        dispatch!(self, store => store.get_leaves_with_indices(key))
+    }
+
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.get_leaves_count(key))
+    }
+
+    fn insert_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        leaves: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        dispatch!(self, store => store.insert_leaves(key, leaves))
+    }
+
+    fn get_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.get_last_deposit_block_number(key))
+    }
+
+    fn insert_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.insert_last_deposit_block_number(key, block_number))
+    }
+}
+
+impl EncryptedOutputCacheStore for AnyStore {
+    type Output = Vec<Vec<u8>>;
+
+    fn get_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        dispatch!(self, store => store.get_encrypted_output(key))
+    }
+
+    fn get_encrypted_outputs_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        dispatch!(self, store => store.get_encrypted_outputs_with_indices(key))
+    }
+
+    fn insert_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        encrypted_output: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        dispatch!(self, store => store.insert_encrypted_output(key, encrypted_output))
+    }
+
+    fn get_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.get_last_deposit_block_number_for_encrypted_output(key))
+    }
+
+    fn insert_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        dispatch!(self, store => store.insert_last_deposit_block_number_for_encrypted_output(key, block_number))
+    }
+}
+
+impl NullifierCacheStore for AnyStore {
+    fn get_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        dispatch!(self, store => store.get_nullifiers(key))
+    }
+
+    fn insert_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        nullifiers: &[Vec<u8>],
+    ) -> crate::Result<()> {
+        dispatch!(self, store => store.insert_nullifiers(key, nullifiers))
+    }
+}
+
+impl CacheSubscriptionStore for AnyStore {
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        dispatch!(self, store => store.get_cached_items(kind, key, from, limit))
+    }
+
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64> {
+        dispatch!(self, store => store.cache_updates(key))
+    }
+}
+
+impl BatchStore for AnyStore {
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()> {
+        dispatch!(self, store => store.commit_batch(batch))
+    }
+}
+
+impl EventHashStore for AnyStore {
+    fn store_event<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        event: &[u8],
+        block_number: u64,
+    ) -> crate::Result<()> {
+        dispatch!(self, store => store.store_event(key, event, block_number))
+    }
+
+    fn contains_event(&self, event: &[u8]) -> crate::Result<bool> {
+        dispatch!(self, store => store.contains_event(event))
+    }
+
+    fn delete_event(&self, event: &[u8]) -> crate::Result<()> {
+        dispatch!(self, store => store.delete_event(event))
+    }
+
+    fn prune_events(&self, depth: u64) -> crate::Result<u64> {
+        dispatch!(self, store => store.prune_events(depth))
+    }
+}
+
+impl<T> QueueStore<T> for AnyStore
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    type Key = SledQueueKey;
+
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: T,
+        options: EnqueueOptions,
+    ) -> crate::Result<()> {
+        dispatch!(self, store => QueueStore::<T>::enqueue_item_with_options(store, key, item, options))
+    }
+
+    fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        dispatch!(self, store => QueueStore::<T>::dequeue_item(store, key))
+    }
+
+    fn peek_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        dispatch!(self, store => QueueStore::<T>::peek_item(store, key))
+    }
+
+    fn has_item(&self, key: Self::Key) -> crate::Result<bool> {
+        dispatch!(self, store => QueueStore::<T>::has_item(store, key))
+    }
+
+    fn remove_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        dispatch!(self, store => QueueStore::<T>::remove_item(store, key))
+    }
+
+    fn queue_items(&self, key: Self::Key) -> crate::Result<Vec<QueuedItem<T>>> {
+        dispatch!(self, store => QueueStore::<T>::queue_items(store, key))
+    }
+
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64> {
+        dispatch!(self, store => QueueStore::<T>::purge_queue(store, key))
+    }
+
+    fn queue_notifier(&self, key: Self::Key) -> Arc<Notify> {
+        dispatch!(self, store => QueueStore::<T>::queue_notifier(store, key))
+    }
+}
+
+impl ProposalStore for AnyStore {
+    type Proposal = SubmittedProposal;
+
+    fn insert_proposal(&self, proposal: Self::Proposal) -> crate::Result<()> {
+        dispatch!(self, store => store.insert_proposal(proposal))
+    }
+
+    fn get_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        dispatch!(self, store => store.get_proposal(data_hash))
+    }
+
+    fn get_proposals(&self) -> crate::Result<Vec<Self::Proposal>> {
+        dispatch!(self, store => store.get_proposals())
+    }
+
+    fn remove_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        dispatch!(self, store => store.remove_proposal(data_hash))
+    }
+}
+
+impl<T> DeadLetterStore<T> for AnyStore
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = SledQueueKey;
+
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<T>,
+    ) -> crate::Result<()> {
+        dispatch!(self, store => DeadLetterStore::<T>::insert_dead_letter(store, key, letter))
+    }
+
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        dispatch!(self, store => DeadLetterStore::<T>::get_dead_letter(store, key, id))
+    }
+
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<T>>> {
+        dispatch!(self, store => DeadLetterStore::<T>::get_dead_letters(store, key))
+    }
+
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        dispatch!(self, store => DeadLetterStore::<T>::remove_dead_letter(store, key, id))
+    }
+}
+
+impl JobStore for AnyStore {
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()> {
+        dispatch!(self, store => store.insert_job(job))
+    }
+
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        dispatch!(self, store => store.get_job(id))
+    }
+
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>> {
+        dispatch!(self, store => store.get_jobs())
+    }
+
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        dispatch!(self, store => store.remove_job(id))
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batches of writes committed to the store at once.
+//!
+//! The event handlers of a watcher do not write the leaves, encrypted outputs,
+//! nullifiers and event hashes to the store directly, they add them to a [`StoreBatch`]
+//! instead. The watcher then commits the batch together with its last block
+//! number, so a crash either keeps all the writes of a range of blocks and
+//! moves the watcher past it, or keeps none of them and the range is handled
+//! again after a restart.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use webb_proposals::ResourceId;
+
+/// A write of a [`StoreBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    /// Inserts (or replaces) leaves, by index.
+    InsertLeaves {
+        /// The anchor the leaves belong to.
+        key: ResourceId,
+        /// The `(index, leaf)` pairs.
+        leaves: Vec<(u32, Vec<u8>)>,
+    },
+    /// Inserts (or replaces) encrypted outputs, by index.
+    InsertEncryptedOutput {
+        /// The anchor the encrypted outputs belong to.
+        key: ResourceId,
+        /// The `(index, encrypted output)` pairs.
+        encrypted_output: Vec<(u32, Vec<u8>)>,
+    },
+    /// Appends nullifiers, after the ones already stored.
+    InsertNullifiers {
+        /// The anchor the nullifiers were spent on.
+        key: ResourceId,
+        /// The nullifiers, in the order they were spent.
+        nullifiers: Vec<Vec<u8>>,
+    },
+    /// Sets the block number of the last deposit.
+    SetLastDepositBlockNumber {
+        /// The anchor the deposit was made to.
+        key: ResourceId,
+        /// The block number of the deposit.
+        block_number: u64,
+    },
+    /// Marks an event as handled.
+    StoreEvent {
+        /// The contract that emitted the event.
+        key: ResourceId,
+        /// The serialized event.
+        event: Vec<u8>,
+        /// The block number of the event.
+        block_number: u64,
+    },
+    /// Sets the last block number synced by an events watcher.
+    SetLastBlockNumber {
+        /// The contract watched.
+        key: ResourceId,
+        /// The last block number synced.
+        block_number: u64,
+    },
+    /// Fails the commit once applied, to check the writes applied before it
+    /// are not kept.
+    #[cfg(test)]
+    Fail,
+}
+
+impl BatchOp {
+    /// Returns the anchor whose cached items are written, if any, so its
+    /// subscribers can be notified once the batch is committed.
+    pub fn cached_anchor(&self) -> Option<ResourceId> {
+        match self {
+            Self::InsertLeaves { key, .. }
+            | Self::InsertEncryptedOutput { key, .. }
+            | Self::InsertNullifiers { key, .. } => Some(*key),
+            _ => None,
+        }
+    }
+}
+
+/// The writes collected while handling a range of blocks, applied in order by
+/// [`BatchStore::commit_batch`].
+///
+/// Writes can be added through a shared reference, so the event handlers of a
+/// range can run concurrently on the same batch.
+#[derive(Debug, Default)]
+pub struct StoreBatch {
+    ops: Mutex<Vec<BatchOp>>,
+}
+
+impl StoreBatch {
+    /// Creates a new, empty, batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a write to the batch.
+    pub fn push(&self, op: BatchOp) {
+        self.ops.lock().push(op);
+    }
+
+    /// Inserts the leaves of the given anchor.
+    pub fn insert_leaves(&self, key: ResourceId, leaves: &[(u32, Vec<u8>)]) {
+        self.push(BatchOp::InsertLeaves {
+            key,
+            leaves: leaves.to_vec(),
+        });
+    }
+
+    /// Inserts the encrypted outputs of the given anchor.
+    pub fn insert_encrypted_output(
+        &self,
+        key: ResourceId,
+        encrypted_output: &[(u32, Vec<u8>)],
+    ) {
+        self.push(BatchOp::InsertEncryptedOutput {
+            key,
+            encrypted_output: encrypted_output.to_vec(),
+        });
+    }
+
+    /// Appends the nullifiers spent on the given anchor.
+    pub fn insert_nullifiers(&self, key: ResourceId, nullifiers: &[Vec<u8>]) {
+        self.push(BatchOp::InsertNullifiers {
+            key,
+            nullifiers: nullifiers.to_vec(),
+        });
+    }
+
+    /// Sets the last deposit block number of the given anchor.
+    pub fn insert_last_deposit_block_number(
+        &self,
+        key: ResourceId,
+        block_number: u64,
+    ) {
+        self.push(BatchOp::SetLastDepositBlockNumber { key, block_number });
+    }
+
+    /// Marks the given event of a contract as handled.
+    pub fn store_event(
+        &self,
+        key: ResourceId,
+        event: &[u8],
+        block_number: u64,
+    ) {
+        self.push(BatchOp::StoreEvent {
+            key,
+            event: event.to_vec(),
+            block_number,
+        });
+    }
+
+    /// Sets the last block number synced for the given contract.
+    pub fn set_last_block_number(&self, key: ResourceId, block_number: u64) {
+        self.push(BatchOp::SetLastBlockNumber { key, block_number });
+    }
+
+    /// Moves the writes of `other` at the end of this batch.
+    pub fn append(&self, other: &StoreBatch) {
+        let mut ops = std::mem::take(&mut *other.ops.lock());
+        self.ops.lock().append(&mut ops);
+    }
+
+    /// Returns the number of writes in the batch.
+    pub fn len(&self) -> usize {
+        self.ops.lock().len()
+    }
+
+    /// Returns `true` if the batch has no writes.
+    pub fn is_empty(&self) -> bool {
+        self.ops.lock().is_empty()
+    }
+
+    /// Takes the writes out of the batch, leaving it empty.
+    pub fn take_ops(&self) -> Vec<BatchOp> {
+        std::mem::take(&mut *self.ops.lock())
+    }
+}
+
+/// A store that can apply all the writes of a [`StoreBatch`] at once: either
+/// all of them are stored, or none.
+pub trait BatchStore {
+    /// Commits the writes of the batch, in order, leaving it empty.
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()>;
+}
+
+impl<S: BatchStore> BatchStore for Arc<S> {
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()> {
+        S::commit_batch(self, batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use webb_proposals::{TargetSystem, TypedChainId};
+
+    use super::*;
+    use crate::{
+        InMemoryStore, LeavesConsistency, RelayerStore, SledStore, SqliteStore,
+    };
+
+    /// The number of blocks of the fake contract.
+    const BLOCKS: u64 = 12;
+    /// The number of leaves inserted in every block.
+    const LEAVES_PER_BLOCK: u32 = 3;
+    /// The number of blocks handled in one batch.
+    const STEP: u64 = 5;
+
+    fn key() -> ResourceId {
+        ResourceId::new(
+            TargetSystem::new_contract_address([1u8; 20]),
+            TypedChainId::Evm(1),
+        )
+    }
+
+    fn leaf(index: u32) -> Vec<u8> {
+        webb::evm::ethers::utils::keccak256(index.to_be_bytes()).to_vec()
+    }
+
+    /// Handles the blocks after the last synced one like the events watcher
+    /// does, one batch per range of blocks. If `crash_after` is set, stops
+    /// after handling that many leaves, without committing the current batch.
+    ///
+    /// Returns `true` if every block was handled.
+    fn sync<S: RelayerStore>(store: &S, crash_after: Option<u32>) -> bool {
+        let mut handled = 0;
+        loop {
+            let block = store.get_last_block_number(key(), 0).unwrap();
+            if block == BLOCKS {
+                return true;
+            }
+            let dest_block = std::cmp::min(block + STEP, BLOCKS);
+            let batch = StoreBatch::new();
+            for block_number in block + 1..=dest_block {
+                for i in 0..LEAVES_PER_BLOCK {
+                    if crash_after == Some(handled) {
+                        return false;
+                    }
+                    let index =
+                        (block_number - 1) as u32 * LEAVES_PER_BLOCK + i;
+                    batch.insert_leaves(key(), &[(index, leaf(index))]);
+                    batch.insert_last_deposit_block_number(key(), block_number);
+                    batch.store_event(key(), &leaf(index), block_number);
+                    handled += 1;
+                }
+            }
+            batch.set_last_block_number(key(), dest_block);
+            store.commit_batch(&batch).unwrap();
+        }
+    }
+
+    /// Crashes the sync after every possible number of handled leaves, then
+    /// restarts it from a reopened store and checks nothing is missing.
+    fn crashes_should_not_leave_gaps<S, F>(open: F)
+    where
+        S: RelayerStore,
+        F: Fn(&Path) -> S,
+    {
+        let total = BLOCKS as u32 * LEAVES_PER_BLOCK;
+        for crash_after in 0..total {
+            let tmp = tempfile::tempdir().unwrap();
+            let store = open(tmp.path());
+            assert!(!sync(&store, Some(crash_after)));
+            // the store is only left with whole ranges of blocks.
+            let block = store.get_last_block_number(key(), 0).unwrap();
+            let leaves = store.get_leaves_with_indices(key()).unwrap();
+            assert_eq!(block % STEP, 0);
+            assert_eq!(leaves.len() as u64, block * LEAVES_PER_BLOCK as u64);
+            drop(store);
+
+            let store = open(tmp.path());
+            assert!(sync(&store, None));
+            let leaves = store.get_leaves_with_indices(key()).unwrap();
+            let consistency = LeavesConsistency::check(&leaves, total);
+            assert!(consistency.is_consistent(), "{consistency:?}");
+            assert!(leaves.iter().all(|(index, v)| *v == leaf(*index)));
+            assert!((0..total)
+                .all(|index| store.contains_event(&leaf(index)).unwrap()));
+            assert_eq!(
+                store.get_last_deposit_block_number(key()).unwrap(),
+                BLOCKS
+            );
+        }
+    }
+
+    #[test]
+    fn sled_crashes_should_not_leave_gaps() {
+        crashes_should_not_leave_gaps(|path| SledStore::open(path).unwrap());
+    }
+
+    #[test]
+    fn sqlite_crashes_should_not_leave_gaps() {
+        crashes_should_not_leave_gaps(|path| {
+            SqliteStore::open(path.join("store.db")).unwrap()
+        });
+    }
+
+    /// Commits a batch writing to every kind of tree that fails partway, then
+    /// checks none of its writes were kept and the store is still usable.
+    fn failed_commits_should_not_write<S: RelayerStore>(store: &S) {
+        let batch = StoreBatch::new();
+        batch.insert_leaves(key(), &[(0, leaf(0))]);
+        batch.insert_encrypted_output(key(), &[(0, leaf(1))]);
+        batch.insert_nullifiers(key(), &[leaf(2)]);
+        batch.insert_last_deposit_block_number(key(), 1);
+        batch.store_event(key(), &leaf(0), 1);
+        batch.set_last_block_number(key(), 1);
+        batch.push(BatchOp::Fail);
+        batch.insert_leaves(key(), &[(1, leaf(3))]);
+        assert!(store.commit_batch(&batch).is_err());
+        assert!(batch.is_empty());
+
+        assert!(store.get_leaves_with_indices(key()).unwrap().is_empty());
+        assert!(store.get_encrypted_output(key()).unwrap().is_empty());
+        assert!(store.get_nullifiers(key()).unwrap().is_empty());
+        assert_eq!(store.get_last_deposit_block_number(key()).unwrap(), 0);
+        assert!(!store.contains_event(&leaf(0)).unwrap());
+        assert_eq!(store.get_last_block_number(key(), 0).unwrap(), 0);
+
+        batch.insert_leaves(key(), &[(0, leaf(0))]);
+        batch.set_last_block_number(key(), 1);
+        store.commit_batch(&batch).unwrap();
+        assert_eq!(store.get_leaves(key()).unwrap(), vec![leaf(0)]);
+        assert_eq!(store.get_last_block_number(key(), 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn sled_failed_commits_should_not_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        failed_commits_should_not_write(&SledStore::open(tmp.path()).unwrap());
+    }
+
+    #[test]
+    fn sqlite_failed_commits_should_not_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SqliteStore::open(tmp.path().join("store.db")).unwrap();
+        failed_commits_should_not_write(&store);
+    }
+
+    #[test]
+    fn mem_failed_commits_should_not_write() {
+        failed_commits_should_not_write(&InMemoryStore::default());
+    }
+}
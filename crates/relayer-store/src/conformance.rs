@@ -0,0 +1,740 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A conformance test suite shared by every store backend, so they all
+//! behave the same as the [`SledStore`](crate::SledStore).
+//!
+//! Use [`store_conformance_tests`] inside a test module to run the suite
+//! against a backend.
+
+use std::time::Duration;
+
+use webb::evm::contract::protocol_solidity::v_anchor_contract::NewNullifierFilter;
+use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
+use webb::evm::ethers::types;
+use webb::evm::ethers::types::transaction::request::TransactionRequest;
+use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+
+use crate::dead_letter::{
+    discard_dead_letter, list_dead_letters, move_to_dead_letters,
+    retry_dead_letter,
+};
+use crate::job::{fail_unfinished_jobs, prune_jobs};
+use crate::sled::SledQueueKey;
+use crate::{
+    wait_for_items, BridgeCommand, BridgeKey, CachedItemKind, DeadLetterStore,
+    EnqueueOptions, QueuePriority, QueueStore, QueuedItem, RelayJob,
+    RelayerStore, StoreBatch, SubmittedProposal,
+};
+
+/// Generates a test for every check of the conformance suite, using the
+/// given expression to create a new, empty, store for each test.
+macro_rules! store_conformance_tests {
+    ($make_store:expr) => {
+        #[test]
+        fn last_block_number_should_work() {
+            $crate::conformance::last_block_number_should_work(&$make_store);
+        }
+
+        #[test]
+        fn leaves_should_work() {
+            $crate::conformance::leaves_should_work(&$make_store);
+        }
+
+        #[test]
+        fn leaves_with_indices_should_work() {
+            $crate::conformance::leaves_with_indices_should_work(&$make_store);
+        }
+
+        #[test]
+        fn batch_should_work() {
+            $crate::conformance::batch_should_work(&$make_store);
+        }
+
+        #[test]
+        fn encrypted_outputs_should_work() {
+            $crate::conformance::encrypted_outputs_should_work(&$make_store);
+        }
+
+        #[test]
+        fn nullifiers_should_work() {
+            $crate::conformance::nullifiers_should_work(&$make_store);
+        }
+
+        #[test]
+        fn cache_subscriptions_should_work() {
+            $crate::conformance::cache_subscriptions_should_work(&$make_store);
+        }
+
+        #[test]
+        fn tx_queue_should_work() {
+            $crate::conformance::tx_queue_should_work(&$make_store);
+        }
+
+        #[test]
+        fn queue_priorities_should_work() {
+            $crate::conformance::queue_priorities_should_work(&$make_store);
+        }
+
+        #[test]
+        fn queue_inspection_should_work() {
+            $crate::conformance::queue_inspection_should_work(&$make_store);
+        }
+
+        #[test]
+        fn queue_notifier_should_work() {
+            $crate::conformance::queue_notifier_should_work(&$make_store);
+        }
+
+        #[test]
+        fn queues_should_be_isolated() {
+            $crate::conformance::queues_should_be_isolated(&$make_store);
+        }
+
+        #[test]
+        fn events_hash_should_work() {
+            $crate::conformance::events_hash_should_work(&$make_store);
+        }
+
+        #[test]
+        fn events_pruning_should_work() {
+            $crate::conformance::events_pruning_should_work(&$make_store);
+        }
+
+        #[test]
+        fn dead_letters_should_work() {
+            $crate::conformance::dead_letters_should_work(&$make_store);
+        }
+
+        #[test]
+        fn proposal_store_should_work() {
+            $crate::conformance::proposal_store_should_work(&$make_store);
+        }
+
+        #[test]
+        fn relay_jobs_should_work() {
+            $crate::conformance::relay_jobs_should_work(&$make_store);
+        }
+    };
+}
+
+pub(crate) use store_conformance_tests;
+
+fn history_store_key() -> (TypedChainId, TargetSystem) {
+    let contract = types::H160::from_slice("11111111111111111111".as_bytes());
+    (
+        TypedChainId::Evm(1),
+        TargetSystem::new_contract_address(contract.to_fixed_bytes()),
+    )
+}
+
+fn random_tx() -> TypedTransaction {
+    TransactionRequest::pay(types::Address::random(), types::U256::one())
+        .from(types::Address::random())
+        .into()
+}
+
+pub fn last_block_number_should_work<S: RelayerStore>(store: &S) {
+    let key = history_store_key();
+    assert_eq!(store.get_last_block_number(key, 1).unwrap(), 1);
+    assert_eq!(store.set_last_block_number(key, 20).unwrap(), 20);
+    assert_eq!(store.get_last_block_number(key, 1).unwrap(), 20);
+    assert_eq!(store.get_last_block_number_or_default(key).unwrap(), 20);
+}
+
+pub fn leaves_should_work<S: RelayerStore>(store: &S) {
+    let key = history_store_key();
+    assert!(store.get_leaves(key).unwrap().is_empty());
+    store
+        .insert_leaves(key, &[(0, vec![1; 32]), (1, vec![2; 32])])
+        .unwrap();
+    // inserting the same index again replaces the leaf.
+    store
+        .insert_leaves(key, &[(1, vec![3; 32]), (2, vec![4; 32])])
+        .unwrap();
+    assert_eq!(
+        store.get_leaves(key).unwrap(),
+        vec![vec![1; 32], vec![3; 32], vec![4; 32]]
+    );
+    assert_eq!(store.get_leaves_count(key).unwrap(), 3);
+    // leaves and encrypted outputs are kept apart.
+    assert!(store.get_encrypted_output(key).unwrap().is_empty());
+
+    assert_eq!(store.get_last_deposit_block_number(key).unwrap(), 0);
+    store.insert_last_deposit_block_number(key, 42).unwrap();
+    assert_eq!(store.get_last_deposit_block_number(key).unwrap(), 42);
+}
+
+pub fn leaves_with_indices_should_work<S: RelayerStore>(store: &S) {
+    let key = history_store_key();
+    assert!(store.get_leaves_with_indices(key).unwrap().is_empty());
+    // inserted out of order, with a gap, and past one byte of indices.
+    store
+        .insert_leaves(key, &[(257, vec![4]), (0, vec![1]), (256, vec![3])])
+        .unwrap();
+    store.insert_leaves(key, &[(1, vec![2])]).unwrap();
+    assert_eq!(
+        store.get_leaves_with_indices(key).unwrap(),
+        vec![(0, vec![1]), (1, vec![2]), (256, vec![3]), (257, vec![4])]
+    );
+    assert_eq!(
+        store.get_leaves(key).unwrap(),
+        vec![vec![1], vec![2], vec![3], vec![4]]
+    );
+}
+
+pub fn batch_should_work<S: RelayerStore>(store: &S) {
+    let (typed_chain_id, target_system) = history_store_key();
+    let key = ResourceId::new(target_system, typed_chain_id);
+    let batch = StoreBatch::new();
+    // an empty batch is a no-op.
+    store.commit_batch(&batch).unwrap();
+    batch.insert_leaves(key, &[(0, vec![1]), (1, vec![2])]);
+    batch.insert_encrypted_output(key, &[(0, vec![3])]);
+    batch.insert_last_deposit_block_number(key, 7);
+    batch.store_event(key, b"event", 7);
+    batch.set_last_block_number(key, 8);
+    // later writes replace the earlier ones.
+    batch.insert_leaves(key, &[(1, vec![4])]);
+    batch.set_last_block_number(key, 9);
+    assert_eq!(batch.len(), 7);
+    // nothing is written until the batch is committed.
+    assert!(store.get_leaves(key).unwrap().is_empty());
+    assert!(!store.contains_event(b"event").unwrap());
+    assert_eq!(store.get_last_block_number(key, 1).unwrap(), 1);
+
+    store.commit_batch(&batch).unwrap();
+    assert!(batch.is_empty());
+    assert_eq!(store.get_leaves(key).unwrap(), vec![vec![1], vec![4]]);
+    assert_eq!(store.get_encrypted_output(key).unwrap(), vec![vec![3]]);
+    assert_eq!(store.get_last_deposit_block_number(key).unwrap(), 7);
+    assert!(store.contains_event(b"event").unwrap());
+    assert_eq!(store.get_last_block_number(key, 1).unwrap(), 9);
+    // the event hashes written in a batch are pruned like the others.
+    assert_eq!(store.prune_events(1).unwrap(), 1);
+    assert!(!store.contains_event(b"event").unwrap());
+}
+
+pub fn encrypted_outputs_should_work<S: RelayerStore>(store: &S) {
+    let key = history_store_key();
+    store
+        .insert_encrypted_output(key, &[(0, vec![1]), (1, vec![2])])
+        .unwrap();
+    assert_eq!(
+        store.get_encrypted_output(key).unwrap(),
+        vec![vec![1], vec![2]]
+    );
+    assert!(store.get_leaves(key).unwrap().is_empty());
+    // read in the order of their indices, past one byte of indices too.
+    store
+        .insert_encrypted_output(key, &[(300, vec![5]), (256, vec![4])])
+        .unwrap();
+    store.insert_encrypted_output(key, &[(2, vec![3])]).unwrap();
+    assert_eq!(
+        store.get_encrypted_output(key).unwrap(),
+        vec![vec![1], vec![2], vec![3], vec![4], vec![5]]
+    );
+    assert_eq!(
+        store.get_encrypted_outputs_with_indices(key).unwrap(),
+        vec![
+            (0, vec![1]),
+            (1, vec![2]),
+            (2, vec![3]),
+            (256, vec![4]),
+            (300, vec![5])
+        ]
+    );
+
+    store
+        .insert_last_deposit_block_number_for_encrypted_output(key, 7)
+        .unwrap();
+    assert_eq!(
+        store
+            .get_last_deposit_block_number_for_encrypted_output(key)
+            .unwrap(),
+        7
+    );
+}
+
+pub fn nullifiers_should_work<S: RelayerStore>(store: &S) {
+    let (typed_chain_id, target_system) = history_store_key();
+    let key = ResourceId::new(target_system, typed_chain_id);
+    assert!(store.get_nullifiers(key).unwrap().is_empty());
+    store.insert_nullifiers(key, &[vec![1], vec![2]]).unwrap();
+    store.insert_nullifiers(key, &[vec![3]]).unwrap();
+    // a batch appends its nullifiers after the stored ones.
+    let batch = StoreBatch::new();
+    batch.insert_nullifiers(key, &[vec![4]]);
+    batch.insert_nullifiers(key, &[vec![5]]);
+    store.commit_batch(&batch).unwrap();
+    assert_eq!(
+        store.get_nullifiers(key).unwrap(),
+        vec![vec![1], vec![2], vec![3], vec![4], vec![5]]
+    );
+    assert_eq!(
+        store
+            .get_cached_items(CachedItemKind::Nullifiers, key, 3, 10)
+            .unwrap(),
+        vec![(3, vec![4]), (4, vec![5])]
+    );
+    // handling the same nullifiers again does not append them twice.
+    store.insert_nullifiers(key, &[vec![1], vec![6]]).unwrap();
+    let batch = StoreBatch::new();
+    batch.insert_nullifiers(key, &[vec![5], vec![7], vec![7]]);
+    store.commit_batch(&batch).unwrap();
+    assert_eq!(
+        store.get_nullifiers(key).unwrap(),
+        vec![
+            vec![1],
+            vec![2],
+            vec![3],
+            vec![4],
+            vec![5],
+            vec![6],
+            vec![7]
+        ]
+    );
+    assert!(store.get_leaves(key).unwrap().is_empty());
+}
+
+pub fn cache_subscriptions_should_work<S: RelayerStore>(store: &S) {
+    let (typed_chain_id, target_system) = history_store_key();
+    let key = ResourceId::new(target_system, typed_chain_id);
+    let other = ResourceId::new(target_system, TypedChainId::Evm(2));
+    let mut updates = store.cache_updates(key);
+    let other_updates = store.cache_updates(other);
+    assert!(!updates.has_changed().unwrap());
+
+    store
+        .insert_leaves(key, &[(257, vec![3]), (0, vec![1]), (256, vec![2])])
+        .unwrap();
+    assert!(updates.has_changed().unwrap());
+    updates.borrow_and_update();
+    assert_eq!(
+        store
+            .get_cached_items(CachedItemKind::Leaves, key, 1, 1)
+            .unwrap(),
+        vec![(256, vec![2])]
+    );
+    assert_eq!(
+        store
+            .get_cached_items(CachedItemKind::Leaves, key, 0, 10)
+            .unwrap(),
+        vec![(0, vec![1]), (256, vec![2]), (257, vec![3])]
+    );
+
+    let batch = StoreBatch::new();
+    batch.insert_encrypted_output(key, &[(0, vec![4]), (1, vec![5])]);
+    // nothing is notified until the batch is committed.
+    assert!(!updates.has_changed().unwrap());
+    store.commit_batch(&batch).unwrap();
+    assert!(updates.has_changed().unwrap());
+    assert_eq!(
+        store
+            .get_cached_items(CachedItemKind::EncryptedOutputs, key, 1, 10)
+            .unwrap(),
+        vec![(1, vec![5])]
+    );
+    assert!(store
+        .get_cached_items(CachedItemKind::Nullifiers, key, 0, 10)
+        .unwrap()
+        .is_empty());
+    // other anchors are not notified.
+    assert!(!other_updates.has_changed().unwrap());
+}
+
+pub fn tx_queue_should_work<S: RelayerStore>(store: &S) {
+    let chain_id = 1u32;
+    let queue = SledQueueKey::from_evm_chain_id(chain_id);
+    // it is now empty
+    assert_eq!(
+        store.dequeue_item(queue).unwrap(),
+        Option::<TypedTransaction>::None
+    );
+
+    let tx1 = random_tx();
+    store
+        .enqueue_item(SledQueueKey::from_evm_tx(chain_id, &tx1), tx1.clone())
+        .unwrap();
+    let tx2 = random_tx();
+    store
+        .enqueue_item(SledQueueKey::from_evm_tx(chain_id, &tx2), tx2.clone())
+        .unwrap();
+
+    // now let's dequeue transactions.
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(tx1.clone()));
+    // a dequeued item is no longer in the queue.
+    assert!(!QueueStore::<TypedTransaction>::has_item(
+        store,
+        SledQueueKey::from_evm_tx(chain_id, &tx1)
+    )
+    .unwrap());
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(tx2));
+
+    let tx3 = random_tx();
+    store
+        .enqueue_item(SledQueueKey::from_evm_tx(chain_id, &tx3), tx3.clone())
+        .unwrap();
+    assert_eq!(store.peek_item(queue).unwrap(), Some(tx3.clone()));
+    assert!(QueueStore::<TypedTransaction>::has_item(
+        store,
+        SledQueueKey::from_evm_tx(chain_id, &tx3)
+    )
+    .unwrap());
+    let removed: Option<TypedTransaction> = store
+        .remove_item(SledQueueKey::from_evm_tx(chain_id, &tx3))
+        .unwrap();
+    assert_eq!(removed, Some(tx3.clone()));
+    assert!(!QueueStore::<TypedTransaction>::has_item(
+        store,
+        SledQueueKey::from_evm_tx(chain_id, &tx3)
+    )
+    .unwrap());
+    assert_eq!(
+        store.dequeue_item(queue).unwrap(),
+        Option::<TypedTransaction>::None
+    );
+}
+
+pub fn queue_priorities_should_work<S: RelayerStore>(store: &S) {
+    let queue = SledQueueKey::from_evm_chain_id(1);
+    let normal = random_tx();
+    store.enqueue_item(queue, normal.clone()).unwrap();
+    let low = random_tx();
+    store
+        .enqueue_item_with_options(
+            queue,
+            low.clone(),
+            EnqueueOptions::with_priority(QueuePriority::Low),
+        )
+        .unwrap();
+    let critical = random_tx();
+    store
+        .enqueue_item_with_options(
+            queue,
+            critical.clone(),
+            EnqueueOptions::with_priority(QueuePriority::Critical),
+        )
+        .unwrap();
+    // delayed items are skipped, whatever their priority.
+    let delayed = random_tx();
+    let key = SledQueueKey::from_evm_tx(1, &delayed);
+    store
+        .enqueue_item_with_options(
+            key,
+            delayed.clone(),
+            EnqueueOptions::with_priority(QueuePriority::Critical)
+                .not_before(u64::MAX),
+        )
+        .unwrap();
+    let normal2 = random_tx();
+    store.enqueue_item(queue, normal2.clone()).unwrap();
+
+    assert_eq!(store.peek_item(queue).unwrap(), Some(critical.clone()));
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(critical));
+    // same priority items are dequeued in FIFO order.
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(normal));
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(normal2));
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(low));
+    assert_eq!(
+        store.dequeue_item(queue).unwrap(),
+        Option::<TypedTransaction>::None
+    );
+    // the delayed item is still in the queue.
+    assert!(QueueStore::<TypedTransaction>::has_item(store, key).unwrap());
+    let removed: Option<TypedTransaction> = store.remove_item(key).unwrap();
+    assert_eq!(removed, Some(delayed));
+
+    // an item delayed to the past is ready.
+    let ready = random_tx();
+    store
+        .enqueue_item_with_options(
+            queue,
+            ready.clone(),
+            EnqueueOptions::default().not_before(1),
+        )
+        .unwrap();
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(ready));
+}
+
+pub fn queue_inspection_should_work<S: RelayerStore>(store: &S) {
+    let chain_id = 1u32;
+    let queue = SledQueueKey::from_evm_chain_id(chain_id);
+    let items: Vec<QueuedItem<TypedTransaction>> =
+        store.queue_items(queue).unwrap();
+    assert!(items.is_empty());
+    let normal = random_tx();
+    store
+        .enqueue_item(
+            SledQueueKey::from_evm_tx(chain_id, &normal),
+            normal.clone(),
+        )
+        .unwrap();
+    let delayed = random_tx();
+    store
+        .enqueue_item_with_options(
+            queue,
+            delayed.clone(),
+            EnqueueOptions::with_priority(QueuePriority::High)
+                .not_before(u64::MAX),
+        )
+        .unwrap();
+    // all the items are listed, in the order they would be dequeued.
+    assert_eq!(
+        store.queue_items(queue).unwrap(),
+        vec![
+            QueuedItem {
+                priority: QueuePriority::High,
+                not_before: Some(u64::MAX),
+                item: delayed,
+            },
+            QueuedItem {
+                priority: QueuePriority::Normal,
+                not_before: None,
+                item: normal.clone(),
+            },
+        ]
+    );
+    // other queues are not affected by a purge.
+    let other = SledQueueKey::from_evm_chain_id(2);
+    store.enqueue_item(other, random_tx()).unwrap();
+    assert_eq!(
+        QueueStore::<TypedTransaction>::purge_queue(store, queue).unwrap(),
+        2
+    );
+    let items: Vec<QueuedItem<TypedTransaction>> =
+        store.queue_items(queue).unwrap();
+    assert!(items.is_empty());
+    assert!(!QueueStore::<TypedTransaction>::has_item(
+        store,
+        SledQueueKey::from_evm_tx(chain_id, &normal)
+    )
+    .unwrap());
+    let items: Vec<QueuedItem<TypedTransaction>> =
+        store.queue_items(other).unwrap();
+    assert_eq!(items.len(), 1);
+}
+
+pub fn queue_notifier_should_work<S: RelayerStore>(store: &S) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let queue = SledQueueKey::from_evm_chain_id(1);
+    let notify = QueueStore::<TypedTransaction>::queue_notifier(store, queue);
+    // nothing was enqueued yet.
+    assert!(!rt.block_on(wait_for_items(&notify, Duration::from_millis(10))));
+    store.enqueue_item(queue, random_tx()).unwrap();
+    assert!(rt.block_on(wait_for_items(&notify, Duration::from_secs(5))));
+    // other queues are not notified.
+    store
+        .enqueue_item(SledQueueKey::from_evm_chain_id(2), random_tx())
+        .unwrap();
+    assert!(!rt.block_on(wait_for_items(&notify, Duration::from_millis(10))));
+}
+
+pub fn queues_should_be_isolated<S: RelayerStore>(store: &S) {
+    let command = BridgeCommand::ExecuteProposalWithSignature {
+        data: vec![1, 2, 3],
+        signature: vec![4, 5, 6],
+    };
+    let bridge_queue =
+        SledQueueKey::from_bridge_key(BridgeKey::new(TypedChainId::Evm(1)));
+    store.enqueue_item(bridge_queue, command.clone()).unwrap();
+    // other queues are not affected.
+    assert_eq!(
+        store.peek_item(SledQueueKey::from_evm_chain_id(1)).unwrap(),
+        Option::<TypedTransaction>::None
+    );
+    assert_eq!(
+        store
+            .dequeue_item(SledQueueKey::from_bridge_key(BridgeKey::new(
+                TypedChainId::Evm(2)
+            )))
+            .unwrap(),
+        Option::<BridgeCommand>::None
+    );
+    assert_eq!(store.dequeue_item(bridge_queue).unwrap(), Some(command));
+}
+
+pub fn dead_letters_should_work<S: RelayerStore>(store: &S) {
+    let chain_id = 1u32;
+    let queue = SledQueueKey::from_evm_chain_id(chain_id);
+    let tx = random_tx();
+    let key = SledQueueKey::from_evm_tx(chain_id, &tx);
+    let letter =
+        move_to_dead_letters(store, key, tx.clone(), "dry run failed").unwrap();
+    assert_eq!(letter.attempts, 1);
+    // failing again only increases the attempts.
+    let letter =
+        move_to_dead_letters(store, key, tx.clone(), "still failing").unwrap();
+    assert_eq!(letter.attempts, 2);
+    assert_eq!(letter.reason, "still failing");
+    assert_eq!(
+        DeadLetterStore::<TypedTransaction>::get_dead_letters(store, queue)
+            .unwrap(),
+        vec![letter.clone()]
+    );
+    // other queues are not affected.
+    assert!(list_dead_letters(store, SledQueueKey::from_evm_chain_id(2))
+        .unwrap()
+        .is_empty());
+
+    // retrying sends the item back to the queue, with its key.
+    assert!(retry_dead_letter(store, queue, &letter.id).unwrap());
+    assert!(QueueStore::<TypedTransaction>::has_item(store, key).unwrap());
+    assert_eq!(store.dequeue_item(queue).unwrap(), Some(tx));
+    // it can not be retried twice.
+    assert!(retry_dead_letter(store, queue, &letter.id).is_err());
+    let listed = list_dead_letters(store, queue).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].retried_at.is_some());
+
+    assert!(discard_dead_letter(store, queue, &letter.id).unwrap());
+    assert!(!discard_dead_letter(store, queue, &letter.id).unwrap());
+    assert!(list_dead_letters(store, queue).unwrap().is_empty());
+}
+
+pub fn events_hash_should_work<S: RelayerStore>(store: &S) {
+    let events = (0..20)
+        .map(|_| NewNullifierFilter {
+            nullifier: types::H256::random().to_fixed_bytes(),
+        })
+        .collect::<Vec<_>>();
+
+    for (block_number, event) in events.iter().enumerate() {
+        let event_bytes = serde_json::to_vec(&event).unwrap();
+        // check if the event is already in the store
+        assert!(!store.contains_event(&event_bytes).unwrap());
+        // add the event
+        store
+            .store_event(history_store_key(), &event_bytes, block_number as u64)
+            .unwrap();
+    }
+
+    for event in &events {
+        let event_bytes = serde_json::to_vec(&event).unwrap();
+        // check if the event is in the store
+        assert!(store.contains_event(&event_bytes).unwrap());
+        // remove the event
+        store.delete_event(&event_bytes).unwrap();
+        assert!(!store.contains_event(&event_bytes).unwrap());
+    }
+}
+
+pub fn events_pruning_should_work<S: RelayerStore>(store: &S) {
+    let other_key = (
+        TypedChainId::Evm(2),
+        TargetSystem::new_contract_address([2u8; 20]),
+    );
+    for block_number in 0..10u64 {
+        let event = format!("event {block_number}");
+        store
+            .store_event(history_store_key(), event.as_bytes(), block_number)
+            .unwrap();
+        let event = format!("other event {block_number}");
+        store
+            .store_event(other_key, event.as_bytes(), block_number)
+            .unwrap();
+    }
+    // nothing is pruned before the contracts are synced.
+    assert_eq!(store.prune_events(3).unwrap(), 0);
+
+    store
+        .set_last_block_number(history_store_key(), 10)
+        .unwrap();
+    // events of the blocks 0 to 6 are more than 3 blocks below the block 10.
+    assert_eq!(store.prune_events(3).unwrap(), 7);
+    assert!(!store.contains_event(b"event 6").unwrap());
+    assert!(store.contains_event(b"event 7").unwrap());
+    assert!(store.contains_event(b"event 9").unwrap());
+    // the events of the other contract are kept.
+    assert!(store.contains_event(b"other event 0").unwrap());
+    // pruning again does nothing.
+    assert_eq!(store.prune_events(3).unwrap(), 0);
+
+    // an event stored again is moved to its new block.
+    store.store_event(other_key, b"other event 0", 9).unwrap();
+    store.set_last_block_number(other_key, 9).unwrap();
+    // the events of the blocks 1 to 5, the first contract has nothing to prune.
+    assert_eq!(store.prune_events(3).unwrap(), 5);
+    assert!(store.contains_event(b"other event 0").unwrap());
+    assert!(store.contains_event(b"other event 9").unwrap());
+    // deleted events are no longer pruned.
+    store.delete_event(b"other event 9").unwrap();
+    store.set_last_block_number(other_key, 20).unwrap();
+    assert_eq!(store.prune_events(3).unwrap(), 4);
+    assert!(!store.contains_event(b"other event 0").unwrap());
+}
+
+pub fn proposal_store_should_work<S: RelayerStore>(store: &S) {
+    let target = ResourceId::new(
+        TargetSystem::new_contract_address([1u8; 20]),
+        TypedChainId::Evm(1),
+    );
+    let mut proposal = SubmittedProposal::new(
+        1,
+        TypedChainId::Evm(2),
+        target,
+        vec![1, 2, 3],
+        100,
+    );
+    store.insert_proposal(proposal.clone()).unwrap();
+    assert_eq!(
+        store.get_proposal(&proposal.data_hash).unwrap(),
+        Some(proposal.clone())
+    );
+
+    proposal.mark_signed(170);
+    store.insert_proposal(proposal.clone()).unwrap();
+    assert_eq!(store.get_proposals().unwrap(), vec![proposal.clone()]);
+
+    assert_eq!(
+        store.remove_proposal(&proposal.data_hash).unwrap(),
+        Some(proposal.clone())
+    );
+    assert_eq!(store.get_proposal(&proposal.data_hash).unwrap(), None);
+    assert_eq!(store.remove_proposal(&proposal.data_hash).unwrap(), None);
+}
+
+pub fn relay_jobs_should_work<S: RelayerStore>(store: &S) {
+    let mut job = RelayJob::new(String::from("0a0b"), 100);
+    assert_eq!(store.get_job(&job.id).unwrap(), None);
+    store.insert_job(&job).unwrap();
+    assert_eq!(store.get_job(&job.id).unwrap(), Some(job.clone()));
+    // inserting the same job again replaces it.
+    job.updated_at = 110;
+    job.finished = true;
+    job.tx_hash = Some(String::from("0x01"));
+    job.status = Some(serde_json::json!({ "withdraw": "valid" }));
+    store.insert_job(&job).unwrap();
+    assert_eq!(store.get_job(&job.id).unwrap(), Some(job.clone()));
+    assert_eq!(store.get_job("0c0d").unwrap(), None);
+
+    // a job left unfinished by a previous run is failed on startup.
+    let unfinished = RelayJob::new(String::from("0c0d"), 100);
+    store.insert_job(&unfinished).unwrap();
+    assert_eq!(fail_unfinished_jobs(store, 120).unwrap(), 1);
+    let failed = store.get_job(&unfinished.id).unwrap().unwrap();
+    assert!(failed.finished);
+    assert_eq!(failed.updated_at, 120);
+    assert_eq!(failed.error.as_deref(), Some("relayer restarted"));
+    assert_eq!(store.get_jobs().unwrap().len(), 2);
+
+    // only the jobs finished before the cutoff are pruned.
+    assert_eq!(prune_jobs(store, 115).unwrap(), 1);
+    assert_eq!(store.get_job(&job.id).unwrap(), None);
+    assert_eq!(store.get_jobs().unwrap(), vec![failed]);
+    assert_eq!(store.remove_job(&job.id).unwrap(), None);
+}
@@ -0,0 +1,353 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dead letters are queued items (transactions or bridge commands) that
+//! failed and were taken out of their queue, kept with the reason of the
+//! failure so they can be inspected, retried or discarded later.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
+use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
+
+use crate::sled::SledQueueKey;
+use crate::{BridgeCommand, QueueKey, QueueStore, RelayerStore};
+
+/// An item that failed, taken out of its queue.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter<T> {
+    /// The keccak256 hash of the serialized item.
+    pub id: [u8; 32],
+    /// The custom key the item was enqueued with, if any.
+    #[serde(default)]
+    pub item_key: Option<Vec<u8>>,
+    /// The failed item.
+    pub item: T,
+    /// Why the item failed the last time.
+    pub reason: String,
+    /// How many times the item failed.
+    pub attempts: u32,
+    /// The unix timestamp (in seconds) of the first failure.
+    pub first_failed_at: u64,
+    /// The unix timestamp (in seconds) of the last failure.
+    pub last_failed_at: u64,
+    /// The unix timestamp (in seconds) the item was sent back to its queue,
+    /// if it is waiting to be retried.
+    #[serde(default)]
+    pub retried_at: Option<u64>,
+}
+
+impl<T: Serialize> DeadLetter<T> {
+    /// Returns the id of the dead letter of the given item.
+    pub fn id_of(item: &T) -> crate::Result<[u8; 32]> {
+        let bytes = serde_json::to_vec(item)?;
+        Ok(webb::evm::ethers::utils::keccak256(bytes))
+    }
+
+    /// Creates a new dead letter of an item that failed at `now`.
+    pub fn new(
+        item: T,
+        item_key: Option<[u8; 64]>,
+        reason: impl Into<String>,
+        now: u64,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            id: Self::id_of(&item)?,
+            item_key: item_key.map(Vec::from),
+            item,
+            reason: reason.into(),
+            attempts: 1,
+            first_failed_at: now,
+            last_failed_at: now,
+            retried_at: None,
+        })
+    }
+
+    /// Records another failure of the item at `now`.
+    pub fn mark_failed(&mut self, reason: impl Into<String>, now: u64) {
+        self.attempts += 1;
+        self.reason = reason.into();
+        self.last_failed_at = now;
+        self.retried_at = None;
+    }
+
+    /// Marks the item as sent back to its queue at `now`.
+    pub fn mark_retried(&mut self, now: u64) {
+        self.retried_at = Some(now);
+    }
+
+    /// Converts the item to JSON, so dead letters of different queues can
+    /// be handled the same way.
+    pub fn into_json(self) -> crate::Result<DeadLetter<serde_json::Value>> {
+        Ok(DeadLetter {
+            id: self.id,
+            item_key: self.item_key,
+            item: serde_json::to_value(&self.item)?,
+            reason: self.reason,
+            attempts: self.attempts,
+            first_failed_at: self.first_failed_at,
+            last_failed_at: self.last_failed_at,
+            retried_at: self.retried_at,
+        })
+    }
+}
+
+/// A Dead Letter Store keeps the dead letters of every queue, keyed by
+/// the queue name and the id of the dead letter.
+pub trait DeadLetterStore<Item>
+where
+    Item: Serialize + DeserializeOwned,
+{
+    /// The type of the queue key.
+    type Key: QueueKey;
+    /// Insert a dead letter, replacing any dead letter with the same id
+    /// in the same queue.
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<Item>,
+    ) -> crate::Result<()>;
+    /// Get a dead letter of the queue by its id.
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<Item>>>;
+    /// Get all the dead letters of the queue.
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<Item>>>;
+    /// Remove a dead letter of the queue.
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<Item>>>;
+}
+
+impl<S, T> DeadLetterStore<T> for Arc<S>
+where
+    S: DeadLetterStore<T>,
+    T: Serialize + DeserializeOwned,
+{
+    type Key = S::Key;
+
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<T>,
+    ) -> crate::Result<()> {
+        S::insert_dead_letter(self, key, letter)
+    }
+
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        S::get_dead_letter(self, key, id)
+    }
+
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<T>>> {
+        S::get_dead_letters(self, key)
+    }
+
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        S::remove_dead_letter(self, key, id)
+    }
+}
+
+/// Moves a failed item to the dead letters of its queue.
+///
+/// If the item already failed before, its attempts count is increased
+/// instead.
+pub fn move_to_dead_letters<S, T>(
+    store: &S,
+    key: S::Key,
+    item: T,
+    reason: impl Into<String>,
+) -> crate::Result<DeadLetter<T>>
+where
+    S: DeadLetterStore<T>,
+    S::Key: Copy,
+    T: Serialize + DeserializeOwned + Clone,
+{
+    let now = webb_relayer_utils::unix_timestamp();
+    let id = DeadLetter::id_of(&item)?;
+    let letter = match store.get_dead_letter(key, &id)? {
+        Some(mut letter) => {
+            letter.mark_failed(reason, now);
+            letter
+        }
+        None => DeadLetter::new(item, key.item_key(), reason, now)?,
+    };
+    store.insert_dead_letter(key, letter.clone())?;
+    Ok(letter)
+}
+
+/// Removes the dead letter of an item that was processed successfully,
+/// if it was retried from the dead letters.
+pub fn clear_dead_letter<S, T>(
+    store: &S,
+    key: S::Key,
+    item: &T,
+) -> crate::Result<()>
+where
+    S: DeadLetterStore<T>,
+    T: Serialize + DeserializeOwned,
+{
+    let id = DeadLetter::id_of(item)?;
+    if let Some(letter) = store.remove_dead_letter(key, &id)? {
+        tracing::debug!(
+            id = %hex::encode(letter.id),
+            attempts = letter.attempts,
+            "Retried dead letter processed successfully",
+        );
+    }
+    Ok(())
+}
+
+/// Lists the dead letters of any queue, with their items as JSON.
+pub fn list_dead_letters<S: RelayerStore>(
+    store: &S,
+    queue: SledQueueKey,
+) -> crate::Result<Vec<DeadLetter<serde_json::Value>>> {
+    fn list<S, T>(
+        store: &S,
+        queue: SledQueueKey,
+    ) -> crate::Result<Vec<DeadLetter<serde_json::Value>>>
+    where
+        S: DeadLetterStore<T, Key = SledQueueKey>,
+        T: Serialize + DeserializeOwned,
+    {
+        store
+            .get_dead_letters(queue)?
+            .into_iter()
+            .map(DeadLetter::into_json)
+            .collect()
+    }
+    match queue {
+        SledQueueKey::EvmTx { .. } => list::<S, TypedTransaction>(store, queue),
+        SledQueueKey::SubstrateTx { .. } => {
+            list::<S, WebbDynamicTxPayload<'static>>(store, queue)
+        }
+        SledQueueKey::BridgeCmd { .. } => {
+            list::<S, BridgeCommand>(store, queue)
+        }
+        SledQueueKey::CosmosTx { .. } => Err(unsupported_queue()),
+    }
+}
+
+/// Sends the item of a dead letter back to its queue, the dead letter is
+/// kept until the item is processed successfully.
+///
+/// Returns `Ok(false)` if there is no such dead letter.
+pub fn retry_dead_letter<S: RelayerStore>(
+    store: &S,
+    queue: SledQueueKey,
+    id: &[u8],
+) -> crate::Result<bool> {
+    fn retry<S, T>(
+        store: &S,
+        queue: SledQueueKey,
+        id: &[u8],
+    ) -> crate::Result<bool>
+    where
+        S: DeadLetterStore<T, Key = SledQueueKey>
+            + QueueStore<T, Key = SledQueueKey>,
+        T: Serialize + DeserializeOwned + Clone,
+    {
+        let mut letter = match store.get_dead_letter(queue, id)? {
+            Some(letter) => letter,
+            None => return Ok(false),
+        };
+        if letter.retried_at.is_some() {
+            return Err(webb_relayer_utils::Error::Generic(
+                "The dead letter is already waiting to be retried",
+            ));
+        }
+        let item_key = letter
+            .item_key
+            .as_deref()
+            .and_then(|k| <[u8; 64]>::try_from(k).ok());
+        let key = match item_key {
+            Some(item_key) => queue.with_item_key(item_key),
+            None => queue,
+        };
+        store.enqueue_item(key, letter.item.clone())?;
+        letter.mark_retried(webb_relayer_utils::unix_timestamp());
+        store.insert_dead_letter(queue, letter)?;
+        Ok(true)
+    }
+    match queue {
+        SledQueueKey::EvmTx { .. } => {
+            retry::<S, TypedTransaction>(store, queue, id)
+        }
+        SledQueueKey::SubstrateTx { .. } => {
+            retry::<S, WebbDynamicTxPayload<'static>>(store, queue, id)
+        }
+        SledQueueKey::BridgeCmd { .. } => {
+            retry::<S, BridgeCommand>(store, queue, id)
+        }
+        SledQueueKey::CosmosTx { .. } => Err(unsupported_queue()),
+    }
+}
+
+/// Discards a dead letter for good.
+///
+/// Returns `Ok(false)` if there is no such dead letter.
+pub fn discard_dead_letter<S: RelayerStore>(
+    store: &S,
+    queue: SledQueueKey,
+    id: &[u8],
+) -> crate::Result<bool> {
+    let removed = match queue {
+        SledQueueKey::EvmTx { .. } => {
+            DeadLetterStore::<TypedTransaction>::remove_dead_letter(
+                store, queue, id,
+            )?
+            .is_some()
+        }
+        SledQueueKey::SubstrateTx { .. } => DeadLetterStore::<
+            WebbDynamicTxPayload<'static>,
+        >::remove_dead_letter(
+            store, queue, id
+        )?
+        .is_some(),
+        SledQueueKey::BridgeCmd { .. } => {
+            DeadLetterStore::<BridgeCommand>::remove_dead_letter(
+                store, queue, id,
+            )?
+            .is_some()
+        }
+        SledQueueKey::CosmosTx { .. } => return Err(unsupported_queue()),
+    };
+    Ok(removed)
+}
+
+fn unsupported_queue() -> webb_relayer_utils::Error {
+    webb_relayer_utils::Error::Generic(
+        "Dead letters are not supported for cosmos queues",
+    )
+}
@@ -0,0 +1,95 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relay jobs are the private transactions submitted over HTTP.
+//!
+//! The relayer answers a submission with the id of its job right away, and
+//! keeps the latest status of the job in the store while the transaction is
+//! being relayed, so clients can poll it (or resubscribe to it) even after
+//! they disconnected. Finished jobs are pruned once they are older than the
+//! configured retention.
+
+use std::sync::Arc;
+
+pub use webb_relayer_handler_utils::RelayJob;
+
+/// A store for the state of the relay jobs.
+pub trait JobStore {
+    /// Inserts a job, replacing any job with the same id.
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()>;
+    /// Get a job by its id.
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>>;
+    /// Get all the stored jobs.
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>>;
+    /// Removes a job by its id, returning it if it was stored.
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>>;
+}
+
+impl<S> JobStore for Arc<S>
+where
+    S: JobStore,
+{
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()> {
+        S::insert_job(self, job)
+    }
+
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        S::get_job(self, id)
+    }
+
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>> {
+        S::get_jobs(self)
+    }
+
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        S::remove_job(self, id)
+    }
+}
+
+/// Marks the jobs left unfinished by a previous run of the relayer as
+/// failed, since nothing is relaying them anymore.
+///
+/// Returns the number of jobs marked as failed.
+pub fn fail_unfinished_jobs<S: JobStore>(
+    store: &S,
+    now: u64,
+) -> crate::Result<u64> {
+    let mut failed = 0;
+    for mut job in store.get_jobs()? {
+        if job.finished {
+            continue;
+        }
+        job.finished = true;
+        job.updated_at = now;
+        job.error = Some(String::from("relayer restarted"));
+        store.insert_job(&job)?;
+        failed += 1;
+    }
+    Ok(failed)
+}
+
+/// Removes the finished jobs that were last updated before the given unix
+/// timestamp (in seconds).
+///
+/// Returns the number of removed jobs.
+pub fn prune_jobs<S: JobStore>(store: &S, before: u64) -> crate::Result<u64> {
+    let mut pruned = 0;
+    for job in store.get_jobs()? {
+        if job.finished && job.updated_at < before {
+            store.remove_job(&job.id)?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
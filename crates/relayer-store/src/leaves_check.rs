@@ -0,0 +1,101 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consistency checks of the leaves cache.
+//!
+//! The leaves handlers insert whatever `(index, leaf)` pairs they see, so a
+//! log range that was never fetched leaves holes in the cache. These checks
+//! compare the cached leaves of an anchor with the `nextIndex` of its
+//! contract and tell which indices have to be fetched again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use webb_proposals::ResourceId;
+
+pub use webb_relayer_handler_utils::{LeavesConsistency, LeavesSyncStatus};
+
+/// The [`LeavesSyncStatus`] of every checked anchor, shared between the
+/// checkers and the API.
+#[derive(Clone, Default)]
+pub struct LeavesSyncStatuses {
+    anchors: Arc<RwLock<HashMap<[u8; 32], LeavesSyncStatus>>>,
+}
+
+impl std::fmt::Debug for LeavesSyncStatuses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LeavesSyncStatuses").finish()
+    }
+}
+
+impl LeavesSyncStatuses {
+    /// Returns the last status of the given anchor, if it was checked.
+    pub fn get(&self, resource_id: ResourceId) -> Option<LeavesSyncStatus> {
+        self.anchors.read().get(&resource_id.into_bytes()).cloned()
+    }
+
+    /// Records the status of the given anchor.
+    pub fn set(&self, resource_id: ResourceId, status: LeavesSyncStatus) {
+        self.anchors
+            .write()
+            .insert(resource_id.into_bytes(), status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(indices: &[u32]) -> Vec<(u32, Vec<u8>)> {
+        indices
+            .iter()
+            .map(|i| (*i, i.to_be_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn complete_leaves_should_be_consistent() {
+        let report = LeavesConsistency::check(&leaves(&[0, 1, 2]), 3);
+        assert!(report.is_consistent());
+        assert!(report.indices_to_refetch().is_empty());
+        assert!(LeavesConsistency::check(&[], 0).is_consistent());
+    }
+
+    #[test]
+    fn gaps_should_be_detected() {
+        let report = LeavesConsistency::check(&leaves(&[0, 3, 4, 7]), 10);
+        assert!(!report.is_consistent());
+        assert_eq!(report.cached, 4);
+        assert_eq!(report.expected, 10);
+        assert_eq!(report.missing, vec![(1, 2), (5, 6), (8, 9)]);
+        assert_eq!(LeavesConsistency::check(&[], 2).missing, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn duplicates_and_unexpected_leaves_should_be_detected() {
+        let mut cached = leaves(&[0, 1, 2, 3]);
+        cached[3].1 = cached[1].1.clone();
+        let report = LeavesConsistency::check(&cached, 3);
+        assert!(!report.is_consistent());
+        assert!(report.missing.is_empty());
+        assert!(report.duplicates.is_empty());
+        assert_eq!(report.unexpected, vec![3]);
+
+        let report = LeavesConsistency::check(&cached, 5);
+        assert_eq!(report.duplicates, vec![3]);
+        assert_eq!(report.missing, vec![(4, 4)]);
+        assert_eq!(report.indices_to_refetch(), vec![(3, 3), (4, 4)]);
+    }
+}
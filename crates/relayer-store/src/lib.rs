@@ -24,23 +24,70 @@
 use std::fmt::{Debug, Display};
 use std::sync::Arc;
 
+use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use webb::evm::ethers::types;
+use webb::evm::ethers::types::transaction::eip2718::TypedTransaction;
 use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
 use webb_relayer_utils::Result;
+/// A module for selecting the store backend at runtime.
+pub mod any;
+/// A module for committing batches of writes at once.
+pub mod batch;
+/// A conformance test suite shared by every store backend.
+#[cfg(test)]
+mod conformance;
+/// A module for keeping the items that failed out of their queues.
+pub mod dead_letter;
+/// A module for keeping the state of the private transactions submitted over HTTP.
+pub mod job;
+/// A module for checking the consistency of the leaves cache.
+pub mod leaves_check;
 /// A module for managing in-memory storage of the relayer.
 pub mod mem;
+/// A module for waking up the consumers of the queues.
+pub mod notify;
+/// A module for inspecting and purging the queues.
+pub mod queue_admin;
 /// A module for setting up and managing a [Sled](https://sled.rs)-based database.
 pub mod sled;
-
+/// A module for exporting and importing snapshots of the leaves cache.
+pub mod snapshot;
+/// A module for setting up and managing a [SQLite](https://sqlite.org)-based database.
+pub mod sqlite;
+/// A module for streaming the cached items of the anchors to subscribers.
+pub mod subscription;
+
+use self::sled::SledQueueKey;
 /// A store that uses [`sled`](https://sled.rs) as the backend.
 pub use self::sled::SledStore;
+/// A store that dispatches to the backend selected in the configuration.
+pub use any::AnyStore;
+/// Writes committed to the store at once.
+pub use batch::{BatchOp, BatchStore, StoreBatch};
+/// An item that failed and was taken out of its queue.
+pub use dead_letter::{DeadLetter, DeadLetterStore};
+/// The state of a private transaction submitted over HTTP.
+pub use job::{JobStore, RelayJob};
+/// Checks of the cached leaves against their contract.
+pub use leaves_check::{
+    LeavesConsistency, LeavesSyncStatus, LeavesSyncStatuses,
+};
 /// A store that uses in memory data structures as the backend.
 pub use mem::InMemoryStore;
+/// Wakes up the consumers of the queues when items are enqueued.
+pub use notify::{wait_for_items, QueueNotifier};
+/// A store that uses [`SQLite`](https://sqlite.org) as the backend.
+pub use sqlite::SqliteStore;
+/// Subscriptions to the items cached for the anchors.
+pub use subscription::{CacheNotifier, CacheSubscriptionStore, CachedItemKind};
+/// The lifecycle status of a proposal sent to the DKG for signing.
+pub use webb_relayer_handler_utils::ProposalStatus;
 
 /// HistoryStoreKey contains the keys used to store the history of events.
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
 pub enum HistoryStoreKey {
     /// Block Queue Key
     Block { chain_id: u32 },
@@ -219,13 +266,20 @@ pub trait HistoryStore: Clone + Send + Sync {
 }
 
 /// A Simple Event Store, that does not store the events, instead it store the hash of the event as the key
-/// and the value is just empty bytes.
+/// along with the contract and the block number of the event.
 ///
-/// This is mainly useful to mark the event as processed.
+/// This is mainly useful to mark the event as processed. The hashes of old events can be pruned
+/// with [`EventHashStore::prune_events`], so the store does not grow forever.
 pub trait EventHashStore: Send + Sync + Clone {
-    /// Store the event in the store.
+    /// Store the event in the store, for the contract of the given key and the block
+    /// the event was emitted in.
     /// the key is the hash of the event.
-    fn store_event(&self, event: &[u8]) -> crate::Result<()>;
+    fn store_event<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        event: &[u8],
+        block_number: u64,
+    ) -> crate::Result<()>;
 
     /// Check if the event is stored in the store.
     /// the key is the hash of the event.
@@ -234,6 +288,14 @@ pub trait EventHashStore: Send + Sync + Clone {
     /// Delete the event from the store.
     /// the key is the hash of the event.
     fn delete_event(&self, event: &[u8]) -> crate::Result<()>;
+
+    /// Removes the events emitted more than `depth` blocks below the last block number
+    /// of their contract (see [`HistoryStore::get_last_block_number`]).
+    /// Once pruned, the events are handled again if the last block number is
+    /// set back before them, so it should not be.
+    ///
+    /// Returns the number of pruned events.
+    fn prune_events(&self, depth: u64) -> crate::Result<u64>;
 }
 
 /// A Leaf Cache Store is a simple trait that would help in
@@ -248,6 +310,20 @@ pub trait LeafCacheStore: HistoryStore {
         key: K,
     ) -> crate::Result<Self::Output>;
 
+    /// Get the leaves for the given key together with their indices,
+    /// ordered by index.
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>>;
+
+    /// Get the number of leaves stored for the given key, without reading
+    /// them.
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64>;
+
     /// Insert the leaves for the given key.
     fn insert_leaves<K: Into<HistoryStoreKey> + Debug>(
         &self,
@@ -283,6 +359,13 @@ pub trait EncryptedOutputCacheStore: HistoryStore {
         key: K,
     ) -> crate::Result<Self::Output>;
 
+    /// Get the encrypted outputs for the given key together with their
+    /// indices, ordered by index.
+    fn get_encrypted_outputs_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>>;
+
     /// Insert the encrypted output for the given key.
     fn insert_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
         &self,
@@ -310,6 +393,24 @@ pub trait EncryptedOutputCacheStore: HistoryStore {
     ) -> crate::Result<u64>;
 }
 
+/// A Nullifier Cache Store keeps the nullifiers spent on an anchor, in the
+/// order they were spent.
+pub trait NullifierCacheStore: HistoryStore {
+    /// Get the nullifiers for the given key, in the order they were inserted.
+    fn get_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<Vec<u8>>>;
+
+    /// Append the nullifiers for the given key, each one gets the index
+    /// following the last nullifier of the key.
+    fn insert_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        nullifiers: &[Vec<u8>],
+    ) -> crate::Result<()>;
+}
+
 /// A Command sent to the Bridge to execute different actions.
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum BridgeCommand {
@@ -333,6 +434,103 @@ pub enum BridgeCommand {
     },
 }
 
+impl BridgeCommand {
+    /// Returns the priority of the command, ownership transfers go before
+    /// anything else since every other command needs the new governor.
+    pub fn priority(&self) -> QueuePriority {
+        match self {
+            Self::ExecuteProposalWithSignature { .. } => QueuePriority::High,
+            Self::TransferOwnershipWithSignature { .. } => {
+                QueuePriority::Critical
+            }
+        }
+    }
+}
+
+/// The priority of a queued item, items with a higher priority are
+/// dequeued first, and items with the same priority in FIFO order.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum QueuePriority {
+    /// Items that can wait for everything else.
+    Low = 0,
+    /// The priority of items enqueued without options.
+    #[default]
+    Normal = 1,
+    /// Items that should not wait behind the normal ones, like the execution
+    /// of signed proposals.
+    High = 2,
+    /// Items that must go before anything else, like governor ownership transfers.
+    Critical = 3,
+}
+
+impl QueuePriority {
+    /// Returns the priority as a number, higher is more important.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the priority from its number, unknown numbers are clamped.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Low,
+            1 => Self::Normal,
+            2 => Self::High,
+            _ => Self::Critical,
+        }
+    }
+}
+
+/// Options of an item being enqueued.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnqueueOptions {
+    /// The priority of the item.
+    pub priority: QueuePriority,
+    /// The unix timestamp (in seconds) before which the item is not dequeued.
+    pub not_before: Option<u64>,
+}
+
+impl EnqueueOptions {
+    /// Options for an item with the given priority.
+    pub fn with_priority(priority: QueuePriority) -> Self {
+        Self {
+            priority,
+            not_before: None,
+        }
+    }
+
+    /// Delays the item until the given unix timestamp (in seconds).
+    pub fn not_before(mut self, timestamp: u64) -> Self {
+        self.not_before = Some(timestamp);
+        self
+    }
+}
+
+/// An item of a queue, with the options it was enqueued with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedItem<T> {
+    /// The priority of the item.
+    pub priority: QueuePriority,
+    /// The unix timestamp (in seconds) before which the item is not dequeued.
+    pub not_before: Option<u64>,
+    /// The item itself.
+    pub item: T,
+}
+
 /// A trait for retrieving queue keys
 pub trait QueueKey {
     /// The Queue name, used as a prefix for the keys.
@@ -346,25 +544,47 @@ pub trait QueueKey {
 }
 
 /// A Queue Store is a simple trait that help storing items in a queue.
-/// The queue is a FIFO queue, that can be used to store anything that can be serialized.
+/// The queue is a priority queue (FIFO for items of the same priority), that can be used to
+/// store anything that can be serialized. Items can also be delayed until a given time.
 ///
-/// There is a simple API to get the items from the queue, from a background task for example.
+/// There is a simple API to get the items from the queue, from a background task for example,
+/// which can wait for the [`QueueStore::queue_notifier`] of the queue instead of busy polling it.
 pub trait QueueStore<Item>
 where
     Item: Serialize + DeserializeOwned + Clone,
 {
     /// The type of the queue key.
     type Key: QueueKey;
-    /// Insert an item into the queue.
-    fn enqueue_item(&self, key: Self::Key, item: Item) -> crate::Result<()>;
-    /// Get an item from the queue, and removes it.
+    /// Insert an item into the queue, with the [`QueuePriority::Normal`] priority.
+    fn enqueue_item(&self, key: Self::Key, item: Item) -> crate::Result<()> {
+        self.enqueue_item_with_options(key, item, EnqueueOptions::default())
+    }
+    /// Insert an item into the queue, with the given priority and delay.
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: Item,
+        options: EnqueueOptions,
+    ) -> crate::Result<()>;
+    /// Get the highest priority ready item from the queue, and removes it.
     fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<Item>>;
-    /// Get an item from the queue, without removing it.
+    /// Get the highest priority ready item from the queue, without removing it.
     fn peek_item(&self, key: Self::Key) -> crate::Result<Option<Item>>;
     /// Check if the item is in the queue.
     fn has_item(&self, key: Self::Key) -> crate::Result<bool>;
     /// Remove an item from the queue.
     fn remove_item(&self, key: Self::Key) -> crate::Result<Option<Item>>;
+    /// Get all the items of the queue, the delayed ones included, in the order
+    /// they would be dequeued if they were all ready.
+    fn queue_items(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<QueuedItem<Item>>>;
+    /// Remove all the items from the queue, returns how many were removed.
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64>;
+    /// Returns the [`Notify`](tokio::sync::Notify) signaled every time an item is enqueued
+    /// in the queue of the given key.
+    fn queue_notifier(&self, key: Self::Key) -> Arc<tokio::sync::Notify>;
 }
 
 impl<S, T> QueueStore<T> for Arc<S>
@@ -374,8 +594,13 @@ where
 {
     type Key = S::Key;
 
-    fn enqueue_item(&self, key: Self::Key, item: T) -> crate::Result<()> {
-        S::enqueue_item(self, key, item)
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: T,
+        options: EnqueueOptions,
+    ) -> crate::Result<()> {
+        S::enqueue_item_with_options(self, key, item, options)
     }
 
     fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
@@ -393,16 +618,168 @@ where
     fn remove_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
         S::remove_item(self, key)
     }
+
+    fn queue_items(&self, key: Self::Key) -> crate::Result<Vec<QueuedItem<T>>> {
+        S::queue_items(self, key)
+    }
+
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64> {
+        S::purge_queue(self, key)
+    }
+
+    fn queue_notifier(&self, key: Self::Key) -> Arc<tokio::sync::Notify> {
+        S::queue_notifier(self, key)
+    }
 }
 /// ProposalStore is a simple trait for inserting and removing proposals.
 pub trait ProposalStore {
     /// The type of the Proposal.
     type Proposal: Serialize + DeserializeOwned;
-    /// Insert a proposal into the store.
+    /// Insert a proposal into the store, replacing any proposal with the same data hash.
     fn insert_proposal(&self, proposal: Self::Proposal) -> crate::Result<()>;
+    /// Get a proposal by its data hash.
+    fn get_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>>;
+    /// Get all the proposals in the store.
+    fn get_proposals(&self) -> crate::Result<Vec<Self::Proposal>>;
     /// Remove a proposal from the store.
     fn remove_proposal(
         &self,
         data_hash: &[u8],
     ) -> crate::Result<Option<Self::Proposal>>;
 }
+
+/// RelayerStore bundles all the store traits the relayer services need,
+/// so that any backend implementing them can be used interchangeably.
+pub trait RelayerStore:
+    HistoryStore
+    + LeafCacheStore<Output = Vec<Vec<u8>>>
+    + EncryptedOutputCacheStore<Output = Vec<Vec<u8>>>
+    + NullifierCacheStore
+    + CacheSubscriptionStore
+    + EventHashStore
+    + BatchStore
+    + ProposalStore<Proposal = SubmittedProposal>
+    + QueueStore<TypedTransaction, Key = SledQueueKey>
+    + QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+    + QueueStore<BridgeCommand, Key = SledQueueKey>
+    + DeadLetterStore<TypedTransaction, Key = SledQueueKey>
+    + DeadLetterStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+    + DeadLetterStore<BridgeCommand, Key = SledQueueKey>
+    + JobStore
+{
+}
+
+impl<S> RelayerStore for S where
+    S: HistoryStore
+        + LeafCacheStore<Output = Vec<Vec<u8>>>
+        + EncryptedOutputCacheStore<Output = Vec<Vec<u8>>>
+        + NullifierCacheStore
+        + CacheSubscriptionStore
+        + EventHashStore
+        + BatchStore
+        + ProposalStore<Proposal = SubmittedProposal>
+        + QueueStore<TypedTransaction, Key = SledQueueKey>
+        + QueueStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + QueueStore<BridgeCommand, Key = SledQueueKey>
+        + DeadLetterStore<TypedTransaction, Key = SledQueueKey>
+        + DeadLetterStore<WebbDynamicTxPayload<'static>, Key = SledQueueKey>
+        + DeadLetterStore<BridgeCommand, Key = SledQueueKey>
+        + JobStore
+{
+}
+
+/// A proposal sent to the DKG for signing, tracked until it gets signed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SubmittedProposal {
+    /// The keccak256 hash of the proposal bytes.
+    pub data_hash: [u8; 32],
+    /// The proposal nonce.
+    pub nonce: u32,
+    /// The typed chain id of the chain the proposal originated from.
+    pub src_chain_id: u64,
+    /// The resource id of the target system of the proposal.
+    pub target: [u8; 32],
+    /// The proposal (encoded as bytes).
+    pub proposal: Vec<u8>,
+    /// The current status of the proposal.
+    pub status: ProposalStatus,
+    /// How many times the proposal was sent to the DKG.
+    pub attempts: u32,
+    /// The unix timestamp (in seconds) of the last time the proposal was sent.
+    pub submitted_at: u64,
+    /// The unix timestamp (in seconds) the proposal was signed at, if any.
+    pub signed_at: Option<u64>,
+}
+
+impl SubmittedProposal {
+    /// Creates a new pending proposal, submitted at `now`.
+    pub fn new(
+        nonce: u32,
+        src_chain_id: TypedChainId,
+        target: ResourceId,
+        proposal: Vec<u8>,
+        now: u64,
+    ) -> Self {
+        Self {
+            data_hash: webb::evm::ethers::utils::keccak256(&proposal),
+            nonce,
+            src_chain_id: src_chain_id.chain_id(),
+            target: target.into_bytes(),
+            proposal,
+            status: ProposalStatus::Pending,
+            attempts: 1,
+            submitted_at: now,
+            signed_at: None,
+        }
+    }
+
+    /// Returns the typed chain id of the chain the proposal originated from.
+    pub fn src_typed_chain_id(&self) -> TypedChainId {
+        TypedChainId::from(self.src_chain_id)
+    }
+
+    /// Returns the resource id of the target system of the proposal.
+    pub fn target_resource_id(&self) -> ResourceId {
+        ResourceId::from(self.target)
+    }
+
+    /// Returns true if the proposal is still pending after `timeout` seconds.
+    pub fn is_timed_out(&self, now: u64, timeout: u64) -> bool {
+        self.status == ProposalStatus::Pending
+            && now.saturating_sub(self.submitted_at) >= timeout
+    }
+
+    /// Returns true if the proposal was signed, or expired, at least
+    /// `retention` seconds before `now`.
+    pub fn is_prunable(&self, now: u64, retention: u64) -> bool {
+        let finished_at = match self.status {
+            ProposalStatus::Pending => return false,
+            ProposalStatus::Signed => {
+                self.signed_at.unwrap_or(self.submitted_at)
+            }
+            // the last submission is the one that expired.
+            ProposalStatus::Expired => self.submitted_at,
+        };
+        now.saturating_sub(finished_at) >= retention
+    }
+
+    /// Marks the proposal as signed at `now`.
+    pub fn mark_signed(&mut self, now: u64) {
+        self.status = ProposalStatus::Signed;
+        self.signed_at = Some(now);
+    }
+
+    /// Marks the proposal as resubmitted at `now`.
+    pub fn mark_resubmitted(&mut self, now: u64) {
+        self.attempts += 1;
+        self.submitted_at = now;
+    }
+
+    /// Marks the proposal as expired.
+    pub fn mark_expired(&mut self) {
+        self.status = ProposalStatus::Expired;
+    }
+}
@@ -12,25 +12,87 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
-use webb::evm::ethers::types;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::{watch, Notify};
+use webb::evm::ethers::{self, types};
+use webb_proposals::ResourceId;
+
+use crate::sled::SledQueueKey;
+use crate::{
+    CacheNotifier, CacheSubscriptionStore, CachedItemKind, JobStore,
+    NullifierCacheStore, QueueKey, QueueNotifier, RelayJob,
+};
 
 use super::{
-    EncryptedOutputCacheStore, HistoryStore, HistoryStoreKey, LeafCacheStore,
+    BatchOp, BatchStore, DeadLetter, DeadLetterStore,
+    EncryptedOutputCacheStore, EnqueueOptions, EventHashStore, HistoryStore,
+    HistoryStoreKey, LeafCacheStore, ProposalStore, QueuePriority, QueueStore,
+    QueuedItem, StoreBatch, SubmittedProposal,
 };
 
-type MemStore = HashMap<HistoryStoreKey, Vec<(u32, types::H256)>>;
-type MemStoreForVec = HashMap<HistoryStoreKey, Vec<(u32, Vec<u8>)>>;
+/// Leaves (encrypted outputs, or nullifiers) of every contract, keyed by the chain id
+/// and the address of the contract, like the [`SledStore`](crate::SledStore) trees.
+type MemTree = HashMap<(u32, types::H160), BTreeMap<u32, Vec<u8>>>;
+
+/// The position of an item in a [`MemQueue`], higher priorities first,
+/// then by their index.
+type MemQueuePosition = (Reverse<QueuePriority>, u64);
+
+/// The dead letters of every queue, keyed by their name.
+type MemDeadLetters = HashMap<String, BTreeMap<Vec<u8>, Vec<u8>>>;
+
+/// The contract key and block number of every handled event, by hash.
+type MemEventHashes = HashMap<[u8; 32], (Vec<u8>, u64)>;
+
+/// A serialized item, with the time it can be dequeued at.
+struct MemQueueItem {
+    not_before: Option<u64>,
+    bytes: Vec<u8>,
+}
+
+/// A queue of serialized items.
+#[derive(Default)]
+struct MemQueue {
+    /// The index of the last enqueued item.
+    last_item_idx: u64,
+    /// The items, ordered by their priority and index.
+    items: BTreeMap<MemQueuePosition, MemQueueItem>,
+    /// The position of the items enqueued with a custom key.
+    keys: HashMap<[u8; 64], MemQueuePosition>,
+}
+
+impl MemQueue {
+    /// Returns the position of the first item that can be dequeued at `now`.
+    fn next_ready(&self, now: u64) -> Option<MemQueuePosition> {
+        self.items
+            .iter()
+            .find(|(_, item)| item.not_before.map(|t| t <= now).unwrap_or(true))
+            .map(|(position, _)| *position)
+    }
+}
+
 /// InMemoryStore is a store that stores the history of events in memory.
 #[derive(Clone, Default)]
 pub struct InMemoryStore {
-    _store: Arc<RwLock<MemStore>>,
-    store_for_vec: Arc<RwLock<MemStoreForVec>>,
-    last_block_numbers: Arc<RwLock<HashMap<HistoryStoreKey, u64>>>,
+    last_block_numbers: Arc<RwLock<HashMap<Vec<u8>, u64>>>,
+    last_deposit_block_numbers: Arc<RwLock<HashMap<Vec<u8>, u64>>>,
+    leaves: Arc<RwLock<MemTree>>,
+    encrypted_outputs: Arc<RwLock<MemTree>>,
+    nullifiers: Arc<RwLock<MemTree>>,
+    event_hashes: Arc<RwLock<MemEventHashes>>,
+    queues: Arc<RwLock<HashMap<String, MemQueue>>>,
+    proposals: Arc<RwLock<BTreeMap<[u8; 32], SubmittedProposal>>>,
+    dead_letters: Arc<RwLock<MemDeadLetters>>,
+    relay_jobs: Arc<RwLock<HashMap<String, RelayJob>>>,
+    notifier: QueueNotifier,
+    cache_notifier: CacheNotifier,
 }
 
 impl std::fmt::Debug for InMemoryStore {
@@ -39,6 +101,98 @@ impl std::fmt::Debug for InMemoryStore {
     }
 }
 
+impl InMemoryStore {
+    /// Gets the total amount of data stored on disk, which is always zero.
+    pub fn get_data_stored_size(&self) -> u64 {
+        0
+    }
+
+    /// Gets the number of entries of every map, named like the trees of
+    /// the [`SledStore`](crate::SledStore).
+    pub fn get_tree_sizes(&self) -> crate::Result<Vec<(String, u64)>> {
+        let count =
+            |tree: &MemTree| tree.values().map(|v| v.len() as u64).sum();
+        let mut sizes = vec![
+            (
+                String::from("last_block_numbers"),
+                self.last_block_numbers.read().len() as u64,
+            ),
+            (
+                String::from("last_deposit_block_numbers"),
+                self.last_deposit_block_numbers.read().len() as u64,
+            ),
+            (String::from("leaves"), count(&self.leaves.read())),
+            (
+                String::from("encrypted_outputs"),
+                count(&self.encrypted_outputs.read()),
+            ),
+            (String::from("nullifiers"), count(&self.nullifiers.read())),
+            (
+                String::from("event_hashes"),
+                self.event_hashes.read().len() as u64,
+            ),
+            (
+                String::from("proposals"),
+                self.proposals.read().len() as u64,
+            ),
+            (
+                String::from("relay_jobs"),
+                self.relay_jobs.read().len() as u64,
+            ),
+        ];
+        for (name, queue) in self.queues.read().iter() {
+            sizes.push((format!("queue_{name}"), queue.items.len() as u64));
+        }
+        for (name, letters) in self.dead_letters.read().iter() {
+            sizes.push((format!("dead_letters_{name}"), letters.len() as u64));
+        }
+        Ok(sizes)
+    }
+}
+
+fn tree_key(key: HistoryStoreKey) -> (u32, types::H160) {
+    (key.chain_id(), key.address())
+}
+
+fn insert_block_number(
+    map: &RwLock<HashMap<Vec<u8>, u64>>,
+    key: HistoryStoreKey,
+    block_number: u64,
+) -> u64 {
+    let old = map.write().insert(key.to_bytes(), block_number);
+    old.unwrap_or(block_number)
+}
+
+fn get_block_number(
+    map: &RwLock<HashMap<Vec<u8>, u64>>,
+    key: HistoryStoreKey,
+    default_block_number: u64,
+) -> u64 {
+    map.read()
+        .get(&key.to_bytes())
+        .copied()
+        .unwrap_or(default_block_number)
+}
+
+fn get_tree(tree: &RwLock<MemTree>, key: HistoryStoreKey) -> Vec<Vec<u8>> {
+    tree.read()
+        .get(&tree_key(key))
+        .map(|values| values.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn insert_tree(
+    tree: &RwLock<MemTree>,
+    key: HistoryStoreKey,
+    values: &[(u32, Vec<u8>)],
+) {
+    let mut guard = tree.write();
+    let entry = guard.entry(tree_key(key)).or_default();
+    for (k, v) in values {
+        entry.insert(*k, v.clone());
+    }
+}
+
 impl HistoryStore for InMemoryStore {
     #[tracing::instrument(skip(self))]
     fn get_last_block_number<K: Into<HistoryStoreKey> + Debug>(
@@ -46,12 +200,11 @@ impl HistoryStore for InMemoryStore {
         key: K,
         default_block_number: u64,
     ) -> crate::Result<u64> {
-        let guard = self.last_block_numbers.read();
-        let val = guard
-            .get(&key.into())
-            .cloned()
-            .unwrap_or(default_block_number);
-        Ok(val)
+        Ok(get_block_number(
+            &self.last_block_numbers,
+            key.into(),
+            default_block_number,
+        ))
     }
 
     #[tracing::instrument(skip(self))]
@@ -60,11 +213,11 @@ impl HistoryStore for InMemoryStore {
         key: K,
         block_number: u64,
     ) -> crate::Result<u64> {
-        let mut guard = self.last_block_numbers.write();
-        let val = guard.entry(key.into()).or_insert(block_number);
-        let old = *val;
-        *val = block_number;
-        Ok(old)
+        Ok(insert_block_number(
+            &self.last_block_numbers,
+            key.into(),
+            block_number,
+        ))
     }
 }
 
@@ -76,15 +229,35 @@ impl LeafCacheStore for InMemoryStore {
         &self,
         key: K,
     ) -> crate::Result<Self::Output> {
-        let guard = self.store_for_vec.read();
-        let val = guard
-            .get(&key.into())
-            .cloned()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|v| v.1)
-            .collect();
-        Ok(val)
+        Ok(get_tree(&self.leaves, key.into()))
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let leaves = self
+            .leaves
+            .read()
+            .get(&tree_key(key.into()))
+            .map(|values| values.iter().map(|(k, v)| (*k, v.clone())).collect())
+            .unwrap_or_default();
+        Ok(leaves)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        let count = self
+            .leaves
+            .read()
+            .get(&tree_key(key.into()))
+            .map(|values| values.len() as u64)
+            .unwrap_or_default();
+        Ok(count)
     }
 
     #[tracing::instrument(skip(self))]
@@ -93,11 +266,9 @@ impl LeafCacheStore for InMemoryStore {
         key: K,
         leaves: &[(u32, Vec<u8>)],
     ) -> crate::Result<()> {
-        let mut guard = self.store_for_vec.write();
-        guard
-            .entry(key.into())
-            .and_modify(|v| v.extend_from_slice(leaves))
-            .or_insert_with(|| leaves.to_vec());
+        let key: HistoryStoreKey = key.into();
+        insert_tree(&self.leaves, key, leaves);
+        self.cache_notifier.notify(&key);
         Ok(())
     }
 
@@ -106,7 +277,11 @@ impl LeafCacheStore for InMemoryStore {
         &self,
         key: K,
     ) -> crate::Result<u64> {
-        Ok(0u64)
+        Ok(get_block_number(
+            &self.last_deposit_block_numbers,
+            key.into(),
+            0,
+        ))
     }
 
     #[tracing::instrument(skip(self))]
@@ -115,7 +290,11 @@ impl LeafCacheStore for InMemoryStore {
         key: K,
         block_number: u64,
     ) -> crate::Result<u64> {
-        Ok(0u64)
+        Ok(insert_block_number(
+            &self.last_deposit_block_numbers,
+            key.into(),
+            block_number,
+        ))
     }
 }
 
@@ -127,15 +306,21 @@ impl EncryptedOutputCacheStore for InMemoryStore {
         &self,
         key: K,
     ) -> crate::Result<Self::Output> {
-        let guard = self.store_for_vec.read();
-        let val = guard
-            .get(&key.into())
-            .cloned()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|v| v.1)
-            .collect();
-        Ok(val)
+        Ok(get_tree(&self.encrypted_outputs, key.into()))
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_encrypted_outputs_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let encrypted_outputs = self
+            .encrypted_outputs
+            .read()
+            .get(&tree_key(key.into()))
+            .map(|values| values.iter().map(|(k, v)| (*k, v.clone())).collect())
+            .unwrap_or_default();
+        Ok(encrypted_outputs)
     }
 
     #[tracing::instrument(skip(self))]
@@ -144,11 +329,9 @@ impl EncryptedOutputCacheStore for InMemoryStore {
         key: K,
         encrypted_outputs: &[(u32, Vec<u8>)],
     ) -> crate::Result<()> {
-        let mut guard = self.store_for_vec.write();
-        guard
-            .entry(key.into())
-            .and_modify(|v| v.extend_from_slice(encrypted_outputs))
-            .or_insert_with(|| encrypted_outputs.to_vec());
+        let key: HistoryStoreKey = key.into();
+        insert_tree(&self.encrypted_outputs, key, encrypted_outputs);
+        self.cache_notifier.notify(&key);
         Ok(())
     }
 
@@ -159,7 +342,11 @@ impl EncryptedOutputCacheStore for InMemoryStore {
         &self,
         key: K,
     ) -> crate::Result<u64> {
-        Ok(0u64)
+        Ok(get_block_number(
+            &self.last_deposit_block_numbers,
+            key.into(),
+            0,
+        ))
     }
 
     #[tracing::instrument(skip(self))]
@@ -170,6 +357,504 @@ impl EncryptedOutputCacheStore for InMemoryStore {
         key: K,
         block_number: u64,
     ) -> crate::Result<u64> {
-        Ok(0u64)
+        Ok(insert_block_number(
+            &self.last_deposit_block_numbers,
+            key.into(),
+            block_number,
+        ))
     }
 }
+
+impl NullifierCacheStore for InMemoryStore {
+    #[tracing::instrument(skip(self))]
+    fn get_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(get_tree(&self.nullifiers, key.into()))
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        nullifiers: &[Vec<u8>],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let mut tree = self.nullifiers.write();
+        append_nullifiers(&mut tree, key, nullifiers.to_vec());
+        drop(tree);
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+}
+
+/// Appends the nullifiers that are not stored yet after the last one of the
+/// given key.
+fn append_nullifiers(
+    tree: &mut MemTree,
+    key: HistoryStoreKey,
+    nullifiers: Vec<Vec<u8>>,
+) {
+    let entry = tree.entry(tree_key(key)).or_default();
+    let mut next = entry.keys().next_back().map(|i| i + 1).unwrap_or(0);
+    for nullifier in nullifiers {
+        if entry.values().any(|stored| *stored == nullifier) {
+            continue;
+        }
+        entry.insert(next, nullifier);
+        next += 1;
+    }
+}
+
+impl CacheSubscriptionStore for InMemoryStore {
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let tree = match kind {
+            CachedItemKind::Leaves => &self.leaves,
+            CachedItemKind::EncryptedOutputs => &self.encrypted_outputs,
+            CachedItemKind::Nullifiers => &self.nullifiers,
+        };
+        let items = tree
+            .read()
+            .get(&tree_key(key.into()))
+            .map(|values| {
+                values
+                    .range(from..)
+                    .take(limit)
+                    .map(|(k, v)| (*k, v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(items)
+    }
+
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64> {
+        self.cache_notifier.subscribe(key)
+    }
+}
+
+impl BatchStore for InMemoryStore {
+    #[tracing::instrument(skip_all, fields(ops = batch.len()))]
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()> {
+        let ops = batch.take_ops();
+        // the writes to the maps can not fail, only the injected failure
+        // has to be found before any of them is applied.
+        #[cfg(test)]
+        if ops.contains(&BatchOp::Fail) {
+            return Err(webb_relayer_utils::Error::Generic("injected failure"));
+        }
+        let anchors: Vec<_> =
+            ops.iter().filter_map(BatchOp::cached_anchor).collect();
+        // every map is locked (always in the same order) for the whole
+        // batch, so it is never seen half applied.
+        let mut last_block_numbers = self.last_block_numbers.write();
+        let mut last_deposit_block_numbers =
+            self.last_deposit_block_numbers.write();
+        let mut leaves = self.leaves.write();
+        let mut encrypted_outputs = self.encrypted_outputs.write();
+        let mut nullifiers = self.nullifiers.write();
+        let mut event_hashes = self.event_hashes.write();
+        for op in ops {
+            match op {
+                BatchOp::InsertLeaves {
+                    key,
+                    leaves: values,
+                } => {
+                    leaves
+                        .entry(tree_key(key.into()))
+                        .or_default()
+                        .extend(values);
+                }
+                BatchOp::InsertEncryptedOutput {
+                    key,
+                    encrypted_output,
+                } => {
+                    encrypted_outputs
+                        .entry(tree_key(key.into()))
+                        .or_default()
+                        .extend(encrypted_output);
+                }
+                BatchOp::InsertNullifiers {
+                    key,
+                    nullifiers: values,
+                } => {
+                    append_nullifiers(&mut nullifiers, key.into(), values);
+                }
+                BatchOp::SetLastDepositBlockNumber { key, block_number } => {
+                    last_deposit_block_numbers.insert(
+                        HistoryStoreKey::from(key).to_bytes(),
+                        block_number,
+                    );
+                }
+                BatchOp::StoreEvent {
+                    key,
+                    event,
+                    block_number,
+                } => {
+                    let hash = ethers::utils::keccak256(event);
+                    event_hashes.insert(
+                        hash,
+                        (HistoryStoreKey::from(key).to_bytes(), block_number),
+                    );
+                }
+                BatchOp::SetLastBlockNumber { key, block_number } => {
+                    last_block_numbers.insert(
+                        HistoryStoreKey::from(key).to_bytes(),
+                        block_number,
+                    );
+                }
+                #[cfg(test)]
+                BatchOp::Fail => unreachable!("checked before applying"),
+            }
+        }
+        for key in anchors {
+            self.cache_notifier.notify(&key.into());
+        }
+        Ok(())
+    }
+}
+
+impl EventHashStore for InMemoryStore {
+    fn store_event<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        event: &[u8],
+        block_number: u64,
+    ) -> crate::Result<()> {
+        let hash = ethers::utils::keccak256(event);
+        let key: HistoryStoreKey = key.into();
+        self.event_hashes
+            .write()
+            .insert(hash, (key.to_bytes(), block_number));
+        Ok(())
+    }
+
+    fn contains_event(&self, event: &[u8]) -> crate::Result<bool> {
+        let hash = ethers::utils::keccak256(event);
+        Ok(self.event_hashes.read().contains_key(&hash))
+    }
+
+    fn delete_event(&self, event: &[u8]) -> crate::Result<()> {
+        let hash = ethers::utils::keccak256(event);
+        self.event_hashes.write().remove(&hash);
+        Ok(())
+    }
+
+    fn prune_events(&self, depth: u64) -> crate::Result<u64> {
+        let last_block_numbers = self.last_block_numbers.read();
+        let mut event_hashes = self.event_hashes.write();
+        let before = event_hashes.len();
+        event_hashes.retain(|_, (key, block_number)| {
+            let last_block = last_block_numbers.get(key).copied();
+            block_number.saturating_add(depth) >= last_block.unwrap_or_default()
+        });
+        Ok((before - event_hashes.len()) as u64)
+    }
+}
+
+impl<T> QueueStore<T> for InMemoryStore
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: T,
+        options: EnqueueOptions,
+    ) -> crate::Result<()> {
+        // items are kept serialized, so they behave exactly like
+        // the ones stored on disk.
+        let item_bytes = serde_json::to_vec(&item)?;
+        let mut guard = self.queues.write();
+        let queue = guard.entry(key.queue_name()).or_default();
+        queue.last_item_idx += 1;
+        let position = (Reverse(options.priority), queue.last_item_idx);
+        queue.items.insert(
+            position,
+            MemQueueItem {
+                not_before: options.not_before,
+                bytes: item_bytes,
+            },
+        );
+        if let Some(k) = key.item_key() {
+            queue.keys.insert(k, position);
+        }
+        drop(guard);
+        self.notifier.notify(&key.queue_name());
+        tracing::trace!("enqueue item under key = {}", key);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let now = webb_relayer_utils::unix_timestamp();
+        let mut guard = self.queues.write();
+        let queue = match guard.get_mut(&key.queue_name()) {
+            Some(queue) => queue,
+            None => return Ok(None),
+        };
+        let position = match queue.next_ready(now) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        let item = serde_json::from_slice(&queue.items[&position].bytes)?;
+        queue.items.remove(&position);
+        Ok(Some(item))
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn peek_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let now = webb_relayer_utils::unix_timestamp();
+        let guard = self.queues.read();
+        let value = guard.get(&key.queue_name()).and_then(|queue| {
+            let position = queue.next_ready(now)?;
+            queue.items.get(&position)
+        });
+        match value {
+            Some(v) => Ok(Some(serde_json::from_slice(&v.bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn has_item(&self, key: Self::Key) -> crate::Result<bool> {
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(false),
+        };
+        let guard = self.queues.read();
+        let exists = guard
+            .get(&key.queue_name())
+            .and_then(|queue| {
+                let position = queue.keys.get(&inner_key)?;
+                Some(queue.items.contains_key(position))
+            })
+            .unwrap_or(false);
+        Ok(exists)
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn remove_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+        let mut guard = self.queues.write();
+        let queue = match guard.get_mut(&key.queue_name()) {
+            Some(queue) => queue,
+            None => return Ok(None),
+        };
+        match queue.keys.remove(&inner_key) {
+            Some(position) => {
+                let item = queue
+                    .items
+                    .remove(&position)
+                    .and_then(|v| serde_json::from_slice(&v.bytes).ok());
+                tracing::trace!("removed item from the queue..");
+                Ok(item)
+            }
+            None => {
+                // not found!
+                tracing::trace!("item with key {} not found in queue", key);
+                Ok(None)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn queue_items(&self, key: Self::Key) -> crate::Result<Vec<QueuedItem<T>>> {
+        let guard = self.queues.read();
+        let queue = match guard.get(&key.queue_name()) {
+            Some(queue) => queue,
+            None => return Ok(Vec::new()),
+        };
+        queue
+            .items
+            .iter()
+            .map(|((Reverse(priority), _), v)| {
+                Ok(QueuedItem {
+                    priority: *priority,
+                    not_before: v.not_before,
+                    item: serde_json::from_slice(&v.bytes)?,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64> {
+        let mut guard = self.queues.write();
+        let queue = match guard.get_mut(&key.queue_name()) {
+            Some(queue) => queue,
+            None => return Ok(0),
+        };
+        let removed = queue.items.len() as u64;
+        queue.items.clear();
+        queue.keys.clear();
+        Ok(removed)
+    }
+
+    fn queue_notifier(&self, key: Self::Key) -> Arc<Notify> {
+        self.notifier.get(&key.queue_name())
+    }
+}
+
+impl ProposalStore for InMemoryStore {
+    type Proposal = SubmittedProposal;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(proposal.data_hash))
+    )]
+    fn insert_proposal(&self, proposal: Self::Proposal) -> crate::Result<()> {
+        self.proposals.write().insert(proposal.data_hash, proposal);
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn get_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let guard = self.proposals.read();
+        let proposal = <[u8; 32]>::try_from(data_hash)
+            .ok()
+            .and_then(|hash| guard.get(&hash).cloned());
+        Ok(proposal)
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_proposals(&self) -> crate::Result<Vec<Self::Proposal>> {
+        Ok(self.proposals.read().values().cloned().collect())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn remove_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let removed = <[u8; 32]>::try_from(data_hash)
+            .ok()
+            .and_then(|hash| self.proposals.write().remove(&hash));
+        if removed.is_none() {
+            tracing::warn!(
+                "Proposal not seen yet; not found in the proposal storage."
+            );
+        }
+        Ok(removed)
+    }
+}
+
+impl<T> DeadLetterStore<T> for InMemoryStore
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(key = %key, id = %hex::encode(letter.id))
+    )]
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<T>,
+    ) -> crate::Result<()> {
+        let bytes = serde_json::to_vec(&letter)?;
+        self.dead_letters
+            .write()
+            .entry(key.queue_name())
+            .or_default()
+            .insert(letter.id.to_vec(), bytes);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let guard = self.dead_letters.read();
+        match guard.get(&key.queue_name()).and_then(|q| q.get(id)) {
+            Some(bytes) => Ok(Some(serde_json::from_slice(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<T>>> {
+        let guard = self.dead_letters.read();
+        guard
+            .get(&key.queue_name())
+            .into_iter()
+            .flat_map(|q| q.values())
+            .map(|bytes| Ok(serde_json::from_slice(bytes)?))
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let removed = self
+            .dead_letters
+            .write()
+            .get_mut(&key.queue_name())
+            .and_then(|q| q.remove(id));
+        match removed {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl JobStore for InMemoryStore {
+    #[tracing::instrument(skip_all, fields(id = %job.id))]
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()> {
+        self.relay_jobs.write().insert(job.id.clone(), job.clone());
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        Ok(self.relay_jobs.read().get(id).cloned())
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>> {
+        Ok(self.relay_jobs.read().values().cloned().collect())
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        Ok(self.relay_jobs.write().remove(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::conformance::store_conformance_tests!(InMemoryStore::default());
+}
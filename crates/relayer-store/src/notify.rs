@@ -0,0 +1,156 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// Wakes up the consumers of a queue when an item is enqueued in it.
+///
+/// There is one [`Notify`] per queue name, created on first use. Notifying a
+/// queue that nobody is waiting on keeps a single permit, so an item
+/// enqueued while its consumer was busy is never missed.
+///
+/// Notifications only reach the consumers of the same process, consumers
+/// should keep polling their queue from time to time for the items enqueued
+/// by another process or delayed until a given time.
+#[derive(Clone, Default)]
+pub struct QueueNotifier {
+    queues: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl std::fmt::Debug for QueueNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueueNotifier").finish()
+    }
+}
+
+impl QueueNotifier {
+    /// Returns the [`Notify`] of the given queue.
+    pub fn get(&self, queue_name: &str) -> Arc<Notify> {
+        self.queues
+            .lock()
+            .entry(queue_name.to_owned())
+            .or_default()
+            .clone()
+    }
+
+    /// Wakes up a consumer of the given queue.
+    pub fn notify(&self, queue_name: &str) {
+        if let Some(notify) = self.queues.lock().get(queue_name) {
+            notify.notify_one();
+        }
+    }
+}
+
+/// Waits until an item is enqueued in the queue of `notify`, or until
+/// `fallback_poll` elapsed.
+///
+/// Returns `true` if it was woken up by a new item.
+pub async fn wait_for_items(notify: &Notify, fallback_poll: Duration) -> bool {
+    tokio::time::timeout(fallback_poll, notify.notified())
+        .await
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
+    use webb::evm::ethers::types;
+    use webb::evm::ethers::types::transaction::request::TransactionRequest;
+
+    use super::*;
+    use crate::sled::SledQueueKey;
+    use crate::{InMemoryStore, QueueStore};
+
+    /// The interval of the busy polling the queue consumers used to do.
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    fn random_tx() -> TypedTransaction {
+        TransactionRequest::pay(types::Address::random(), types::U256::one())
+            .into()
+    }
+
+    /// Enqueues an item once `consumer` is waiting for it, and returns how
+    /// long it took to be dequeued.
+    async fn dequeue_latency<F, Fut>(
+        store: InMemoryStore,
+        consumer: F,
+    ) -> Duration
+    where
+        F: FnOnce(InMemoryStore) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let queue = SledQueueKey::from_evm_chain_id(1);
+        let handle = tokio::spawn(consumer(store.clone()));
+        // let the consumer find the queue empty.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let start = Instant::now();
+        store.enqueue_item(queue, random_tx()).unwrap();
+        handle.await.unwrap();
+        start.elapsed()
+    }
+
+    #[tokio::test]
+    async fn notified_consumers_should_be_faster_than_polling() {
+        let queue = SledQueueKey::from_evm_chain_id(1);
+        let polling =
+            dequeue_latency(InMemoryStore::default(), |store| async move {
+                while QueueStore::<TypedTransaction>::dequeue_item(
+                    &store, queue,
+                )
+                .unwrap()
+                .is_none()
+                {
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            })
+            .await;
+        let notified =
+            dequeue_latency(InMemoryStore::default(), |store| async move {
+                let notify = QueueStore::<TypedTransaction>::queue_notifier(
+                    &store, queue,
+                );
+                while QueueStore::<TypedTransaction>::dequeue_item(
+                    &store, queue,
+                )
+                .unwrap()
+                .is_none()
+                {
+                    wait_for_items(&notify, POLL_INTERVAL).await;
+                }
+            })
+            .await;
+        assert!(polling >= POLL_INTERVAL / 2);
+        assert!(notified < POLL_INTERVAL / 10);
+    }
+
+    #[tokio::test]
+    async fn items_enqueued_while_busy_should_not_be_missed() {
+        let store = InMemoryStore::default();
+        let queue = SledQueueKey::from_evm_chain_id(1);
+        let notify =
+            QueueStore::<TypedTransaction>::queue_notifier(&store, queue);
+        // nobody is waiting yet.
+        store.enqueue_item(queue, random_tx()).unwrap();
+        assert!(wait_for_items(&notify, Duration::from_secs(5)).await);
+        // the permit is consumed.
+        assert!(!wait_for_items(&notify, Duration::from_millis(10)).await);
+    }
+}
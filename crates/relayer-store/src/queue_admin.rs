@@ -0,0 +1,84 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to inspect and purge any queue of the relayer by its key, with
+//! the items as JSON, used by the admin API.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
+use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
+
+use crate::sled::SledQueueKey;
+use crate::{BridgeCommand, QueueStore, QueuedItem, RelayerStore};
+
+/// Lists the items of any queue, with the items as JSON.
+pub fn list_queue_items<S: RelayerStore>(
+    store: &S,
+    queue: SledQueueKey,
+) -> crate::Result<Vec<QueuedItem<serde_json::Value>>> {
+    fn list<S, T>(
+        store: &S,
+        queue: SledQueueKey,
+    ) -> crate::Result<Vec<QueuedItem<serde_json::Value>>>
+    where
+        S: QueueStore<T, Key = SledQueueKey>,
+        T: Serialize + DeserializeOwned + Clone,
+    {
+        store
+            .queue_items(queue)?
+            .into_iter()
+            .map(|i| {
+                Ok(QueuedItem {
+                    priority: i.priority,
+                    not_before: i.not_before,
+                    item: serde_json::to_value(&i.item)?,
+                })
+            })
+            .collect()
+    }
+    match queue {
+        SledQueueKey::EvmTx { .. } => list::<S, TypedTransaction>(store, queue),
+        SledQueueKey::SubstrateTx { .. } => {
+            list::<S, WebbDynamicTxPayload<'static>>(store, queue)
+        }
+        SledQueueKey::BridgeCmd { .. } => {
+            list::<S, BridgeCommand>(store, queue)
+        }
+        SledQueueKey::CosmosTx { .. } => Err(unsupported_queue()),
+    }
+}
+
+/// Removes all the items of any queue, returns how many were removed.
+pub fn purge_queue_items<S: RelayerStore>(
+    store: &S,
+    queue: SledQueueKey,
+) -> crate::Result<u64> {
+    match queue {
+        SledQueueKey::EvmTx { .. } => {
+            QueueStore::<TypedTransaction>::purge_queue(store, queue)
+        }
+        SledQueueKey::SubstrateTx { .. } => QueueStore::<
+            WebbDynamicTxPayload<'static>,
+        >::purge_queue(store, queue),
+        SledQueueKey::BridgeCmd { .. } => {
+            QueueStore::<BridgeCommand>::purge_queue(store, queue)
+        }
+        SledQueueKey::CosmosTx { .. } => Err(unsupported_queue()),
+    }
+}
+
+fn unsupported_queue() -> webb_relayer_utils::Error {
+    webb_relayer_utils::Error::Generic("Cosmos queues are not supported")
+}
@@ -0,0 +1,352 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioning of the [Sled](https://sled.rs) database layout.
+//!
+//! The schema version is stored in the default tree, and every time the layout of the
+//! trees or their keys changes, the version is bumped and a migration function is added
+//! to [`MIGRATIONS`] to upgrade older databases in place when they get opened.
+
+/// The version of the database layout written by this version of the relayer.
+pub const CURRENT_VERSION: u32 = 4;
+
+/// The key (in the default tree) where the schema version is stored.
+const VERSION_KEY: &[u8] = b"__webb_relayer_schema_version";
+
+/// A migration upgrades the database layout by exactly one version.
+type Migration = fn(&sled::Db) -> crate::Result<()>;
+
+/// The ordered list of migrations, `MIGRATIONS[n]` upgrades a database
+/// from version `n` to version `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] =
+    [v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4];
+
+/// Version 0 is any database created before the schema got versioned, its layout
+/// is the same as version 1, so there is nothing to move around.
+fn v0_to_v1(_db: &sled::Db) -> crate::Result<()> {
+    Ok(())
+}
+
+/// Version 2 added priorities and delays to the queues: the keys of the queued items went
+/// from `"item" + index` to `"item" + inverted priority + index + not before`, so every
+/// queued item is moved to its new key with the normal priority and no delay, and the
+/// special keys pointing at them are updated.
+fn v1_to_v2(db: &sled::Db) -> crate::Result<()> {
+    use super::{queue_item_key, QUEUE_ITEM_KEY_LEN};
+    use crate::QueuePriority;
+
+    const OLD_ITEM_KEY_LEN: usize = 4 + 8;
+    for name in db.tree_names() {
+        if !name.starts_with(b"queue_") {
+            continue;
+        }
+        let tree = db.open_tree(&name)?;
+        let mut moved = std::collections::HashMap::new();
+        for entry in tree.scan_prefix(b"item") {
+            let (key, value) = entry?;
+            if key.len() != OLD_ITEM_KEY_LEN {
+                continue;
+            }
+            let mut idx = [0u8; 8];
+            idx.copy_from_slice(&key[4..]);
+            let new_key = queue_item_key(
+                &key[..4],
+                QueuePriority::Normal,
+                u64::from_be_bytes(idx),
+                0,
+            );
+            tree.insert(&new_key[..], value)?;
+            tree.remove(&key)?;
+            moved.insert(key.to_vec(), new_key);
+        }
+        // the special keys store the key of their item as the value.
+        for entry in tree.iter() {
+            let (key, value) = entry?;
+            if key.len() == QUEUE_ITEM_KEY_LEN {
+                continue;
+            }
+            if let Some(new_key) = moved.get(value.as_ref()) {
+                tree.insert(key, &new_key[..])?;
+            }
+        }
+    }
+    db.flush()?;
+    Ok(())
+}
+
+/// Version 3 stores the contract and the block number of the handled events, so their
+/// hashes can be pruned. The hashes stored before do not have them and are dropped, they
+/// only mark the events as handled and the event watchers resume from their last block
+/// numbers anyway.
+fn v2_to_v3(db: &sled::Db) -> crate::Result<()> {
+    db.drop_tree("event_hashes")?;
+    db.flush()?;
+    Ok(())
+}
+
+/// Version 4 keys the leaves and the encrypted outputs by their big endian index instead
+/// of the little endian one, so they can be read in order from any index, and maps every
+/// nullifier to its index, so a nullifier handled again is not appended twice.
+///
+/// The re-keyed entries are written to new trees and the old trees are dropped once
+/// copied, so an interrupted upgrade can safely copy them again.
+fn v3_to_v4(db: &sled::Db) -> crate::Result<()> {
+    const RENAMED: [(&[u8], &[u8]); 2] = [
+        (b"leaves/", b"leaves_by_index/"),
+        (b"encrypted_outputs/", b"encrypted_outputs_by_index/"),
+    ];
+    for name in db.tree_names() {
+        for (old_prefix, new_prefix) in RENAMED {
+            let suffix = match name.strip_prefix(old_prefix) {
+                Some(suffix) => suffix,
+                None => continue,
+            };
+            let old = db.open_tree(&name)?;
+            let new = db.open_tree([new_prefix, suffix].concat())?;
+            for entry in old.iter() {
+                let (key, value) = entry?;
+                let mut index = [0u8; 4];
+                index.copy_from_slice(&key);
+                new.insert(u32::from_le_bytes(index).to_be_bytes(), value)?;
+            }
+            new.flush()?;
+            db.drop_tree(&name)?;
+        }
+        if let Some(suffix) = name.strip_prefix(b"nullifiers/") {
+            let nullifiers = db.open_tree(&name)?;
+            let indices =
+                db.open_tree([&b"nullifier_indices/"[..], suffix].concat())?;
+            for entry in nullifiers.iter() {
+                let (index, nullifier) = entry?;
+                // the first index of a nullifier stored twice is kept.
+                if !indices.contains_key(&nullifier)? {
+                    indices.insert(nullifier, index)?;
+                }
+            }
+        }
+    }
+    db.flush()?;
+    Ok(())
+}
+
+/// Returns the schema version stored in the database, if any.
+pub fn schema_version(db: &sled::Db) -> crate::Result<Option<u32>> {
+    match db.get(VERSION_KEY)? {
+        Some(v) => {
+            let mut output = [0u8; 4];
+            output.copy_from_slice(&v);
+            Ok(Some(u32::from_be_bytes(output)))
+        }
+        None => Ok(None),
+    }
+}
+
+fn set_schema_version(db: &sled::Db, version: u32) -> crate::Result<()> {
+    db.insert(VERSION_KEY, &version.to_be_bytes())?;
+    db.flush()?;
+    Ok(())
+}
+
+/// Brings the database to the [`CURRENT_VERSION`], running all the pending migrations in order.
+///
+/// Returns an error, without touching the database, if it was written by a newer relayer.
+pub fn migrate(db: &sled::Db) -> crate::Result<()> {
+    let version = match schema_version(db)? {
+        Some(version) => version,
+        // a brand new database, nothing to migrate.
+        None if db.is_empty() && db.tree_names().len() <= 1 => {
+            return set_schema_version(db, CURRENT_VERSION);
+        }
+        // created before the schema got versioned.
+        None => 0,
+    };
+    if version > CURRENT_VERSION {
+        return Err(webb_relayer_utils::Error::UnsupportedStoreVersion {
+            found: version,
+            supported: CURRENT_VERSION,
+        });
+    }
+    for (from, migration) in
+        MIGRATIONS.iter().enumerate().skip(version as usize)
+    {
+        let to = from as u32 + 1;
+        tracing::info!(from, to, "Migrating the store schema");
+        migration(db)?;
+        // persist the progress after every step, so an interrupted
+        // upgrade resumes from where it stopped.
+        set_schema_version(db, to)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sled::{SledQueueKey, SledStore};
+    use crate::{
+        CacheSubscriptionStore, CachedItemKind, EventHashStore, HistoryStore,
+        HistoryStoreKey, LeafCacheStore, NullifierCacheStore, QueueStore,
+    };
+    use webb::evm::ethers;
+    use webb_proposals::{TargetSystem, TypedChainId};
+
+    fn history_key() -> (TypedChainId, TargetSystem) {
+        (
+            TypedChainId::Evm(1),
+            TargetSystem::new_contract_address([1u8; 20]),
+        )
+    }
+
+    fn resource_id() -> webb_proposals::ResourceId {
+        let (typed_chain_id, target_system) = history_key();
+        webb_proposals::ResourceId::new(target_system, typed_chain_id)
+    }
+
+    /// Writes a fixture database using the layout of the first schema version,
+    /// without any version marker, like the relayer did before versioning.
+    fn write_unversioned_fixture(path: &std::path::Path) {
+        let db = sled::open(path).unwrap();
+        let key: HistoryStoreKey = history_key().into();
+        db.open_tree("last_block_numbers")
+            .unwrap()
+            .insert(key.to_bytes(), &20u64.to_le_bytes())
+            .unwrap();
+        db.open_tree("last_deposit_block_number")
+            .unwrap()
+            .insert(key.to_bytes(), &15u64.to_le_bytes())
+            .unwrap();
+        let leaves = db
+            .open_tree(format!("leaves/{}/{}", key.chain_id(), key.address()))
+            .unwrap();
+        leaves.insert(0u32.to_le_bytes(), vec![1u8; 32]).unwrap();
+        leaves.insert(1u32.to_le_bytes(), vec![2u8; 32]).unwrap();
+        leaves.insert(256u32.to_le_bytes(), vec![3u8; 32]).unwrap();
+        let encrypted_outputs = db
+            .open_tree(format!(
+                "encrypted_outputs/{}/{}",
+                key.chain_id(),
+                key.address()
+            ))
+            .unwrap();
+        encrypted_outputs
+            .insert(1u32.to_le_bytes(), vec![5u8])
+            .unwrap();
+        encrypted_outputs
+            .insert(256u32.to_le_bytes(), vec![6u8])
+            .unwrap();
+        let nullifiers = db
+            .open_tree(format!(
+                "nullifiers/{}/{}",
+                key.chain_id(),
+                key.address()
+            ))
+            .unwrap();
+        nullifiers
+            .insert(0u32.to_be_bytes(), vec![9u8; 32])
+            .unwrap();
+        let queue = db.open_tree("queue_evm_tx_1").unwrap();
+        queue.insert("last_item_idx", &1u64.to_be_bytes()).unwrap();
+        queue.insert("key_prefix", "item").unwrap();
+        let mut item_key = [0u8; 12];
+        item_key[0..4].copy_from_slice(b"item");
+        item_key[4..].copy_from_slice(&1u64.to_be_bytes());
+        queue
+            .insert(item_key, serde_json::to_vec("queued").unwrap())
+            .unwrap();
+        queue.insert([7u8; 64], &item_key).unwrap();
+        db.open_tree("event_hashes")
+            .unwrap()
+            .insert(ethers::utils::keccak256(b"event"), &[])
+            .unwrap();
+        db.flush().unwrap();
+    }
+
+    #[test]
+    fn new_database_should_use_current_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SledStore::open(tmp.path()).unwrap();
+        assert_eq!(store.schema_version().unwrap(), Some(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn unversioned_database_should_be_upgraded() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_unversioned_fixture(tmp.path());
+
+        let store = SledStore::open(tmp.path()).unwrap();
+        assert_eq!(store.schema_version().unwrap(), Some(CURRENT_VERSION));
+        // the data is still readable after the upgrade.
+        assert_eq!(store.get_last_block_number(history_key(), 0).unwrap(), 20);
+        assert_eq!(
+            store.get_last_deposit_block_number(history_key()).unwrap(),
+            15
+        );
+        // the leaves and encrypted outputs are read in the order of their indices.
+        assert_eq!(
+            store.get_leaves(history_key()).unwrap(),
+            vec![vec![1u8; 32], vec![2u8; 32], vec![3u8; 32]]
+        );
+        assert_eq!(
+            store
+                .get_cached_items(
+                    CachedItemKind::EncryptedOutputs,
+                    resource_id(),
+                    2,
+                    10
+                )
+                .unwrap(),
+            vec![(256, vec![6u8])]
+        );
+        // the stored nullifiers are not appended again.
+        store
+            .insert_nullifiers(history_key(), &[vec![9u8; 32], vec![8u8; 32]])
+            .unwrap();
+        assert_eq!(
+            store.get_nullifiers(history_key()).unwrap(),
+            vec![vec![9u8; 32], vec![8u8; 32]]
+        );
+        // the queued item can still be found by its special key.
+        assert!(QueueStore::<String>::has_item(
+            &store,
+            SledQueueKey::from_evm_with_custom_key(1, [7u8; 64])
+        )
+        .unwrap());
+        assert_eq!(
+            store
+                .dequeue_item(SledQueueKey::from_evm_chain_id(1))
+                .unwrap(),
+            Some(String::from("queued"))
+        );
+        // the event hashes without a block number are dropped.
+        assert!(!store.contains_event(b"event").unwrap());
+    }
+
+    #[test]
+    fn newer_database_should_be_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_unversioned_fixture(tmp.path());
+        {
+            let db = sled::open(tmp.path()).unwrap();
+            set_schema_version(&db, CURRENT_VERSION + 1).unwrap();
+        }
+        let result = SledStore::open(tmp.path());
+        assert!(matches!(
+            result,
+            Err(webb_relayer_utils::Error::UnsupportedStoreVersion {
+                found,
+                supported: CURRENT_VERSION,
+            }) if found == CURRENT_VERSION + 1
+        ));
+    }
+}
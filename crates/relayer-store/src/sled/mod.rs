@@ -0,0 +1,1515 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    BridgeKey, CacheNotifier, CacheSubscriptionStore, CachedItemKind, JobStore,
+    NullifierCacheStore, QueueKey, QueueNotifier, RelayJob,
+};
+use core::fmt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult,
+    TransactionalTree,
+};
+use sled::Transactional;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{watch, Notify};
+use webb::evm::ethers;
+use webb_proposals::ResourceId;
+
+use super::HistoryStoreKey;
+use super::{
+    BatchOp, BatchStore, DeadLetter, DeadLetterStore,
+    EncryptedOutputCacheStore, EnqueueOptions, EventHashStore, HistoryStore,
+    LeafCacheStore, ProposalStore, QueuePriority, QueueStore, QueuedItem,
+    StoreBatch, SubmittedProposal,
+};
+/// Versioning and migrations of the database layout.
+pub mod migrations;
+
+/// SledStore is a store that stores the history of events in  a [Sled](https://sled.rs)-based database.
+#[derive(Clone)]
+pub struct SledStore {
+    db: sled::Db,
+    notifier: QueueNotifier,
+    cache_notifier: CacheNotifier,
+}
+
+impl std::fmt::Debug for SledStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SledStore").finish()
+    }
+}
+
+impl SledStore {
+    /// Create a new SledStore.
+    pub fn open<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let db = sled::Config::new()
+            .path(path)
+            .temporary(cfg!(test))
+            .mode(sled::Mode::HighThroughput)
+            .open()?;
+        migrations::migrate(&db)?;
+        Ok(Self {
+            db,
+            notifier: QueueNotifier::default(),
+            cache_notifier: CacheNotifier::default(),
+        })
+    }
+    /// Creates a temporary SledStore.
+    pub fn temporary() -> crate::Result<Self> {
+        let dir = tempfile::tempdir()?;
+        Self::open(dir.path())
+    }
+
+    /// Gets the total amount of data stored on disk
+    pub fn get_data_stored_size(&self) -> u64 {
+        self.db.size_on_disk().unwrap_or_default()
+    }
+
+    /// Gets the number of entries of every tree of the database.
+    pub fn get_tree_sizes(&self) -> crate::Result<Vec<(String, u64)>> {
+        self.db
+            .tree_names()
+            .into_iter()
+            .map(|name| {
+                let tree = self.db.open_tree(&name)?;
+                let name = String::from_utf8_lossy(&name).into_owned();
+                Ok((name, tree.len() as u64))
+            })
+            .collect()
+    }
+
+    /// Gets the schema version of the database layout.
+    pub fn schema_version(&self) -> crate::Result<Option<u32>> {
+        migrations::schema_version(&self.db)
+    }
+}
+
+impl HistoryStore for SledStore {
+    #[tracing::instrument(skip(self))]
+    fn set_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_block_numbers")?;
+        let bytes = block_number.to_le_bytes();
+        let key: HistoryStoreKey = key.into();
+        let old = tree.insert(key.to_bytes(), &bytes)?;
+        match old {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_be_bytes(output))
+            }
+            None => Ok(block_number),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        default_block_number: u64,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_block_numbers")?;
+        let key: HistoryStoreKey = key.into();
+        let val = tree.get(key.to_bytes())?;
+        match val {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_le_bytes(output))
+            }
+            None => Ok(default_block_number),
+        }
+    }
+}
+
+impl LeafCacheStore for SledStore {
+    type Output = Vec<Vec<u8>>;
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        let leaves = self
+            .get_leaves_with_indices(key)?
+            .into_iter()
+            .map(|(_, leaf)| leaf)
+            .collect();
+        Ok(leaves)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(leaves_tree_name(&key))?;
+        read_indexed(&tree)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(leaves_tree_name(&key))?;
+        Ok(tree.len() as u64)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        leaves: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(leaves_tree_name(&key))?;
+        for (k, v) in leaves {
+            tree.insert(k.to_be_bytes(), v.as_slice())?;
+        }
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+
+    fn get_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_deposit_block_number")?;
+        let key: HistoryStoreKey = key.into();
+        let val = tree.get(key.to_bytes())?;
+        match val {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_le_bytes(output))
+            }
+            None => Ok(0u64),
+        }
+    }
+
+    fn insert_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_deposit_block_number")?;
+        let bytes = block_number.to_le_bytes();
+        let key: HistoryStoreKey = key.into();
+        let old = tree.insert(key.to_bytes(), &bytes)?;
+        match old {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_le_bytes(output))
+            }
+            None => Ok(block_number),
+        }
+    }
+}
+
+impl EncryptedOutputCacheStore for SledStore {
+    type Output = Vec<Vec<u8>>;
+
+    #[tracing::instrument(skip(self))]
+    fn get_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        let encrypted_outputs = self
+            .get_encrypted_outputs_with_indices(key)?
+            .into_iter()
+            .map(|(_, encrypted_output)| encrypted_output)
+            .collect();
+        Ok(encrypted_outputs)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_encrypted_outputs_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(encrypted_outputs_tree_name(&key))?;
+        read_indexed(&tree)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        encrypted_output: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+
+        let tree = self.db.open_tree(encrypted_outputs_tree_name(&key))?;
+        for (k, v) in encrypted_output {
+            tree.insert(k.to_be_bytes(), v.as_slice())?;
+        }
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+
+    fn get_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_deposit_block_number")?;
+        let key: HistoryStoreKey = key.into();
+        let val = tree.get(key.to_bytes())?;
+        match val {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_le_bytes(output))
+            }
+            None => Ok(0u64),
+        }
+    }
+
+    fn insert_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        let tree = self.db.open_tree("last_deposit_block_number")?;
+        let bytes = block_number.to_le_bytes();
+        let key: HistoryStoreKey = key.into();
+        let old = tree.insert(key.to_bytes(), &bytes)?;
+        match old {
+            Some(v) => {
+                let mut output = [0u8; 8];
+                output.copy_from_slice(&v);
+                Ok(u64::from_be_bytes(output))
+            }
+            None => Ok(block_number),
+        }
+    }
+}
+
+impl NullifierCacheStore for SledStore {
+    #[tracing::instrument(skip(self))]
+    fn get_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(nullifiers_tree_name(&key))?;
+        let nullifiers =
+            tree.iter().values().flatten().map(|v| v.to_vec()).collect();
+        Ok(nullifiers)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        nullifiers: &[Vec<u8>],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let tree = self.db.open_tree(nullifiers_tree_name(&key))?;
+        let indices = self.db.open_tree(nullifier_indices_tree_name(&key))?;
+        let next = next_nullifier_index(&tree)?;
+        (&tree, &indices).transaction(|(tree, indices)| {
+            // restarted from scratch if the transaction is retried.
+            let mut next = next;
+            append_nullifiers(tree, indices, &mut next, nullifiers)
+        })?;
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+}
+
+impl CacheSubscriptionStore for SledStore {
+    #[tracing::instrument(skip(self))]
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let key = HistoryStoreKey::from(key);
+        let name = match kind {
+            CachedItemKind::Leaves => leaves_tree_name(&key),
+            CachedItemKind::EncryptedOutputs => {
+                encrypted_outputs_tree_name(&key)
+            }
+            CachedItemKind::Nullifiers => nullifiers_tree_name(&key),
+        };
+        let tree = self.db.open_tree(name)?;
+        tree.range(from.to_be_bytes()..)
+            .take(limit)
+            .map(|entry| {
+                let (k, v) = entry?;
+                Ok((index_of(&k), v.to_vec()))
+            })
+            .collect()
+    }
+
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64> {
+        self.cache_notifier.subscribe(key)
+    }
+}
+
+impl EventHashStore for SledStore {
+    #[tracing::instrument(skip(self, event))]
+    fn store_event<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        event: &[u8],
+        block_number: u64,
+    ) -> crate::Result<()> {
+        let hashes = self.db.open_tree("event_hashes")?;
+        let blocks = self.db.open_tree("event_blocks")?;
+        let key: HistoryStoreKey = key.into();
+        // both trees are updated in the same transaction.
+        (&hashes, &blocks).transaction(|(hashes, blocks)| {
+            store_event_hash(hashes, blocks, &key, event, block_number)
+        })?;
+        Ok(())
+    }
+
+    fn contains_event(&self, event: &[u8]) -> crate::Result<bool> {
+        let tree = self.db.open_tree("event_hashes")?;
+        let hash = ethers::utils::keccak256(event);
+        let exists = tree.contains_key(hash)?;
+        Ok(exists)
+    }
+
+    fn delete_event(&self, event: &[u8]) -> crate::Result<()> {
+        let hashes = self.db.open_tree("event_hashes")?;
+        let blocks = self.db.open_tree("event_blocks")?;
+        let hash = ethers::utils::keccak256(event);
+        if let Some(location) = hashes.remove(hash)? {
+            blocks.remove([&location[..], &hash[..]].concat())?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn prune_events(&self, depth: u64) -> crate::Result<u64> {
+        let hashes = self.db.open_tree("event_hashes")?;
+        let blocks = self.db.open_tree("event_blocks")?;
+        let last_block_numbers = self.db.open_tree("last_block_numbers")?;
+        let mut pruned = 0u64;
+        let mut cursor = Vec::new();
+        // the events are grouped by contract, so we go through the contracts
+        // one by one, only reading the events below their cutoff block.
+        while let Some(entry) = blocks.range(cursor.as_slice()..).next() {
+            let (first, _) = entry?;
+            let prefix = first[..1 + first[0] as usize].to_vec();
+            let last_block = match last_block_numbers.get(&prefix[1..])? {
+                Some(v) => {
+                    let mut output = [0u8; 8];
+                    output.copy_from_slice(&v);
+                    u64::from_le_bytes(output)
+                }
+                None => 0,
+            };
+            let cutoff = last_block.saturating_sub(depth);
+            let end = [&prefix[..], &cutoff.to_be_bytes()[..]].concat();
+            for entry in blocks.range(prefix.as_slice()..end.as_slice()) {
+                let (location, _) = entry?;
+                hashes.remove(&location[location.len() - 32..])?;
+                blocks.remove(&location)?;
+                pruned += 1;
+            }
+            // skip to the next contract.
+            cursor = prefix;
+            cursor.extend_from_slice(&[u8::MAX; 8 + 32 + 1]);
+        }
+        tracing::debug!(pruned, "pruned the old event hashes");
+        Ok(pruned)
+    }
+}
+
+impl BatchStore for SledStore {
+    #[tracing::instrument(skip_all, fields(ops = batch.len()))]
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()> {
+        let ops = batch.take_ops();
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let anchors: Vec<_> =
+            ops.iter().filter_map(BatchOp::cached_anchor).collect();
+        // the trees written by the batch, the fixed ones first.
+        let mut names = vec![
+            String::from("last_block_numbers"),
+            String::from("last_deposit_block_number"),
+            String::from("event_hashes"),
+            String::from("event_blocks"),
+        ];
+        for op in &ops {
+            let name = match op {
+                BatchOp::InsertLeaves { key, .. } => {
+                    leaves_tree_name(&HistoryStoreKey::from(*key))
+                }
+                BatchOp::InsertEncryptedOutput { key, .. } => {
+                    encrypted_outputs_tree_name(&HistoryStoreKey::from(*key))
+                }
+                BatchOp::InsertNullifiers { key, .. } => {
+                    let key = HistoryStoreKey::from(*key);
+                    let indices = nullifier_indices_tree_name(&key);
+                    if !names.contains(&indices) {
+                        names.push(indices);
+                    }
+                    nullifiers_tree_name(&key)
+                }
+                _ => continue,
+            };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let trees = names
+            .iter()
+            .map(|name| self.db.open_tree(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tree_index = |name: &str| {
+            names
+                .iter()
+                .position(|n| n == name)
+                .expect("every tree of the batch is opened")
+        };
+        // the nullifiers are appended after the last ones of their tree,
+        // which can not be read from within the transaction.
+        let mut nullifier_indices = HashMap::new();
+        for op in &ops {
+            if let BatchOp::InsertNullifiers { key, .. } = op {
+                let name = nullifiers_tree_name(&HistoryStoreKey::from(*key));
+                let next = next_nullifier_index(&trees[tree_index(&name)])?;
+                nullifier_indices.entry(name).or_insert(next);
+            }
+        }
+        trees.as_slice().transaction(|trees| {
+            let mut nullifier_indices = nullifier_indices.clone();
+            for op in &ops {
+                match op {
+                    BatchOp::InsertLeaves { key, leaves } => {
+                        let key = HistoryStoreKey::from(*key);
+                        let tree = &trees[tree_index(&leaves_tree_name(&key))];
+                        for (k, v) in leaves {
+                            tree.insert(&k.to_be_bytes()[..], v.as_slice())?;
+                        }
+                    }
+                    BatchOp::InsertEncryptedOutput {
+                        key,
+                        encrypted_output,
+                    } => {
+                        let key = HistoryStoreKey::from(*key);
+                        let tree = &trees
+                            [tree_index(&encrypted_outputs_tree_name(&key))];
+                        for (k, v) in encrypted_output {
+                            tree.insert(&k.to_be_bytes()[..], v.as_slice())?;
+                        }
+                    }
+                    BatchOp::InsertNullifiers { key, nullifiers } => {
+                        let key = HistoryStoreKey::from(*key);
+                        let name = nullifiers_tree_name(&key);
+                        let tree = &trees[tree_index(&name)];
+                        let indices = &trees
+                            [tree_index(&nullifier_indices_tree_name(&key))];
+                        let next = nullifier_indices
+                            .get_mut(&name)
+                            .expect("every nullifier tree has an index");
+                        append_nullifiers(tree, indices, next, nullifiers)?;
+                    }
+                    BatchOp::SetLastDepositBlockNumber {
+                        key,
+                        block_number,
+                    } => {
+                        trees[1].insert(
+                            HistoryStoreKey::from(*key).to_bytes(),
+                            &block_number.to_le_bytes()[..],
+                        )?;
+                    }
+                    BatchOp::StoreEvent {
+                        key,
+                        event,
+                        block_number,
+                    } => {
+                        store_event_hash(
+                            &trees[2],
+                            &trees[3],
+                            &HistoryStoreKey::from(*key),
+                            event,
+                            *block_number,
+                        )?;
+                    }
+                    BatchOp::SetLastBlockNumber { key, block_number } => {
+                        trees[0].insert(
+                            HistoryStoreKey::from(*key).to_bytes(),
+                            &block_number.to_le_bytes()[..],
+                        )?;
+                    }
+                    #[cfg(test)]
+                    BatchOp::Fail => {
+                        return Err(ConflictableTransactionError::Abort(
+                            std::io::Error::new(
+                                std::io::ErrorKind::Other,
+                                "injected failure",
+                            ),
+                        ));
+                    }
+                }
+            }
+            Ok::<_, ConflictableTransactionError<std::io::Error>>(())
+        })?;
+        for key in anchors {
+            self.cache_notifier.notify(&key.into());
+        }
+        Ok(())
+    }
+}
+
+/// Reads the values of a tree keyed by big endian indices (the leaves, the
+/// encrypted outputs and the nullifiers), in the order of their indices.
+fn read_indexed(tree: &sled::Tree) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+    tree.iter()
+        .map(|entry| {
+            let (k, v) = entry?;
+            Ok((index_of(&k), v.to_vec()))
+        })
+        .collect()
+}
+
+/// Decodes the big endian index of an entry of an indexed tree.
+fn index_of(key: &[u8]) -> u32 {
+    let mut index = [0u8; 4];
+    index.copy_from_slice(key);
+    u32::from_be_bytes(index)
+}
+
+/// The name of the tree where the leaves of the given key are stored,
+/// by their big endian index.
+fn leaves_tree_name(key: &HistoryStoreKey) -> String {
+    format!("leaves_by_index/{}/{}", key.chain_id(), key.address())
+}
+
+/// The name of the tree where the encrypted outputs of the given key are stored,
+/// by their big endian index.
+fn encrypted_outputs_tree_name(key: &HistoryStoreKey) -> String {
+    format!(
+        "encrypted_outputs_by_index/{}/{}",
+        key.chain_id(),
+        key.address()
+    )
+}
+
+/// The name of the tree where the nullifiers of the given key are stored,
+/// by their big endian index.
+fn nullifiers_tree_name(key: &HistoryStoreKey) -> String {
+    format!("nullifiers/{}/{}", key.chain_id(), key.address())
+}
+
+/// The name of the tree that maps the nullifiers of the given key to their
+/// index, so a nullifier handled again is not appended twice.
+fn nullifier_indices_tree_name(key: &HistoryStoreKey) -> String {
+    format!("nullifier_indices/{}/{}", key.chain_id(), key.address())
+}
+
+/// Returns the index following the last nullifier of the given tree.
+fn next_nullifier_index(tree: &sled::Tree) -> crate::Result<u32> {
+    let next = match tree.last()? {
+        Some((k, _)) => index_of(&k) + 1,
+        None => 0,
+    };
+    Ok(next)
+}
+
+/// Appends the nullifiers that are not stored yet after the last one, `next`
+/// being the index of the next nullifier.
+fn append_nullifiers(
+    tree: &TransactionalTree,
+    indices: &TransactionalTree,
+    next: &mut u32,
+    nullifiers: &[Vec<u8>],
+) -> ConflictableTransactionResult<(), std::io::Error> {
+    for v in nullifiers {
+        if indices.get(v.as_slice())?.is_some() {
+            continue;
+        }
+        let index = next.to_be_bytes();
+        tree.insert(&index[..], v.as_slice())?;
+        indices.insert(v.as_slice(), &index[..])?;
+        *next += 1;
+    }
+    Ok(())
+}
+
+/// Stores the hash of an event in the `event_hashes` tree, and indexes it by
+/// block in the `event_blocks` tree.
+fn store_event_hash(
+    hashes: &TransactionalTree,
+    blocks: &TransactionalTree,
+    key: &HistoryStoreKey,
+    event: &[u8],
+    block_number: u64,
+) -> ConflictableTransactionResult<(), std::io::Error> {
+    let hash = ethers::utils::keccak256(event);
+    let mut location = event_blocks_prefix(key);
+    location.extend_from_slice(&block_number.to_be_bytes());
+    // an event stored again is moved to its new block.
+    let old = hashes.insert(&hash[..], location.as_slice())?;
+    if let Some(old) = old {
+        blocks.remove([&old[..], &hash[..]].concat())?;
+    }
+    blocks
+        .insert([&location[..], &hash[..]].concat(), sled::IVec::default())?;
+    Ok(())
+}
+
+/// The prefix of the entries of a contract in the `event_blocks` tree, which keeps the
+/// hashes of the events ordered by contract and block number: the length of the key,
+/// then the key itself, followed by the block number and the hash of each event.
+fn event_blocks_prefix(key: &HistoryStoreKey) -> Vec<u8> {
+    let bytes = key.to_bytes();
+    let mut prefix = Vec::with_capacity(1 + bytes.len());
+    prefix.push(bytes.len() as u8);
+    prefix.extend_from_slice(&bytes);
+    prefix
+}
+
+/// SledQueueKey is a key for a queue in Sled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SledQueueKey {
+    /// Queue Key for EVM based Transaction Queue.
+    EvmTx {
+        /// EVM Chain Id.
+        chain_id: u32,
+        /// an optional key for this transaction.
+        optional_key: Option<[u8; 64]>,
+    },
+    /// Queue Key for Substrate based Transaction Queue.
+    SubstrateTx {
+        /// Substrate Chain Id.
+        chain_id: u32,
+        /// an optional key for this transaction.
+        optional_key: Option<[u8; 64]>,
+    },
+    /// Queue Key for cosmos based Transaction Queue.
+    CosmosTx {
+        /// cosmos-SDK Chain Id.
+        chain_id: u32,
+        /// an optional key for this transaction.
+        optional_key: Option<[u8; 64]>,
+    },
+    /// Queue Key for Bridge Watcher Command Queue.
+    BridgeCmd {
+        /// Specific Bridge Key.
+        bridge_key: BridgeKey,
+    },
+}
+
+impl SledQueueKey {
+    /// Create a new SledQueueKey from an evm chain id.
+    pub fn from_evm_chain_id(chain_id: u32) -> Self {
+        Self::EvmTx {
+            chain_id,
+            optional_key: None,
+        }
+    }
+
+    /// from_evm_with_custom_key returns an EVM specific SledQueueKey.
+    pub fn from_evm_with_custom_key(chain_id: u32, key: [u8; 64]) -> Self {
+        Self::EvmTx {
+            chain_id,
+            optional_key: Some(key),
+        }
+    }
+
+    /// Create a new SledQueueKey from an substrate chain id.
+    pub fn from_substrate_chain_id(chain_id: u32) -> Self {
+        Self::SubstrateTx {
+            chain_id,
+            optional_key: None,
+        }
+    }
+
+    /// from_substrate_with_custom_key returns an Substrate specific SledQueueKey.
+    pub fn from_substrate_with_custom_key(
+        chain_id: u32,
+        key: [u8; 64],
+    ) -> Self {
+        Self::SubstrateTx {
+            chain_id,
+            optional_key: Some(key),
+        }
+    }
+
+    /// Create a new SledQueueKey from an cosmos-sdk chain id.
+    pub fn from_cosmos_chain_id(chain_id: u32) -> Self {
+        Self::CosmosTx {
+            chain_id,
+            optional_key: None,
+        }
+    }
+
+    /// from_cosmos_with_custom_key returns an Cosmos-SDK chain specific SledQueueKey.
+    pub fn from_cosmos_with_custom_key(chain_id: u32, key: [u8; 64]) -> Self {
+        Self::CosmosTx {
+            chain_id,
+            optional_key: Some(key),
+        }
+    }
+
+    /// from_bridge_key returns a Bridge specific SledQueueKey.
+    pub fn from_bridge_key(bridge_key: BridgeKey) -> Self {
+        Self::BridgeCmd { bridge_key }
+    }
+
+    /// Returns the same queue key with a custom key for the item.
+    ///
+    /// Bridge command queues do not have custom keys, so they are returned
+    /// as they are.
+    pub fn with_item_key(self, key: [u8; 64]) -> Self {
+        match self {
+            Self::EvmTx { chain_id, .. } => {
+                Self::from_evm_with_custom_key(chain_id, key)
+            }
+            Self::SubstrateTx { chain_id, .. } => {
+                Self::from_substrate_with_custom_key(chain_id, key)
+            }
+            Self::CosmosTx { chain_id, .. } => {
+                Self::from_cosmos_with_custom_key(chain_id, key)
+            }
+            Self::BridgeCmd { .. } => self,
+        }
+    }
+
+    /// Parses a queue name, as returned by [`QueueKey::queue_name`], back
+    /// into a SledQueueKey.
+    pub fn from_queue_name(name: &str) -> Option<Self> {
+        if let Some(id) = name.strip_prefix("evm_tx_") {
+            return id.parse().ok().map(Self::from_evm_chain_id);
+        }
+        if let Some(id) = name.strip_prefix("substrate_tx_") {
+            return id.parse().ok().map(Self::from_substrate_chain_id);
+        }
+        if let Some(id) = name.strip_prefix("cosmos_tx_") {
+            return id.parse().ok().map(Self::from_cosmos_chain_id);
+        }
+        if let Some(id) = name.strip_prefix("bridge_cmd_") {
+            return id.parse::<u64>().ok().map(|id| {
+                Self::from_bridge_key(BridgeKey::new(
+                    webb_proposals::TypedChainId::from(id),
+                ))
+            });
+        }
+        None
+    }
+}
+
+impl fmt::Display for SledQueueKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EvmTx {
+                chain_id,
+                optional_key,
+            } => write!(
+                f,
+                "EvmTx({}, {:?})",
+                chain_id,
+                optional_key.map(hex::encode)
+            ),
+            Self::SubstrateTx {
+                chain_id,
+                optional_key,
+            } => write!(
+                f,
+                "SubstrateTx({}, {:?})",
+                chain_id,
+                optional_key.map(hex::encode)
+            ),
+            Self::CosmosTx {
+                chain_id,
+                optional_key,
+            } => write!(
+                f,
+                "CosmosTx({}, {:?})",
+                chain_id,
+                optional_key.map(hex::encode)
+            ),
+            Self::BridgeCmd { bridge_key } => {
+                write!(f, "BridgeCmd({bridge_key})")
+            }
+        }
+    }
+}
+
+impl QueueKey for SledQueueKey {
+    fn queue_name(&self) -> String {
+        match self {
+            Self::EvmTx { chain_id, .. } => format!("evm_tx_{chain_id}"),
+            Self::SubstrateTx { chain_id, .. } => {
+                format!("substrate_tx_{chain_id}")
+            }
+            Self::CosmosTx { chain_id, .. } => {
+                format!("cosmos_tx_{chain_id}")
+            }
+            Self::BridgeCmd { bridge_key, .. } => {
+                format!("bridge_cmd_{}", bridge_key.chain_id.chain_id())
+            }
+        }
+    }
+
+    fn item_key(&self) -> Option<[u8; 64]> {
+        match self {
+            Self::EvmTx { optional_key, .. } => *optional_key,
+            Self::SubstrateTx { optional_key, .. } => *optional_key,
+            Self::CosmosTx { optional_key, .. } => *optional_key,
+            Self::BridgeCmd { .. } => None,
+        }
+    }
+}
+
+/// The length of the key of an item in a queue tree: the 4 bytes prefix,
+/// the inverted priority, the index and the not before timestamp.
+pub(crate) const QUEUE_ITEM_KEY_LEN: usize = 4 + 1 + 8 + 8;
+
+/// Creates the key of an item in a queue tree.
+///
+/// The priority is inverted so that a scan over the keys returns the highest
+/// priority items first, and the items of the same priority by their index.
+pub(crate) fn queue_item_key(
+    prefix: &[u8],
+    priority: QueuePriority,
+    idx: u64,
+    not_before: u64,
+) -> [u8; QUEUE_ITEM_KEY_LEN] {
+    let mut item_key = [0u8; QUEUE_ITEM_KEY_LEN];
+    item_key[0..4].copy_from_slice(&prefix[..4]);
+    item_key[4] = u8::MAX - priority.as_u8();
+    item_key[5..13].copy_from_slice(&idx.to_be_bytes());
+    item_key[13..].copy_from_slice(&not_before.to_be_bytes());
+    item_key
+}
+
+/// Returns the first item of a queue tree that can be dequeued at `now`.
+fn next_ready_item(
+    tree: &sled::Tree,
+    now: u64,
+) -> crate::Result<Option<(sled::IVec, sled::IVec)>> {
+    // a lazy iterator that will scan over all saved items in the queue
+    // with the specific key prefix.
+    let prefix = tree.get("key_prefix")?.unwrap_or_else(|| b"item".into());
+    for entry in tree.scan_prefix(prefix) {
+        let (key, value) = entry?;
+        // skip anything that is not an item, like the special keys.
+        if key.len() != QUEUE_ITEM_KEY_LEN {
+            continue;
+        }
+        let mut not_before = [0u8; 8];
+        not_before.copy_from_slice(&key[13..]);
+        if u64::from_be_bytes(not_before) <= now {
+            return Ok(Some((key, value)));
+        }
+    }
+    Ok(None)
+}
+
+impl<T> QueueStore<T> for SledStore
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: T,
+        options: EnqueueOptions,
+    ) -> crate::Result<()> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let item_bytes = serde_json::to_vec(&item)?;
+        // we do everything inside a single transaction
+        // so everything happens atomically and if anything fails
+        // we revert everything back to the old state.
+        tree.transaction::<_, _, std::io::Error>(|db| {
+            // get the last id of the queue.
+            let last_item_idx = match db.get("last_item_idx")? {
+                Some(v) => {
+                    let mut output = [0u8; 8];
+                    output.copy_from_slice(&v);
+                    u64::from_be_bytes(output)
+                }
+                None => 0u64,
+            };
+            // increment it.
+            let next_idx = last_item_idx + 1u64;
+            let idx_bytes = next_idx.to_be_bytes();
+            // then save it.
+            db.insert("last_item_idx", &idx_bytes)?;
+            db.insert("key_prefix", "item")?;
+            let prefix =
+                db.get("key_prefix")?.unwrap_or_else(|| b"item".into());
+            let item_key = queue_item_key(
+                &prefix,
+                options.priority,
+                next_idx,
+                options.not_before.unwrap_or_default(),
+            );
+            // then we save it.
+            db.insert(&item_key[..], item_bytes.as_slice())?;
+            if let Some(k) = key.item_key() {
+                // also save the key where we can find it by special key.
+                db.insert(&k[..], &item_key[..])?;
+            }
+            tracing::trace!("enqueue item under key = {}", key);
+            Ok(())
+        })?;
+        // flush the db to make sure we don't lose anything.
+        self.db.flush()?;
+        self.notifier.notify(&key.queue_name());
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let now = webb_relayer_utils::unix_timestamp();
+        let (key, value) = match next_ready_item(&tree, now)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let item = serde_json::from_slice(&value)?;
+        // now it is safe to remove it from the queue.
+        tree.remove(key)?;
+        // flush db
+        self.db.flush()?;
+        Ok(Some(item))
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn peek_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        // this method, is similar to dequeue_tx, expect we don't
+        // remove anything from the queue.
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let now = webb_relayer_utils::unix_timestamp();
+        match next_ready_item(&tree, now)? {
+            Some((_, value)) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn has_item(&self, key: Self::Key) -> crate::Result<bool> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(false),
+        };
+        // the special key is not removed when the item gets dequeued,
+        // so we need to check that the item itself is still in the queue.
+        match tree.get(&inner_key[..])? {
+            Some(k) => tree.contains_key(k).map_err(Into::into),
+            None => Ok(false),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn remove_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+        match tree.get(&inner_key[..])? {
+            Some(k) => {
+                let exists = tree.remove(&k)?;
+                tree.remove(inner_key)?;
+                let item = exists.and_then(|v| serde_json::from_slice(&v).ok());
+                tracing::trace!("removed item from the queue..");
+                self.db.flush()?;
+                Ok(item)
+            }
+            None => {
+                // not found!
+                tracing::trace!("item with key {} not found in queue", key);
+                Ok(None)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn queue_items(&self, key: Self::Key) -> crate::Result<Vec<QueuedItem<T>>> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let prefix = tree.get("key_prefix")?.unwrap_or_else(|| b"item".into());
+        let mut items = Vec::new();
+        for entry in tree.scan_prefix(prefix) {
+            let (key, value) = entry?;
+            // skip anything that is not an item, like the special keys.
+            if key.len() != QUEUE_ITEM_KEY_LEN {
+                continue;
+            }
+            let mut not_before = [0u8; 8];
+            not_before.copy_from_slice(&key[13..]);
+            let not_before = u64::from_be_bytes(not_before);
+            items.push(QueuedItem {
+                priority: QueuePriority::from_u8(u8::MAX - key[4]),
+                not_before: (not_before != 0).then_some(not_before),
+                item: serde_json::from_slice(&value)?,
+            });
+        }
+        Ok(items)
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64> {
+        let tree = self.db.open_tree(format!("queue_{}", key.queue_name()))?;
+        let prefix = tree.get("key_prefix")?.unwrap_or_else(|| b"item".into());
+        let mut removed = 0;
+        for entry in tree.scan_prefix(prefix) {
+            let (key, _) = entry?;
+            if key.len() != QUEUE_ITEM_KEY_LEN {
+                continue;
+            }
+            tree.remove(key)?;
+            removed += 1;
+        }
+        self.db.flush()?;
+        Ok(removed)
+    }
+
+    fn queue_notifier(&self, key: Self::Key) -> Arc<Notify> {
+        self.notifier.get(&key.queue_name())
+    }
+}
+
+impl ProposalStore for SledStore {
+    type Proposal = SubmittedProposal;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(proposal.data_hash))
+    )]
+    fn insert_proposal(&self, proposal: Self::Proposal) -> crate::Result<()> {
+        let tree = self.db.open_tree("proposal_store")?;
+        tree.insert(
+            proposal.data_hash,
+            serde_json::to_vec(&proposal)?.as_slice(),
+        )?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn get_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let tree = self.db.open_tree("proposal_store")?;
+        match tree.get(data_hash)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_proposals(&self) -> crate::Result<Vec<Self::Proposal>> {
+        let tree = self.db.open_tree("proposal_store")?;
+        tree.iter()
+            .values()
+            .map(|v| {
+                let bytes = v?;
+                Ok(serde_json::from_slice(&bytes)?)
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn remove_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let tree = self.db.open_tree("proposal_store")?;
+        match tree.remove(data_hash)? {
+            Some(bytes) => {
+                self.db.flush()?;
+                Ok(Some(serde_json::from_slice(&bytes)?))
+            }
+            None => {
+                tracing::warn!(
+                    "Proposal not seen yet; not found in the proposal storage."
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<T> DeadLetterStore<T> for SledStore
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(key = %key, id = %hex::encode(letter.id))
+    )]
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<T>,
+    ) -> crate::Result<()> {
+        let tree = self
+            .db
+            .open_tree(format!("dead_letters_{}", key.queue_name()))?;
+        tree.insert(letter.id, serde_json::to_vec(&letter)?.as_slice())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let tree = self
+            .db
+            .open_tree(format!("dead_letters_{}", key.queue_name()))?;
+        match tree.get(id)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<T>>> {
+        let tree = self
+            .db
+            .open_tree(format!("dead_letters_{}", key.queue_name()))?;
+        tree.iter()
+            .values()
+            .map(|v| {
+                let bytes = v?;
+                Ok(serde_json::from_slice(&bytes)?)
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let tree = self
+            .db
+            .open_tree(format!("dead_letters_{}", key.queue_name()))?;
+        match tree.remove(id)? {
+            Some(bytes) => {
+                self.db.flush()?;
+                Ok(Some(serde_json::from_slice(&bytes)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl JobStore for SledStore {
+    #[tracing::instrument(skip_all, fields(id = %job.id))]
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()> {
+        let tree = self.db.open_tree("relay_jobs")?;
+        tree.insert(job.id.as_bytes(), serde_json::to_vec(job)?.as_slice())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        let tree = self.db.open_tree("relay_jobs")?;
+        match tree.get(id.as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>> {
+        let tree = self.db.open_tree("relay_jobs")?;
+        tree.iter()
+            .values()
+            .map(|bytes| Ok(serde_json::from_slice(&bytes?)?))
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        let tree = self.db.open_tree("relay_jobs")?;
+        match tree.remove(id.as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
+    use webb::evm::ethers::types;
+    use webb::evm::ethers::types::transaction::request::TransactionRequest;
+    use webb::evm::{
+        contract::protocol_solidity::v_anchor_contract::NewNullifierFilter,
+        ethers::types::U64,
+    };
+    use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+
+    impl SledQueueKey {
+        pub fn from_evm_tx(chain_id: u32, tx: &TypedTransaction) -> Self {
+            let key = {
+                let mut bytes = [0u8; 64];
+                let tx_hash =
+                    tx.clone().set_chain_id(U64::from(chain_id)).sighash();
+                bytes[..32].copy_from_slice(tx_hash.as_fixed_bytes());
+                bytes
+            };
+            Self::from_evm_with_custom_key(chain_id, key)
+        }
+    }
+
+    #[test]
+    fn get_leaves_should_work() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SledStore::open(tmp.path()).unwrap();
+        let chain_id = 1u32;
+        let contract =
+            types::H160::from_slice("11111111111111111111".as_bytes());
+        let block_number = 20u64;
+        let default_block_number = 1u64;
+        let history_store_key = (
+            TypedChainId::Evm(chain_id),
+            TargetSystem::new_contract_address(contract.to_fixed_bytes()),
+        );
+        store
+            .set_last_block_number(history_store_key, block_number)
+            .unwrap();
+
+        let block = store
+            .get_last_block_number(history_store_key, default_block_number);
+
+        match block {
+            Ok(b) => {
+                println!("retrieved block {:?}", block);
+                assert_eq!(b, 20u64);
+            }
+            Err(e) => {
+                println!("Error encountered {:?}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn tx_queue_should_work() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SledStore::open(tmp.path()).unwrap();
+        let chain_id = 1u32;
+        // it is now empty
+        assert_eq!(
+            store
+                .dequeue_item(SledQueueKey::from_evm_chain_id(chain_id))
+                .unwrap(),
+            Option::<TypedTransaction>::None
+        );
+
+        let tx1: TypedTransaction = TransactionRequest::pay(
+            types::Address::random(),
+            types::U256::one(),
+        )
+        .from(types::Address::random())
+        .into();
+        store
+            .enqueue_item(
+                SledQueueKey::from_evm_tx(chain_id, &tx1),
+                tx1.clone(),
+            )
+            .unwrap();
+        let tx2: TypedTransaction = TransactionRequest::pay(
+            types::Address::random(),
+            types::U256::one(),
+        )
+        .from(types::Address::random())
+        .into();
+        store
+            .enqueue_item(
+                SledQueueKey::from_evm_tx(chain_id, &tx2),
+                tx2.clone(),
+            )
+            .unwrap();
+
+        // now let's dequeue transactions.
+        assert_eq!(
+            store
+                .dequeue_item(SledQueueKey::from_evm_chain_id(chain_id))
+                .unwrap(),
+            Some(tx1.clone())
+        );
+        // a dequeued item is no longer in the queue.
+        assert!(!QueueStore::<TypedTransaction>::has_item(
+            &store,
+            SledQueueKey::from_evm_tx(chain_id, &tx1)
+        )
+        .unwrap());
+        assert_eq!(
+            store
+                .dequeue_item(SledQueueKey::from_evm_chain_id(chain_id))
+                .unwrap(),
+            Some(tx2)
+        );
+
+        let tx3: TypedTransaction = TransactionRequest::pay(
+            types::Address::random(),
+            types::U256::one(),
+        )
+        .from(types::Address::random())
+        .into();
+        store
+            .enqueue_item(
+                SledQueueKey::from_evm_tx(chain_id, &tx3),
+                tx3.clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .peek_item(SledQueueKey::from_evm_chain_id(chain_id))
+                .unwrap(),
+            Some(tx3.clone())
+        );
+        assert!(QueueStore::<TypedTransaction>::has_item(
+            &store,
+            SledQueueKey::from_evm_tx(chain_id, &tx3)
+        )
+        .unwrap());
+        let _: Option<TypedTransaction> = store
+            .remove_item(SledQueueKey::from_evm_tx(chain_id, &tx3))
+            .unwrap();
+        assert!(!QueueStore::<TypedTransaction>::has_item(
+            &store,
+            SledQueueKey::from_evm_tx(chain_id, &tx3)
+        )
+        .unwrap());
+        assert_eq!(
+            store
+                .dequeue_item(SledQueueKey::from_evm_chain_id(chain_id))
+                .unwrap(),
+            Option::<TypedTransaction>::None
+        );
+    }
+
+    #[test]
+    fn events_hash_should_work() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SledStore::open(tmp.path()).unwrap();
+
+        let events = (0..20)
+            .map(|_| NewNullifierFilter {
+                nullifier: types::H256::random().to_fixed_bytes(),
+            })
+            .collect::<Vec<_>>();
+        let key = (
+            TypedChainId::Evm(1),
+            TargetSystem::new_contract_address([1u8; 20]),
+        );
+
+        for event in &events {
+            let event_bytes = serde_json::to_vec(&event).unwrap();
+            // check if the event is already in the store
+            assert!(!store.contains_event(&event_bytes).unwrap());
+            // add the event
+            store.store_event(key, &event_bytes, 1).unwrap();
+        }
+
+        for event in &events {
+            let event_bytes = serde_json::to_vec(&event).unwrap();
+            // check if the event is in the store
+            assert!(store.contains_event(&event_bytes).unwrap());
+            // remove the event
+            store.delete_event(&event_bytes).unwrap();
+        }
+    }
+
+    #[test]
+    fn proposal_store_should_work() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SledStore::open(tmp.path()).unwrap();
+        let target = ResourceId::new(
+            TargetSystem::new_contract_address([1u8; 20]),
+            TypedChainId::Evm(1),
+        );
+        let mut proposal = SubmittedProposal::new(
+            1,
+            TypedChainId::Evm(2),
+            target,
+            vec![1, 2, 3],
+            100,
+        );
+        assert_eq!(proposal.src_typed_chain_id(), TypedChainId::Evm(2));
+        assert_eq!(proposal.target_resource_id(), target);
+        store.insert_proposal(proposal.clone()).unwrap();
+        assert_eq!(
+            store.get_proposal(&proposal.data_hash).unwrap(),
+            Some(proposal.clone())
+        );
+        assert!(!proposal.is_timed_out(150, 60));
+        assert!(proposal.is_timed_out(160, 60));
+
+        proposal.mark_signed(170);
+        store.insert_proposal(proposal.clone()).unwrap();
+        assert!(!proposal.is_timed_out(1000, 60));
+        assert_eq!(store.get_proposals().unwrap(), vec![proposal.clone()]);
+
+        assert_eq!(
+            store.remove_proposal(&proposal.data_hash).unwrap(),
+            Some(proposal.clone())
+        );
+        assert_eq!(store.get_proposal(&proposal.data_hash).unwrap(), None);
+    }
+
+    mod conformance {
+        use super::*;
+
+        crate::conformance::store_conformance_tests!(
+            SledStore::temporary().unwrap()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshots of the leaves cache, used to bootstrap a new relayer without
+//! replaying every anchor from the block it got deployed at.
+//!
+//! A snapshot file is a JSON document that carries its format version and the
+//! keccak256 checksum of its content, both are checked before importing it.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use webb::evm::ethers::utils::keccak256;
+use webb_proposals::ResourceId;
+
+use crate::{
+    BatchStore, EncryptedOutputCacheStore, HistoryStore, LeafCacheStore,
+    NullifierCacheStore, StoreBatch,
+};
+
+/// The version of the snapshot format written by this version of the relayer.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// The cached data of a single anchor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorSnapshot {
+    /// The resource id of the anchor.
+    pub resource_id: [u8; 32],
+    /// The last block the events watcher synced, the watcher resumes from there.
+    pub last_block_number: u64,
+    /// The block number of the last deposit.
+    pub last_deposit_block_number: u64,
+    /// The last root of the anchor at `last_block_number`, if known.
+    pub last_root: Option<[u8; 32]>,
+    /// The `(index, leaf)` pairs of the anchor, ordered by index.
+    pub leaves: Vec<(u32, Vec<u8>)>,
+    /// The `(index, encrypted output)` pairs of the anchor, ordered by index.
+    pub encrypted_outputs: Vec<(u32, Vec<u8>)>,
+    /// The nullifiers spent on the anchor, in the order they were spent.
+    pub nullifiers: Vec<Vec<u8>>,
+}
+
+impl AnchorSnapshot {
+    /// The resource id of the anchor.
+    pub fn resource_id(&self) -> ResourceId {
+        ResourceId::from(self.resource_id)
+    }
+}
+
+/// A versioned and checksummed snapshot of the leaves cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// The version of the snapshot format.
+    pub version: u32,
+    /// The keccak256 hash of the serialized anchors.
+    pub checksum: [u8; 32],
+    /// The snapshots of the anchors.
+    pub anchors: Vec<AnchorSnapshot>,
+}
+
+impl Snapshot {
+    /// Creates a new snapshot of the given anchors.
+    pub fn new(anchors: Vec<AnchorSnapshot>) -> crate::Result<Self> {
+        let checksum = checksum(&anchors)?;
+        Ok(Self {
+            version: SNAPSHOT_VERSION,
+            checksum,
+            anchors,
+        })
+    }
+
+    /// Checks the version and the checksum of the snapshot.
+    pub fn verify(&self) -> crate::Result<()> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(webb_relayer_utils::Error::Generic(
+                "unsupported snapshot version",
+            ));
+        }
+        if checksum(&self.anchors)? != self.checksum {
+            return Err(webb_relayer_utils::Error::Generic(
+                "snapshot checksum mismatch",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Writes the snapshot as JSON.
+    pub fn write_to<W: Write>(&self, writer: W) -> crate::Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Reads and verifies a snapshot written by [`Snapshot::write_to`].
+    pub fn read_from<R: Read>(reader: R) -> crate::Result<Self> {
+        let snapshot: Self = serde_json::from_reader(reader)?;
+        snapshot.verify()?;
+        Ok(snapshot)
+    }
+}
+
+fn checksum(anchors: &[AnchorSnapshot]) -> crate::Result<[u8; 32]> {
+    let bytes = serde_json::to_vec(anchors)?;
+    Ok(keccak256(bytes))
+}
+
+/// Exports the cached data of the anchor with the given resource id.
+///
+/// The `last_root` is left empty, it has to be read from the chain by the caller.
+pub fn export_anchor<S>(
+    store: &S,
+    resource_id: ResourceId,
+) -> crate::Result<AnchorSnapshot>
+where
+    S: HistoryStore
+        + LeafCacheStore<Output = Vec<Vec<u8>>>
+        + EncryptedOutputCacheStore<Output = Vec<Vec<u8>>>
+        + NullifierCacheStore,
+{
+    Ok(AnchorSnapshot {
+        resource_id: resource_id.into_bytes(),
+        last_block_number: store.get_last_block_number(resource_id, 0)?,
+        last_deposit_block_number: store
+            .get_last_deposit_block_number(resource_id)?,
+        last_root: None,
+        leaves: store.get_leaves_with_indices(resource_id)?,
+        encrypted_outputs: store
+            .get_encrypted_outputs_with_indices(resource_id)?,
+        nullifiers: store.get_nullifiers(resource_id)?,
+    })
+}
+
+/// Imports the snapshot of an anchor, the store must not have any data for this anchor yet.
+///
+/// Everything is written in a single batch, so an import that fails midway
+/// leaves the anchor empty and can be retried.
+pub fn import_anchor<S>(store: &S, anchor: &AnchorSnapshot) -> crate::Result<()>
+where
+    S: HistoryStore
+        + BatchStore
+        + LeafCacheStore<Output = Vec<Vec<u8>>>
+        + EncryptedOutputCacheStore<Output = Vec<Vec<u8>>>
+        + NullifierCacheStore,
+{
+    let resource_id = anchor.resource_id();
+    let is_empty = store.get_last_block_number(resource_id, 0)? == 0
+        && store.get_leaves(resource_id)?.is_empty()
+        && store.get_encrypted_output(resource_id)?.is_empty()
+        && store.get_nullifiers(resource_id)?.is_empty();
+    if !is_empty {
+        return Err(webb_relayer_utils::Error::Generic(
+            "the store already has data for the snapshot anchor",
+        ));
+    }
+    let batch = StoreBatch::new();
+    batch.insert_leaves(resource_id, &anchor.leaves);
+    batch.insert_encrypted_output(resource_id, &anchor.encrypted_outputs);
+    batch.insert_nullifiers(resource_id, &anchor.nullifiers);
+    batch.insert_last_deposit_block_number(
+        resource_id,
+        anchor.last_deposit_block_number,
+    );
+    batch.set_last_block_number(resource_id, anchor.last_block_number);
+    store.commit_batch(&batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SledStore;
+    use webb_proposals::{TargetSystem, TypedChainId};
+
+    fn resource_id() -> ResourceId {
+        ResourceId::new(
+            TargetSystem::new_contract_address([1u8; 20]),
+            TypedChainId::Evm(1),
+        )
+    }
+
+    #[test]
+    fn snapshot_should_round_trip() {
+        let source = SledStore::temporary().unwrap();
+        let resource_id = resource_id();
+        source
+            .insert_leaves(resource_id, &[(0, vec![1; 32]), (1, vec![2; 32])])
+            .unwrap();
+        source
+            .insert_encrypted_output(resource_id, &[(0, vec![3]), (1, vec![4])])
+            .unwrap();
+        source
+            .insert_nullifiers(resource_id, &[vec![5; 32], vec![6; 32]])
+            .unwrap();
+        source
+            .insert_last_deposit_block_number(resource_id, 90)
+            .unwrap();
+        source.set_last_block_number(resource_id, 100).unwrap();
+
+        let anchor = export_anchor(&source, resource_id).unwrap();
+        let snapshot = Snapshot::new(vec![anchor]).unwrap();
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+        let snapshot = Snapshot::read_from(bytes.as_slice()).unwrap();
+
+        let target = SledStore::temporary().unwrap();
+        import_anchor(&target, &snapshot.anchors[0]).unwrap();
+        assert_eq!(
+            target.get_leaves_with_indices(resource_id).unwrap(),
+            vec![(0, vec![1; 32]), (1, vec![2; 32])]
+        );
+        assert_eq!(
+            target
+                .get_encrypted_outputs_with_indices(resource_id)
+                .unwrap(),
+            vec![(0, vec![3]), (1, vec![4])]
+        );
+        assert_eq!(
+            target.get_nullifiers(resource_id).unwrap(),
+            vec![vec![5; 32], vec![6; 32]]
+        );
+        assert_eq!(target.get_last_block_number(resource_id, 0).unwrap(), 100);
+        // importing twice is refused.
+        assert!(import_anchor(&target, &snapshot.anchors[0]).is_err());
+    }
+
+    #[test]
+    fn tampered_snapshot_should_be_refused() {
+        let mut snapshot = Snapshot::new(vec![AnchorSnapshot {
+            resource_id: resource_id().into_bytes(),
+            last_block_number: 10,
+            last_deposit_block_number: 10,
+            last_root: None,
+            leaves: vec![(0, vec![1; 32])],
+            encrypted_outputs: vec![],
+            nullifiers: vec![],
+        }])
+        .unwrap();
+        let mut outdated = snapshot.clone();
+        outdated.version = 1;
+        assert!(outdated.verify().is_err());
+        snapshot.anchors[0].leaves.push((1, vec![2; 32]));
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+        assert!(Snapshot::read_from(bytes.as_slice()).is_err());
+    }
+}
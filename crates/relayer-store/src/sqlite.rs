@@ -0,0 +1,1161 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::{watch, Notify};
+use webb::evm::ethers;
+use webb_proposals::ResourceId;
+
+use crate::sled::SledQueueKey;
+use crate::{
+    CacheNotifier, CacheSubscriptionStore, CachedItemKind, JobStore,
+    NullifierCacheStore, QueueKey, QueueNotifier, RelayJob,
+};
+
+use super::HistoryStoreKey;
+use super::{
+    BatchOp, BatchStore, DeadLetter, DeadLetterStore,
+    EncryptedOutputCacheStore, EnqueueOptions, EventHashStore, HistoryStore,
+    LeafCacheStore, ProposalStore, QueuePriority, QueueStore, QueuedItem,
+    StoreBatch, SubmittedProposal,
+};
+
+/// The database schema, every table is created only if it does not exist yet.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS last_block_numbers (
+    key BLOB PRIMARY KEY NOT NULL,
+    block_number INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS last_deposit_block_numbers (
+    key BLOB PRIMARY KEY NOT NULL,
+    block_number INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS leaves (
+    tree TEXT NOT NULL,
+    leaf_index INTEGER NOT NULL,
+    leaf BLOB NOT NULL,
+    PRIMARY KEY (tree, leaf_index)
+);
+CREATE TABLE IF NOT EXISTS encrypted_outputs (
+    tree TEXT NOT NULL,
+    output_index INTEGER NOT NULL,
+    encrypted_output BLOB NOT NULL,
+    PRIMARY KEY (tree, output_index)
+);
+CREATE TABLE IF NOT EXISTS nullifiers (
+    tree TEXT NOT NULL,
+    nullifier_index INTEGER NOT NULL,
+    nullifier BLOB NOT NULL,
+    PRIMARY KEY (tree, nullifier_index)
+);
+CREATE INDEX IF NOT EXISTS nullifiers_by_value ON nullifiers (tree, nullifier);
+CREATE TABLE IF NOT EXISTS event_hashes (
+    hash BLOB PRIMARY KEY NOT NULL,
+    history_key BLOB NOT NULL,
+    block_number INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS queue_items (
+    idx INTEGER PRIMARY KEY AUTOINCREMENT,
+    queue TEXT NOT NULL,
+    item_key BLOB,
+    item BLOB NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 1,
+    not_before INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS queue_items_by_queue ON queue_items (queue, idx);
+CREATE INDEX IF NOT EXISTS queue_items_by_key ON queue_items (queue, item_key);
+CREATE TABLE IF NOT EXISTS proposals (
+    data_hash BLOB PRIMARY KEY NOT NULL,
+    proposal BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS dead_letters (
+    queue TEXT NOT NULL,
+    id BLOB NOT NULL,
+    letter BLOB NOT NULL,
+    PRIMARY KEY (queue, id)
+);
+CREATE TABLE IF NOT EXISTS relay_jobs (
+    id TEXT PRIMARY KEY NOT NULL,
+    job BLOB NOT NULL
+);
+";
+
+/// Appends a nullifier to its tree, with the index following the last one,
+/// unless it is already stored.
+const APPEND_NULLIFIER: &str = "INSERT INTO nullifiers (tree, nullifier_index, nullifier)
+     SELECT ?1, (SELECT COALESCE(MAX(nullifier_index) + 1, 0) FROM nullifiers WHERE tree = ?1), ?2
+     WHERE NOT EXISTS (SELECT 1 FROM nullifiers WHERE tree = ?1 AND nullifier = ?2)";
+
+/// Adds the columns that were added to the schema after its first version
+/// to existing databases.
+fn upgrade_schema(conn: &Connection) -> crate::Result<()> {
+    let has_priority = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('queue_items') WHERE name = 'priority'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_priority {
+        conn.execute_batch(
+            "ALTER TABLE queue_items ADD COLUMN priority INTEGER NOT NULL DEFAULT 1;
+             ALTER TABLE queue_items ADD COLUMN not_before INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS queue_items_by_priority ON queue_items (queue, priority DESC, idx);",
+    )?;
+    // the event hashes stored without their contract and block number can not
+    // be pruned, they only mark the events as handled and the event watchers
+    // resume from their last block numbers anyway, so they are dropped.
+    let has_block_number = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('event_hashes') WHERE name = 'block_number'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_block_number {
+        conn.execute_batch("DROP TABLE event_hashes;")?;
+        conn.execute_batch(SCHEMA)?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS event_hashes_by_block ON event_hashes (history_key, block_number);",
+    )?;
+    Ok(())
+}
+
+/// SqliteStore is a store that stores the history of events in a [SQLite](https://sqlite.org) database.
+///
+/// Unlike the [`SledStore`](crate::SledStore), the database can be opened by more than one
+/// process at the same time (for example an API only relayer next to the watchers) and
+/// inspected with the standard SQLite tools.
+#[derive(Clone)]
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+    /// Only wakes up the consumers of this process, the ones of
+    /// another process sharing the database have to poll their queues.
+    notifier: QueueNotifier,
+    /// Same for the subscribers of the cached items of the anchors.
+    cache_notifier: CacheNotifier,
+}
+
+impl std::fmt::Debug for SqliteStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteStore").finish()
+    }
+}
+
+/// How long a write waits for the lock held by another connection to the
+/// same database (i.e. the API process next to the watchers) before failing.
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl SqliteStore {
+    /// Create a new SqliteStore, the database file is created if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let conn = Connection::open(path)?;
+        // WAL mode allows readers in other processes while we are writing.
+        conn.pragma_update(None, "journal_mode", &"WAL")?;
+        conn.pragma_update(None, "synchronous", &"NORMAL")?;
+        // the writers of other processes are waited for, instead of failing
+        // right away with `SQLITE_BUSY`.
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Self::init(conn)
+    }
+
+    /// Creates a temporary, in memory, SqliteStore.
+    pub fn temporary() -> crate::Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> crate::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        upgrade_schema(&conn)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            notifier: QueueNotifier::default(),
+            cache_notifier: CacheNotifier::default(),
+        })
+    }
+
+    /// Gets the number of rows of every table of the database.
+    pub fn get_tree_sizes(&self) -> crate::Result<Vec<(String, u64)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        tables
+            .into_iter()
+            .map(|table| {
+                let rows = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {table}"),
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )?;
+                Ok((table, rows as u64))
+            })
+            .collect()
+    }
+
+    /// Gets the total amount of data stored on disk
+    pub fn get_data_stored_size(&self) -> u64 {
+        let conn = self.conn.lock();
+        let size = conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get::<_, i64>(0),
+        );
+        size.map(|v| v as u64).unwrap_or_default()
+    }
+
+    fn set_block_number(
+        &self,
+        table: &str,
+        key: HistoryStoreKey,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        let conn = self.conn.lock();
+        let old = conn
+            .query_row(
+                &format!("SELECT block_number FROM {table} WHERE key = ?1"),
+                params![key.to_bytes()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        conn.execute(
+            &format!(
+                "INSERT INTO {table} (key, block_number) VALUES (?1, ?2)
+                 ON CONFLICT (key) DO UPDATE SET block_number = excluded.block_number"
+            ),
+            params![key.to_bytes(), block_number as i64],
+        )?;
+        Ok(old.map(|v| v as u64).unwrap_or(block_number))
+    }
+
+    fn get_block_number(
+        &self,
+        table: &str,
+        key: HistoryStoreKey,
+        default_block_number: u64,
+    ) -> crate::Result<u64> {
+        let conn = self.conn.lock();
+        let val = conn
+            .query_row(
+                &format!("SELECT block_number FROM {table} WHERE key = ?1"),
+                params![key.to_bytes()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        Ok(val.map(|v| v as u64).unwrap_or(default_block_number))
+    }
+}
+
+/// The name of the tree where the leaves/encrypted outputs of the given key are stored.
+fn tree_name(key: &HistoryStoreKey) -> String {
+    format!("{}/{}", key.chain_id(), key.address())
+}
+
+impl HistoryStore for SqliteStore {
+    #[tracing::instrument(skip(self))]
+    fn set_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        self.set_block_number("last_block_numbers", key.into(), block_number)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_last_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        default_block_number: u64,
+    ) -> crate::Result<u64> {
+        self.get_block_number(
+            "last_block_numbers",
+            key.into(),
+            default_block_number,
+        )
+    }
+}
+
+impl LeafCacheStore for SqliteStore {
+    type Output = Vec<Vec<u8>>;
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT leaf FROM leaves WHERE tree = ?1 ORDER BY leaf_index",
+        )?;
+        let leaves = stmt
+            .query_map(params![tree_name(&key)], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(leaves)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT leaf_index, leaf FROM leaves WHERE tree = ?1 ORDER BY leaf_index",
+        )?;
+        let leaves = stmt
+            .query_map(params![tree_name(&key)], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(leaves)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_leaves_count<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM leaves WHERE tree = ?1",
+            params![tree_name(&key)],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_leaves<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        leaves: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let tree = tree_name(&key);
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO leaves (tree, leaf_index, leaf) VALUES (?1, ?2, ?3)",
+            )?;
+            for (k, v) in leaves {
+                stmt.execute(params![tree, k, v])?;
+            }
+        }
+        tx.commit()?;
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+
+    fn get_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        self.get_block_number("last_deposit_block_numbers", key.into(), 0)
+    }
+
+    fn insert_last_deposit_block_number<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        self.set_block_number(
+            "last_deposit_block_numbers",
+            key.into(),
+            block_number,
+        )
+    }
+}
+
+impl EncryptedOutputCacheStore for SqliteStore {
+    type Output = Vec<Vec<u8>>;
+
+    #[tracing::instrument(skip(self))]
+    fn get_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Self::Output> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT encrypted_output FROM encrypted_outputs WHERE tree = ?1 ORDER BY output_index",
+        )?;
+        let encrypted_outputs = stmt
+            .query_map(params![tree_name(&key)], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(encrypted_outputs)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn get_encrypted_outputs_with_indices<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT output_index, encrypted_output FROM encrypted_outputs WHERE tree = ?1 ORDER BY output_index",
+        )?;
+        let encrypted_outputs = stmt
+            .query_map(params![tree_name(&key)], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(encrypted_outputs)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_encrypted_output<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        encrypted_output: &[(u32, Vec<u8>)],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let tree = tree_name(&key);
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO encrypted_outputs (tree, output_index, encrypted_output) VALUES (?1, ?2, ?3)",
+            )?;
+            for (k, v) in encrypted_output {
+                stmt.execute(params![tree, k, v])?;
+            }
+        }
+        tx.commit()?;
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+
+    fn get_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+    ) -> crate::Result<u64> {
+        self.get_block_number("last_deposit_block_numbers", key.into(), 0)
+    }
+
+    fn insert_last_deposit_block_number_for_encrypted_output<
+        K: Into<HistoryStoreKey> + Debug,
+    >(
+        &self,
+        key: K,
+        block_number: u64,
+    ) -> crate::Result<u64> {
+        self.set_block_number(
+            "last_deposit_block_numbers",
+            key.into(),
+            block_number,
+        )
+    }
+}
+
+impl NullifierCacheStore for SqliteStore {
+    #[tracing::instrument(skip(self))]
+    fn get_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT nullifier FROM nullifiers WHERE tree = ?1 ORDER BY nullifier_index",
+        )?;
+        let nullifiers = stmt
+            .query_map(params![tree_name(&key)], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(nullifiers)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn insert_nullifiers<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        nullifiers: &[Vec<u8>],
+    ) -> crate::Result<()> {
+        let key: HistoryStoreKey = key.into();
+        let tree = tree_name(&key);
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(APPEND_NULLIFIER)?;
+            for v in nullifiers {
+                stmt.execute(params![tree, v])?;
+            }
+        }
+        tx.commit()?;
+        self.cache_notifier.notify(&key);
+        Ok(())
+    }
+}
+
+impl CacheSubscriptionStore for SqliteStore {
+    #[tracing::instrument(skip(self))]
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        let query = match kind {
+            CachedItemKind::Leaves => {
+                "SELECT leaf_index, leaf FROM leaves WHERE tree = ?1 AND leaf_index >= ?2 ORDER BY leaf_index LIMIT ?3"
+            }
+            CachedItemKind::EncryptedOutputs => {
+                "SELECT output_index, encrypted_output FROM encrypted_outputs WHERE tree = ?1 AND output_index >= ?2 ORDER BY output_index LIMIT ?3"
+            }
+            CachedItemKind::Nullifiers => {
+                "SELECT nullifier_index, nullifier FROM nullifiers WHERE tree = ?1 AND nullifier_index >= ?2 ORDER BY nullifier_index LIMIT ?3"
+            }
+        };
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(query)?;
+        let tree = tree_name(&key.into());
+        let items = stmt
+            .query_map(params![tree, from, limit as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(items)
+    }
+
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64> {
+        self.cache_notifier.subscribe(key)
+    }
+}
+
+impl EventHashStore for SqliteStore {
+    #[tracing::instrument(skip(self, event))]
+    fn store_event<K: Into<HistoryStoreKey> + Debug>(
+        &self,
+        key: K,
+        event: &[u8],
+        block_number: u64,
+    ) -> crate::Result<()> {
+        let hash = ethers::utils::keccak256(event);
+        let key: HistoryStoreKey = key.into();
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO event_hashes (hash, history_key, block_number) VALUES (?1, ?2, ?3)
+             ON CONFLICT (hash) DO UPDATE SET history_key = excluded.history_key, block_number = excluded.block_number",
+            params![&hash[..], key.to_bytes(), block_number as i64],
+        )?;
+        Ok(())
+    }
+
+    fn contains_event(&self, event: &[u8]) -> crate::Result<bool> {
+        let hash = ethers::utils::keccak256(event);
+        let conn = self.conn.lock();
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM event_hashes WHERE hash = ?1",
+                params![&hash[..]],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        Ok(exists)
+    }
+
+    fn delete_event(&self, event: &[u8]) -> crate::Result<()> {
+        let hash = ethers::utils::keccak256(event);
+        let conn = self.conn.lock();
+        conn.execute(
+            "DELETE FROM event_hashes WHERE hash = ?1",
+            params![&hash[..]],
+        )?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn prune_events(&self, depth: u64) -> crate::Result<u64> {
+        let conn = self.conn.lock();
+        let pruned = conn.execute(
+            "DELETE FROM event_hashes WHERE rowid IN (
+                SELECT e.rowid FROM event_hashes e
+                JOIN last_block_numbers l ON l.key = e.history_key
+                WHERE e.block_number + ?1 < l.block_number
+            )",
+            params![depth as i64],
+        )?;
+        tracing::debug!(pruned, "pruned the old event hashes");
+        Ok(pruned as u64)
+    }
+}
+
+impl BatchStore for SqliteStore {
+    #[tracing::instrument(skip_all, fields(ops = batch.len()))]
+    fn commit_batch(&self, batch: &StoreBatch) -> crate::Result<()> {
+        let ops = batch.take_ops();
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let anchors: Vec<_> =
+            ops.iter().filter_map(BatchOp::cached_anchor).collect();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        for op in ops {
+            match op {
+                BatchOp::InsertLeaves { key, leaves } => {
+                    let tree = tree_name(&key.into());
+                    let mut stmt = tx.prepare_cached(
+                        "INSERT OR REPLACE INTO leaves (tree, leaf_index, leaf) VALUES (?1, ?2, ?3)",
+                    )?;
+                    for (k, v) in leaves {
+                        stmt.execute(params![tree, k, v])?;
+                    }
+                }
+                BatchOp::InsertEncryptedOutput {
+                    key,
+                    encrypted_output,
+                } => {
+                    let tree = tree_name(&key.into());
+                    let mut stmt = tx.prepare_cached(
+                        "INSERT OR REPLACE INTO encrypted_outputs (tree, output_index, encrypted_output) VALUES (?1, ?2, ?3)",
+                    )?;
+                    for (k, v) in encrypted_output {
+                        stmt.execute(params![tree, k, v])?;
+                    }
+                }
+                BatchOp::InsertNullifiers { key, nullifiers } => {
+                    let tree = tree_name(&key.into());
+                    let mut stmt = tx.prepare_cached(APPEND_NULLIFIER)?;
+                    for v in nullifiers {
+                        stmt.execute(params![tree, v])?;
+                    }
+                }
+                BatchOp::SetLastDepositBlockNumber { key, block_number } => {
+                    tx.execute(
+                        "INSERT INTO last_deposit_block_numbers (key, block_number) VALUES (?1, ?2)
+                         ON CONFLICT (key) DO UPDATE SET block_number = excluded.block_number",
+                        params![
+                            HistoryStoreKey::from(key).to_bytes(),
+                            block_number as i64
+                        ],
+                    )?;
+                }
+                BatchOp::StoreEvent {
+                    key,
+                    event,
+                    block_number,
+                } => {
+                    let hash = ethers::utils::keccak256(event);
+                    tx.execute(
+                        "INSERT INTO event_hashes (hash, history_key, block_number) VALUES (?1, ?2, ?3)
+                         ON CONFLICT (hash) DO UPDATE SET history_key = excluded.history_key, block_number = excluded.block_number",
+                        params![
+                            &hash[..],
+                            HistoryStoreKey::from(key).to_bytes(),
+                            block_number as i64
+                        ],
+                    )?;
+                }
+                BatchOp::SetLastBlockNumber { key, block_number } => {
+                    tx.execute(
+                        "INSERT INTO last_block_numbers (key, block_number) VALUES (?1, ?2)
+                         ON CONFLICT (key) DO UPDATE SET block_number = excluded.block_number",
+                        params![
+                            HistoryStoreKey::from(key).to_bytes(),
+                            block_number as i64
+                        ],
+                    )?;
+                }
+                #[cfg(test)]
+                BatchOp::Fail => {
+                    return Err(webb_relayer_utils::Error::Generic(
+                        "injected failure",
+                    ));
+                }
+            }
+        }
+        tx.commit()?;
+        for key in anchors {
+            self.cache_notifier.notify(&key.into());
+        }
+        Ok(())
+    }
+}
+
+impl<T> QueueStore<T> for SqliteStore
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn enqueue_item_with_options(
+        &self,
+        key: Self::Key,
+        item: T,
+        options: EnqueueOptions,
+    ) -> crate::Result<()> {
+        let item_bytes = serde_json::to_vec(&item)?;
+        let item_key = key.item_key().map(|k| k.to_vec());
+        // sqlite integers are signed, so timestamps past `i64::MAX` are
+        // stored negative and the queries below treat them as never ready.
+        let not_before = options.not_before.unwrap_or_default() as i64;
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO queue_items (queue, item_key, item, priority, not_before) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                key.queue_name(),
+                item_key,
+                item_bytes,
+                options.priority.as_u8(),
+                not_before
+            ],
+        )?;
+        drop(conn);
+        self.notifier.notify(&key.queue_name());
+        tracing::trace!("enqueue item under key = {}", key);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn dequeue_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let now = webb_relayer_utils::unix_timestamp() as i64;
+        let mut conn = self.conn.lock();
+        // the read and the removal happen in the same transaction,
+        // so the item can only be dequeued once.
+        let tx = conn.transaction()?;
+        let row = tx
+            .query_row(
+                "SELECT idx, item FROM queue_items WHERE queue = ?1 AND not_before BETWEEN 0 AND ?2 ORDER BY priority DESC, idx LIMIT 1",
+                params![key.queue_name(), now],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .optional()?;
+        let (idx, value) = match row {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let item = serde_json::from_slice(&value)?;
+        // now it is safe to remove it from the queue.
+        tx.execute("DELETE FROM queue_items WHERE idx = ?1", params![idx])?;
+        tx.commit()?;
+        Ok(Some(item))
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn peek_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let now = webb_relayer_utils::unix_timestamp() as i64;
+        let conn = self.conn.lock();
+        let value = conn
+            .query_row(
+                "SELECT item FROM queue_items WHERE queue = ?1 AND not_before BETWEEN 0 AND ?2 ORDER BY priority DESC, idx LIMIT 1",
+                params![key.queue_name(), now],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+        match value {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn has_item(&self, key: Self::Key) -> crate::Result<bool> {
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(false),
+        };
+        let conn = self.conn.lock();
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM queue_items WHERE queue = ?1 AND item_key = ?2",
+                params![key.queue_name(), &inner_key[..]],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        Ok(exists)
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn remove_item(&self, key: Self::Key) -> crate::Result<Option<T>> {
+        let inner_key = match key.item_key() {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let value = tx
+            .query_row(
+                "SELECT item FROM queue_items WHERE queue = ?1 AND item_key = ?2 ORDER BY idx DESC LIMIT 1",
+                params![key.queue_name(), &inner_key[..]],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+        match value {
+            Some(v) => {
+                tx.execute(
+                    "DELETE FROM queue_items WHERE queue = ?1 AND item_key = ?2",
+                    params![key.queue_name(), &inner_key[..]],
+                )?;
+                tx.commit()?;
+                tracing::trace!("removed item from the queue..");
+                Ok(serde_json::from_slice(&v).ok())
+            }
+            None => {
+                // not found!
+                tracing::trace!("item with key {} not found in queue", key);
+                Ok(None)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn queue_items(&self, key: Self::Key) -> crate::Result<Vec<QueuedItem<T>>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT priority, not_before, item FROM queue_items WHERE queue = ?1 ORDER BY priority DESC, idx",
+        )?;
+        let rows = stmt
+            .query_map(params![key.queue_name()], |row| {
+                Ok((
+                    row.get::<_, u8>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|(priority, not_before, bytes)| {
+                Ok(QueuedItem {
+                    priority: QueuePriority::from_u8(priority),
+                    not_before: (not_before != 0).then_some(not_before as u64),
+                    item: serde_json::from_slice(&bytes)?,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn purge_queue(&self, key: Self::Key) -> crate::Result<u64> {
+        let conn = self.conn.lock();
+        let removed = conn.execute(
+            "DELETE FROM queue_items WHERE queue = ?1",
+            params![key.queue_name()],
+        )?;
+        Ok(removed as u64)
+    }
+
+    fn queue_notifier(&self, key: Self::Key) -> Arc<Notify> {
+        self.notifier.get(&key.queue_name())
+    }
+}
+
+impl ProposalStore for SqliteStore {
+    type Proposal = SubmittedProposal;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(proposal.data_hash))
+    )]
+    fn insert_proposal(&self, proposal: Self::Proposal) -> crate::Result<()> {
+        let bytes = serde_json::to_vec(&proposal)?;
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO proposals (data_hash, proposal) VALUES (?1, ?2)",
+            params![&proposal.data_hash[..], bytes],
+        )?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn get_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let conn = self.conn.lock();
+        let value = conn
+            .query_row(
+                "SELECT proposal FROM proposals WHERE data_hash = ?1",
+                params![data_hash],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+        match value {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_proposals(&self) -> crate::Result<Vec<Self::Proposal>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT proposal FROM proposals ORDER BY data_hash",
+        )?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.iter()
+            .map(|bytes| Ok(serde_json::from_slice(bytes)?))
+            .collect()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(data_hash = %hex::encode(data_hash))
+    )]
+    fn remove_proposal(
+        &self,
+        data_hash: &[u8],
+    ) -> crate::Result<Option<Self::Proposal>> {
+        let proposal = self.get_proposal(data_hash)?;
+        match proposal {
+            Some(proposal) => {
+                let conn = self.conn.lock();
+                conn.execute(
+                    "DELETE FROM proposals WHERE data_hash = ?1",
+                    params![data_hash],
+                )?;
+                Ok(Some(proposal))
+            }
+            None => {
+                tracing::warn!(
+                    "Proposal not seen yet; not found in the proposal storage."
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<T> DeadLetterStore<T> for SqliteStore
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = SledQueueKey;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(key = %key, id = %hex::encode(letter.id))
+    )]
+    fn insert_dead_letter(
+        &self,
+        key: Self::Key,
+        letter: DeadLetter<T>,
+    ) -> crate::Result<()> {
+        let bytes = serde_json::to_vec(&letter)?;
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO dead_letters (queue, id, letter) VALUES (?1, ?2, ?3)",
+            params![key.queue_name(), &letter.id[..], bytes],
+        )?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn get_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let conn = self.conn.lock();
+        let value = conn
+            .query_row(
+                "SELECT letter FROM dead_letters WHERE queue = ?1 AND id = ?2",
+                params![key.queue_name(), id],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+        match value {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key))]
+    fn get_dead_letters(
+        &self,
+        key: Self::Key,
+    ) -> crate::Result<Vec<DeadLetter<T>>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT letter FROM dead_letters WHERE queue = ?1 ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map(params![key.queue_name()], |row| {
+                row.get::<_, Vec<u8>>(0)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.iter()
+            .map(|bytes| Ok(serde_json::from_slice(bytes)?))
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(key = %key, id = %hex::encode(id)))]
+    fn remove_dead_letter(
+        &self,
+        key: Self::Key,
+        id: &[u8],
+    ) -> crate::Result<Option<DeadLetter<T>>> {
+        let letter = self.get_dead_letter(key, id)?;
+        if letter.is_some() {
+            let conn = self.conn.lock();
+            conn.execute(
+                "DELETE FROM dead_letters WHERE queue = ?1 AND id = ?2",
+                params![key.queue_name(), id],
+            )?;
+        }
+        Ok(letter)
+    }
+}
+
+impl JobStore for SqliteStore {
+    #[tracing::instrument(skip_all, fields(id = %job.id))]
+    fn insert_job(&self, job: &RelayJob) -> crate::Result<()> {
+        let bytes = serde_json::to_vec(job)?;
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO relay_jobs (id, job) VALUES (?1, ?2)",
+            params![job.id, bytes],
+        )?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn get_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        let conn = self.conn.lock();
+        let value = conn
+            .query_row(
+                "SELECT job FROM relay_jobs WHERE id = ?1",
+                params![id],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+        match value {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn get_jobs(&self) -> crate::Result<Vec<RelayJob>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT job FROM relay_jobs")?;
+        let jobs = stmt
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .map(|v| Ok(serde_json::from_slice(&v?)?))
+            .collect::<crate::Result<_>>()?;
+        Ok(jobs)
+    }
+
+    #[tracing::instrument(skip_all, fields(id = %id))]
+    fn remove_job(&self, id: &str) -> crate::Result<Option<RelayJob>> {
+        let job = self.get_job(id)?;
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM relay_jobs WHERE id = ?1", params![id])?;
+        Ok(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webb_proposals::{TargetSystem, TypedChainId};
+
+    crate::conformance::store_conformance_tests!(
+        SqliteStore::temporary().unwrap()
+    );
+
+    #[test]
+    fn a_second_connection_should_read_the_data() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("store.db");
+        let writer = SqliteStore::open(&path).unwrap();
+        let reader = SqliteStore::open(&path).unwrap();
+        let history_store_key = (
+            TypedChainId::Evm(1),
+            TargetSystem::new_contract_address([1u8; 20]),
+        );
+        writer
+            .insert_leaves(history_store_key, &[(0, vec![1]), (1, vec![2])])
+            .unwrap();
+        assert_eq!(
+            reader.get_leaves(history_store_key).unwrap(),
+            vec![vec![1], vec![2]]
+        );
+    }
+
+    #[test]
+    fn writes_should_wait_for_other_connections() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("store.db");
+        let store = SqliteStore::open(&path).unwrap();
+        // another process holding the write lock for a while.
+        let other = Connection::open(&path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE").unwrap();
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            other.execute_batch("COMMIT").unwrap();
+        });
+        let history_store_key = (
+            TypedChainId::Evm(1),
+            TargetSystem::new_contract_address([1u8; 20]),
+        );
+        store
+            .insert_leaves(history_store_key, &[(0, vec![1])])
+            .unwrap();
+        holder.join().unwrap();
+        assert_eq!(store.get_leaves(history_store_key).unwrap(), vec![vec![1]]);
+    }
+
+    #[test]
+    fn event_hashes_without_block_numbers_should_be_dropped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("store.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE event_hashes (hash BLOB PRIMARY KEY NOT NULL);",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO event_hashes (hash) VALUES (?1)",
+                params![&ethers::utils::keccak256(b"event")[..]],
+            )
+            .unwrap();
+        }
+        let store = SqliteStore::open(&path).unwrap();
+        assert!(!store.contains_event(b"event").unwrap());
+        let history_store_key = (
+            TypedChainId::Evm(1),
+            TargetSystem::new_contract_address([1u8; 20]),
+        );
+        store.store_event(history_store_key, b"event", 1).unwrap();
+        assert!(store.contains_event(b"event").unwrap());
+    }
+}
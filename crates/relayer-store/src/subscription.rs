@@ -0,0 +1,143 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Subscriptions to the items cached for the anchors.
+//!
+//! The leaves, encrypted outputs and nullifiers of an anchor are streamed to
+//! its subscribers as the watchers store them. A subscriber first gets a
+//! receiver from the [`CacheNotifier`] of the store, then reads the items it
+//! is missing, and reads again every time the receiver changes, so nothing
+//! stored in between is missed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::watch;
+use webb_proposals::ResourceId;
+
+use crate::HistoryStoreKey;
+
+pub use webb_relayer_handler_utils::CachedItemKind;
+
+/// A store that can stream the items it caches for the anchors.
+pub trait CacheSubscriptionStore {
+    /// Get up to `limit` items of the given kind of an anchor, with an index
+    /// of at least `from`, together with their indices, ordered by index.
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>>;
+
+    /// Returns a receiver that changes every time items are stored for the
+    /// given anchor.
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64>;
+}
+
+impl<S> CacheSubscriptionStore for Arc<S>
+where
+    S: CacheSubscriptionStore,
+{
+    fn get_cached_items(
+        &self,
+        kind: CachedItemKind,
+        key: ResourceId,
+        from: u32,
+        limit: usize,
+    ) -> crate::Result<Vec<(u32, Vec<u8>)>> {
+        S::get_cached_items(self, kind, key, from, limit)
+    }
+
+    fn cache_updates(&self, key: ResourceId) -> watch::Receiver<u64> {
+        S::cache_updates(self, key)
+    }
+}
+
+/// Wakes up the subscribers of an anchor when items are stored for it.
+///
+/// There is one channel per subscribed anchor, holding the number of times
+/// it was updated. Like the [`QueueNotifier`](crate::QueueNotifier), it only
+/// reaches the subscribers of the same process, so they should read the
+/// store again from time to time for the items stored by another process.
+#[derive(Clone, Default)]
+pub struct CacheNotifier {
+    anchors: Arc<Mutex<HashMap<Vec<u8>, watch::Sender<u64>>>>,
+}
+
+impl std::fmt::Debug for CacheNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheNotifier").finish()
+    }
+}
+
+impl CacheNotifier {
+    /// Returns a receiver of the updates of the given anchor.
+    ///
+    /// The channels of the anchors nobody follows anymore are dropped.
+    pub fn subscribe(&self, key: ResourceId) -> watch::Receiver<u64> {
+        let mut anchors = self.anchors.lock();
+        anchors.retain(|_, updates| updates.receiver_count() > 0);
+        anchors
+            .entry(HistoryStoreKey::from(key).to_bytes())
+            .or_insert_with(|| watch::channel(0).0)
+            .subscribe()
+    }
+
+    /// Tells the subscribers of the given anchor that items were stored
+    /// for it.
+    pub fn notify(&self, key: &HistoryStoreKey) {
+        let key = key.to_bytes();
+        let mut anchors = self.anchors.lock();
+        if let Some(updates) = anchors.get(&key) {
+            if updates.receiver_count() == 0 {
+                anchors.remove(&key);
+            } else {
+                updates.send_modify(|count| *count += 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use webb_proposals::{TargetSystem, TypedChainId};
+
+    use super::*;
+
+    fn key(chain_id: u32) -> ResourceId {
+        ResourceId::new(
+            TargetSystem::new_contract_address([1u8; 20]),
+            TypedChainId::Evm(chain_id),
+        )
+    }
+
+    #[test]
+    fn unfollowed_anchors_should_be_dropped() {
+        let notifier = CacheNotifier::default();
+        let updates = notifier.subscribe(key(1));
+        let other = notifier.subscribe(key(2));
+        assert_eq!(notifier.anchors.lock().len(), 2);
+
+        drop(updates);
+        notifier.notify(&key(1).into());
+        assert_eq!(notifier.anchors.lock().len(), 1);
+
+        drop(other);
+        let _updates = notifier.subscribe(key(1));
+        assert_eq!(notifier.anchors.lock().len(), 1);
+    }
+}
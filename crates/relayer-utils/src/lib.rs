@@ -82,6 +82,9 @@ pub enum Error {
     SledTransaction(
         #[from] sled::transaction::TransactionError<std::io::Error>,
     ),
+    /// SQLite database error.
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
     /// Generic error.
     #[error("{}", _0)]
     Generic(&'static str),
@@ -106,6 +109,18 @@ pub enum Error {
         /// The chain id of the node.
         chain_id: String,
     },
+    /// The store was written by a newer version of the relayer.
+    #[error(
+        "Store schema version {} is newer than the supported version {}",
+        found,
+        supported
+    )]
+    UnsupportedStoreVersion {
+        /// The schema version found in the store.
+        found: u32,
+        /// The latest schema version supported by this relayer.
+        supported: u32,
+    },
     /// Missing Secrets in the config, either Private key, SURI, ...etc.
     #[error("Missing required private-key or SURI in the config")]
     MissingSecrets,
@@ -122,3 +137,11 @@ pub enum Error {
 
 /// A type alias for the result for webb relayer, that uses the `Error` enum.
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Returns the current unix timestamp, in seconds.
+pub fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use prometheus::core::{AtomicF64, GenericCounter, GenericGauge};
-use prometheus::{register_counter, register_gauge, Encoder, TextEncoder};
+use prometheus::{
+    register_counter, register_counter_vec, register_gauge, register_gauge_vec,
+    CounterVec, Encoder, GaugeVec, TextEncoder,
+};
 
 /// A struct definition for collecting metrics in the relayer
 #[derive(Debug, Clone)]
@@ -26,8 +29,18 @@ pub struct Metrics {
     pub total_transaction_made: GenericCounter<AtomicF64>,
     /// Anchor update proposals proposed by relayer
     pub anchor_update_proposals: GenericCounter<AtomicF64>,
+    /// Governance proposals (token, fee, limits and resource id updates) proposed by relayer
+    pub governance_proposals: GenericCounter<AtomicF64>,
     /// No of proposal signed by dkg/mocked
     pub proposals_signed: GenericCounter<AtomicF64>,
+    /// Signed proposals dropped since no bridge is configured for their target chain
+    pub proposals_dropped_no_handler: GenericCounter<AtomicF64>,
+    /// Proposals sent to the DKG for signing
+    pub proposals_sent_to_dkg: GenericCounter<AtomicF64>,
+    /// Proposals not sent to the DKG again since they were already sent
+    pub proposals_deduplicated: GenericCounter<AtomicF64>,
+    /// Transactions moved to the dead letters by the transaction queues
+    pub dead_letters: GenericCounter<AtomicF64>,
     /// Proposals dequeued and executed through transaction queue
     pub proposals_processed_tx_queue: GenericCounter<AtomicF64>,
     /// Proposals dequeued and executed through transaction queue
@@ -46,6 +59,14 @@ pub struct Metrics {
     pub gas_spent: GenericCounter<AtomicF64>,
     /// Total amount of data stored metric
     pub total_amount_of_data_stored: GenericGauge<AtomicF64>,
+    /// Number of entries of every tree of the store
+    pub store_tree_entries: GaugeVec,
+    /// Event hashes pruned from the store
+    pub pruned_event_hashes: GenericCounter<AtomicF64>,
+    /// Leaves fetched again after they were found missing from the cache
+    pub refetched_leaves: GenericCounter<AtomicF64>,
+    /// Requests rejected by the rate limits, for every group of routes
+    pub rate_limited_requests: CounterVec,
 }
 
 impl Metrics {
@@ -72,11 +93,36 @@ impl Metrics {
             "The total number of anchor update proposal proposed by relayer",
         );
 
+        let governance_proposals_counter = register_counter!(
+            "governance_proposals",
+            "The total number of governance proposals (token, fee, limits and resource id updates) proposed by relayer",
+        );
+
+        let dead_letters_counter = register_counter!(
+            "dead_letters",
+            "The total number of transactions moved to the dead letters by the transaction queues",
+        );
+
         let proposals_signed_counter = register_counter!(
             "proposals_signed",
             "The total number of proposal signed by dkg/mocked backend",
         );
 
+        let proposals_dropped_no_handler_counter = register_counter!(
+            "proposals_dropped_no_handler",
+            "The total number of signed proposals dropped since no bridge is configured for the target chain",
+        );
+
+        let proposals_sent_to_dkg_counter = register_counter!(
+            "proposals_sent_to_dkg",
+            "The total number of proposals sent to the DKG for signing",
+        );
+
+        let proposals_deduplicated_counter = register_counter!(
+            "proposals_deduplicated",
+            "The total number of proposals not sent to the DKG again since they were already sent",
+        );
+
         let proposals_processed_tx_queue_counter = register_counter!(
             "proposals_processed_tx_queue",
             "Total number of signed proposals processed by transaction queue",
@@ -120,12 +166,40 @@ impl Metrics {
             "The Total number of data stored",
         );
 
+        let store_tree_entries_gauge = register_gauge_vec!(
+            "store_tree_entries",
+            "The number of entries of every tree of the store",
+            &["tree"]
+        );
+
+        let pruned_event_hashes_counter = register_counter!(
+            "pruned_event_hashes",
+            "The total number of event hashes pruned from the store",
+        );
+
+        let refetched_leaves_counter = register_counter!(
+            "refetched_leaves",
+            "The total number of leaves fetched again after they were found missing from the cache",
+        );
+
+        let rate_limited_requests_counter = register_counter_vec!(
+            "rate_limited_requests",
+            "The total number of requests rejected by the rate limits",
+            &["group"]
+        );
+
         Self {
             bridge_watcher_back_off: bridge_watcher_back_off_counter.unwrap(),
             total_active_relayer: total_active_relayer_counter.unwrap(),
             total_transaction_made: total_transaction_made_counter.unwrap(),
             anchor_update_proposals: anchor_update_proposals_counter.unwrap(),
+            governance_proposals: governance_proposals_counter.unwrap(),
             proposals_signed: proposals_signed_counter.unwrap(),
+            dead_letters: dead_letters_counter.unwrap(),
+            proposals_dropped_no_handler: proposals_dropped_no_handler_counter
+                .unwrap(),
+            proposals_sent_to_dkg: proposals_sent_to_dkg_counter.unwrap(),
+            proposals_deduplicated: proposals_deduplicated_counter.unwrap(),
             proposals_processed_tx_queue: proposals_processed_tx_queue_counter
                 .unwrap(),
             proposals_processed_substrate_tx_queue:
@@ -142,6 +216,10 @@ impl Metrics {
             gas_spent: gas_spent_counter.unwrap(),
             total_amount_of_data_stored: total_amount_of_data_stored_counter
                 .unwrap(),
+            store_tree_entries: store_tree_entries_gauge.unwrap(),
+            pruned_event_hashes: pruned_event_hashes_counter.unwrap(),
+            refetched_leaves: refetched_leaves_counter.unwrap(),
+            rate_limited_requests: rate_limited_requests_counter.unwrap(),
         }
     }
 
@@ -43,4 +43,7 @@ pub enum Kind {
     /// Relayer Encrypted Output Store state on a specific chain/node.
     #[display(fmt = "encrypted_outputs_store")]
     EncryptedOutputStore,
+    /// Smart Anchor Updates decisions for a specific anchor edge.
+    #[display(fmt = "smart_anchor_updates")]
+    SmartAnchorUpdates,
 }
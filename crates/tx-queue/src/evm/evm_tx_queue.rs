@@ -17,20 +17,31 @@ use std::time::Duration;
 
 use ethereum_types::{H256, U64};
 use futures::TryFutureExt;
-use rand::Rng;
 use webb::evm::ethers::core::types::transaction::eip2718::TypedTransaction;
 use webb::evm::ethers::middleware::SignerMiddleware;
 use webb::evm::ethers::providers::Middleware;
 
+use webb_proposals::TypedChainId;
 use webb_relayer_context::RelayerContext;
+use webb_relayer_store::dead_letter::{
+    clear_dead_letter, move_to_dead_letters,
+};
 use webb_relayer_store::sled::SledQueueKey;
-use webb_relayer_store::QueueStore;
+use webb_relayer_store::{
+    wait_for_items, DeadLetterStore, EnqueueOptions, QueuePriority, QueueStore,
+};
 use webb_relayer_utils::clickable_link::ClickableLink;
 
+/// How long (in seconds) a transaction dropped from the mempool waits before
+/// it is retried.
+const DROPPED_TX_RETRY_DELAY: u64 = 30;
+
 /// The TxQueue stores transaction requests so the relayer can process them later.
 /// This prevents issues such as creating transactions with the same nonce.
-/// Randomized sleep intervals are used to prevent relayers from submitting
-/// the same transaction.
+/// The queue sleeps until a transaction is enqueued, or until it is polled again
+/// after a random interval. A randomized interval is also waited after a wake up
+/// and between two transactions, to prevent relayers from submitting the same
+/// transaction.
 #[derive(Clone)]
 pub struct TxQueue<S: QueueStore<TypedTransaction>> {
     ctx: RelayerContext,
@@ -40,7 +51,8 @@ pub struct TxQueue<S: QueueStore<TypedTransaction>> {
 
 impl<S> TxQueue<S>
 where
-    S: QueueStore<TypedTransaction, Key = SledQueueKey>,
+    S: QueueStore<TypedTransaction, Key = SledQueueKey>
+        + DeadLetterStore<TypedTransaction, Key = SledQueueKey>,
 {
     /// Creates a new TxQueue instance.
     ///
@@ -50,7 +62,7 @@ where
     ///
     /// * `ctx` - RelayContext reference that holds the configuration
     /// * `chain_id` - The chainId that this queue is for
-    /// * `store` - [Sled](https://sled.rs)-based database store
+    /// * `store` - database store, also keeping the transactions that failed
     pub fn new(ctx: RelayerContext, chain_id: String, store: Arc<S>) -> Self {
         Self {
             ctx,
@@ -106,8 +118,24 @@ where
         // gas spent metric
         metrics.gas_spent.inc_by(gas_price.as_u64() as f64);
 
+        let notify = QueueStore::<TypedTransaction>::queue_notifier(
+            &store,
+            SledQueueKey::from_evm_chain_id(chain_id),
+        );
+        let controls = self.ctx.controls.clone();
         let task = || async {
             loop {
+                // the queue was paused from the admin API, keep the
+                // transactions in the queue until it gets resumed.
+                if controls.is_queue_paused(TypedChainId::Evm(chain_id)) {
+                    let max_sleep_interval =
+                        chain_config.tx_queue.max_sleep_interval;
+                    tokio::time::sleep(Duration::from_millis(
+                        max_sleep_interval,
+                    ))
+                    .await;
+                    continue;
+                }
                 tracing::trace!("Checking for any txs in the queue ...");
                 let maybe_tx = store
                     .dequeue_item(SledQueueKey::from_evm_chain_id(chain_id))?;
@@ -145,6 +173,21 @@ where
                                 dry_run = "failed",
                                 %tx_hash,
                             );
+                            // keep the failed transaction around, so it
+                            // can be inspected and retried later.
+                            let letter = move_to_dead_letters(
+                                &store,
+                                SledQueueKey::from_evm_chain_id(chain_id),
+                                raw_tx,
+                                format!("dry run failed: {err}"),
+                            )?;
+                            metrics.dead_letters.inc();
+                            tracing::warn!(
+                                id = %hex::encode(letter.id),
+                                attempts = letter.attempts,
+                                "Tx 0x{:x} moved to the dead letters",
+                                tx_hash,
+                            );
                             continue; // keep going.
                         }
                     }
@@ -236,6 +279,12 @@ where
                                     tx_hash_string,
                                 );
                             }
+                            // a retried transaction is no longer dead.
+                            clear_dead_letter(
+                                &store,
+                                SledQueueKey::from_evm_chain_id(chain_id),
+                                &raw_tx,
+                            )?;
                             // metrics for  transaction processed by evm tx queue
                             metrics.proposals_processed_tx_queue.inc();
                             metrics.proposals_processed_evm_tx_queue.inc();
@@ -260,10 +309,19 @@ where
                                 "Tx {} Dropped from Mempool!!",
                                 tx_hash_string
                             );
-                            // enquing the tx again
-                            store.enqueue_item(
+                            // enquing the tx again, ahead of the new ones
+                            // but only after giving the node some time.
+                            let options = EnqueueOptions::with_priority(
+                                QueuePriority::High,
+                            )
+                            .not_before(
+                                webb_relayer_utils::unix_timestamp()
+                                    + DROPPED_TX_RETRY_DELAY,
+                            );
+                            store.enqueue_item_with_options(
                                 SledQueueKey::from_evm_chain_id(chain_id),
                                 raw_tx,
+                                options,
                             )?;
                         }
                         Err(e) => {
@@ -300,14 +358,39 @@ where
                             );
                         }
                     };
+                    // sleep for a random amount of time before the next
+                    // transaction.
+                    let delay = crate::random_delay(
+                        chain_config.tx_queue.max_sleep_interval,
+                    );
+                    tracing::trace!(
+                        "next queue round after {} ms",
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                } else {
+                    // the queue is empty, wait for the next transaction, or
+                    // for the next poll of the queue.
+                    let max_sleep_interval =
+                        chain_config.tx_queue.max_sleep_interval;
+                    // the polls are randomized too, so the relayers missing
+                    // a notification do not all poll at the same time.
+                    let notified = wait_for_items(
+                        &notify,
+                        crate::random_delay(max_sleep_interval),
+                    )
+                    .await;
+                    if notified {
+                        // sleep for a random amount of time, so the relayers
+                        // do not all submit the same transaction.
+                        let delay = crate::random_delay(max_sleep_interval);
+                        tracing::trace!(
+                            "next queue round after {} ms",
+                            delay.as_millis()
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
                 }
-                // sleep for a random amount of time.
-                let max_sleep_interval =
-                    chain_config.tx_queue.max_sleep_interval;
-                let s =
-                    rand::thread_rng().gen_range(1_000..=max_sleep_interval);
-                tracing::trace!("next queue round after {} ms", s);
-                tokio::time::sleep(Duration::from_millis(s)).await;
             }
         };
         // transaction queue backoff metric
@@ -18,3 +18,16 @@ pub mod evm;
 /// Substrate Transaction Queue.
 #[cfg(feature = "substrate")]
 pub mod substrate;
+
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Returns a random delay between `1_000` and `max_sleep_interval`
+/// milliseconds (or `max_sleep_interval` when lower), taken between two
+/// transactions so that the relayers do not all submit the same one.
+pub(crate) fn random_delay(max_sleep_interval: u64) -> Duration {
+    let millis = rand::thread_rng()
+        .gen_range(max_sleep_interval.min(1_000)..=max_sleep_interval);
+    Duration::from_millis(millis)
+}
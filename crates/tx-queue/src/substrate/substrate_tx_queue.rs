@@ -14,10 +14,13 @@
 
 use futures::StreamExt;
 use futures::TryFutureExt;
-use rand::Rng;
+use webb_proposals::TypedChainId;
 use webb_relayer_context::RelayerContext;
+use webb_relayer_store::dead_letter::{
+    clear_dead_letter, move_to_dead_letters,
+};
 use webb_relayer_store::sled::SledQueueKey;
-use webb_relayer_store::QueueStore;
+use webb_relayer_store::{wait_for_items, DeadLetterStore, QueueStore};
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -35,8 +38,10 @@ use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
 
 /// The SubstrateTxQueue stores transaction call params in bytes so the relayer can process them later.
 /// This prevents issues such as creating transactions with the same nonce.
-/// Randomized sleep intervals are used to prevent relayers from submitting
-/// the same transaction.
+/// The queue sleeps until a transaction is enqueued, or until it is polled again
+/// after a random interval. A randomized interval is also waited after a wake up
+/// and between two transactions, to prevent relayers from submitting the same
+/// transaction.
 #[derive(Clone)]
 pub struct SubstrateTxQueue<'a, S>
 where
@@ -49,7 +54,8 @@ where
 }
 impl<'a, S> SubstrateTxQueue<'a, S>
 where
-    S: QueueStore<WebbDynamicTxPayload<'a>, Key = SledQueueKey>,
+    S: QueueStore<WebbDynamicTxPayload<'a>, Key = SledQueueKey>
+        + DeadLetterStore<WebbDynamicTxPayload<'a>, Key = SledQueueKey>,
 {
     /// Creates a new SubstrateTxQueue instance.
     ///
@@ -59,7 +65,7 @@ where
     ///
     /// * `ctx` - RelayContext reference that holds the configuration
     /// * `chain_name` - The name of the chain that this queue is for
-    /// * `store` - [Sled](https://sled.rs)-based database store
+    /// * `store` - database store, also keeping the transactions that failed
     pub fn new(ctx: RelayerContext, chain_id: u32, store: Arc<S>) -> Self {
         Self {
             ctx,
@@ -115,14 +121,32 @@ where
         );
 
         let metrics = self.ctx.metrics.clone();
+        let notify = QueueStore::<WebbDynamicTxPayload<'a>>::queue_notifier(
+            &store,
+            SledQueueKey::from_substrate_chain_id(chain_id),
+        );
+        let controls = self.ctx.controls.clone();
         let task = || async {
             loop {
+                // the queue was paused from the admin API, keep the
+                // transactions in the queue until it gets resumed.
+                if controls.is_queue_paused(TypedChainId::Substrate(chain_id)) {
+                    let max_sleep_interval =
+                        chain_config.tx_queue.max_sleep_interval;
+                    tokio::time::sleep(Duration::from_millis(
+                        max_sleep_interval,
+                    ))
+                    .await;
+                    continue;
+                }
                 tracing::trace!("Checking for any txs in the queue ...");
                 // dequeue transaction call data. This are call params stored as bytes
                 let maybe_call_data = store.dequeue_item(
                     SledQueueKey::from_substrate_chain_id(chain_id),
                 )?;
                 if let Some(payload) = maybe_call_data {
+                    // kept to move it to the dead letters if it fails.
+                    let item = payload.clone();
                     let dynamic_tx_payload = subxt::dynamic::tx(
                         payload.pallet_name,
                         payload.call_name,
@@ -162,6 +186,20 @@ where
                                 error = %err,
                                 dry_run = "failed"
                             );
+                            // keep the failed transaction around, so it
+                            // can be inspected and retried later.
+                            let letter = move_to_dead_letters(
+                                &store,
+                                SledQueueKey::from_substrate_chain_id(chain_id),
+                                item,
+                                format!("dry run failed: {err}"),
+                            )?;
+                            metrics.dead_letters.inc();
+                            tracing::warn!(
+                                id = %hex::encode(letter.id),
+                                attempts = letter.attempts,
+                                "Tx moved to the dead letters",
+                            );
                             continue; // keep going.
                         }
                     }
@@ -264,6 +302,14 @@ where
                                     status = "Finalized",
                                     finalized = true,
                                 );
+                                // a retried transaction is no longer dead.
+                                clear_dead_letter(
+                                    &store,
+                                    SledQueueKey::from_substrate_chain_id(
+                                        chain_id,
+                                    ),
+                                    &item,
+                                )?;
                                 // metrics for proposal processed by substrate tx queue
                                 metrics.proposals_processed_tx_queue.inc();
                                 metrics
@@ -303,14 +349,39 @@ where
                             }
                         }
                     }
+                    // sleep for a random amount of time before the next
+                    // transaction.
+                    let delay = crate::random_delay(
+                        chain_config.tx_queue.max_sleep_interval,
+                    );
+                    tracing::trace!(
+                        "next queue round after {} ms",
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                } else {
+                    // the queue is empty, wait for the next transaction, or
+                    // for the next poll of the queue.
+                    let max_sleep_interval =
+                        chain_config.tx_queue.max_sleep_interval;
+                    // the polls are randomized too, so the relayers missing
+                    // a notification do not all poll at the same time.
+                    let notified = wait_for_items(
+                        &notify,
+                        crate::random_delay(max_sleep_interval),
+                    )
+                    .await;
+                    if notified {
+                        // sleep for a random amount of time, so the relayers
+                        // do not all submit the same transaction.
+                        let delay = crate::random_delay(max_sleep_interval);
+                        tracing::trace!(
+                            "next queue round after {} ms",
+                            delay.as_millis()
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
                 }
-                // sleep for a random amount of time.
-                let max_sleep_interval =
-                    chain_config.tx_queue.max_sleep_interval;
-                let s =
-                    rand::thread_rng().gen_range(1_000..=max_sleep_interval);
-                tracing::trace!("next queue round after {} ms", s);
-                tokio::time::sleep(Duration::from_millis(s)).await;
             }
         };
         // transaction queue backoff metric
@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use webb::evm::ethers::types::U256;
 
 /// Contains data that is relayed to the Mixers
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MixerRelayTransaction<Id, P, E, I, B> {
     /// one of the supported chains of this relayer
@@ -68,7 +69,7 @@ pub struct AnchorRelayTransaction<Id, P, R, E, I, B> {
 }
 
 /// Proof data object for VAnchor proofs on any chain
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProofData<P, R, E> {
     /// Encoded proof
@@ -86,7 +87,7 @@ pub struct ProofData<P, R, E> {
 }
 
 /// External data for the VAnchor on any chain.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtData<E, I, B, A, T> {
     /// Recipient identifier of the withdrawn funds
@@ -108,7 +109,7 @@ pub struct ExtData<E, I, B, A, T> {
 }
 
 /// Contains data that is relayed to the VAnchors
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VAnchorRelayTransaction<Id, P, R, E, I, B, A, T> {
     /// one of the supported chains of this relayer
@@ -18,8 +18,11 @@ use webb::substrate::dkg_runtime::api as RuntimeApi;
 use webb::substrate::dkg_runtime::{self, api::dkg};
 use webb::substrate::subxt::{self, OnlineClient};
 use webb_event_watcher_traits::SubstrateEventWatcher;
-use webb_relayer_store::sled::{SledQueueKey, SledStore};
-use webb_relayer_store::{BridgeCommand, BridgeKey, QueueStore};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{
+    BridgeCommand, BridgeKey, EnqueueOptions, QueueStore,
+};
 use webb_relayer_utils::metric;
 
 use webb_event_watcher_traits::substrate::BlockNumberOf;
@@ -50,7 +53,7 @@ impl SubstrateEventWatcher for DKGGovernorWatcher {
     // when the DKG public key signature changes, we know the DKG is changed.
     type FilteredEvent = dkg::events::PublicKeySignatureChanged;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_event(
         &self,
@@ -113,13 +116,16 @@ impl SubstrateEventWatcher for DKGGovernorWatcher {
                 nonce = %refresh_nonce,
                 signature = %hex::encode(&event.pub_key_sig),
             );
-            store.enqueue_item(
+            let cmd = BridgeCommand::TransferOwnershipWithSignature {
+                public_key: public_key_uncompressed.clone(),
+                nonce: refresh_nonce,
+                signature: event.pub_key_sig.clone(),
+            };
+            let options = EnqueueOptions::with_priority(cmd.priority());
+            store.enqueue_item_with_options(
                 SledQueueKey::from_bridge_key(bridge_key),
-                BridgeCommand::TransferOwnershipWithSignature {
-                    public_key: public_key_uncompressed.clone(),
-                    nonce: refresh_nonce,
-                    signature: event.pub_key_sig.clone(),
-                },
+                cmd,
+                options,
             )?;
         }
         Ok(())
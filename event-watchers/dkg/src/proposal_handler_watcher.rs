@@ -12,23 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use webb::evm::ethers::utils;
 use webb::substrate::dkg_runtime;
 use webb::substrate::dkg_runtime::api::dkg_proposal_handler;
 use webb::substrate::dkg_runtime::api::runtime_types::webb_proposals::header::TypedChainId;
 use webb::substrate::subxt::{self, OnlineClient};
 use webb_event_watcher_traits::SubstrateEventWatcher;
-use webb_relayer_store::sled::{SledQueueKey, SledStore};
-use webb_relayer_store::{BridgeCommand, BridgeKey, QueueStore};
+use webb_relayer_config::evm::Contract;
+use webb_relayer_config::substrate::Pallet;
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{
+    BridgeCommand, BridgeKey, EnqueueOptions, ProposalStatus, ProposalStore,
+    QueueStore,
+};
 use webb_relayer_utils::metric;
 
 use webb_event_watcher_traits::substrate::BlockNumberOf;
 
 /// A ProposalHandler watcher for the DKG Substrate runtime.
 /// It watches for the `ProposalSigned` event and sends the proposal to the signature bridge.
-#[derive(Copy, Clone, Debug, Default)]
-pub struct ProposalHandlerWatcher;
+#[derive(Clone, Debug)]
+pub struct ProposalHandlerWatcher {
+    /// The bridges configured on this relayer, signed proposals targeting
+    /// any other chain are dropped.
+    bridges: HashSet<BridgeKey>,
+}
+
+impl ProposalHandlerWatcher {
+    pub fn new(webb_config: &webb_relayer_config::WebbRelayerConfig) -> Self {
+        Self {
+            bridges: configured_bridges(webb_config),
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl SubstrateEventWatcher for ProposalHandlerWatcher {
@@ -42,7 +62,7 @@ impl SubstrateEventWatcher for ProposalHandlerWatcher {
 
     type FilteredEvent = dkg_proposal_handler::events::ProposalSigned;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_event(
         &self,
@@ -61,67 +81,47 @@ impl SubstrateEventWatcher for ProposalHandlerWatcher {
             ?event.key,
             ?block_number,
         );
-        let maybe_bridge_key = match event.target_chain {
-            TypedChainId::None => {
-                tracing::debug!(
-                    "Received `ProposalSigned` Event with no chain id, ignoring",
-                );
-                None
-            }
-            TypedChainId::Evm(id) => {
-                tracing::trace!(
-                    "`ProposalSigned` Event with evm chain id : {}",
-                    id
-                );
-                Some(BridgeKey::new(webb_proposals::TypedChainId::Evm(id)))
-            }
-            TypedChainId::Substrate(id) => {
-                tracing::trace!(
-                    "`ProposalSigned` Event with substrate chain id : {}",
-                    id
-                );
-                Some(BridgeKey::new(webb_proposals::TypedChainId::Substrate(
-                    id,
-                )))
-            }
-            TypedChainId::PolkadotParachain(_) => {
-                tracing::warn!("Unhandled `ProposalSigned` Event with polkadot parachain chain id");
-                None
-            }
-            TypedChainId::KusamaParachain(_) => {
-                tracing::warn!("Unhandled `ProposalSigned` Event with kusama parachain chain id");
-                None
-            }
-            TypedChainId::RococoParachain(_) => {
-                tracing::warn!("Unhandled `ProposalSigned` Event with rococo parachain chain id");
-                None
-            }
-            TypedChainId::Cosmos(_) => {
-                tracing::warn!(
-                    "Unhandled `ProposalSigned` Event with cosmos chain id"
-                );
-                None
-            }
-            TypedChainId::Solana(_) => {
-                tracing::warn!(
-                    "Unhandled `ProposalSigned` Event with solana chain id"
-                );
-                None
-            }
-            TypedChainId::Ink(_) => {
-                tracing::warn!(
-                    "Unhandled `ProposalSigned` Event with Ink chain id"
-                );
-                None
+        // mark the proposal as signed, if it was submitted by this relayer.
+        let data_hash = utils::keccak256(&event.data);
+        if let Some(mut proposal) = store.get_proposal(&data_hash)? {
+            if proposal.status != ProposalStatus::Signed {
+                proposal.mark_signed(webb_relayer_utils::unix_timestamp());
+                store.insert_proposal(proposal)?;
             }
-        };
+        }
+        self.signal_bridge(
+            &store,
+            &event.target_chain,
+            event.data,
+            event.signature,
+            &metrics,
+        )
+    }
+}
+
+impl ProposalHandlerWatcher {
+    /// Enqueues the execution of a signed proposal on the bridge of its
+    /// target chain, the proposal is dropped (and counted as such) if no
+    /// bridge is configured for that chain.
+    fn signal_bridge(
+        &self,
+        store: &AnyStore,
+        target_chain: &TypedChainId,
+        data: Vec<u8>,
+        signature: Vec<u8>,
+        metrics: &metric::Metrics,
+    ) -> webb_relayer_utils::Result<()> {
+        let maybe_bridge_key = bridge_key(target_chain);
         tracing::debug!(?maybe_bridge_key, "Sending Proposal to the bridge");
         // now we just signal the bridge with the proposal.
         let bridge_key = match maybe_bridge_key {
-            Some(bridge_key) => bridge_key,
-            None => {
+            Some(bridge_key) if self.bridges.contains(&bridge_key) => {
+                bridge_key
+            }
+            _ => {
+                metrics.proposals_dropped_no_handler.inc();
                 tracing::warn!(
-                    ?event.target_chain,
+                    ?target_chain,
                     "No bridge configured for that chain, skipping",
                 );
                 return Ok(());
@@ -129,7 +129,7 @@ impl SubstrateEventWatcher for ProposalHandlerWatcher {
         };
         tracing::debug!(
             %bridge_key,
-            proposal = ?event,
+            data = ?hex::encode(&data),
             "Signaling Signature Bridge to execute proposal",
         );
         tracing::event!(
@@ -138,18 +138,262 @@ impl SubstrateEventWatcher for ProposalHandlerWatcher {
             kind = %webb_relayer_utils::probe::Kind::SigningBackend,
             backend = "DKG",
             signal_bridge = %bridge_key,
-            data = ?hex::encode(&event.data),
-            signature = ?hex::encode(&event.signature),
+            data = ?hex::encode(&data),
+            signature = ?hex::encode(&signature),
         );
         // Proposal signed metric
         metrics.proposals_signed.inc();
-        store.enqueue_item(
+        let cmd =
+            BridgeCommand::ExecuteProposalWithSignature { data, signature };
+        let options = EnqueueOptions::with_priority(cmd.priority());
+        store.enqueue_item_with_options(
             SledQueueKey::from_bridge_key(bridge_key),
-            BridgeCommand::ExecuteProposalWithSignature {
-                data: event.data.clone(),
-                signature: event.signature,
-            },
+            cmd,
+            options,
         )?;
         Ok(())
     }
 }
+
+/// Returns the key of the bridge executing the proposals signed for the
+/// given target chain, proposals without a target chain have none.
+fn bridge_key(target_chain: &TypedChainId) -> Option<BridgeKey> {
+    use webb_proposals::TypedChainId as Target;
+    let typed_chain_id = match *target_chain {
+        TypedChainId::None => {
+            tracing::debug!(
+                "Received `ProposalSigned` Event with no chain id, ignoring",
+            );
+            return None;
+        }
+        TypedChainId::Evm(id) => Target::Evm(id),
+        TypedChainId::Substrate(id) => Target::Substrate(id),
+        TypedChainId::PolkadotParachain(id) => Target::PolkadotParachain(id),
+        TypedChainId::KusamaParachain(id) => Target::KusamaParachain(id),
+        TypedChainId::RococoParachain(id) => Target::RococoParachain(id),
+        TypedChainId::Cosmos(id) => Target::Cosmos(id),
+        TypedChainId::Solana(id) => Target::Solana(id),
+        TypedChainId::Ink(id) => Target::Ink(id),
+    };
+    tracing::trace!(?typed_chain_id, "`ProposalSigned` Event target chain");
+    Some(BridgeKey::new(typed_chain_id))
+}
+
+/// Collects the bridge keys of every signature bridge configured on this relayer.
+fn configured_bridges(
+    webb_config: &webb_relayer_config::WebbRelayerConfig,
+) -> HashSet<BridgeKey> {
+    let evm_bridges = webb_config
+        .evm
+        .values()
+        .filter(|c| c.enabled)
+        .filter(|c| {
+            c.contracts
+                .iter()
+                .any(|c| matches!(c, Contract::SignatureBridge(_)))
+        })
+        .map(|c| webb_proposals::TypedChainId::Evm(c.chain_id));
+    let substrate_bridges = webb_config
+        .substrate
+        .values()
+        .filter(|c| c.enabled)
+        .flat_map(|c| {
+            c.pallets.iter().filter_map(|p| match p {
+                Pallet::SignatureBridge(_) => Some(c.typed_chain_id()),
+                Pallet::InkSignatureBridge(_) => {
+                    Some(webb_proposals::TypedChainId::Ink(c.chain_id))
+                }
+                _ => None,
+            })
+        });
+    // there is no watcher executing the proposals of the cosmwasm signature
+    // bridges yet, so they are left out to be counted as dropped rather than
+    // queued where nothing reads them. Solana is out of scope altogether: the
+    // relayer can not be configured with Solana chains, so the proposals
+    // signed for them are always dropped.
+    evm_bridges
+        .chain(substrate_bridges)
+        .map(BridgeKey::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use serde_json::json;
+    use webb_relayer_config::evm::EvmChainConfig;
+    use webb_relayer_config::substrate::SubstrateConfig;
+    use webb_relayer_config::WebbRelayerConfig;
+    use webb_relayer_store::InMemoryStore;
+
+    use super::*;
+
+    /// The metrics can only be registered once per process.
+    fn metrics() -> &'static metric::Metrics {
+        static METRICS: OnceLock<metric::Metrics> = OnceLock::new();
+        METRICS.get_or_init(metric::Metrics::new)
+    }
+
+    fn evm(chain_id: u32, enabled: bool) -> EvmChainConfig {
+        let address = "0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df";
+        serde_json::from_value(json!({
+            "name": format!("evm-{chain_id}"),
+            "enabled": enabled,
+            "http-endpoint": "http://localhost:5001",
+            "ws-endpoint": "ws://localhost:5001",
+            "block-confirmations": 1,
+            "chain-id": chain_id,
+            "contracts": [{
+                "contract": "SignatureBridge",
+                "address": address,
+                "deployed-at": 1,
+                "events-watcher": { "polling-interval": 1000 },
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn substrate(
+        chain_id: u32,
+        chain_type: &str,
+        pallet: serde_json::Value,
+    ) -> SubstrateConfig {
+        serde_json::from_value(json!({
+            "name": format!("substrate-{chain_id}"),
+            "enabled": true,
+            "http-endpoint": "http://localhost:9933",
+            "ws-endpoint": "ws://localhost:9944",
+            "chain-id": chain_id,
+            "chain-type": chain_type,
+            "runtime": "WebbProtocol",
+            "pallets": [pallet],
+        }))
+        .unwrap()
+    }
+
+    fn signature_bridge() -> serde_json::Value {
+        json!({
+            "pallet": "SignatureBridge",
+            "events-watcher": { "polling-interval": 1000 },
+        })
+    }
+
+    /// A relayer with a bridge on every kind of chain it can execute the
+    /// signed proposals on.
+    fn config() -> WebbRelayerConfig {
+        let mut config = WebbRelayerConfig::default();
+        for chain in [evm(5001, true), evm(5002, false)] {
+            config.evm.insert(chain.chain_id.to_string(), chain);
+        }
+        let ink = json!({
+            "pallet": "InkSignatureBridge",
+            "address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "events-watcher": { "polling-interval": 1000 },
+        });
+        for chain in [
+            substrate(1080, "Substrate", signature_bridge()),
+            substrate(2000, "PolkadotParachain", signature_bridge()),
+            substrate(2001, "KusamaParachain", signature_bridge()),
+            substrate(2002, "RococoParachain", signature_bridge()),
+            substrate(1090, "Substrate", ink),
+        ] {
+            config.substrate.insert(chain.chain_id.to_string(), chain);
+        }
+        config
+    }
+
+    fn dequeue(
+        store: &AnyStore,
+        chain: webb_proposals::TypedChainId,
+    ) -> Option<BridgeCommand> {
+        let key = SledQueueKey::from_bridge_key(BridgeKey::new(chain));
+        QueueStore::<BridgeCommand>::dequeue_item(store, key).unwrap()
+    }
+
+    #[test]
+    fn signed_proposals_should_be_routed_to_their_bridge() {
+        use webb_proposals::TypedChainId as Target;
+        let watcher = ProposalHandlerWatcher::new(&config());
+        let store = AnyStore::from(InMemoryStore::default());
+        let targets = [
+            (TypedChainId::Evm(5001), Target::Evm(5001)),
+            (TypedChainId::Substrate(1080), Target::Substrate(1080)),
+            (
+                TypedChainId::PolkadotParachain(2000),
+                Target::PolkadotParachain(2000),
+            ),
+            (
+                TypedChainId::KusamaParachain(2001),
+                Target::KusamaParachain(2001),
+            ),
+            (
+                TypedChainId::RococoParachain(2002),
+                Target::RococoParachain(2002),
+            ),
+            (TypedChainId::Ink(1090), Target::Ink(1090)),
+        ];
+        for (i, (target_chain, bridge)) in targets.into_iter().enumerate() {
+            let data = vec![i as u8; 40];
+            watcher
+                .signal_bridge(
+                    &store,
+                    &target_chain,
+                    data.clone(),
+                    vec![1; 65],
+                    metrics(),
+                )
+                .unwrap();
+            match dequeue(&store, bridge) {
+                Some(BridgeCommand::ExecuteProposalWithSignature {
+                    data: queued,
+                    signature,
+                }) => {
+                    assert_eq!(queued, data, "{bridge:?}");
+                    assert_eq!(signature, vec![1; 65]);
+                }
+                other => panic!("{bridge:?}: unexpected command {other:?}"),
+            }
+            assert!(dequeue(&store, bridge).is_none());
+        }
+    }
+
+    #[test]
+    fn proposals_without_a_bridge_should_be_counted_as_dropped() {
+        use webb_proposals::TypedChainId as Target;
+        let watcher = ProposalHandlerWatcher::new(&config());
+        let store = AnyStore::from(InMemoryStore::default());
+        let targets = [
+            (TypedChainId::None, None),
+            // no bridge is configured on these chains.
+            (TypedChainId::Evm(5003), Some(Target::Evm(5003))),
+            (TypedChainId::Substrate(2000), Some(Target::Substrate(2000))),
+            (TypedChainId::Ink(1080), Some(Target::Ink(1080))),
+            // the bridge of a disabled chain.
+            (TypedChainId::Evm(5002), Some(Target::Evm(5002))),
+            // not supported by the relayer.
+            (TypedChainId::Cosmos(4000), Some(Target::Cosmos(4000))),
+            (TypedChainId::Solana(4001), Some(Target::Solana(4001))),
+        ];
+        // the other test only signals proposals that are routed.
+        let dropped = metrics().proposals_dropped_no_handler.get();
+        for (target_chain, bridge) in &targets {
+            watcher
+                .signal_bridge(
+                    &store,
+                    target_chain,
+                    vec![0; 40],
+                    vec![1; 65],
+                    metrics(),
+                )
+                .unwrap();
+            if let Some(bridge) = bridge {
+                assert!(dequeue(&store, *bridge).is_none(), "{bridge:?}");
+            }
+        }
+        assert_eq!(
+            metrics().proposals_dropped_no_handler.get() - dropped,
+            targets.len() as f64,
+        );
+    }
+}
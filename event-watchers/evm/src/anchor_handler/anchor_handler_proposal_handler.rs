@@ -0,0 +1,166 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use webb::evm::ethers::prelude::LogMeta;
+use webb_event_watcher_traits::evm::EventHandler;
+use webb_proposal_signing_backends::{
+    proposal_handler, ProposalSigningBackend,
+};
+use webb_relayer_store::{AnyStore, StoreBatch};
+use webb_relayer_utils::metric;
+
+use super::AnchorHandlerGovernanceEvents;
+use crate::{
+    emitted_by_proposal_execution, linked_evm_resource_ids,
+    propose_governance_proposals, store_handled_event,
+    AnchorHandlerContractWrapper, HttpProvider, ProposalTarget,
+};
+
+/// Represents an Anchor Handler handler which turns deposit/withdrawal limit and
+/// resource id updates into governance proposals for the linked contracts,
+/// signed by the configured signing backend.
+pub struct AnchorHandlerProposalHandler<B> {
+    proposal_signing_backend: B,
+}
+
+impl<B> AnchorHandlerProposalHandler<B>
+where
+    B: ProposalSigningBackend,
+{
+    pub fn new(proposal_signing_backend: B) -> Self {
+        Self {
+            proposal_signing_backend,
+        }
+    }
+}
+
+impl<B> AnchorHandlerProposalHandler<B>
+where
+    B: ProposalSigningBackend + Send + Sync,
+{
+    /// Proposes the event to the linked anchors.
+    async fn propose(
+        &self,
+        wrapper: &AnchorHandlerContractWrapper<HttpProvider>,
+        event: &AnchorHandlerGovernanceEvents,
+        metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        use AnchorHandlerGovernanceEvents::*;
+        let targets = linked_evm_resource_ids(&wrapper.config);
+        let backend = &self.proposal_signing_backend;
+        match event {
+            MaxDepositLimitUpdatedFilter(data) => {
+                propose_governance_proposals(
+                    &wrapper.webb_config,
+                    ProposalTarget::Anchor,
+                    &targets,
+                    backend,
+                    metrics,
+                    |nonce, target| {
+                        proposal_handler::evm_max_deposit_limit_proposal(
+                            nonce,
+                            data.max_deposit_limit,
+                            target,
+                        )
+                    },
+                )
+                .await
+            }
+            MinWithdrawalLimitUpdatedFilter(data) => {
+                propose_governance_proposals(
+                    &wrapper.webb_config,
+                    ProposalTarget::Anchor,
+                    &targets,
+                    backend,
+                    metrics,
+                    |nonce, target| {
+                        proposal_handler::evm_min_withdrawal_limit_proposal(
+                            nonce,
+                            data.min_withdrawal_limit,
+                            target,
+                        )
+                    },
+                )
+                .await
+            }
+            ResourceIdUpdatedFilter(data) => {
+                let new_resource_id =
+                    webb_proposals::ResourceId::from(data.resource_id);
+                propose_governance_proposals(
+                    &wrapper.webb_config,
+                    ProposalTarget::SignatureBridge,
+                    &targets,
+                    backend,
+                    metrics,
+                    |nonce, target| {
+                        proposal_handler::evm_resource_id_update_proposal(
+                            nonce,
+                            new_resource_id,
+                            data.handler_address,
+                            target,
+                        )
+                    },
+                )
+                .await
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<B> EventHandler for AnchorHandlerProposalHandler<B>
+where
+    B: ProposalSigningBackend + Send + Sync,
+{
+    type Contract = AnchorHandlerContractWrapper<HttpProvider>;
+
+    type Events = AnchorHandlerGovernanceEvents;
+
+    type Store = AnyStore;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle_event(
+        &self,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
+        wrapper: &Self::Contract,
+        (event, log): (Self::Events, LogMeta),
+        metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        use AnchorHandlerGovernanceEvents::*;
+        tracing::debug!(
+            event = ?event,
+            block_number = %log.block_number,
+            "Anchor Handler governance event",
+        );
+        if emitted_by_proposal_execution(wrapper, &log).await? {
+            // the linked anchors executed the same proposal already.
+            tracing::debug!(
+                tx = ?log.transaction_hash,
+                "Anchor Handler event emitted by a proposal execution, not proposing it again",
+            );
+        } else {
+            self.propose(wrapper, &event, metrics).await?;
+        }
+        let events_bytes = match event {
+            MaxDepositLimitUpdatedFilter(data) => serde_json::to_vec(&data)?,
+            MinWithdrawalLimitUpdatedFilter(data) => serde_json::to_vec(&data)?,
+            ResourceIdUpdatedFilter(data) => serde_json::to_vec(&data)?,
+        };
+        // mark this event as processed.
+        store_handled_event(batch, wrapper, &log, &events_bytes).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The governance events of the Anchor Handler, which are not part of the
+//! contract bindings shipped with the `webb` crate.
+
+use serde::Serialize;
+use webb::evm::ethers::abi::{self, ParamType, RawLog};
+use webb::evm::ethers::contract::EthLogDecode;
+use webb::evm::ethers::types::{Address, U256};
+
+use crate::{decode_event_data, token_into_address, token_into_uint};
+
+/// Emitted when the maximum deposit limit of an anchor is updated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaxDepositLimitUpdatedFilter {
+    pub max_deposit_limit: U256,
+}
+
+/// Emitted when the minimal withdrawal limit of an anchor is updated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MinWithdrawalLimitUpdatedFilter {
+    pub min_withdrawal_limit: U256,
+}
+
+/// Emitted when a resource id is bound to a new handler.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceIdUpdatedFilter {
+    pub resource_id: [u8; 32],
+    pub handler_address: Address,
+}
+
+/// The Anchor Handler events that are turned into governance proposals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum AnchorHandlerGovernanceEvents {
+    MaxDepositLimitUpdatedFilter(MaxDepositLimitUpdatedFilter),
+    MinWithdrawalLimitUpdatedFilter(MinWithdrawalLimitUpdatedFilter),
+    ResourceIdUpdatedFilter(ResourceIdUpdatedFilter),
+}
+
+impl EthLogDecode for AnchorHandlerGovernanceEvents {
+    fn decode_log(log: &RawLog) -> Result<Self, abi::Error> {
+        if let Ok(mut data) = decode_event_data(
+            log,
+            "MaxDepositLimitUpdated(uint256)",
+            &[ParamType::Uint(256)],
+        ) {
+            let max_deposit_limit = token_into_uint(data.remove(0))?;
+            return Ok(Self::MaxDepositLimitUpdatedFilter(
+                MaxDepositLimitUpdatedFilter { max_deposit_limit },
+            ));
+        }
+        if let Ok(mut data) = decode_event_data(
+            log,
+            "MinWithdrawalLimitUpdated(uint256)",
+            &[ParamType::Uint(256)],
+        ) {
+            let min_withdrawal_limit = token_into_uint(data.remove(0))?;
+            return Ok(Self::MinWithdrawalLimitUpdatedFilter(
+                MinWithdrawalLimitUpdatedFilter {
+                    min_withdrawal_limit,
+                },
+            ));
+        }
+        if let Ok(mut data) = decode_event_data(
+            log,
+            "ResourceIdUpdated(bytes32,address)",
+            &[ParamType::FixedBytes(32), ParamType::Address],
+        ) {
+            let resource_id = data
+                .remove(0)
+                .into_fixed_bytes()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(abi::Error::InvalidData)?;
+            let handler_address = token_into_address(data.remove(0))?;
+            return Ok(Self::ResourceIdUpdatedFilter(
+                ResourceIdUpdatedFilter {
+                    resource_id,
+                    handler_address,
+                },
+            ));
+        }
+        Err(abi::Error::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::event_log;
+    use webb::evm::ethers::abi::Token;
+
+    #[test]
+    fn governance_events_should_be_decoded() {
+        let limit = U256::from(1_000_000u64);
+        let log =
+            event_log("MaxDepositLimitUpdated(uint256)", &[Token::Uint(limit)]);
+        assert_eq!(
+            AnchorHandlerGovernanceEvents::decode_log(&log).unwrap(),
+            AnchorHandlerGovernanceEvents::MaxDepositLimitUpdatedFilter(
+                MaxDepositLimitUpdatedFilter {
+                    max_deposit_limit: limit
+                }
+            )
+        );
+        let log = event_log(
+            "MinWithdrawalLimitUpdated(uint256)",
+            &[Token::Uint(limit)],
+        );
+        assert_eq!(
+            AnchorHandlerGovernanceEvents::decode_log(&log).unwrap(),
+            AnchorHandlerGovernanceEvents::MinWithdrawalLimitUpdatedFilter(
+                MinWithdrawalLimitUpdatedFilter {
+                    min_withdrawal_limit: limit
+                }
+            )
+        );
+        let handler_address = Address::repeat_byte(4);
+        let log = event_log(
+            "ResourceIdUpdated(bytes32,address)",
+            &[
+                Token::FixedBytes(vec![5; 32]),
+                Token::Address(handler_address),
+            ],
+        );
+        assert_eq!(
+            AnchorHandlerGovernanceEvents::decode_log(&log).unwrap(),
+            AnchorHandlerGovernanceEvents::ResourceIdUpdatedFilter(
+                ResourceIdUpdatedFilter {
+                    resource_id: [5; 32],
+                    handler_address,
+                }
+            )
+        );
+    }
+}
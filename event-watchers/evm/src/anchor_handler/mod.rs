@@ -0,0 +1,7 @@
+pub mod anchor_handler_proposal_handler;
+pub mod events;
+
+#[doc(hidden)]
+pub use anchor_handler_proposal_handler::*;
+#[doc(hidden)]
+pub use events::*;
@@ -0,0 +1,513 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detects the leaves missing from the cache of an anchor and fetches them
+//! again from the contract.
+//!
+//! The block range of every missing range of leaves is found by bisecting
+//! the `nextIndex` of the contract between its deployment and the last block
+//! synced by its events watcher, so only the blocks holding the missing leaves
+//! are queried again. Reading the `nextIndex` at past blocks needs an archive
+//! node, when the node does not have their state the `NewCommitment` events
+//! are queried back from the last synced block instead, down to the block of
+//! the first missing leaf.
+
+use std::cmp;
+
+use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+use webb_relayer_context::RelayerContext;
+use webb_relayer_store::{
+    HistoryStore, LeafCacheStore, LeavesConsistency, LeavesSyncStatus,
+};
+
+use super::*;
+
+/// A contract whose leaves are cached by the relayer.
+#[async_trait::async_trait]
+pub trait LeavesSource:
+    ops::Deref<Target = Contract<HttpProvider>> + WatchableContract
+{
+    /// The number of leaves of the contract (its `nextIndex`) at the given block.
+    async fn next_index_at(
+        &self,
+        block: u64,
+    ) -> webb_relayer_utils::Result<u32>;
+
+    /// The `(index, leaf)` pairs inserted in the given (inclusive) block range.
+    async fn leaves_between(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>>;
+}
+
+#[async_trait::async_trait]
+impl LeavesSource for VAnchorContractWrapper<HttpProvider> {
+    async fn next_index_at(
+        &self,
+        block: u64,
+    ) -> webb_relayer_utils::Result<u32> {
+        let next_index = self.contract.next_index().block(block).call().await?;
+        Ok(next_index)
+    }
+
+    async fn leaves_between(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>> {
+        let events = self
+            .contract
+            .event_with_filter::<VAnchorContractEvents>(Default::default())
+            .from_block(from)
+            .to_block(to)
+            .query()
+            .await?;
+        let leaves = events
+            .into_iter()
+            .filter_map(|event| match event {
+                VAnchorContractEvents::NewCommitmentFilter(e) => {
+                    Some((e.index.as_u32(), e.commitment.to_vec()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(leaves)
+    }
+}
+
+#[async_trait::async_trait]
+impl LeavesSource for OpenVAnchorContractWrapper<HttpProvider> {
+    async fn next_index_at(
+        &self,
+        block: u64,
+    ) -> webb_relayer_utils::Result<u32> {
+        let next_index = self.contract.next_index().block(block).call().await?;
+        Ok(next_index)
+    }
+
+    async fn leaves_between(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>> {
+        let events = self
+            .contract
+            .event_with_filter::<OpenVAnchorContractEvents>(Default::default())
+            .from_block(from)
+            .to_block(to)
+            .query()
+            .await?;
+        let leaves = events
+            .into_iter()
+            .filter_map(|event| match event {
+                OpenVAnchorContractEvents::NewCommitmentFilter(e) => {
+                    Some((e.index.as_u32(), e.commitment.to_vec()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(leaves)
+    }
+}
+
+/// Returns the first block in `[from, to]` at which the contract had the leaf
+/// at `index`, the contract must have it at `to`.
+async fn block_of_leaf<C: LeavesSource>(
+    contract: &C,
+    index: u32,
+    mut from: u64,
+    mut to: u64,
+) -> webb_relayer_utils::Result<u64> {
+    while from < to {
+        let mid = from + (to - from) / 2;
+        if contract.next_index_at(mid).await? > index {
+            to = mid;
+        } else {
+            from = mid + 1;
+        }
+    }
+    Ok(to)
+}
+
+/// Fetches the leaves inserted in the given (inclusive) block range whose
+/// index is kept by `filter`, `step` blocks at a time.
+async fn fetch_leaves<C: LeavesSource>(
+    contract: &C,
+    from: u64,
+    to: u64,
+    step: u64,
+    filter: impl Fn(u32) -> bool,
+) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>> {
+    let mut fetched = Vec::new();
+    let mut chunk_start = from;
+    while chunk_start <= to {
+        let chunk_end = cmp::min(chunk_start + step - 1, to);
+        let leaves = contract.leaves_between(chunk_start, chunk_end).await?;
+        fetched.extend(leaves.into_iter().filter(|(index, _)| filter(*index)));
+        chunk_start = chunk_end + 1;
+    }
+    Ok(fetched)
+}
+
+/// Checks the cached leaves against the `nextIndex` of the contract at
+/// `block`, and fetches the missing ones from the blocks found by bisecting
+/// it. This reads the state of old blocks, which needs an archive node.
+async fn refetch_from_state<C: LeavesSource>(
+    contract: &C,
+    cached: &[(u32, Vec<u8>)],
+    deployed_at: u64,
+    block: u64,
+    step: u64,
+) -> webb_relayer_utils::Result<(LeavesConsistency, Vec<(u32, Vec<u8>)>)> {
+    let expected = contract.next_index_at(block).await?;
+    let consistency = LeavesConsistency::check(cached, expected);
+    let mut fetched = Vec::new();
+    for (first, last) in consistency.indices_to_refetch() {
+        let from = block_of_leaf(contract, first, deployed_at, block).await?;
+        let to = block_of_leaf(contract, last, from, block).await?;
+        tracing::debug!(
+            first,
+            last,
+            from,
+            to,
+            "Fetching the missing leaves again",
+        );
+        let range = first..=last;
+        let leaves =
+            fetch_leaves(contract, from, to, step, |i| range.contains(&i))
+                .await?;
+        fetched.extend(leaves);
+    }
+    Ok((consistency, fetched))
+}
+
+/// Checks the cached leaves against the `NewCommitment` events of the
+/// contract up to `block`, and keeps the missing ones. The events are queried
+/// back from `block`, until the one of the first leaf to fetch again, and only
+/// their logs are read, so any node can serve it.
+async fn refetch_from_logs<C: LeavesSource>(
+    contract: &C,
+    cached: &[(u32, Vec<u8>)],
+    deployed_at: u64,
+    block: u64,
+    step: u64,
+) -> webb_relayer_utils::Result<(LeavesConsistency, Vec<(u32, Vec<u8>)>)> {
+    let mut leaves = Vec::new();
+    // the leaves are inserted in order, so the next index follows the last
+    // inserted leaf.
+    let mut expected = None;
+    let mut consistency = LeavesConsistency::check(cached, 0);
+    let mut to = block;
+    while to >= deployed_at {
+        let from = cmp::max(deployed_at, to.saturating_sub(step - 1));
+        let chunk = contract.leaves_between(from, to).await?;
+        let lowest = chunk.iter().map(|(index, _)| *index).min();
+        if expected.is_none() {
+            expected = chunk.iter().map(|(index, _)| index + 1).max();
+        }
+        leaves.extend(chunk);
+        if let Some(expected) = expected {
+            consistency = LeavesConsistency::check(cached, expected);
+            let first = consistency
+                .indices_to_refetch()
+                .first()
+                .map_or(expected, |(first, _)| *first);
+            // every leaf from the first one to fetch again is in the
+            // queried blocks.
+            if lowest.map_or(false, |lowest| lowest <= first) {
+                break;
+            }
+        }
+        if from == deployed_at {
+            break;
+        }
+        to = from - 1;
+    }
+    let ranges = consistency.indices_to_refetch();
+    let fetched = leaves
+        .into_iter()
+        .filter(|(index, _)| {
+            ranges
+                .iter()
+                .any(|(first, last)| (first..=last).contains(&index))
+        })
+        .collect();
+    Ok((consistency, fetched))
+}
+
+/// Returns `true` if the error tells that the node does not have the state
+/// of the block it was asked for, as nodes that are not archive nodes only
+/// keep the state of the latest blocks.
+fn is_missing_state(e: &webb_relayer_utils::Error) -> bool {
+    const MESSAGES: [&str; 5] = [
+        "missing trie node",
+        "header not found",
+        "state not available",
+        "historical state",
+        "pruned",
+    ];
+    let message = e.to_string().to_lowercase();
+    MESSAGES.iter().any(|m| message.contains(m))
+}
+
+/// Checks the cached leaves of the given anchor against its contract, as of
+/// the last block synced by its events watcher, and fetches the missing ones
+/// again.
+///
+/// The contract is read at past blocks while `archive_state` is set, until
+/// the node turns out not to keep their state, in which case it is cleared
+/// and the leaves are checked against the events of the contract instead.
+///
+/// Returns `None` if the events watcher did not sync any block yet.
+pub async fn check_leaves<C: LeavesSource>(
+    contract: &C,
+    store: &AnyStore,
+    resource_id: ResourceId,
+    archive_state: &mut bool,
+) -> webb_relayer_utils::Result<Option<LeavesSyncStatus>> {
+    let deployed_at = contract.deployed_at().as_u64();
+    let block = store.get_last_block_number(resource_id, deployed_at)?;
+    if block <= deployed_at {
+        return Ok(None);
+    }
+    let cached = store.get_leaves_with_indices(resource_id)?;
+    let step = contract.max_blocks_per_step().as_u64().max(1);
+    let from_state = if *archive_state {
+        match refetch_from_state(contract, &cached, deployed_at, block, step)
+            .await
+        {
+            Ok(result) => Some(result),
+            Err(e) if is_missing_state(&e) => {
+                tracing::info!(
+                    error = %e,
+                    "The node does not keep the state of past blocks, reading the events from now on",
+                );
+                *archive_state = false;
+                None
+            }
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+    let (consistency, fetched) = match from_state {
+        Some(result) => result,
+        None => {
+            refetch_from_logs(contract, &cached, deployed_at, block, step)
+                .await?
+        }
+    };
+    let refetched = fetched.len() as u32;
+    if !fetched.is_empty() {
+        store.insert_leaves(resource_id, &fetched)?;
+    }
+    let healthy = if refetched == 0 {
+        consistency.missing.is_empty() && consistency.duplicates.is_empty()
+    } else {
+        let leaves = store.get_leaves_with_indices(resource_id)?;
+        let after = LeavesConsistency::check(&leaves, consistency.expected);
+        after.missing.is_empty() && after.duplicates.is_empty()
+    };
+    Ok(Some(LeavesSyncStatus {
+        checked_at_block: block,
+        consistency,
+        refetched,
+        healthy,
+    }))
+}
+
+/// Checks the leaves cache of the given anchor every `interval`, and records
+/// its sync status in the [`RelayerContext`].
+pub async fn run_leaves_checker<C: LeavesSource>(
+    contract: C,
+    chain_id: u32,
+    store: Arc<AnyStore>,
+    ctx: RelayerContext,
+    interval: Duration,
+) {
+    let resource_id = ResourceId::new(
+        TargetSystem::new_contract_address(contract.address().to_fixed_bytes()),
+        TypedChainId::Evm(chain_id),
+    );
+    let mut archive_state = true;
+    loop {
+        tokio::time::sleep(interval).await;
+        match check_leaves(&contract, &store, resource_id, &mut archive_state)
+            .await
+        {
+            Ok(Some(status)) => {
+                if status.refetched > 0 {
+                    ctx.metrics
+                        .refetched_leaves
+                        .inc_by(f64::from(status.refetched));
+                }
+                if status.healthy {
+                    tracing::debug!(
+                        block = status.checked_at_block,
+                        refetched = status.refetched,
+                        "Leaves cache checked",
+                    );
+                } else {
+                    tracing::warn!(
+                        block = status.checked_at_block,
+                        consistency = ?status.consistency,
+                        refetched = status.refetched,
+                        "Leaves cache is still missing leaves",
+                    );
+                }
+                ctx.leaves_sync.set(resource_id, status);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("Failed to check the leaves cache: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// A contract with one leaf every ten blocks from block 10, which only
+    /// serves its logs.
+    struct Logs {
+        contract: Contract<HttpProvider>,
+        leaves: u32,
+        queried: Mutex<Vec<(u64, u64)>>,
+    }
+
+    impl Logs {
+        fn new(leaves: u32) -> Self {
+            let provider = HttpProvider::try_from("http://localhost:8545")
+                .expect("valid url");
+            Self {
+                contract: Contract::new(
+                    types::Address::zero(),
+                    abi::Abi::default(),
+                    Arc::new(provider),
+                ),
+                leaves,
+                queried: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ops::Deref for Logs {
+        type Target = Contract<HttpProvider>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.contract
+        }
+    }
+
+    impl WatchableContract for Logs {
+        fn deployed_at(&self) -> types::U64 {
+            1.into()
+        }
+
+        fn polling_interval(&self) -> Duration {
+            Duration::from_millis(1)
+        }
+
+        fn max_blocks_per_step(&self) -> types::U64 {
+            10.into()
+        }
+
+        fn print_progress_interval(&self) -> Duration {
+            Duration::from_millis(1)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LeavesSource for Logs {
+        async fn next_index_at(
+            &self,
+            _block: u64,
+        ) -> webb_relayer_utils::Result<u32> {
+            Err(webb_relayer_utils::Error::Generic("missing trie node"))
+        }
+
+        async fn leaves_between(
+            &self,
+            from: u64,
+            to: u64,
+        ) -> webb_relayer_utils::Result<Vec<(u32, Vec<u8>)>> {
+            self.queried.lock().unwrap().push((from, to));
+            let leaves = (0..self.leaves)
+                .filter(|index| {
+                    (from..=to).contains(&(10 + 10 * *index as u64))
+                })
+                .map(|index| (index, vec![index as u8]))
+                .collect();
+            Ok(leaves)
+        }
+    }
+
+    fn cached(indices: impl Iterator<Item = u32>) -> Vec<(u32, Vec<u8>)> {
+        indices.map(|index| (index, vec![index as u8])).collect()
+    }
+
+    #[tokio::test]
+    async fn only_the_blocks_of_the_missing_leaves_should_be_queried() {
+        let contract = Logs::new(10);
+        // leaves 7 and 8 are missing, they are in blocks 80 and 90.
+        let cached = cached((0..7).chain(9..10));
+        let (consistency, fetched) =
+            refetch_from_logs(&contract, &cached, 1, 105, 10)
+                .await
+                .unwrap();
+        assert_eq!(consistency.expected, 10);
+        assert_eq!(consistency.missing, vec![(7, 8)]);
+        assert_eq!(fetched, vec![(8, vec![8]), (7, vec![7])]);
+        let queried = contract.queried.lock().unwrap().clone();
+        assert_eq!(queried, vec![(96, 105), (86, 95), (76, 85)]);
+    }
+
+    #[tokio::test]
+    async fn a_complete_cache_should_stop_at_the_last_leaf() {
+        let contract = Logs::new(10);
+        let (consistency, fetched) =
+            refetch_from_logs(&contract, &cached(0..10), 1, 125, 10)
+                .await
+                .unwrap();
+        assert!(consistency.is_consistent());
+        assert!(fetched.is_empty());
+        let queried = contract.queried.lock().unwrap().clone();
+        assert_eq!(queried, vec![(116, 125), (106, 115), (96, 105)]);
+    }
+
+    #[tokio::test]
+    async fn an_empty_cache_should_be_fetched_entirely() {
+        let contract = Logs::new(3);
+        let (consistency, fetched) =
+            refetch_from_logs(&contract, &[], 1, 35, 10).await.unwrap();
+        assert_eq!(consistency.missing, vec![(0, 2)]);
+        assert_eq!(fetched.len(), 3);
+        let queried = contract.queried.lock().unwrap().clone();
+        assert_eq!(queried.last(), Some(&(6, 15)));
+    }
+
+    #[test]
+    fn only_missing_state_errors_should_fall_back_to_the_logs() {
+        use webb_relayer_utils::Error;
+        assert!(is_missing_state(&Error::Generic("missing trie node 1a2b")));
+        assert!(is_missing_state(&Error::Generic("header not found")));
+        assert!(!is_missing_state(&Error::Generic("connection refused")));
+        assert!(!is_missing_state(&Error::Generic("execution reverted")));
+    }
+}
@@ -16,24 +16,33 @@ use std::ops;
 use std::sync::Arc;
 use std::time::Duration;
 use webb::evm::contract::protocol_solidity::{
-    OpenVAnchorContract, OpenVAnchorContractEvents, VAnchorContract,
+    AnchorHandlerContract, GovernedTokenWrapperContract, OpenVAnchorContract,
+    OpenVAnchorContractEvents, SignatureBridgeContract, VAnchorContract,
     VAnchorContractEvents,
 };
+use webb::evm::ethers::abi::{self, ParamType, RawLog};
 use webb::evm::ethers::contract::Contract;
 use webb::evm::ethers::prelude::Middleware;
-use webb::evm::ethers::{providers, types};
+use webb::evm::ethers::{providers, types, utils};
 
+pub mod leaves_checker;
 pub mod signature_bridge_watcher;
 
+/// A module for listening on anchor handler events.
+#[doc(hidden)]
+pub mod anchor_handler;
 /// A module for listening on open vanchor events.
 #[doc(hidden)]
 pub mod open_vanchor;
+/// A module for listening on token wrapper events.
+#[doc(hidden)]
+pub mod token_wrapper;
 /// A module for listening on vanchor events.
 #[doc(hidden)]
 pub mod vanchor;
 
 use webb_event_watcher_traits::evm::{EventWatcher, WatchableContract};
-use webb_relayer_store::SledStore;
+use webb_relayer_store::AnyStore;
 
 // VAnchorContractWrapper contains VAnchorContract contract along with configurations for Anchor contract, and Relayer.
 #[derive(Clone, Debug)]
@@ -118,7 +127,7 @@ impl EventWatcher for VAnchorContractWatcher {
 
     type Events = VAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 }
 
 // OpenVAnchorContractWrapper contains VAnchorContract contract along with configurations for Anchor contract, and Relayer.
@@ -197,5 +206,505 @@ impl EventWatcher for OpenVAnchorContractWatcher {
 
     type Events = OpenVAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
+}
+
+// TokenWrapperContractWrapper contains GovernedTokenWrapperContract contract along with configurations for Token Wrapper contract, and Relayer.
+#[derive(Clone, Debug)]
+pub struct TokenWrapperContractWrapper<M>
+where
+    M: Middleware,
+{
+    pub config: webb_relayer_config::evm::GovernanceContractConfig,
+    pub webb_config: webb_relayer_config::WebbRelayerConfig,
+    pub contract: GovernedTokenWrapperContract<M>,
+}
+
+impl<M> TokenWrapperContractWrapper<M>
+where
+    M: Middleware,
+{
+    /// Creates a new TokenWrapperContractWrapper.
+    pub fn new(
+        config: webb_relayer_config::evm::GovernanceContractConfig,
+        webb_config: webb_relayer_config::WebbRelayerConfig,
+        client: Arc<M>,
+    ) -> Self {
+        Self {
+            contract: GovernedTokenWrapperContract::new(
+                config.common.address,
+                client,
+            ),
+            config,
+            webb_config,
+        }
+    }
+}
+
+impl<M> ops::Deref for TokenWrapperContractWrapper<M>
+where
+    M: Middleware,
+{
+    type Target = Contract<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.contract
+    }
+}
+
+impl<M> WatchableContract for TokenWrapperContractWrapper<M>
+where
+    M: Middleware,
+{
+    fn deployed_at(&self) -> types::U64 {
+        self.config.common.deployed_at.into()
+    }
+
+    fn polling_interval(&self) -> Duration {
+        Duration::from_millis(self.config.events_watcher.polling_interval)
+    }
+
+    fn max_blocks_per_step(&self) -> types::U64 {
+        self.config.events_watcher.max_blocks_per_step.into()
+    }
+
+    fn print_progress_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.config.events_watcher.print_progress_interval,
+        )
+    }
+}
+
+/// A Token Wrapper Contract Watcher that watches for the Token Wrapper contract events and calls the event
+/// handlers.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TokenWrapperContractWatcher;
+
+#[async_trait::async_trait]
+impl EventWatcher for TokenWrapperContractWatcher {
+    const TAG: &'static str = "Token Wrapper Contract Watcher";
+
+    type Contract = TokenWrapperContractWrapper<HttpProvider>;
+
+    type Events = token_wrapper::TokenWrapperGovernanceEvents;
+
+    type Store = AnyStore;
+}
+
+// AnchorHandlerContractWrapper contains AnchorHandlerContract contract along with configurations for Anchor Handler contract, and Relayer.
+#[derive(Clone, Debug)]
+pub struct AnchorHandlerContractWrapper<M>
+where
+    M: Middleware,
+{
+    pub config: webb_relayer_config::evm::GovernanceContractConfig,
+    pub webb_config: webb_relayer_config::WebbRelayerConfig,
+    pub contract: AnchorHandlerContract<M>,
+}
+
+impl<M> AnchorHandlerContractWrapper<M>
+where
+    M: Middleware,
+{
+    /// Creates a new AnchorHandlerContractWrapper.
+    pub fn new(
+        config: webb_relayer_config::evm::GovernanceContractConfig,
+        webb_config: webb_relayer_config::WebbRelayerConfig,
+        client: Arc<M>,
+    ) -> Self {
+        Self {
+            contract: AnchorHandlerContract::new(config.common.address, client),
+            config,
+            webb_config,
+        }
+    }
+}
+
+impl<M> ops::Deref for AnchorHandlerContractWrapper<M>
+where
+    M: Middleware,
+{
+    type Target = Contract<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.contract
+    }
+}
+
+impl<M> WatchableContract for AnchorHandlerContractWrapper<M>
+where
+    M: Middleware,
+{
+    fn deployed_at(&self) -> types::U64 {
+        self.config.common.deployed_at.into()
+    }
+
+    fn polling_interval(&self) -> Duration {
+        Duration::from_millis(self.config.events_watcher.polling_interval)
+    }
+
+    fn max_blocks_per_step(&self) -> types::U64 {
+        self.config.events_watcher.max_blocks_per_step.into()
+    }
+
+    fn print_progress_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.config.events_watcher.print_progress_interval,
+        )
+    }
+}
+
+/// A Anchor Handler Contract Watcher that watches for the Anchor Handler contract events and calls the event
+/// handlers.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AnchorHandlerContractWatcher;
+
+#[async_trait::async_trait]
+impl EventWatcher for AnchorHandlerContractWatcher {
+    const TAG: &'static str = "Anchor Handler Contract Watcher";
+
+    type Contract = AnchorHandlerContractWrapper<HttpProvider>;
+
+    type Events = anchor_handler::AnchorHandlerGovernanceEvents;
+
+    type Store = AnyStore;
+}
+
+/// Returns the resource ids of the linked EVM contracts that should receive
+/// the governance proposals created from the events of the given contract.
+///
+/// Linked contracts on Substrate based chains are skipped, since these proposals
+/// only target EVM contracts.
+pub(crate) fn linked_evm_resource_ids(
+    config: &webb_relayer_config::evm::GovernanceContractConfig,
+) -> Vec<webb_proposals::ResourceId> {
+    use webb_relayer_config::anchor::LinkedAnchorConfig;
+    let linked_contracts = match &config.linked_contracts {
+        Some(contracts) => contracts,
+        None => {
+            tracing::error!(
+                "Linked contracts not configured for : ({})",
+                config.common.address
+            );
+            return Vec::new();
+        }
+    };
+    linked_contracts
+        .iter()
+        .filter_map(|linked_contract| {
+            let target_resource_id = match linked_contract {
+                LinkedAnchorConfig::Raw(target) => {
+                    let bytes: [u8; 32] = target.resource_id.into();
+                    webb_proposals::ResourceId::from(bytes)
+                }
+                _ => unreachable!("unsupported"),
+            };
+            match target_resource_id.target_system() {
+                webb_proposals::TargetSystem::ContractAddress(_) => {
+                    Some(target_resource_id)
+                }
+                webb_proposals::TargetSystem::Substrate(_) => {
+                    tracing::warn!(
+                        target = ?target_resource_id,
+                        "Governance proposals are not supported for substrate targets, skipping",
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the address of the given token of this chain on the chain of the
+/// target, from the linked tokens of the contract configuration.
+pub(crate) fn linked_token_address(
+    config: &webb_relayer_config::evm::GovernanceContractConfig,
+    token: types::Address,
+    target: webb_proposals::ResourceId,
+) -> Option<types::Address> {
+    let chain_id = match target.typed_chain_id() {
+        webb_proposals::TypedChainId::Evm(chain_id) => chain_id,
+        _ => return None,
+    };
+    config
+        .linked_tokens
+        .iter()
+        .find(|linked| linked.token == token && linked.chain_id == chain_id)
+        .map(|linked| linked.address)
+}
+
+/// Whether the call data executes a signed proposal on the signature bridge.
+pub(crate) fn is_proposal_execution(input: &[u8]) -> bool {
+    use webb::evm::contract::protocol_solidity::{
+        AdminSetResourceWithSignatureCall, ExecuteProposalWithSignatureCall,
+    };
+    use webb::evm::ethers::contract::EthCall;
+    [
+        ExecuteProposalWithSignatureCall::selector(),
+        AdminSetResourceWithSignatureCall::selector(),
+    ]
+    .iter()
+    .any(|selector| input.starts_with(selector))
+}
+
+/// Whether the event was emitted while executing a proposal, in which case
+/// the linked contracts already got the same proposal and proposing it
+/// again would echo it back and forth between the chains.
+pub(crate) async fn emitted_by_proposal_execution(
+    contract: &Contract<HttpProvider>,
+    log: &webb::evm::ethers::contract::LogMeta,
+) -> webb_relayer_utils::Result<bool> {
+    let tx = contract
+        .client()
+        .get_transaction(log.transaction_hash)
+        .await?;
+    Ok(tx.map_or(false, |tx| is_proposal_execution(&tx.input)))
+}
+
+/// The kind of contract executing a governance proposal on a linked chain,
+/// which tells how to read the proposal nonce the proposal has to follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProposalTarget {
+    /// A Token Wrapper, executing the token and wrapping fee proposals.
+    TokenWrapper,
+    /// A VAnchor, executing the deposit and withdrawal limit proposals.
+    Anchor,
+    /// A Signature Bridge, executing the resource id proposals.
+    SignatureBridge,
+}
+
+impl ProposalTarget {
+    /// Reads the proposal nonce of the target contract deployed at `address`.
+    async fn proposal_nonce(
+        self,
+        client: Arc<HttpProvider>,
+        address: types::Address,
+    ) -> webb_relayer_utils::Result<types::U256> {
+        let nonce = match self {
+            Self::TokenWrapper => {
+                GovernedTokenWrapperContract::new(address, client)
+                    .proposal_nonce()
+                    .call()
+                    .await?
+            }
+            Self::Anchor => VAnchorContract::new(address, client)
+                .get_proposal_nonce()
+                .call()
+                .await?
+                .into(),
+            Self::SignatureBridge => {
+                SignatureBridgeContract::new(address, client)
+                    .proposal_nonce()
+                    .call()
+                    .await?
+            }
+        };
+        Ok(nonce)
+    }
+}
+
+/// Returns the nonce of the next proposal executed by a contract whose
+/// proposal nonce is `current`.
+pub(crate) fn next_proposal_nonce(
+    current: types::U256,
+) -> webb_relayer_utils::Result<u32> {
+    u32::try_from(current)
+        .ok()
+        .and_then(|nonce| nonce.checked_add(1))
+        .ok_or(webb_relayer_utils::Error::Generic(
+            "proposal nonce does not fit in u32",
+        ))
+}
+
+/// Reads the nonce the next proposal for the target has to use, from the
+/// target contract itself, since every linked contract keeps its own nonce.
+///
+/// Returns `None` when the chain of the target is not configured, as there is
+/// no way to read its nonce then.
+async fn target_proposal_nonce(
+    webb_config: &webb_relayer_config::WebbRelayerConfig,
+    target: ProposalTarget,
+    target_resource_id: webb_proposals::ResourceId,
+) -> webb_relayer_utils::Result<Option<u32>> {
+    let chain_config = match target_resource_id.typed_chain_id() {
+        webb_proposals::TypedChainId::Evm(chain_id) => {
+            webb_config.evm.get(&chain_id.to_string())
+        }
+        _ => None,
+    };
+    let (chain_config, address) = match (
+        chain_config,
+        target_resource_id.target_system(),
+    ) {
+        (
+            Some(chain_config),
+            webb_proposals::TargetSystem::ContractAddress(address),
+        ) => (chain_config, types::Address::from(address)),
+        _ => {
+            tracing::warn!(
+                target = ?target_resource_id,
+                "The chain of the governance proposal target is not configured, skipping",
+            );
+            return Ok(None);
+        }
+    };
+    let client =
+        Arc::new(HttpProvider::try_from(chain_config.http_endpoint.as_str())?);
+    let nonce = target.proposal_nonce(client, address).await?;
+    next_proposal_nonce(nonce).map(Some)
+}
+
+/// Builds a governance proposal for every target, with the next proposal
+/// nonce of that target, and routes it through the proposal signing backend.
+pub(crate) async fn propose_governance_proposals<B, P>(
+    webb_config: &webb_relayer_config::WebbRelayerConfig,
+    target: ProposalTarget,
+    targets: &[webb_proposals::ResourceId],
+    proposal_signing_backend: &B,
+    metrics: Arc<webb_relayer_utils::metric::Metrics>,
+    build_proposal: impl Fn(u32, webb_proposals::ResourceId) -> P,
+) -> webb_relayer_utils::Result<()>
+where
+    B: webb_proposal_signing_backends::ProposalSigningBackend,
+    P: webb_proposals::ProposalTrait + Sync + Send + 'static,
+{
+    for target_resource_id in targets {
+        let nonce = match target_proposal_nonce(
+            webb_config,
+            target,
+            *target_resource_id,
+        )
+        .await?
+        {
+            Some(nonce) => nonce,
+            None => continue,
+        };
+        let proposal = build_proposal(nonce, *target_resource_id);
+        // Governance proposal proposed metric
+        metrics.governance_proposals.inc();
+        webb_proposal_signing_backends::proposal_handler::handle_proposal(
+            &proposal,
+            proposal_signing_backend,
+            metrics.clone(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Decodes the data of a log emitted by the event with the given signature,
+/// the parameters of the governance events are not indexed.
+pub(crate) fn decode_event_data(
+    log: &RawLog,
+    signature: &str,
+    params: &[ParamType],
+) -> Result<Vec<abi::Token>, abi::Error> {
+    let topic = types::H256::from(utils::keccak256(signature));
+    if log.topics.first() != Some(&topic) {
+        return Err(abi::Error::InvalidData);
+    }
+    abi::decode(params, &log.data)
+}
+
+pub(crate) fn token_into_address(
+    token: abi::Token,
+) -> Result<types::Address, abi::Error> {
+    token.into_address().ok_or(abi::Error::InvalidData)
+}
+
+pub(crate) fn token_into_uint(
+    token: abi::Token,
+) -> Result<types::U256, abi::Error> {
+    token.into_uint().ok_or(abi::Error::InvalidData)
+}
+
+/// Marks the event as handled in the batch, under the key of the contract that emitted it,
+/// which is the key its event watcher keeps the last synced block under, so it can be pruned
+/// later.
+pub(crate) async fn store_handled_event(
+    batch: &webb_relayer_store::StoreBatch,
+    contract: &Contract<HttpProvider>,
+    log: &webb::evm::ethers::contract::LogMeta,
+    event_bytes: &[u8],
+) -> webb_relayer_utils::Result<()> {
+    let chain_id = contract.client().get_chainid().await?;
+    let history_store_key = webb_proposals::ResourceId::new(
+        webb_proposals::TargetSystem::new_contract_address(
+            contract.address().to_fixed_bytes(),
+        ),
+        webb_proposals::TypedChainId::Evm(chain_id.as_u32()),
+    );
+    batch.store_event(
+        history_store_key,
+        event_bytes,
+        log.block_number.as_u64(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use webb::evm::contract::protocol_solidity::ExecuteProposalWithSignatureCall;
+
+    /// A log of the event with the given signature and (not indexed) data.
+    pub(crate) fn event_log(signature: &str, data: &[abi::Token]) -> RawLog {
+        RawLog {
+            topics: vec![types::H256::from(utils::keccak256(signature))],
+            data: abi::encode(data),
+        }
+    }
+
+    #[test]
+    fn proposals_should_follow_the_target_nonce() {
+        assert_eq!(next_proposal_nonce(types::U256::zero()).unwrap(), 1);
+        assert_eq!(next_proposal_nonce(types::U256::from(41)).unwrap(), 42);
+        assert!(next_proposal_nonce(types::U256::from(u32::MAX)).is_err());
+        assert!(next_proposal_nonce(types::U256::MAX).is_err());
+    }
+
+    #[test]
+    fn tokens_should_be_mapped_per_linked_chain() {
+        let token = types::Address::repeat_byte(1);
+        let linked_token = types::Address::repeat_byte(2);
+        let config: webb_relayer_config::evm::GovernanceContractConfig =
+            serde_json::from_value(serde_json::json!({
+                "address": types::Address::repeat_byte(9),
+                "deployed-at": 1,
+                "events-watcher": { "polling-interval": 1000 },
+                "linked-tokens": [{
+                    "token": token,
+                    "chain-id": 5002,
+                    "address": linked_token,
+                }],
+            }))
+            .unwrap();
+        let target = |chain_id| {
+            webb_proposals::ResourceId::new(
+                webb_proposals::TargetSystem::new_contract_address([3; 20]),
+                webb_proposals::TypedChainId::Evm(chain_id),
+            )
+        };
+        assert_eq!(
+            linked_token_address(&config, token, target(5002)),
+            Some(linked_token)
+        );
+        assert_eq!(linked_token_address(&config, token, target(5003)), None);
+        assert_eq!(
+            linked_token_address(&config, linked_token, target(5002)),
+            None
+        );
+    }
+
+    #[test]
+    fn proposal_executions_should_be_recognized() {
+        use webb::evm::ethers::abi::AbiEncode;
+        let execution = ExecuteProposalWithSignatureCall {
+            data: vec![1, 2, 3].into(),
+            sig: vec![4, 5, 6].into(),
+        };
+        assert!(is_proposal_execution(&execution.encode()));
+        assert!(!is_proposal_execution(&[]));
+        assert!(!is_proposal_execution(&[0xde, 0xad, 0xbe, 0xef, 0x00]));
+    }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use super::{HttpProvider, OpenVAnchorContractWrapper};
+use crate::store_handled_event;
 use ethereum_types::H256;
 use std::sync::Arc;
 use webb::evm::contract::protocol_solidity::OpenVAnchorContractEvents;
@@ -22,8 +23,7 @@ use webb_proposal_signing_backends::{
     proposal_handler, ProposalSigningBackend,
 };
 use webb_relayer_config::anchor::LinkedAnchorConfig;
-use webb_relayer_store::EventHashStore;
-use webb_relayer_store::SledStore;
+use webb_relayer_store::{AnyStore, StoreBatch};
 use webb_relayer_utils::metric;
 
 /// Represents an VAnchor Contract Watcher which will use a configured signing backend for signing proposals.
@@ -51,12 +51,13 @@ where
 
     type Events = OpenVAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     #[tracing::instrument(skip_all)]
     async fn handle_event(
         &self,
-        store: Arc<Self::Store>,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
         wrapper: &Self::Contract,
         (event, log): (Self::Events, LogMeta),
         metrics: Arc<metric::Metrics>,
@@ -165,7 +166,7 @@ where
         }
         // mark this event as processed.
         let events_bytes = serde_json::to_vec(&event_data)?;
-        store.store_event(&events_bytes)?;
+        store_handled_event(batch, wrapper, &log, &events_bytes).await?;
         Ok(())
     }
 }
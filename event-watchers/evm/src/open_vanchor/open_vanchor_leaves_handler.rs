@@ -19,8 +19,7 @@ use webb::evm::contract::protocol_solidity::OpenVAnchorContractEvents;
 use webb::evm::ethers::prelude::{LogMeta, Middleware};
 use webb_event_watcher_traits::EventHandler;
 use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
-use webb_relayer_store::SledStore;
-use webb_relayer_store::{EventHashStore, LeafCacheStore};
+use webb_relayer_store::{AnyStore, StoreBatch};
 use webb_relayer_utils::metric;
 /// An VAnchor Leaves Handler that handles `NewCommitment` events and saves the leaves to the store.
 /// It serves as a cache for leaves that could be used by dApp for proof generation.
@@ -33,12 +32,13 @@ impl EventHandler for OpenVAnchorLeavesHandler {
 
     type Events = OpenVAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     #[tracing::instrument(skip_all)]
     async fn handle_event(
         &self,
-        store: Arc<Self::Store>,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
         wrapper: &Self::Contract,
         (event, log): (Self::Events, LogMeta),
         _metrics: Arc<metric::Metrics>,
@@ -56,13 +56,17 @@ impl EventHandler for OpenVAnchorLeavesHandler {
                 let typed_chain_id = TypedChainId::Evm(chain_id.as_u32());
                 let history_store_key =
                     ResourceId::new(target_system, typed_chain_id);
-                store.insert_leaves(history_store_key, &[value.clone()])?;
-                store.insert_last_deposit_block_number(
+                batch.insert_leaves(history_store_key, &[value.clone()]);
+                batch.insert_last_deposit_block_number(
                     history_store_key,
                     log.block_number.as_u64(),
-                )?;
+                );
                 let events_bytes = serde_json::to_vec(&deposit)?;
-                store.store_event(&events_bytes)?;
+                batch.store_event(
+                    history_store_key,
+                    &events_bytes,
+                    log.block_number.as_u64(),
+                );
                 tracing::trace!(
                     %log.block_number,
                     "detected block number",
@@ -29,8 +29,15 @@ use webb::evm::ethers::utils;
 use webb_event_watcher_traits::evm::{
     BridgeWatcher, EventHandler, EventWatcher, WatchableContract,
 };
-use webb_relayer_store::sled::{SledQueueKey, SledStore};
-use webb_relayer_store::{BridgeCommand, QueueStore};
+use webb_relayer_store::dead_letter::{
+    clear_dead_letter, move_to_dead_letters,
+};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{
+    BridgeCommand, BridgeKey, EnqueueOptions, QueuePriority, QueueStore,
+    StoreBatch,
+};
 use webb_relayer_utils::metric;
 
 type HttpProvider = providers::Provider<providers::Http>;
@@ -100,7 +107,7 @@ impl EventWatcher for SignatureBridgeContractWatcher {
 
     type Events = SignatureBridgeContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 }
 
 #[async_trait::async_trait]
@@ -109,7 +116,7 @@ impl EventHandler for SignatureBridgeGovernanceOwnershipTransferredHandler {
 
     type Events = SignatureBridgeContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
     #[tracing::instrument(
         skip_all,
         fields(event_type = ?e.0),
@@ -117,6 +124,7 @@ impl EventHandler for SignatureBridgeGovernanceOwnershipTransferredHandler {
     async fn handle_event(
         &self,
         store: Arc<Self::Store>,
+        _batch: &StoreBatch,
         wrapper: &Self::Contract,
         e: (Self::Events, LogMeta),
         _metrics: Arc<metric::Metrics>,
@@ -196,17 +204,30 @@ where
         (proposal_data, signature): (Vec<u8>, Vec<u8>),
     ) -> webb_relayer_utils::Result<()> {
         let proposal_data_hex = hex::encode(&proposal_data);
+        let chain_id = contract.get_chain_id().call().await?;
+        // commands failing the checks below are kept in the dead letters
+        // of the bridge queue, so the signed proposals are not lost.
+        let cmd_queue = SledQueueKey::from_bridge_key(BridgeKey::new(chain_id));
+        let cmd = BridgeCommand::ExecuteProposalWithSignature {
+            data: proposal_data.clone(),
+            signature: signature.clone(),
+        };
         // 1. Verify proposal length. Proposal lenght should be greater than 40 bytes (proposal header(40B) + proposal body).
         if proposal_data.len() < 40 {
             tracing::warn!(
                 proposal_data = ?proposal_data_hex,
                 "Skipping execution of this proposal :  Invalid Proposal",
             );
+            move_to_dead_letters(
+                &store,
+                cmd_queue,
+                cmd,
+                "Invalid proposal: shorter than the proposal header",
+            )?;
             return Ok(());
         }
 
         // 2. Verify if proposal already exists in transaction queue
-        let chain_id = contract.get_chain_id().call().await?;
         let proposal_data_hash = utils::keccak256(&proposal_data);
         let tx_key = SledQueueKey::from_evm_with_custom_key(
             chain_id.as_u32(),
@@ -247,6 +268,12 @@ where
                 signature = ?signature_hex,
                 "Skipping execution of this proposal : Invalid Signature ",
             );
+            move_to_dead_letters(
+                &store,
+                cmd_queue,
+                cmd,
+                "Invalid signature: not signed by the current governor",
+            )?;
             return Ok(());
         }
 
@@ -266,7 +293,14 @@ where
             proposal_data.into(),
             signature.into(),
         );
-        QueueStore::<TypedTransaction>::enqueue_item(&store, tx_key, call.tx)?;
+        QueueStore::<TypedTransaction>::enqueue_item_with_options(
+            &store,
+            tx_key,
+            call.tx,
+            EnqueueOptions::with_priority(cmd.priority()),
+        )?;
+        // a retried command is no longer dead.
+        clear_dead_letter(&store, cmd_queue, &cmd)?;
         tracing::debug!(
             proposal_data_hash = ?hex::encode(proposal_data_hash),
             "Enqueued execute-proposal call for execution through evm tx queue",
@@ -346,7 +380,14 @@ where
             nonce,
             signature.into(),
         );
-        QueueStore::<TypedTransaction>::enqueue_item(&store, tx_key, call.tx)?;
+        // ownership transfers go before anything else in the queue, since
+        // all other proposals are signed by the new governor.
+        QueueStore::<TypedTransaction>::enqueue_item_with_options(
+            &store,
+            tx_key,
+            call.tx,
+            EnqueueOptions::with_priority(QueuePriority::Critical),
+        )?;
         tracing::debug!(
             chain_id = %chain_id.as_u64(),
             "Enqueued the ownership transfer for execution in the tx queue",
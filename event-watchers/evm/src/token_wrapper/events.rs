@@ -0,0 +1,115 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The governance events of the Token Wrapper, which are not part of the
+//! contract bindings shipped with the `webb` crate.
+
+use serde::Serialize;
+use webb::evm::ethers::abi::{self, ParamType, RawLog};
+use webb::evm::ethers::contract::EthLogDecode;
+use webb::evm::ethers::types::Address;
+
+use crate::{decode_event_data, token_into_address, token_into_uint};
+
+/// Emitted when a token is added to the wrapped tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenAddedFilter {
+    pub token: Address,
+}
+
+/// Emitted when a token is removed from the wrapped tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenRemovedFilter {
+    pub token: Address,
+}
+
+/// Emitted when the wrapping fee percentage is updated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeeUpdatedFilter {
+    pub fee: u16,
+}
+
+/// The Token Wrapper events that are turned into governance proposals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TokenWrapperGovernanceEvents {
+    TokenAddedFilter(TokenAddedFilter),
+    TokenRemovedFilter(TokenRemovedFilter),
+    FeeUpdatedFilter(FeeUpdatedFilter),
+}
+
+impl EthLogDecode for TokenWrapperGovernanceEvents {
+    fn decode_log(log: &RawLog) -> Result<Self, abi::Error> {
+        if let Ok(mut data) =
+            decode_event_data(log, "TokenAdded(address)", &[ParamType::Address])
+        {
+            let token = token_into_address(data.remove(0))?;
+            return Ok(Self::TokenAddedFilter(TokenAddedFilter { token }));
+        }
+        if let Ok(mut data) = decode_event_data(
+            log,
+            "TokenRemoved(address)",
+            &[ParamType::Address],
+        ) {
+            let token = token_into_address(data.remove(0))?;
+            return Ok(Self::TokenRemovedFilter(TokenRemovedFilter { token }));
+        }
+        if let Ok(mut data) =
+            decode_event_data(log, "FeeUpdated(uint16)", &[ParamType::Uint(16)])
+        {
+            let fee = token_into_uint(data.remove(0))?;
+            let fee = u16::try_from(fee.low_u32())
+                .map_err(|_| abi::Error::InvalidData)?;
+            return Ok(Self::FeeUpdatedFilter(FeeUpdatedFilter { fee }));
+        }
+        Err(abi::Error::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::event_log;
+    use webb::evm::ethers::abi::Token;
+    use webb::evm::ethers::types::U256;
+
+    #[test]
+    fn governance_events_should_be_decoded() {
+        let token = Address::repeat_byte(1);
+        let log = event_log("TokenAdded(address)", &[Token::Address(token)]);
+        assert_eq!(
+            TokenWrapperGovernanceEvents::decode_log(&log).unwrap(),
+            TokenWrapperGovernanceEvents::TokenAddedFilter(TokenAddedFilter {
+                token
+            })
+        );
+        let log = event_log("TokenRemoved(address)", &[Token::Address(token)]);
+        assert_eq!(
+            TokenWrapperGovernanceEvents::decode_log(&log).unwrap(),
+            TokenWrapperGovernanceEvents::TokenRemovedFilter(
+                TokenRemovedFilter { token }
+            )
+        );
+        let log =
+            event_log("FeeUpdated(uint16)", &[Token::Uint(U256::from(250))]);
+        assert_eq!(
+            TokenWrapperGovernanceEvents::decode_log(&log).unwrap(),
+            TokenWrapperGovernanceEvents::FeeUpdatedFilter(FeeUpdatedFilter {
+                fee: 250
+            })
+        );
+        // the other events of the contract are skipped.
+        let log = event_log("Paused(address)", &[Token::Address(token)]);
+        assert!(TokenWrapperGovernanceEvents::decode_log(&log).is_err());
+    }
+}
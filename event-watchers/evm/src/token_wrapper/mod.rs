@@ -0,0 +1,7 @@
+pub mod events;
+pub mod token_wrapper_proposal_handler;
+
+#[doc(hidden)]
+pub use events::*;
+#[doc(hidden)]
+pub use token_wrapper_proposal_handler::*;
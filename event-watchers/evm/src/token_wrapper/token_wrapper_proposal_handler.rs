@@ -0,0 +1,184 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use webb::evm::ethers::prelude::LogMeta;
+use webb_event_watcher_traits::evm::EventHandler;
+use webb_proposal_signing_backends::{
+    proposal_handler, ProposalSigningBackend,
+};
+use webb_relayer_store::{AnyStore, StoreBatch};
+use webb_relayer_utils::metric;
+
+use super::TokenWrapperGovernanceEvents;
+use crate::{
+    emitted_by_proposal_execution, linked_evm_resource_ids,
+    linked_token_address, propose_governance_proposals, store_handled_event,
+    HttpProvider, ProposalTarget, TokenWrapperContractWrapper,
+};
+
+/// Represents a Token Wrapper handler which turns token additions/removals and
+/// wrapping fee updates into governance proposals for the linked token wrappers,
+/// signed by the configured signing backend.
+pub struct TokenWrapperProposalHandler<B> {
+    proposal_signing_backend: B,
+}
+
+impl<B> TokenWrapperProposalHandler<B>
+where
+    B: ProposalSigningBackend,
+{
+    pub fn new(proposal_signing_backend: B) -> Self {
+        Self {
+            proposal_signing_backend,
+        }
+    }
+}
+
+impl<B> TokenWrapperProposalHandler<B>
+where
+    B: ProposalSigningBackend + Send + Sync,
+{
+    /// Proposes the event to the linked token wrappers. Token additions and
+    /// removals only go to the linked chains the token is mapped on, with
+    /// the address of the token there.
+    async fn propose(
+        &self,
+        wrapper: &TokenWrapperContractWrapper<HttpProvider>,
+        event: &TokenWrapperGovernanceEvents,
+        metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        use TokenWrapperGovernanceEvents::*;
+        let targets = linked_evm_resource_ids(&wrapper.config);
+        let backend = &self.proposal_signing_backend;
+        let (token, add) = match event {
+            TokenAddedFilter(data) => (data.token, true),
+            TokenRemovedFilter(data) => (data.token, false),
+            FeeUpdatedFilter(data) => {
+                return propose_governance_proposals(
+                    &wrapper.webb_config,
+                    ProposalTarget::TokenWrapper,
+                    &targets,
+                    backend,
+                    metrics,
+                    |nonce, target| {
+                        proposal_handler::evm_wrapping_fee_update_proposal(
+                            nonce, data.fee, target,
+                        )
+                    },
+                )
+                .await;
+            }
+        };
+        for target in targets {
+            let linked_token = match linked_token_address(
+                &wrapper.config,
+                token,
+                target,
+            ) {
+                Some(linked_token) => linked_token,
+                None => {
+                    tracing::warn!(
+                        %token,
+                        target = ?target,
+                        "Token not mapped on the chain of the linked token wrapper, skipping",
+                    );
+                    continue;
+                }
+            };
+            let webb_config = &wrapper.webb_config;
+            let target_kind = ProposalTarget::TokenWrapper;
+            if add {
+                propose_governance_proposals(
+                    webb_config,
+                    target_kind,
+                    &[target],
+                    backend,
+                    metrics.clone(),
+                    |nonce, target| {
+                        proposal_handler::evm_token_add_proposal(
+                            nonce,
+                            linked_token,
+                            target,
+                        )
+                    },
+                )
+                .await?;
+            } else {
+                propose_governance_proposals(
+                    webb_config,
+                    target_kind,
+                    &[target],
+                    backend,
+                    metrics.clone(),
+                    |nonce, target| {
+                        proposal_handler::evm_token_remove_proposal(
+                            nonce,
+                            linked_token,
+                            target,
+                        )
+                    },
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<B> EventHandler for TokenWrapperProposalHandler<B>
+where
+    B: ProposalSigningBackend + Send + Sync,
+{
+    type Contract = TokenWrapperContractWrapper<HttpProvider>;
+
+    type Events = TokenWrapperGovernanceEvents;
+
+    type Store = AnyStore;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle_event(
+        &self,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
+        wrapper: &Self::Contract,
+        (event, log): (Self::Events, LogMeta),
+        metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        use TokenWrapperGovernanceEvents::*;
+        tracing::debug!(
+            event = ?event,
+            block_number = %log.block_number,
+            "Token Wrapper governance event",
+        );
+        if emitted_by_proposal_execution(wrapper, &log).await? {
+            // the linked token wrappers executed the same proposal already.
+            tracing::debug!(
+                tx = ?log.transaction_hash,
+                "Token Wrapper event emitted by a proposal execution, not proposing it again",
+            );
+        } else {
+            self.propose(wrapper, &event, metrics).await?;
+        }
+        let events_bytes = match event {
+            TokenAddedFilter(data) => serde_json::to_vec(&data)?,
+            TokenRemovedFilter(data) => serde_json::to_vec(&data)?,
+            FeeUpdatedFilter(data) => serde_json::to_vec(&data)?,
+        };
+        // mark this event as processed.
+        store_handled_event(batch, wrapper, &log, &events_bytes).await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+pub mod smart_anchor_updates;
 pub mod vanchor_deposit_handler;
 pub mod vanchor_encrypted_outputs_handler;
 pub mod vanchor_leaves_handler;
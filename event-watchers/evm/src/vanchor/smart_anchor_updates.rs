@@ -0,0 +1,264 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Smart Anchor Updates (experimental).
+//!
+//! Instead of proposing an anchor update for every deposit, the relayer checks the
+//! target anchor edge first and only proposes an update when the target is still lagging
+//! behind the source anchor.
+
+use std::convert::TryFrom;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethereum_types::U256;
+use rand::Rng;
+use webb::evm::contract::protocol_solidity::VAnchorContract;
+use webb::evm::ethers::providers;
+use webb::evm::ethers::types;
+use webb::substrate::protocol_substrate_runtime::api as RuntimeApi;
+use webb::substrate::subxt::{self, OnlineClient};
+use webb_proposals::{ResourceId, TargetSystem};
+
+/// The base interval between two checks of a lagging anchor edge.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Checks whether the target anchor edge already has a leaf index equal or newer than `leaf_index`,
+/// retrying up to `smart_anchor_updates_retries` times while the target is still lagging.
+///
+/// Returns `true` if the anchor update could be skipped.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        src = ?src_resource_id,
+        target = ?target_resource_id,
+        leaf_index = %leaf_index,
+    )
+)]
+pub async fn is_edge_up_to_date(
+    webb_config: &webb_relayer_config::WebbRelayerConfig,
+    src_resource_id: ResourceId,
+    target_resource_id: ResourceId,
+    leaf_index: u32,
+) -> bool {
+    let retries = webb_config.experimental.smart_anchor_updates_retries;
+    check_edge(
+        src_resource_id,
+        target_resource_id,
+        leaf_index,
+        retries,
+        retry_delay,
+        || {
+            edge_latest_leaf_index(
+                webb_config,
+                src_resource_id,
+                target_resource_id,
+            )
+        },
+    )
+    .await
+}
+
+/// The delay before checking a lagging anchor edge again, with a bit of
+/// jitter so that relayers watching the same anchors do not check (and
+/// propose) at the exact same time.
+fn retry_delay() -> Duration {
+    let jitter = rand::thread_rng().gen_range(0..=1_000);
+    RETRY_INTERVAL + Duration::from_millis(jitter)
+}
+
+/// Reads the latest leaf index of the edge with `read_edge` until it covers
+/// `leaf_index`, at most `retries` more times, waiting `delay()` between two
+/// reads.
+///
+/// Returns `true` if the edge covers the leaf.
+async fn check_edge<D, F, Fut>(
+    src_resource_id: ResourceId,
+    target_resource_id: ResourceId,
+    leaf_index: u32,
+    retries: u32,
+    delay: D,
+    mut read_edge: F,
+) -> bool
+where
+    D: Fn() -> Duration,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = webb_relayer_utils::Result<Option<u32>>>,
+{
+    for attempt in 0..=retries {
+        match read_edge().await {
+            Ok(Some(edge_leaf_index)) if edge_leaf_index >= leaf_index => {
+                tracing::event!(
+                    target: webb_relayer_utils::probe::TARGET,
+                    tracing::Level::DEBUG,
+                    kind = %webb_relayer_utils::probe::Kind::SmartAnchorUpdates,
+                    decision = "skip",
+                    src = ?src_resource_id,
+                    target = ?target_resource_id,
+                    leaf_index = %leaf_index,
+                    edge_leaf_index = %edge_leaf_index,
+                    attempt = %attempt,
+                );
+                return true;
+            }
+            Ok(edge_leaf_index) => {
+                tracing::event!(
+                    target: webb_relayer_utils::probe::TARGET,
+                    tracing::Level::DEBUG,
+                    kind = %webb_relayer_utils::probe::Kind::SmartAnchorUpdates,
+                    decision = "lagging",
+                    src = ?src_resource_id,
+                    target = ?target_resource_id,
+                    leaf_index = %leaf_index,
+                    edge_leaf_index = ?edge_leaf_index,
+                    attempt = %attempt,
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    attempt = %attempt,
+                    "Failed to read the target anchor edge",
+                );
+            }
+        }
+        if attempt < retries {
+            tokio::time::sleep(delay()).await;
+        }
+    }
+    false
+}
+
+/// Returns the latest leaf index of the `src_resource_id` edge stored on the target anchor,
+/// or `None` if the target anchor does not have that edge yet.
+pub async fn edge_latest_leaf_index(
+    webb_config: &webb_relayer_config::WebbRelayerConfig,
+    src_resource_id: ResourceId,
+    target_resource_id: ResourceId,
+) -> webb_relayer_utils::Result<Option<u32>> {
+    let src_chain_id = src_resource_id.typed_chain_id().chain_id();
+    let target_chain_id =
+        target_resource_id.typed_chain_id().underlying_chain_id();
+    match target_resource_id.target_system() {
+        TargetSystem::ContractAddress(address) => {
+            let chain_config = webb_config
+                .evm
+                .get(&target_chain_id.to_string())
+                .ok_or_else(|| webb_relayer_utils::Error::ChainNotFound {
+                    chain_id: target_chain_id.to_string(),
+                })?;
+            let provider = providers::Provider::<providers::Http>::try_from(
+                chain_config.http_endpoint.as_str(),
+            )?;
+            let contract = VAnchorContract::new(
+                types::Address::from(address),
+                Arc::new(provider),
+            );
+            let src_chain_id = U256::from(src_chain_id);
+            let has_edge = contract.has_edge(src_chain_id).call().await?;
+            if !has_edge {
+                return Ok(None);
+            }
+            let edge_index = contract.edge_index(src_chain_id).call().await?;
+            let (_, _, latest_leaf_index, _) =
+                contract.edge_list(edge_index).call().await?;
+            Ok(Some(latest_leaf_index.as_u32()))
+        }
+        TargetSystem::Substrate(system) => {
+            let node_config = webb_config
+                .substrate
+                .values()
+                .find(|c| c.chain_id == target_chain_id)
+                .ok_or_else(|| webb_relayer_utils::Error::NodeNotFound {
+                    chain_id: target_chain_id.to_string(),
+                })?;
+            let client = OnlineClient::<subxt::SubstrateConfig>::from_url(
+                node_config.ws_endpoint.as_str(),
+            )
+            .await?;
+            let edge_addrs = RuntimeApi::storage()
+                .linkable_tree_bn254()
+                .edge_list(system.tree_id, src_chain_id);
+            let edge = client.storage().fetch(&edge_addrs, None).await?;
+            Ok(edge.map(|e| e.latest_leaf_index))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use webb_proposals::TypedChainId;
+
+    use super::*;
+
+    fn resource_id(chain_id: u32) -> ResourceId {
+        ResourceId::new(
+            TargetSystem::new_contract_address([1u8; 20]),
+            TypedChainId::Evm(chain_id),
+        )
+    }
+
+    /// Checks the edge against leaf `5`, reading the given edges in order,
+    /// returns the decision and the number of reads.
+    async fn check(
+        retries: u32,
+        edges: &[webb_relayer_utils::Result<Option<u32>>],
+    ) -> (bool, usize) {
+        let reads = Cell::new(0);
+        let up_to_date = check_edge(
+            resource_id(1),
+            resource_id(2),
+            5,
+            retries,
+            || Duration::ZERO,
+            || {
+                let edge = match &edges[reads.get()] {
+                    Ok(edge) => Ok(*edge),
+                    Err(_) => {
+                        Err(webb_relayer_utils::Error::Generic("rpc error"))
+                    }
+                };
+                reads.set(reads.get() + 1);
+                async move { edge }
+            },
+        )
+        .await;
+        (up_to_date, reads.get())
+    }
+
+    #[tokio::test]
+    async fn edges_covering_the_leaf_should_be_skipped() {
+        assert_eq!(check(0, &[Ok(Some(5))]).await, (true, 1));
+        assert_eq!(check(3, &[Ok(Some(7))]).await, (true, 1));
+    }
+
+    #[tokio::test]
+    async fn lagging_edges_should_be_checked_again() {
+        let edges = [Ok(None), Ok(Some(3)), Ok(Some(5))];
+        assert_eq!(check(2, &edges).await, (true, 3));
+        // the edge is still lagging after the last retry.
+        assert_eq!(check(1, &edges).await, (false, 2));
+        assert_eq!(check(0, &[Ok(Some(4))]).await, (false, 1));
+    }
+
+    #[tokio::test]
+    async fn failed_reads_should_count_as_lagging() {
+        let edges = [Err(webb_relayer_utils::Error::Generic("")), Ok(Some(6))];
+        assert_eq!(check(1, &edges).await, (true, 2));
+        assert_eq!(check(0, &edges).await, (false, 1));
+    }
+}
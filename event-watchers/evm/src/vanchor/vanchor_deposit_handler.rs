@@ -13,7 +13,9 @@
 // limitations under the License.
 
 use ethereum_types::H256;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use webb::evm::contract::protocol_solidity::VAnchorContractEvents;
 use webb::evm::ethers::prelude::{LogMeta, Middleware};
 use webb_event_watcher_traits::evm::EventHandler;
@@ -21,15 +23,66 @@ use webb_proposal_signing_backends::{
     proposal_handler, ProposalSigningBackend,
 };
 use webb_relayer_config::anchor::LinkedAnchorConfig;
-use webb_relayer_store::EventHashStore;
-use webb_relayer_store::SledStore;
+use webb_relayer_store::{AnyStore, StoreBatch};
 use webb_relayer_utils::metric;
 
-use crate::{HttpProvider, VAnchorContractWrapper};
+use super::smart_anchor_updates;
+use crate::{store_handled_event, HttpProvider, VAnchorContractWrapper};
 
 /// Represents an VAnchor Contract Watcher which will use a configured signing backend for signing proposals.
 pub struct VAnchorDepositHandler<B> {
-    proposal_signing_backend: B,
+    proposal_signing_backend: Arc<B>,
+    /// The deposits covered by the last (smart) anchor update, the deposits
+    /// seen during its time window are coalesced into it.
+    update_window: UpdateWindow,
+}
+
+/// The time window during which the deposits of an anchor are coalesced into
+/// a single (smart) anchor update.
+#[derive(Debug, Clone, Default)]
+struct UpdateWindow {
+    /// The index of the latest leaf covered by the last flushed update.
+    covered: Arc<Mutex<Option<u32>>>,
+}
+
+impl UpdateWindow {
+    /// Returns `true` if the leaf at `leaf_index` is covered by the last
+    /// flushed update: its deposit was seen during the window of that update.
+    fn covers(&self, leaf_index: u32) -> bool {
+        let covered = self.covered.lock().unwrap_or_else(|e| e.into_inner());
+        matches!(*covered, Some(covered) if covered >= leaf_index)
+    }
+
+    /// Waits for the end of the window, then flushes the deposits seen during
+    /// it with `flush`, which returns the index of the latest leaf it covers.
+    async fn flush_after<F, Fut>(
+        &self,
+        duration: Duration,
+        flush: F,
+    ) -> webb_relayer_utils::Result<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = webb_relayer_utils::Result<u32>>,
+    {
+        tokio::time::sleep(duration).await;
+        let covered = flush().await?;
+        *self.covered.lock().unwrap_or_else(|e| e.into_inner()) = Some(covered);
+        Ok(())
+    }
+}
+
+/// Returns `true` if the insertion of the leaf at `leaf_index` should be
+/// proposed as an anchor update.
+///
+/// `VAnchor` emits two insertion events on every `transact` call, a la two
+/// `UTXO`, the target `VAnchor` only needs to be updated after the second one.
+fn proposes_update(leaf_index: u32) -> bool {
+    leaf_index % 2 != 0
+}
+
+/// The index of the latest leaf of an anchor, given its `nextIndex`.
+fn latest_leaf_index(next_index: u32) -> u32 {
+    next_index.saturating_sub(1)
 }
 
 impl<B> VAnchorDepositHandler<B>
@@ -38,7 +91,8 @@ where
 {
     pub fn new(proposal_signing_backend: B) -> Self {
         Self {
-            proposal_signing_backend,
+            proposal_signing_backend: Arc::new(proposal_signing_backend),
+            update_window: UpdateWindow::default(),
         }
     }
 }
@@ -46,18 +100,19 @@ where
 #[async_trait::async_trait]
 impl<B> EventHandler for VAnchorDepositHandler<B>
 where
-    B: ProposalSigningBackend + Send + Sync,
+    B: ProposalSigningBackend + Send + Sync + 'static,
 {
     type Contract = VAnchorContractWrapper<HttpProvider>;
 
     type Events = VAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     #[tracing::instrument(skip_all)]
     async fn handle_event(
         &self,
-        store: Arc<Self::Store>,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
         wrapper: &Self::Contract,
         (event, log): (Self::Events, LogMeta),
         metrics: Arc<metric::Metrics>,
@@ -81,17 +136,14 @@ where
             }
             _ => return Ok(()),
         };
-        // Only construct the `AnchorUpdateProposal` if this condition evaluates to `true`: `leaf_index % 2 != 0`
-        // The reason behind this is that `VAnchor` on every `transact` call, emits two events,
-        // similar to the `Deposit` event but we call it the `Insertion` event, a la two `UTXO`
-        // and since we only need to update the target `VAnchor` only when needed,
-        // the first `Insertion` event sounds redundant in this case.
+        // Only construct the `AnchorUpdateProposal` for the second `Insertion` event of a `transact` call,
+        // the first one sounds redundant in this case (see `proposes_update`).
         tracing::debug!(
             event = ?event_data,
             "VAnchor new leaf event",
         );
 
-        if event_data.index.as_u32() % 2 == 0 {
+        if !proposes_update(event_data.index.as_u32()) {
             tracing::debug!(
                 leaf_index = %event_data.index,
                 is_even_index = %event_data.index.as_u32() % 2 == 0,
@@ -100,75 +152,232 @@ where
             return Ok(());
         }
 
-        let client = wrapper.contract.client();
-        let chain_id = client.get_chainid().await?;
-        let root = wrapper.contract.get_last_root().call().await?;
         let leaf_index = event_data.index.as_u32();
-        let src_chain_id = webb_proposals::TypedChainId::Evm(chain_id.as_u32());
-        let src_target_system =
-            webb_proposals::TargetSystem::new_contract_address(
-                wrapper.contract.address().to_fixed_bytes(),
+        if wrapper.webb_config.experimental.smart_anchor_updates {
+            // the event is only marked as handled once its update is sent,
+            // so that it is handled again if the relayer stops before.
+            self.smart_anchor_update(wrapper, leaf_index, metrics.clone())
+                .await?;
+        } else {
+            let root = wrapper.contract.get_last_root().call().await?;
+            propose_anchor_updates(
+                wrapper,
+                self.proposal_signing_backend.as_ref(),
+                root,
+                leaf_index,
+                metrics.clone(),
+            )
+            .await?;
+        }
+        // mark this event as processed.
+        let events_bytes = serde_json::to_vec(&event_data)?;
+        store_handled_event(batch, wrapper, &log, &events_bytes).await?;
+        metrics.total_transaction_made.inc();
+        Ok(())
+    }
+}
+
+impl<B> VAnchorDepositHandler<B>
+where
+    B: ProposalSigningBackend + Send + Sync + 'static,
+{
+    /// Sends an anchor update at the end of the configured time window, unless
+    /// the deposit is covered by the last one already.
+    async fn smart_anchor_update(
+        &self,
+        wrapper: &VAnchorContractWrapper<HttpProvider>,
+        leaf_index: u32,
+        metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        if self.update_window.covers(leaf_index) {
+            tracing::event!(
+                target: webb_relayer_utils::probe::TARGET,
+                tracing::Level::DEBUG,
+                kind = %webb_relayer_utils::probe::Kind::SmartAnchorUpdates,
+                decision = "coalesce",
+                anchor = %wrapper.contract.address(),
+                leaf_index = %leaf_index,
             );
-        let src_resource_id =
-            webb_proposals::ResourceId::new(src_target_system, src_chain_id);
-
-        let linked_anchors = match &wrapper.config.linked_anchors {
-            Some(anchors) => anchors,
-            None => {
-                tracing::error!(
-                    "Linked anchors not configured for : ({})",
-                    chain_id
-                );
-                return Ok(());
-            }
+            return Ok(());
+        }
+        let window = Duration::from_millis(
+            wrapper.webb_config.experimental.smart_anchor_updates_window,
+        );
+        let flush = || async {
+            // read the latest state of the anchor, so that it covers all
+            // the deposits seen during the window.
+            let root = wrapper.contract.get_last_root().call().await?;
+            let next_index = wrapper.contract.next_index().call().await?;
+            let latest_leaf_index = latest_leaf_index(next_index);
+            propose_anchor_updates(
+                wrapper,
+                self.proposal_signing_backend.as_ref(),
+                root,
+                latest_leaf_index,
+                metrics,
+            )
+            .await?;
+            Ok(latest_leaf_index)
         };
+        self.update_window.flush_after(window, flush).await
+    }
+}
 
-        for linked_anchor in linked_anchors {
-            let target_resource_id = match linked_anchor {
-                LinkedAnchorConfig::Raw(target) => {
-                    let bytes: [u8; 32] = target.resource_id.into();
-                    webb_proposals::ResourceId::from(bytes)
-                }
-                _ => unreachable!("unsupported"),
-            };
-            // Anchor update proposal proposed metric
-            metrics.anchor_update_proposals.inc();
-            let _ = match target_resource_id.target_system() {
-                webb_proposals::TargetSystem::ContractAddress(_) => {
-                    let proposal = proposal_handler::evm_anchor_update_proposal(
+/// Proposes an anchor update with the given root and leaf index to every linked anchor.
+///
+/// When smart anchor updates are enabled, linked anchors whose edge is already up to date are skipped.
+async fn propose_anchor_updates<B>(
+    wrapper: &VAnchorContractWrapper<HttpProvider>,
+    proposal_signing_backend: &B,
+    root: [u8; 32],
+    leaf_index: u32,
+    metrics: Arc<metric::Metrics>,
+) -> webb_relayer_utils::Result<()>
+where
+    B: ProposalSigningBackend,
+{
+    let client = wrapper.contract.client();
+    let chain_id = client.get_chainid().await?;
+    let src_chain_id = webb_proposals::TypedChainId::Evm(chain_id.as_u32());
+    let src_target_system = webb_proposals::TargetSystem::new_contract_address(
+        wrapper.contract.address().to_fixed_bytes(),
+    );
+    let src_resource_id =
+        webb_proposals::ResourceId::new(src_target_system, src_chain_id);
+
+    let linked_anchors = match &wrapper.config.linked_anchors {
+        Some(anchors) => anchors,
+        None => {
+            tracing::error!(
+                "Linked anchors not configured for : ({})",
+                chain_id
+            );
+            return Ok(());
+        }
+    };
+    let smart_anchor_updates =
+        wrapper.webb_config.experimental.smart_anchor_updates;
+
+    for linked_anchor in linked_anchors {
+        let target_resource_id = match linked_anchor {
+            LinkedAnchorConfig::Raw(target) => {
+                let bytes: [u8; 32] = target.resource_id.into();
+                webb_proposals::ResourceId::from(bytes)
+            }
+            _ => unreachable!("unsupported"),
+        };
+        if smart_anchor_updates {
+            let up_to_date = smart_anchor_updates::is_edge_up_to_date(
+                &wrapper.webb_config,
+                src_resource_id,
+                target_resource_id,
+                leaf_index,
+            )
+            .await;
+            if up_to_date {
+                continue;
+            }
+            tracing::event!(
+                target: webb_relayer_utils::probe::TARGET,
+                tracing::Level::DEBUG,
+                kind = %webb_relayer_utils::probe::Kind::SmartAnchorUpdates,
+                decision = "propose",
+                src = ?src_resource_id,
+                target = ?target_resource_id,
+                leaf_index = %leaf_index,
+            );
+        }
+        // Anchor update proposal proposed metric
+        metrics.anchor_update_proposals.inc();
+        let _ = match target_resource_id.target_system() {
+            webb_proposals::TargetSystem::ContractAddress(_) => {
+                let proposal = proposal_handler::evm_anchor_update_proposal(
+                    root,
+                    leaf_index,
+                    target_resource_id,
+                    src_resource_id,
+                );
+                proposal_handler::handle_proposal(
+                    &proposal,
+                    proposal_signing_backend,
+                    metrics.clone(),
+                )
+                .await
+            }
+            webb_proposals::TargetSystem::Substrate(_) => {
+                let proposal =
+                    proposal_handler::substrate_anchor_update_proposal(
                         root,
                         leaf_index,
                         target_resource_id,
                         src_resource_id,
                     );
-                    proposal_handler::handle_proposal(
-                        &proposal,
-                        &self.proposal_signing_backend,
-                        metrics.clone(),
-                    )
-                    .await
-                }
-                webb_proposals::TargetSystem::Substrate(_) => {
-                    let proposal =
-                        proposal_handler::substrate_anchor_update_proposal(
-                            root,
-                            leaf_index,
-                            target_resource_id,
-                            src_resource_id,
-                        );
-                    proposal_handler::handle_proposal(
-                        &proposal,
-                        &self.proposal_signing_backend,
-                        metrics.clone(),
-                    )
-                    .await
-                }
-            };
-        }
-        // mark this event as processed.
-        let events_bytes = serde_json::to_vec(&event_data)?;
-        store.store_event(&events_bytes)?;
-        metrics.total_transaction_made.inc();
-        Ok(())
+                proposal_handler::handle_proposal(
+                    &proposal,
+                    proposal_signing_backend,
+                    metrics.clone(),
+                )
+                .await
+            }
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn only_the_second_insertion_of_a_transaction_should_be_proposed() {
+        assert!(!proposes_update(0));
+        assert!(proposes_update(1));
+        assert!(!proposes_update(4));
+        assert!(proposes_update(5));
+    }
+
+    #[test]
+    fn flushed_updates_should_cover_the_latest_leaf() {
+        assert_eq!(latest_leaf_index(6), 5);
+        assert_eq!(latest_leaf_index(0), 0);
+    }
+
+    #[tokio::test]
+    async fn deposits_should_be_coalesced_into_the_flushed_update() {
+        let window = UpdateWindow::default();
+        let flushes = Arc::new(AtomicU32::new(0));
+        assert!(!window.covers(1));
+        let flush = |covered| {
+            let flushes = flushes.clone();
+            move || async move {
+                flushes.fetch_add(1, Ordering::SeqCst);
+                Ok(covered)
+            }
+        };
+        // the deposits seen during the window are covered by the update.
+        window
+            .flush_after(Duration::from_millis(10), flush(5))
+            .await
+            .unwrap();
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+        assert!(window.covers(3));
+        assert!(window.covers(5));
+        assert!(!window.covers(7));
+        // a failed flush covers nothing more, the deposit is handled again.
+        let failed = window
+            .flush_after(Duration::from_millis(10), || async {
+                Err(webb_relayer_utils::Error::Generic("flush failed"))
+            })
+            .await;
+        assert!(failed.is_err());
+        assert!(!window.covers(7));
+        window
+            .flush_after(Duration::from_millis(10), flush(9))
+            .await
+            .unwrap();
+        assert_eq!(flushes.load(Ordering::SeqCst), 2);
+        assert!(window.covers(7));
     }
 }
@@ -19,8 +19,7 @@ use webb::evm::contract::protocol_solidity::VAnchorContractEvents;
 use webb::evm::ethers::prelude::{LogMeta, Middleware};
 use webb_event_watcher_traits::evm::EventHandler;
 use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
-use webb_relayer_store::SledStore;
-use webb_relayer_store::{EncryptedOutputCacheStore, EventHashStore};
+use webb_relayer_store::{AnyStore, StoreBatch};
 use webb_relayer_utils::metric;
 
 /// An Encrypted Output Handler that handles `NewCommitment` events and saves the encrypted_output to the store.
@@ -34,12 +33,13 @@ impl EventHandler for VAnchorEncryptedOutputHandler {
 
     type Events = VAnchorContractEvents;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     #[tracing::instrument(skip_all)]
     async fn handle_event(
         &self,
-        store: Arc<Self::Store>,
+        _store: Arc<Self::Store>,
+        batch: &StoreBatch,
         wrapper: &Self::Contract,
         (event, log): (Self::Events, LogMeta),
         _metrics: Arc<metric::Metrics>,
@@ -58,16 +58,20 @@ impl EventHandler for VAnchorEncryptedOutputHandler {
                 let typed_chain_id = TypedChainId::Evm(chain_id.as_u32());
                 let history_store_key =
                     ResourceId::new(target_system, typed_chain_id);
-                store.insert_encrypted_output(
+                batch.insert_encrypted_output(
                     history_store_key,
                     &[value.clone()],
-                )?;
-                store.insert_last_deposit_block_number_for_encrypted_output(
+                );
+                batch.insert_last_deposit_block_number(
                     history_store_key,
                     log.block_number.as_u64(),
-                )?;
+                );
                 let events_bytes = serde_json::to_vec(&deposit)?;
-                store.store_event(&events_bytes)?;
+                batch.store_event(
+                    history_store_key,
+                    &events_bytes,
+                    log.block_number.as_u64(),
+                );
                 tracing::trace!(
                     %log.block_number,
                     "detected block number",
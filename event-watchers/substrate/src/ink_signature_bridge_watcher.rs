@@ -0,0 +1,319 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use webb::evm::ethers::utils;
+use webb::substrate::protocol_substrate_runtime;
+use webb::substrate::scale::{self, Decode, Encode};
+use webb::substrate::subxt::ext::sp_core::hashing::blake2_256;
+use webb::substrate::subxt::ext::sp_runtime::AccountId32;
+use webb::substrate::subxt::{self, dynamic::Value, OnlineClient};
+use webb_event_watcher_traits::substrate::{
+    BlockNumberOf, SubstrateBridgeWatcher,
+};
+use webb_event_watcher_traits::SubstrateEventWatcher;
+use webb_proposals::TypedChainId;
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{
+    BridgeCommand, EnqueueOptions, QueuePriority, QueueStore,
+};
+use webb_relayer_types::dynamic_payload::WebbDynamicTxPayload;
+use webb_relayer_utils::metric;
+
+use crate::signature_bridge_watcher::make_execute_proposal_key;
+
+/// The gas limit (`ref_time`) used when calling the Ink! SignatureBridge contract.
+const INK_CALL_GAS_LIMIT: u64 = 100_000_000_000;
+/// The proof size limit used when calling the Ink! SignatureBridge contract.
+const INK_CALL_PROOF_SIZE_LIMIT: u64 = 1024 * 1024;
+
+/// The event emitted by the `Contracts` pallet for the events of an Ink!
+/// contract, which is not part of the generated runtime api since the
+/// protocol-substrate runtime does not include that pallet.
+#[derive(Debug, Clone)]
+pub struct ContractEmitted {
+    /// The contract that emitted the event.
+    pub contract: AccountId32,
+    /// The SCALE encoded event of the contract.
+    pub data: Vec<u8>,
+}
+
+impl Decode for ContractEmitted {
+    fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
+        Ok(Self {
+            contract: AccountId32::decode(input)?,
+            data: Vec::<u8>::decode(input)?,
+        })
+    }
+}
+
+impl subxt::events::StaticEvent for ContractEmitted {
+    const PALLET: &'static str = "Contracts";
+    const EVENT: &'static str = "ContractEmitted";
+}
+
+/// An Ink! SignatureBridge contract events & commands watcher.
+///
+/// Bridge commands are sent to the [`TypedChainId::Ink`] of the chain the contract
+/// is deployed on, and executed as `Contracts::call` extrinsics through the
+/// protocol-substrate transaction queue. The signature checks are done by the contract itself.
+#[derive(Clone, Debug)]
+pub struct InkBridgeEventWatcher {
+    contract: AccountId32,
+}
+
+impl InkBridgeEventWatcher {
+    pub fn new(contract: AccountId32) -> Self {
+        Self { contract }
+    }
+}
+
+#[async_trait::async_trait]
+impl SubstrateEventWatcher for InkBridgeEventWatcher {
+    const TAG: &'static str = "Ink bridge contract Watcher";
+
+    type RuntimeConfig = subxt::SubstrateConfig;
+
+    type Client = OnlineClient<Self::RuntimeConfig>;
+
+    type Event = protocol_substrate_runtime::api::Event;
+
+    type FilteredEvent = ContractEmitted;
+
+    type Store = AnyStore;
+
+    async fn handle_event(
+        &self,
+        _store: Arc<Self::Store>,
+        _api: Arc<Self::Client>,
+        (event, _block_number): (Self::FilteredEvent, BlockNumberOf<Self>),
+        _metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        if event.contract != self.contract {
+            return Ok(());
+        }
+        tracing::event!(
+            target: webb_relayer_utils::probe::TARGET,
+            tracing::Level::DEBUG,
+            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+            call = "ink_signature_bridge::contract_emitted",
+            contract = %self.contract,
+            data = %hex::encode(&event.data),
+        );
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SubstrateBridgeWatcher for InkBridgeEventWatcher {
+    fn typed_chain_id(&self, chain_id: u32) -> TypedChainId {
+        TypedChainId::Ink(chain_id)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle_cmd(
+        &self,
+        chain_id: u32,
+        store: Arc<Self::Store>,
+        _api: Arc<Self::Client>,
+        cmd: BridgeCommand,
+    ) -> webb_relayer_utils::Result<()> {
+        use BridgeCommand::*;
+        tracing::trace!("Got cmd {:?}", cmd);
+        match cmd {
+            ExecuteProposalWithSignature { data, signature } => self
+                .execute_proposal_with_signature(
+                    chain_id,
+                    store,
+                    (data, signature),
+                )?,
+            TransferOwnershipWithSignature {
+                public_key,
+                nonce,
+                signature,
+            } => self.transfer_ownership_with_signature(
+                chain_id,
+                store,
+                (public_key, nonce, signature),
+            )?,
+        };
+        Ok(())
+    }
+}
+
+impl InkBridgeEventWatcher {
+    #[tracing::instrument(skip_all)]
+    fn execute_proposal_with_signature(
+        &self,
+        chain_id: u32,
+        store: Arc<AnyStore>,
+        (proposal_data, signature): (Vec<u8>, Vec<u8>),
+    ) -> webb_relayer_utils::Result<()> {
+        let proposal_data_hex = hex::encode(&proposal_data);
+        // Proposal length should be greater than 40 bytes (proposal header(40B) + proposal body).
+        if proposal_data.len() < 40 {
+            tracing::warn!(
+                proposal_data = ?proposal_data_hex,
+                "Skipping execution of this proposal :  Invalid Proposal",
+            );
+            return Ok(());
+        }
+        let typed_chain_id = self.typed_chain_id(chain_id);
+        tracing::event!(
+            target: webb_relayer_utils::probe::TARGET,
+            tracing::Level::DEBUG,
+            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+            call = "ink_execute_proposal_with_signature",
+            chain_id = %chain_id,
+            proposal_data = ?proposal_data_hex,
+            signature = %hex::encode(&signature),
+        );
+        let input = encode_message(
+            "execute_proposal",
+            (typed_chain_id.chain_id(), proposal_data, signature),
+        );
+        self.enqueue_contract_call(chain_id, store, input, QueuePriority::High)
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn transfer_ownership_with_signature(
+        &self,
+        chain_id: u32,
+        store: Arc<AnyStore>,
+        (public_key, nonce, signature): (Vec<u8>, u32, Vec<u8>),
+    ) -> webb_relayer_utils::Result<()> {
+        tracing::event!(
+            target: webb_relayer_utils::probe::TARGET,
+            tracing::Level::DEBUG,
+            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+            call = "ink_transfer_ownership_with_signature_pub_key",
+            chain_id = %chain_id,
+            new_maintainer = %hex::encode(&public_key),
+            %nonce,
+            signature = %hex::encode(&signature),
+        );
+        // the nonce is part of the signed message, so the contract needs it
+        // to check the signature.
+        let input =
+            encode_message("set_maintainer", (public_key, nonce, signature));
+        self.enqueue_contract_call(
+            chain_id,
+            store,
+            input,
+            QueuePriority::Critical,
+        )
+    }
+
+    /// Enqueues a `Contracts::call` to the SignatureBridge contract with the given input
+    /// in the protocol-substrate transaction queue, with the given priority.
+    fn enqueue_contract_call(
+        &self,
+        chain_id: u32,
+        store: Arc<AnyStore>,
+        input: Vec<u8>,
+        priority: QueuePriority,
+    ) -> webb_relayer_utils::Result<()> {
+        let data_hash = utils::keccak256(&input);
+        let contract_call_tx = WebbDynamicTxPayload {
+            pallet_name: Cow::Borrowed("Contracts"),
+            call_name: Cow::Borrowed("call"),
+            fields: vec![
+                Value::unnamed_variant(
+                    "Id",
+                    vec![Value::from_bytes(&self.contract)],
+                ),
+                Value::u128(0),
+                Value::named_composite(vec![
+                    ("ref_time", Value::u128(INK_CALL_GAS_LIMIT as u128)),
+                    (
+                        "proof_size",
+                        Value::u128(INK_CALL_PROOF_SIZE_LIMIT as u128),
+                    ),
+                ]),
+                Value::unnamed_variant("None", vec![]),
+                Value::from_bytes(input),
+            ],
+        };
+        let tx_key = SledQueueKey::from_substrate_with_custom_key(
+            chain_id,
+            make_execute_proposal_key(data_hash),
+        );
+        QueueStore::<WebbDynamicTxPayload>::enqueue_item_with_options(
+            &store,
+            tx_key,
+            contract_call_tx,
+            EnqueueOptions::with_priority(priority),
+        )?;
+        tracing::debug!(
+            data_hash = ?hex::encode(data_hash),
+            "Enqueued Ink! contract call for execution through protocol-substrate tx queue",
+        );
+        Ok(())
+    }
+}
+
+/// Encodes an Ink! message call, that is the message selector
+/// (the first 4 bytes of the blake2 hash of the message name) followed by the encoded arguments.
+fn encode_message<Args: Encode>(message: &str, args: Args) -> Vec<u8> {
+    let selector = &blake2_256(message.as_bytes())[0..4];
+    let mut input = selector.to_vec();
+    args.encode_to(&mut input);
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_should_start_with_their_selector() {
+        // the selectors of the messages of the ink! `flipper` example.
+        assert_eq!(encode_message("flip", ()), [0x63, 0x3a, 0xa5, 0x51]);
+        assert_eq!(encode_message("get", ()), [0x2f, 0x86, 0x5b, 0xd9]);
+    }
+
+    #[test]
+    fn message_arguments_should_be_scale_encoded() {
+        let input = encode_message(
+            "execute_proposal",
+            (5001u64, vec![1u8, 2], vec![3u8]),
+        );
+        let selector = &blake2_256(b"execute_proposal")[0..4];
+        assert_eq!(&input[0..4], selector);
+        assert_eq!(
+            input[4..],
+            [
+                0x89, 0x13, 0, 0, 0, 0, 0,
+                0, // the chain id, little endian
+                0x08, 1,
+                2, // the proposal, prefixed by its compact length
+                0x04, 3, // the signature
+            ]
+        );
+        let (chain_id, proposal, signature) =
+            <(u64, Vec<u8>, Vec<u8>)>::decode(&mut &input[4..]).unwrap();
+        assert_eq!(chain_id, 5001);
+        assert_eq!(proposal, vec![1, 2]);
+        assert_eq!(signature, vec![3]);
+
+        let input =
+            encode_message("set_maintainer", (vec![7u8; 33], 9u32, vec![8u8]));
+        assert_eq!(&input[0..4], &blake2_256(b"set_maintainer")[0..4]);
+        // the 33 bytes of the public key, then the nonce and the signature.
+        assert_eq!(&input[4..6], [0x84, 7]);
+        assert_eq!(&input[38..], [9, 0, 0, 0, 0x04, 8]);
+    }
+}
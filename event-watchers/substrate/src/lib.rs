@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod ink_signature_bridge_watcher;
+mod parachain_signature_bridge_watcher;
 mod signature_bridge_watcher;
 mod vanchor_encrypted_output_watcher;
 mod vanchor_leaves_watcher;
 mod vanchor_watcher;
 
+#[doc(hidden)]
+pub use ink_signature_bridge_watcher::*;
+#[doc(hidden)]
+pub use parachain_signature_bridge_watcher::*;
 #[doc(hidden)]
 pub use signature_bridge_watcher::*;
 #[doc(hidden)]
@@ -0,0 +1,126 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use webb::substrate::protocol_substrate_runtime;
+use webb::substrate::protocol_substrate_runtime::api::signature_bridge;
+use webb::substrate::subxt::{self, OnlineClient};
+use webb_event_watcher_traits::substrate::{
+    BlockNumberOf, SubstrateBridgeWatcher,
+};
+use webb_event_watcher_traits::SubstrateEventWatcher;
+use webb_proposals::TypedChainId;
+use webb_relayer_config::substrate::SubstrateChainType;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::BridgeCommand;
+use webb_relayer_utils::metric;
+
+use crate::signature_bridge_watcher::{
+    execute_proposal_with_signature, transfer_ownership_with_signature,
+};
+
+/// A SignatureBridge pallet events & commands watcher for parachains.
+///
+/// It works the same way as the [`SubstrateBridgeEventWatcher`](crate::SubstrateBridgeEventWatcher)
+/// but drains the bridge commands sent to the parachain typed chain id.
+#[derive(Copy, Clone, Debug)]
+pub struct ParachainBridgeEventWatcher {
+    chain_type: SubstrateChainType,
+}
+
+impl ParachainBridgeEventWatcher {
+    pub fn new(chain_type: SubstrateChainType) -> Self {
+        Self { chain_type }
+    }
+}
+
+#[async_trait::async_trait]
+impl SubstrateEventWatcher for ParachainBridgeEventWatcher {
+    const TAG: &'static str = "Parachain bridge pallet Watcher";
+
+    type RuntimeConfig = subxt::SubstrateConfig;
+
+    type Client = OnlineClient<Self::RuntimeConfig>;
+
+    type Event = protocol_substrate_runtime::api::Event;
+
+    type FilteredEvent = signature_bridge::events::MaintainerSet;
+
+    type Store = AnyStore;
+
+    async fn handle_event(
+        &self,
+        _store: Arc<Self::Store>,
+        _api: Arc<Self::Client>,
+        (event, _block_number): (Self::FilteredEvent, BlockNumberOf<Self>),
+        _metrics: Arc<metric::Metrics>,
+    ) -> webb_relayer_utils::Result<()> {
+        tracing::event!(
+            target: webb_relayer_utils::probe::TARGET,
+            tracing::Level::DEBUG,
+            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+            call = "pallet_signature_bridge:: set_maintainer",
+            msg = "Maintainer set",
+            chain_type = ?self.chain_type,
+            new_maintainer = ?event.new_maintainer,
+            old_maintainer = ?event.old_maintainer,
+        );
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SubstrateBridgeWatcher for ParachainBridgeEventWatcher {
+    fn typed_chain_id(&self, chain_id: u32) -> TypedChainId {
+        self.chain_type.typed_chain_id(chain_id)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle_cmd(
+        &self,
+        chain_id: u32,
+        store: Arc<Self::Store>,
+        api: Arc<Self::Client>,
+        cmd: BridgeCommand,
+    ) -> webb_relayer_utils::Result<()> {
+        use BridgeCommand::*;
+        tracing::trace!("Got cmd {:?}", cmd);
+        let typed_chain_id = self.typed_chain_id(chain_id);
+        match cmd {
+            ExecuteProposalWithSignature { data, signature } => {
+                execute_proposal_with_signature(
+                    typed_chain_id,
+                    store,
+                    api,
+                    (data, signature),
+                )
+                .await?
+            }
+            TransferOwnershipWithSignature {
+                public_key,
+                nonce,
+                signature,
+            } => {
+                transfer_ownership_with_signature(
+                    typed_chain_id,
+                    store,
+                    api,
+                    (public_key, nonce, signature),
+                )
+                .await?
+            }
+        };
+        Ok(())
+    }
+}
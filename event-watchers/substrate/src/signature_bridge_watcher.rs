@@ -19,8 +19,11 @@ use webb::substrate::protocol_substrate_runtime::api::runtime_types::webb_standa
 use webb::substrate::protocol_substrate_runtime::api::signature_bridge::calls::{ExecuteProposal,SetMaintainer};
 use webb_event_watcher_traits::substrate::{BlockNumberOf, SubstrateBridgeWatcher};
 use webb_event_watcher_traits::SubstrateEventWatcher;
-use webb_relayer_store::sled::{SledQueueKey,SledStore};
-use webb_relayer_store::{BridgeCommand, QueueStore};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{
+    BridgeCommand, EnqueueOptions, QueuePriority, QueueStore,
+};
 use webb::substrate::{
     protocol_substrate_runtime,
 };
@@ -49,7 +52,7 @@ impl SubstrateEventWatcher for SubstrateBridgeEventWatcher {
 
     type FilteredEvent = signature_bridge::events::MaintainerSet;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_event(
         &self,
@@ -92,10 +95,11 @@ impl SubstrateBridgeWatcher for SubstrateBridgeEventWatcher {
     ) -> webb_relayer_utils::Result<()> {
         use BridgeCommand::*;
         tracing::trace!("Got cmd {:?}", cmd);
+        let typed_chain_id = self.typed_chain_id(chain_id);
         match cmd {
             ExecuteProposalWithSignature { data, signature } => {
-                self.execute_proposal_with_signature(
-                    chain_id,
+                execute_proposal_with_signature(
+                    typed_chain_id,
                     store,
                     api.clone(),
                     (data, signature),
@@ -107,8 +111,8 @@ impl SubstrateBridgeWatcher for SubstrateBridgeEventWatcher {
                 nonce,
                 signature,
             } => {
-                self.transfer_ownership_with_signature(
-                    chain_id,
+                transfer_ownership_with_signature(
+                    typed_chain_id,
                     store,
                     api.clone(),
                     (public_key, nonce, signature),
@@ -120,204 +124,206 @@ impl SubstrateBridgeWatcher for SubstrateBridgeEventWatcher {
     }
 }
 
-impl SubstrateBridgeEventWatcher
-where
-    Self: SubstrateBridgeWatcher,
-{
-    #[tracing::instrument(skip_all)]
-    async fn execute_proposal_with_signature(
-        &self,
-        chain_id: u32,
-        store: Arc<<Self as SubstrateEventWatcher>::Store>,
-        api: Arc<<Self as SubstrateEventWatcher>::Client>,
-        (proposal_data, signature): (Vec<u8>, Vec<u8>),
-    ) -> webb_relayer_utils::Result<()> {
-        let proposal_data_hex = hex::encode(&proposal_data);
-        // 1. Verify proposal length. Proposal length should be greater than 40 bytes (proposal header(40B) + proposal body).
-        if proposal_data.len() < 40 {
-            tracing::warn!(
-                proposal_data = ?proposal_data_hex,
-                "Skipping execution of this proposal :  Invalid Proposal",
-            );
-            return Ok(());
-        }
-
-        // 2. Verify proposal signature. Proposal should be signed by active maintainer/dkg-key
-        let signature_hex = hex::encode(&signature);
-
-        // get current maintainer
-        let current_maintainer_addrs =
-            RuntimeApi::storage().signature_bridge().maintainer();
-
-        let current_maintainer = api
-            .storage()
-            .fetch(&current_maintainer_addrs, None)
-            .await?
-            .unwrap();
-
-        // Verify proposal signature
-        let is_signature_valid = validate_ecdsa_signature(
-            proposal_data.as_slice(),
-            signature.as_slice(),
-            current_maintainer.as_slice(),
-        )
-        .unwrap_or(false);
-
-        if !is_signature_valid {
-            tracing::warn!(
-                proposal_data = ?proposal_data_hex,
-                signature = ?signature_hex,
-                "Skipping execution of this proposal : Invalid Signature ",
-            );
-            return Ok(());
-        }
-
-        // 3. Enqueue proposal for execution.
-        tracing::event!(
-            target: webb_relayer_utils::probe::TARGET,
-            tracing::Level::DEBUG,
-            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
-            call = "execute_proposal_with_signature",
-            chain_id = %chain_id,
+/// Verifies the signed proposal against the current maintainer of the `SignatureBridge` pallet
+/// and enqueues its execution in the protocol-substrate transaction queue.
+///
+/// Shared between the standalone and the parachain bridge watchers, `typed_chain_id`
+/// is the typed chain id of the chain the pallet lives on.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn execute_proposal_with_signature(
+    typed_chain_id: webb_proposals::TypedChainId,
+    store: Arc<AnyStore>,
+    api: Arc<OnlineClient<subxt::SubstrateConfig>>,
+    (proposal_data, signature): (Vec<u8>, Vec<u8>),
+) -> webb_relayer_utils::Result<()> {
+    let chain_id = typed_chain_id.underlying_chain_id();
+    let proposal_data_hex = hex::encode(&proposal_data);
+    // 1. Verify proposal length. Proposal length should be greater than 40 bytes (proposal header(40B) + proposal body).
+    if proposal_data.len() < 40 {
+        tracing::warn!(
             proposal_data = ?proposal_data_hex,
-            signature = ?signature_hex,
+            "Skipping execution of this proposal :  Invalid Proposal",
         );
+        return Ok(());
+    }
 
-        // parse proposal call
-        let parsed_proposal_bytes =
-            parse_call_from_proposal_data(&proposal_data);
-        let _proposal_encoded_call: Call =
-            scale::Decode::decode(&mut parsed_proposal_bytes.as_slice())?;
-        let typed_chain_id = webb_proposals::TypedChainId::Substrate(chain_id);
-
-        // Enqueue transaction call data in protocol-substrate transaction queue
-        let execute_proposal_call = ExecuteProposal {
-            src_id: typed_chain_id.chain_id(),
-            proposal_data: proposal_data.clone(),
-            signature: signature.clone(),
-        };
-        // webb dynamic payload
-        let execute_proposal_tx = WebbDynamicTxPayload {
-            pallet_name: Cow::Borrowed("SignatureBridge"),
-            call_name: Cow::Borrowed("execute_proposal"),
-            fields: vec![
-                Value::u128(typed_chain_id.chain_id() as u128),
-                Value::from_bytes(proposal_data),
-                Value::from_bytes(signature),
-            ],
-        };
-
-        let data_hash = utils::keccak256(execute_proposal_call.encode());
-        let tx_key = SledQueueKey::from_substrate_with_custom_key(
-            chain_id,
-            make_execute_proposal_key(data_hash),
-        );
-        // Enqueue WebbDynamicTxPayload in protocol-substrate transaction queue
-        QueueStore::<WebbDynamicTxPayload>::enqueue_item(
-            &store,
-            tx_key,
-            execute_proposal_tx,
-        )?;
-        tracing::debug!(
-            data_hash = ?hex::encode(data_hash),
-            "Enqueued execute-proposal call for execution through protocol-substrate tx queue",
+    // 2. Verify proposal signature. Proposal should be signed by active maintainer/dkg-key
+    let signature_hex = hex::encode(&signature);
+
+    // get current maintainer
+    let current_maintainer_addrs =
+        RuntimeApi::storage().signature_bridge().maintainer();
+
+    let current_maintainer = api
+        .storage()
+        .fetch(&current_maintainer_addrs, None)
+        .await?
+        .unwrap();
+
+    // Verify proposal signature
+    let is_signature_valid = validate_ecdsa_signature(
+        proposal_data.as_slice(),
+        signature.as_slice(),
+        current_maintainer.as_slice(),
+    )
+    .unwrap_or(false);
+
+    if !is_signature_valid {
+        tracing::warn!(
+            proposal_data = ?proposal_data_hex,
+            signature = ?signature_hex,
+            "Skipping execution of this proposal : Invalid Signature ",
         );
-        Ok(())
+        return Ok(());
     }
 
-    #[tracing::instrument(skip_all)]
-    async fn transfer_ownership_with_signature(
-        &self,
-        chain_id: u32,
-        store: Arc<<Self as SubstrateEventWatcher>::Store>,
-        api: Arc<<Self as SubstrateEventWatcher>::Client>,
-        (public_key, nonce, signature): (Vec<u8>, u32, Vec<u8>),
-    ) -> webb_relayer_utils::Result<()> {
-        let new_maintainer = public_key.clone();
-        // get current maintainer
-        let current_maintainer_addrs =
-            RuntimeApi::storage().signature_bridge().maintainer();
-
-        let current_maintainer = api
-            .storage()
-            .fetch(&current_maintainer_addrs, None)
-            .await?
-            .unwrap();
-        // we need to do some checks here:
-        // 1. convert the public key to address and check it is not the same as the current maintainer.
-        // 2. check if the nonce is greater than the current nonce.
-        // 3. ~check if the signature is valid.~
-
-        if new_maintainer == current_maintainer {
-            tracing::warn!(
-                current_maintainer =  %hex::encode(&current_maintainer),
-                new_maintainer = %hex::encode(&new_maintainer),
-                %nonce,
-                signature = %hex::encode(&signature),
-                "Skipping transfer ownership since the new maintainer is the same as the current one",
-            );
-            return Ok(());
-        }
-        let current_nonce =
-            RuntimeApi::storage().signature_bridge().maintainer_nonce();
-
-        let current_nonce =
-            api.storage().fetch(&current_nonce, None).await?.unwrap();
-
-        if nonce <= current_nonce {
-            tracing::warn!(
-                %current_nonce,
-                new_maintainer = %hex::encode(&new_maintainer),
-                %nonce,
-                signature = %hex::encode(&signature),
-                "Skipping transfer ownership since the nonce is not greater than the current one",
-            );
-            return Ok(());
-        }
+    // 3. Enqueue proposal for execution.
+    tracing::event!(
+        target: webb_relayer_utils::probe::TARGET,
+        tracing::Level::DEBUG,
+        kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+        call = "execute_proposal_with_signature",
+        chain_id = %chain_id,
+        proposal_data = ?proposal_data_hex,
+        signature = ?signature_hex,
+    );
+
+    // parse proposal call
+    let parsed_proposal_bytes = parse_call_from_proposal_data(&proposal_data);
+    let _proposal_encoded_call: Call =
+        scale::Decode::decode(&mut parsed_proposal_bytes.as_slice())?;
+
+    // Enqueue transaction call data in protocol-substrate transaction queue
+    let execute_proposal_call = ExecuteProposal {
+        src_id: typed_chain_id.chain_id(),
+        proposal_data: proposal_data.clone(),
+        signature: signature.clone(),
+    };
+    // webb dynamic payload
+    let execute_proposal_tx = WebbDynamicTxPayload {
+        pallet_name: Cow::Borrowed("SignatureBridge"),
+        call_name: Cow::Borrowed("execute_proposal"),
+        fields: vec![
+            Value::u128(typed_chain_id.chain_id() as u128),
+            Value::from_bytes(proposal_data),
+            Value::from_bytes(signature),
+        ],
+    };
+
+    let data_hash = utils::keccak256(execute_proposal_call.encode());
+    let tx_key = SledQueueKey::from_substrate_with_custom_key(
+        chain_id,
+        make_execute_proposal_key(data_hash),
+    );
+    // Enqueue WebbDynamicTxPayload in protocol-substrate transaction queue
+    QueueStore::<WebbDynamicTxPayload>::enqueue_item_with_options(
+        &store,
+        tx_key,
+        execute_proposal_tx,
+        EnqueueOptions::with_priority(QueuePriority::High),
+    )?;
+    tracing::debug!(
+        data_hash = ?hex::encode(data_hash),
+        "Enqueued execute-proposal call for execution through protocol-substrate tx queue",
+    );
+    Ok(())
+}
 
-        tracing::event!(
-            target: webb_relayer_utils::probe::TARGET,
-            tracing::Level::DEBUG,
-            kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
-            call = "transfer_ownership_with_signature_pub_key",
-            chain_id = %chain_id,
+/// Verifies the new maintainer and its nonce against the `SignatureBridge` pallet
+/// and enqueues the `set_maintainer` call in the protocol-substrate transaction queue.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn transfer_ownership_with_signature(
+    typed_chain_id: webb_proposals::TypedChainId,
+    store: Arc<AnyStore>,
+    api: Arc<OnlineClient<subxt::SubstrateConfig>>,
+    (public_key, nonce, signature): (Vec<u8>, u32, Vec<u8>),
+) -> webb_relayer_utils::Result<()> {
+    let chain_id = typed_chain_id.underlying_chain_id();
+    let new_maintainer = public_key.clone();
+    // get current maintainer
+    let current_maintainer_addrs =
+        RuntimeApi::storage().signature_bridge().maintainer();
+
+    let current_maintainer = api
+        .storage()
+        .fetch(&current_maintainer_addrs, None)
+        .await?
+        .unwrap();
+    // we need to do some checks here:
+    // 1. convert the public key to address and check it is not the same as the current maintainer.
+    // 2. check if the nonce is greater than the current nonce.
+    // 3. ~check if the signature is valid.~
+
+    if new_maintainer == current_maintainer {
+        tracing::warn!(
+            current_maintainer =  %hex::encode(&current_maintainer),
             new_maintainer = %hex::encode(&new_maintainer),
             %nonce,
             signature = %hex::encode(&signature),
+            "Skipping transfer ownership since the new maintainer is the same as the current one",
         );
+        return Ok(());
+    }
+    let current_nonce =
+        RuntimeApi::storage().signature_bridge().maintainer_nonce();
 
-        let set_maintainer_call = SetMaintainer {
-            message: new_maintainer.clone(),
-            signature: signature.clone(),
-        };
-
-        // webb dynamic payload
-        let set_maintainer_tx = WebbDynamicTxPayload {
-            pallet_name: Cow::Borrowed("SignatureBridge"),
-            call_name: Cow::Borrowed("set_maintainer"),
-            fields: vec![
-                Value::from_bytes(new_maintainer),
-                Value::from_bytes(signature),
-            ],
-        };
+    let current_nonce =
+        api.storage().fetch(&current_nonce, None).await?.unwrap();
 
-        let data_hash = utils::keccak256(set_maintainer_call.encode());
-        let tx_key = SledQueueKey::from_substrate_with_custom_key(
-            chain_id,
-            make_execute_proposal_key(data_hash),
-        );
-        // Enqueue WebbDynamicTxPayload in protocol-substrate transaction queue
-        QueueStore::<WebbDynamicTxPayload>::enqueue_item(
-            &store,
-            tx_key,
-            set_maintainer_tx,
-        )?;
-        tracing::debug!(
-            data_hash = ?hex::encode(data_hash),
-            "Enqueued set-maintainer call for execution through protocol-substrate tx queue",
+    if nonce <= current_nonce {
+        tracing::warn!(
+            %current_nonce,
+            new_maintainer = %hex::encode(&new_maintainer),
+            %nonce,
+            signature = %hex::encode(&signature),
+            "Skipping transfer ownership since the nonce is not greater than the current one",
         );
-        Ok(())
+        return Ok(());
     }
+
+    tracing::event!(
+        target: webb_relayer_utils::probe::TARGET,
+        tracing::Level::DEBUG,
+        kind = %webb_relayer_utils::probe::Kind::SignatureBridge,
+        call = "transfer_ownership_with_signature_pub_key",
+        chain_id = %chain_id,
+        new_maintainer = %hex::encode(&new_maintainer),
+        %nonce,
+        signature = %hex::encode(&signature),
+    );
+
+    let set_maintainer_call = SetMaintainer {
+        message: new_maintainer.clone(),
+        signature: signature.clone(),
+    };
+
+    // webb dynamic payload
+    let set_maintainer_tx = WebbDynamicTxPayload {
+        pallet_name: Cow::Borrowed("SignatureBridge"),
+        call_name: Cow::Borrowed("set_maintainer"),
+        fields: vec![
+            Value::from_bytes(new_maintainer),
+            Value::from_bytes(signature),
+        ],
+    };
+
+    let data_hash = utils::keccak256(set_maintainer_call.encode());
+    let tx_key = SledQueueKey::from_substrate_with_custom_key(
+        chain_id,
+        make_execute_proposal_key(data_hash),
+    );
+    // Enqueue WebbDynamicTxPayload in protocol-substrate transaction queue
+    QueueStore::<WebbDynamicTxPayload>::enqueue_item_with_options(
+        &store,
+        tx_key,
+        set_maintainer_tx,
+        EnqueueOptions::with_priority(QueuePriority::Critical),
+    )?;
+    tracing::debug!(
+        data_hash = ?hex::encode(data_hash),
+        "Enqueued set-maintainer call for execution through protocol-substrate tx queue",
+    );
+    Ok(())
 }
 
 pub fn parse_nonce_from_proposal_data(proposal_data: &[u8]) -> u32 {
@@ -368,7 +374,7 @@ fn secp256k1_ecdsa_recover(
     Ok(res)
 }
 
-fn make_execute_proposal_key(data_hash: [u8; 32]) -> [u8; 64] {
+pub(crate) fn make_execute_proposal_key(data_hash: [u8; 32]) -> [u8; 64] {
     let mut result = [0u8; 64];
     let prefix = b"execute_proposal_with_signature_";
     result[0..32].copy_from_slice(prefix);
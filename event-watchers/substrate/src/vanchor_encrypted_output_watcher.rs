@@ -22,7 +22,7 @@ use webb_event_watcher_traits::SubstrateEventWatcher;
 use webb_proposals::{
     ResourceId, SubstrateTargetSystem, TargetSystem, TypedChainId,
 };
-use webb_relayer_store::sled::SledStore;
+use webb_relayer_store::AnyStore;
 use webb_relayer_store::EncryptedOutputCacheStore;
 use webb_relayer_utils::metric;
 // An Substrate VAnchor encrypted output Watcher that watches for Deposit events and save the encrypted output to the store.
@@ -42,7 +42,7 @@ impl SubstrateEventWatcher for SubstrateVAnchorEncryptedOutputHandler {
 
     type FilteredEvent = v_anchor_bn254::events::Transaction;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_event(
         &self,
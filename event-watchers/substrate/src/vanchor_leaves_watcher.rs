@@ -16,14 +16,18 @@ use std::sync::Arc;
 use webb::substrate::protocol_substrate_runtime;
 use webb::substrate::protocol_substrate_runtime::api as RuntimeApi;
 use webb::substrate::protocol_substrate_runtime::api::v_anchor_bn254;
-use webb::substrate::subxt::{self, OnlineClient};
+use webb::substrate::scale::{Decode, Encode};
+use webb::substrate::subxt::events::Phase;
+use webb::substrate::subxt::ext::scale_value;
+use webb::substrate::subxt::ext::sp_core::H256;
+use webb::substrate::subxt::{self, Metadata, OnlineClient};
 use webb_event_watcher_traits::substrate::BlockNumberOf;
 use webb_event_watcher_traits::SubstrateEventWatcher;
 use webb_proposals::{
     ResourceId, SubstrateTargetSystem, TargetSystem, TypedChainId,
 };
-use webb_relayer_store::sled::SledStore;
-use webb_relayer_store::LeafCacheStore;
+use webb_relayer_store::AnyStore;
+use webb_relayer_store::{LeafCacheStore, NullifierCacheStore};
 use webb_relayer_utils::metric;
 // An Substrate VAnchor Leaves Watcher that watches for Deposit events and save the leaves to the store.
 /// It serves as a cache for leaves that could be used by dApp for proof generation.
@@ -42,7 +46,7 @@ impl SubstrateEventWatcher for SubstrateVAnchorLeavesWatcher {
 
     type FilteredEvent = v_anchor_bn254::events::Transaction;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_event(
         &self,
@@ -86,6 +90,7 @@ impl SubstrateEventWatcher for SubstrateVAnchorLeavesWatcher {
         let src_target_system = TargetSystem::Substrate(target);
         let history_store_key =
             ResourceId::new(src_target_system, src_chain_id);
+        let nullifiers = spent_nullifiers(&api, at_hash, &event).await?;
         let mut leaf_index = next_leaf_index.saturating_sub(leaf_count as u32);
         let mut leaf_store = Vec::with_capacity(leaf_count);
         for leaf in event.leafs {
@@ -98,6 +103,13 @@ impl SubstrateEventWatcher for SubstrateVAnchorLeavesWatcher {
             leaf_index += 1;
             leaf_store.push(leaf.0);
         }
+        for nullifier in &nullifiers {
+            tracing::debug!(
+                "new nullifier {} found",
+                H256::from_slice(nullifier)
+            );
+        }
+        store.insert_nullifiers(history_store_key, &nullifiers)?;
         tracing::event!(
             target: webb_relayer_utils::probe::TARGET,
             tracing::Level::DEBUG,
@@ -111,3 +123,100 @@ impl SubstrateEventWatcher for SubstrateVAnchorLeavesWatcher {
         Ok(())
     }
 }
+
+/// Returns the nullifiers spent by the transaction that emitted the given event.
+///
+/// The `Transaction` event does not carry them, so they are decoded from the
+/// call of its extrinsic.
+async fn spent_nullifiers(
+    api: &OnlineClient<subxt::SubstrateConfig>,
+    at_hash: H256,
+    event: &v_anchor_bn254::events::Transaction,
+) -> webb_relayer_utils::Result<Vec<Vec<u8>>> {
+    let events = api.events().at(Some(at_hash)).await?;
+    let mut phase = None;
+    for details in events.iter() {
+        let details = details?;
+        let found =
+            details.as_event::<v_anchor_bn254::events::Transaction>()?;
+        if found.as_ref() == Some(event) {
+            phase = Some(details.phase());
+            break;
+        }
+    }
+    let index = match phase {
+        Some(Phase::ApplyExtrinsic(index)) => index as usize,
+        _ => {
+            return Err(webb_relayer_utils::Error::Generic(
+                "transaction event not emitted by an extrinsic",
+            ))
+        }
+    };
+    let block = api
+        .rpc()
+        .block(Some(at_hash))
+        .await?
+        .ok_or(webb_relayer_utils::Error::Generic("block not found"))?;
+    let extrinsic = block
+        .block
+        .extrinsics
+        .get(index)
+        .ok_or(webb_relayer_utils::Error::Generic("extrinsic not found"))?;
+    let bytes = Vec::<u8>::decode(&mut extrinsic.encode().as_slice())?;
+    decode_nullifiers(&api.metadata(), &bytes)
+}
+
+/// Decodes the input nullifiers of an extrinsic calling `transact` (or
+/// `register_and_transact`) on the VAnchor pallet, the other calls (like
+/// batches) do not spend any nullifier we can read.
+fn decode_nullifiers(
+    metadata: &Metadata,
+    extrinsic: &[u8],
+) -> webb_relayer_utils::Result<Vec<Vec<u8>>> {
+    use v_anchor_bn254::calls::{RegisterAndTransact, Transact};
+
+    let mut cursor = extrinsic;
+    let version = u8::decode(&mut cursor)?;
+    // a signed extrinsic starts with its address, signature and signed
+    // extensions, skipped using the types of the runtime.
+    if version & 0b1000_0000 != 0 {
+        let types = metadata.types();
+        let ty = types
+            .resolve(metadata.runtime_metadata().extrinsic.ty.id())
+            .ok_or(webb_relayer_utils::Error::Generic(
+                "unknown extrinsic type",
+            ))?;
+        for param in ty.type_params() {
+            let param_ty = match param.ty() {
+                Some(param_ty) if param.name() != "Call" => param_ty,
+                _ => continue,
+            };
+            scale_value::scale::decode_as_type(
+                &mut cursor,
+                param_ty.id(),
+                types,
+            )
+            .map_err(|_| {
+                webb_relayer_utils::Error::Generic("invalid signed extrinsic")
+            })?;
+        }
+    }
+    let pallet = metadata.pallet("VAnchorBn254")?;
+    let (pallet_index, call_index) = <(u8, u8)>::decode(&mut cursor)?;
+    if pallet_index != pallet.index() {
+        return Ok(Vec::new());
+    }
+    let proof_data = if call_index == pallet.call_index("transact")? {
+        Transact::decode(&mut cursor)?.proof_data
+    } else if call_index == pallet.call_index("register_and_transact")? {
+        RegisterAndTransact::decode(&mut cursor)?.proof_data
+    } else {
+        return Ok(Vec::new());
+    };
+    let nullifiers = proof_data
+        .input_nullifiers
+        .into_iter()
+        .map(|nullifier| nullifier.0.to_vec())
+        .collect();
+    Ok(nullifiers)
+}
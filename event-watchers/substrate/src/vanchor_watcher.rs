@@ -18,14 +18,14 @@ use webb::substrate::protocol_substrate_runtime::api as RuntimeApi;
 use webb::substrate::protocol_substrate_runtime::api::v_anchor_bn254;
 use webb::substrate::scale::Encode;
 use webb::substrate::subxt::{self, OnlineClient};
-use webb_event_watcher_traits::substrate::BlockNumberOf;
+use webb_event_watcher_traits::substrate::{history_store_key, BlockNumberOf};
 use webb_event_watcher_traits::SubstrateEventWatcher;
 use webb_proposal_signing_backends::{
     proposal_handler, ProposalSigningBackend,
 };
 use webb_relayer_config::anchor::LinkedAnchorConfig;
+use webb_relayer_store::AnyStore;
 use webb_relayer_store::EventHashStore;
-use webb_relayer_store::SledStore;
 use webb_relayer_utils::metric;
 /// Represents an Anchor Watcher which will use a configured signing backend for signing proposals.
 pub struct SubstrateVAnchorWatcher<B> {
@@ -63,7 +63,7 @@ where
 
     type FilteredEvent = v_anchor_bn254::events::Transaction;
 
-    type Store = SledStore;
+    type Store = AnyStore;
 
     #[tracing::instrument(skip_all)]
     async fn handle_event(
@@ -165,7 +165,11 @@ where
         }
         // mark this event as processed.
         let events_bytes = &event.encode();
-        store.store_event(events_bytes)?;
+        store.store_event(
+            history_store_key(chain_id as u32),
+            events_bytes,
+            block_number as u64,
+        )?;
         metrics.total_transaction_made.inc();
         Ok(())
     }
@@ -78,6 +78,7 @@ async fn main() -> anyhow::Result<()> {
                             polling_interval: 3000,
                             max_blocks_per_step: 1000,
                             print_progress_interval: 60_000,
+                            leaves_check_interval: 600_000,
                         },
                         withdraw_config: Some(VAnchorWithdrawConfig {
                             withdraw_gaslimit: 21000.into(),
@@ -104,6 +105,7 @@ async fn main() -> anyhow::Result<()> {
                             polling_interval: 3000,
                             max_blocks_per_step: 1000,
                             print_progress_interval: 60_000,
+                            leaves_check_interval: 0,
                         },
                     }),
                 ],
@@ -127,21 +129,26 @@ async fn main() -> anyhow::Result<()> {
     let ctx = RelayerContext::new(config);
 
     // next is to build the store, or the storage backend:
-    let store = webb_relayer_store::sled::SledStore::open("path/to/store")?;
+    let store: webb_relayer_store::AnyStore =
+        webb_relayer_store::SledStore::open("path/to/store")?.into();
+    // or a SQLite database:
+    let store: webb_relayer_store::AnyStore =
+        webb_relayer_store::SqliteStore::open("path/to/store.db")?.into();
     // or temporary store:
-    let store = webb_relayer_store::sled::SledStore::temporary()?;
+    let store: webb_relayer_store::AnyStore =
+        webb_relayer_store::SledStore::temporary()?.into();
 
     // it is now up to you to start the web interface/server for the relayer and the background
     // services.
 
     // Start the web server:
-    let (addr, web_services) =
+    let (addrs, web_services) =
         service::build_web_services(ctx.clone(), store.clone())?;
     // and also the background services:
     // this does not block, will fire the services on background tasks.
     service::ignite(&ctx, Arc::new(store)).await?;
 
-    println!("Listening on {}", addr);
+    println!("Listening on {:?}", addrs);
     web_services.await;
     Ok(())
 }
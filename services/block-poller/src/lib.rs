@@ -6,7 +6,7 @@ use webb::evm::ethers::types::{Block, TxHash};
 use webb_relayer::service::{Client, Store};
 use webb_relayer_config::block_poller::BlockPollerConfig;
 use webb_relayer_context::RelayerContext;
-use webb_relayer_store::SledStore;
+use webb_relayer_store::AnyStore;
 use webb_relayer_utils::{Error, Result};
 
 mod block_poller;
@@ -19,7 +19,7 @@ pub struct BlockWatcher;
 #[async_trait::async_trait]
 impl BlockPoller for BlockWatcher {
     const TAG: &'static str = "Block Watcher";
-    type Store = SledStore;
+    type Store = AnyStore;
 }
 
 #[derive(Clone, Debug)]
@@ -27,7 +27,7 @@ struct BlockListener;
 
 #[async_trait::async_trait]
 impl BlockPollingHandler for BlockListener {
-    type Store = SledStore;
+    type Store = AnyStore;
 
     async fn handle_block(
         &self,
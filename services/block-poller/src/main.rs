@@ -19,7 +19,7 @@ use webb_relayer_utils::Result;
 /// # Arguments
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub async fn ignite(
     ctx: &RelayerContext,
     store: Arc<Store>,
@@ -84,13 +84,15 @@ async fn main(args: Opts) -> anyhow::Result<()> {
     // as a convenient place to access the configuration.
     let ctx = RelayerContext::new(config);
     // persistent storage for the relayer
-    let store = create_store(&args).await?;
+    let store = create_store(&args, &ctx.config.store).await?;
     tracing::trace!("Created persistent storage..");
     // the build_web_relayer command sets up routing (endpoint queries / requests mapped to handled code)
     // so clients can interact with the relayer
-    let (addr, server) =
+    let (addrs, server) =
         webb_relayer::service::build_web_services(ctx.clone(), store.clone())?;
-    tracing::info!("Starting the server on {}", addr);
+    for addr in &addrs {
+        tracing::info!("Starting the server on {}", addr);
+    }
     // start the server.
     let server_handle = tokio::spawn(server);
     // start all background services.
@@ -0,0 +1,349 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Relayer CLI 🛠️
+//!
+//! A command-line client for the operators of a relayer, to query it and
+//! test relaying through its API without writing the requests by hand.
+
+#![warn(missing_docs)]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use structopt::StructOpt;
+use webb::evm::ethers::types::Address;
+use webb_relayer_client::{
+    Command as RelayCommand, LeavesStatusResponse, RelayerClient, RelayerInfo,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The Webb Relayer Command-line client
+///
+/// Show what a relayer is configured for:
+///
+/// $ webb-relayer-cli --url <RELAYER_URL> info
+///
+/// Relay a transaction, the file holds the command as sent over the
+/// websocket (e.g. `{ "evm": { "vAnchor": { ... } } }`):
+///
+/// $ webb-relayer-cli --url <RELAYER_URL> relay proof.json
+#[derive(StructOpt)]
+#[structopt(name = "Webb Relayer CLI")]
+pub struct Opts {
+    /// The root url of the relayer.
+    #[structopt(
+        long,
+        env = "WEBB_RELAYER_URL",
+        default_value = "http://localhost:9955",
+        value_name = "URL"
+    )]
+    pub url: String,
+    /// The command to run against the relayer.
+    #[structopt(subcommand)]
+    pub command: Command,
+}
+
+/// Commands of the relayer client.
+#[derive(StructOpt)]
+pub enum Command {
+    /// Show the chains, contracts and features of the relayer.
+    Info,
+    /// Dump the cached leaves of an anchor to a JSON file.
+    Leaves {
+        /// The anchor to dump the leaves of.
+        #[structopt(flatten)]
+        anchor: AnchorOpts,
+        /// The file to write.
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Dump the cached encrypted outputs of an evm anchor to a JSON file.
+    EncryptedOutputs {
+        /// The chain id of the anchor.
+        #[structopt(long)]
+        chain_id: u32,
+        /// The address of the VAnchor contract.
+        #[structopt(long)]
+        address: Address,
+        /// The file to write.
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Watch the sync status of the leaves cache of an anchor, until
+    /// interrupted.
+    SyncStatus {
+        /// The anchor to watch.
+        #[structopt(flatten)]
+        anchor: AnchorOpts,
+        /// The number of seconds between two checks.
+        #[structopt(
+            long,
+            default_value = "10",
+            parse(try_from_str = parse_interval)
+        )]
+        interval: u64,
+    },
+    /// Relay a transaction from a JSON proof file, and follow it until the
+    /// relayer is done with it.
+    Relay {
+        /// The file holding the command to send.
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
+/// An anchor cached by the relayer, an evm contract or a substrate tree.
+#[derive(Debug, StructOpt)]
+pub struct AnchorOpts {
+    /// The chain id of the anchor.
+    #[structopt(long)]
+    pub chain_id: u32,
+    /// The address of the VAnchor contract, for an evm anchor.
+    #[structopt(
+        long,
+        required_unless = "tree-id",
+        conflicts_with_all = &["tree-id", "pallet-id"]
+    )]
+    pub address: Option<Address>,
+    /// The id of the merkle tree, for a substrate anchor.
+    #[structopt(long, requires = "pallet-id")]
+    pub tree_id: Option<u32>,
+    /// The index of the pallet of the merkle tree, for a substrate anchor.
+    #[structopt(long, requires = "tree-id")]
+    pub pallet_id: Option<u8>,
+}
+
+/// Runs a command against the relayer, writing what it shows to `out`.
+pub async fn run(
+    client: &RelayerClient,
+    command: &Command,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    match command {
+        Command::Info => {
+            let info = client.info().await?;
+            write_info(&info, out)?;
+        }
+        Command::Leaves { anchor, output } => {
+            let leaves = leaves(client, anchor).await?;
+            let dump = serde_json::json!({
+                "lastQueriedBlock": leaves.last_queried_block,
+                "leaves": hex_items(&leaves.leaves),
+            });
+            write_dump(output, &dump)?;
+            writeln!(
+                out,
+                "{} leaves written to {}",
+                leaves.leaves.len(),
+                output.display()
+            )?;
+        }
+        Command::EncryptedOutputs {
+            chain_id,
+            address,
+            output,
+        } => {
+            let outputs =
+                client.evm_encrypted_outputs(*chain_id, *address).await?;
+            let dump = serde_json::json!({
+                "lastQueriedBlock": outputs.last_queried_block,
+                "encryptedOutputs": hex_items(&outputs.encrypted_outputs),
+            });
+            write_dump(output, &dump)?;
+            writeln!(
+                out,
+                "{} encrypted outputs written to {}",
+                outputs.encrypted_outputs.len(),
+                output.display()
+            )?;
+        }
+        Command::SyncStatus { anchor, interval } => {
+            let mut ticks =
+                tokio::time::interval(Duration::from_secs(*interval));
+            loop {
+                ticks.tick().await;
+                let status = leaves_status(client, anchor).await?;
+                writeln!(out, "{}", sync_status_line(&status))?;
+            }
+        }
+        Command::Relay { file } => {
+            let command: RelayCommand =
+                serde_json::from_slice(&std::fs::read(file)?)?;
+            let mut updates = client.relay(&command).await?;
+            let mut last = None;
+            while let Some(update) = updates.try_next().await? {
+                match &update.job.status {
+                    Some(status) => writeln!(out, "{status}")?,
+                    None => writeln!(out, "job {} submitted", update.job.id)?,
+                }
+                last = Some(update.job);
+            }
+            let job = last.ok_or_else(|| anyhow::anyhow!("No job updates"))?;
+            if let Some(error) = job.error {
+                anyhow::bail!("Relaying failed: {}", error);
+            }
+            if let Some(tx_hash) = job.tx_hash {
+                writeln!(out, "Relayed in transaction {tx_hash}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the cached leaves of an anchor.
+async fn leaves(
+    client: &RelayerClient,
+    anchor: &AnchorOpts,
+) -> anyhow::Result<webb_relayer_client::LeavesCacheResponse> {
+    let leaves = match (anchor.address, anchor.tree_id, anchor.pallet_id) {
+        (Some(address), _, _) => {
+            client.evm_leaves(anchor.chain_id, address).await?
+        }
+        (None, Some(tree_id), Some(pallet_id)) => {
+            client
+                .substrate_leaves(anchor.chain_id, tree_id, pallet_id)
+                .await?
+        }
+        _ => {
+            anyhow::bail!("An anchor needs an address, or a tree and pallet id")
+        }
+    };
+    Ok(leaves)
+}
+
+/// Fetches the state of the leaves cache of an anchor, without its leaves.
+async fn leaves_status(
+    client: &RelayerClient,
+    anchor: &AnchorOpts,
+) -> anyhow::Result<LeavesStatusResponse> {
+    let status = match (anchor.address, anchor.tree_id, anchor.pallet_id) {
+        (Some(address), _, _) => {
+            client.evm_leaves_status(anchor.chain_id, address).await?
+        }
+        (None, Some(tree_id), Some(pallet_id)) => {
+            client
+                .substrate_leaves_status(anchor.chain_id, tree_id, pallet_id)
+                .await?
+        }
+        _ => {
+            anyhow::bail!("An anchor needs an address, or a tree and pallet id")
+        }
+    };
+    Ok(status)
+}
+
+/// Parses the number of seconds between two checks, which can not be zero.
+fn parse_interval(value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(0) => Err(String::from("the interval must be at least one second")),
+        Ok(interval) => Ok(interval),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Writes a summary of the configuration of the relayer.
+pub fn write_info(
+    info: &RelayerInfo,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    writeln!(
+        out,
+        "Features: data-query {}, governance-relay {}, private-tx-relay {}",
+        on_off(info.features.data_query),
+        on_off(info.features.governance_relay),
+        on_off(info.features.private_tx_relay),
+    )?;
+    let mut evm: Vec<_> = info.evm.values().collect();
+    evm.sort_by_key(|chain| chain.chain_id);
+    for chain in evm {
+        writeln!(
+            out,
+            "EVM {} ({}): {}",
+            chain.name,
+            chain.chain_id,
+            if chain.enabled { "enabled" } else { "disabled" }
+        )?;
+        for contract in &chain.contracts {
+            write!(out, "  {} {:?}", contract.contract, contract.address)?;
+            match &contract.withdraw_config {
+                Some(withdraw) => writeln!(
+                    out,
+                    " (fee: {}%, gas limit: {})",
+                    withdraw.withdraw_fee_percentage * 100.0,
+                    withdraw.withdraw_gaslimit
+                )?,
+                None => writeln!(out)?,
+            }
+        }
+    }
+    let mut substrate: Vec<_> = info.substrate.values().collect();
+    substrate.sort_by_key(|chain| chain.chain_id);
+    for chain in substrate {
+        writeln!(
+            out,
+            "Substrate {} ({}): {}",
+            chain.name,
+            chain.chain_id,
+            if chain.enabled { "enabled" } else { "disabled" }
+        )?;
+        for pallet in &chain.pallets {
+            writeln!(out, "  {}", pallet.pallet)?;
+        }
+    }
+    Ok(())
+}
+
+/// Describes the sync status of a leaves cache in one line.
+pub fn sync_status_line(status: &LeavesStatusResponse) -> String {
+    let cached = format!(
+        "{} leaves up to block {}",
+        status.leaves_count, status.last_queried_block
+    );
+    match &status.sync_status {
+        Some(status) => format!(
+            "{cached}, {} at block {} ({} of {} leaves, {} refetched)",
+            if status.healthy {
+                "healthy"
+            } else {
+                "unhealthy"
+            },
+            status.checked_at_block,
+            status.consistency.cached,
+            status.consistency.expected,
+            status.refetched,
+        ),
+        None => format!("{cached}, not checked yet"),
+    }
+}
+
+/// Hex encodes the cached items, `0x` prefixed.
+fn hex_items(items: &[Vec<u8>]) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| format!("0x{}", hex::encode(item)))
+        .collect()
+}
+
+/// Writes a dump of cached items to a file, as pretty printed JSON.
+fn write_dump(path: &Path, dump: &serde_json::Value) -> anyhow::Result<()> {
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, dump)?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use webb_relayer_cli::Opts;
+use webb_relayer_client::RelayerClient;
+
+/// The main entry point for the relayer client.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments.
+#[paw::main]
+#[tokio::main]
+async fn main(args: Opts) -> anyhow::Result<()> {
+    let client = RelayerClient::new(&args.url)?;
+    let mut stdout = std::io::stdout();
+    webb_relayer_cli::run(&client, &args.command, &mut stdout).await
+}
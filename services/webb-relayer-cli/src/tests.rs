@@ -0,0 +1,181 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::json;
+use structopt::StructOpt;
+use webb_relayer_client::{LeavesCacheResponse, LeavesStatusResponse};
+
+use super::*;
+
+const ADDRESS: &str = "0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df";
+
+#[test]
+fn anchors_are_evm_contracts_or_substrate_trees() {
+    let opts = Opts::from_iter_safe([
+        "webb-relayer-cli",
+        "leaves",
+        "--chain-id",
+        "5001",
+        "--address",
+        ADDRESS,
+        "-o",
+        "leaves.json",
+    ])
+    .unwrap();
+    match opts.command {
+        Command::Leaves { anchor, output } => {
+            assert_eq!(anchor.chain_id, 5001);
+            assert_eq!(anchor.address, Some(ADDRESS.parse().unwrap()));
+            assert_eq!(output, PathBuf::from("leaves.json"));
+        }
+        _ => panic!("expected the leaves command"),
+    }
+
+    let opts = Opts::from_iter_safe([
+        "webb-relayer-cli",
+        "sync-status",
+        "--chain-id",
+        "1080",
+        "--tree-id",
+        "5",
+        "--pallet-id",
+        "44",
+    ])
+    .unwrap();
+    match opts.command {
+        Command::SyncStatus { anchor, interval } => {
+            assert_eq!(anchor.tree_id, Some(5));
+            assert_eq!(anchor.pallet_id, Some(44));
+            assert_eq!(interval, 10);
+        }
+        _ => panic!("expected the sync-status command"),
+    }
+
+    let zero_interval = [
+        "webb-relayer-cli",
+        "sync-status",
+        "--chain-id",
+        "1080",
+        "--address",
+        ADDRESS,
+        "--interval",
+        "0",
+    ];
+    assert!(Opts::from_iter_safe(zero_interval).is_err());
+
+    let missing_anchor =
+        ["webb-relayer-cli", "sync-status", "--chain-id", "1080"];
+    assert!(Opts::from_iter_safe(missing_anchor).is_err());
+    let both_anchors = [
+        "webb-relayer-cli",
+        "sync-status",
+        "--chain-id",
+        "1080",
+        "--address",
+        ADDRESS,
+        "--tree-id",
+        "5",
+        "--pallet-id",
+        "44",
+    ];
+    assert!(Opts::from_iter_safe(both_anchors).is_err());
+}
+
+#[test]
+fn info_is_summarized() {
+    let info: RelayerInfo = serde_json::from_value(json!({
+        "evm": {
+            "5001": {
+                "name": "hermes",
+                "enabled": true,
+                "chainId": 5001,
+                "beneficiary": ADDRESS,
+                "contracts": [{
+                    "contract": "VAnchor",
+                    "address": ADDRESS,
+                    "deployedAt": 1,
+                    "withdrawConfig": {
+                        "withdrawFeePercentage": 0.05,
+                        "withdrawGaslimit": "0x5b8d80",
+                    },
+                }],
+            },
+        },
+        "substrate": {
+            "1080": {
+                "name": "localnode",
+                "enabled": false,
+                "chainId": 1080,
+                "beneficiary": null,
+                "pallets": [{ "pallet": "VAnchorBn254" }],
+            },
+        },
+        "features": {
+            "data-query": true,
+            "governance-relay": false,
+            "private-tx-relay": true,
+        },
+    }))
+    .unwrap();
+    let mut out = Vec::new();
+    write_info(&info, &mut out).unwrap();
+    let expected = format!(
+        "Features: data-query on, governance-relay off, \
+         private-tx-relay on\n\
+         EVM hermes (5001): enabled\n  \
+         VAnchor {ADDRESS} (fee: 5%, gas limit: 6000000)\n\
+         Substrate localnode (1080): disabled\n  \
+         VAnchorBn254\n"
+    );
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn sync_status_is_described() {
+    let leaves: LeavesCacheResponse = serde_json::from_value(json!({
+        "leaves": [[1, 2], [3, 4]],
+        "lastQueriedBlock": 42,
+    }))
+    .unwrap();
+    assert_eq!(hex_items(&leaves.leaves), vec!["0x0102", "0x0304"]);
+
+    let mut status: LeavesStatusResponse = serde_json::from_value(json!({
+        "leavesCount": 2,
+        "lastQueriedBlock": 42,
+    }))
+    .unwrap();
+    assert_eq!(
+        sync_status_line(&status),
+        "2 leaves up to block 42, not checked yet"
+    );
+
+    status.sync_status = serde_json::from_value(json!({
+        "checkedAtBlock": 40,
+        "consistency": {
+            "cached": 1,
+            "expected": 2,
+            "missing": [[1, 1]],
+            "duplicates": [],
+            "unexpected": [],
+        },
+        "refetched": 1,
+        "healthy": true,
+    }))
+    .unwrap();
+    assert_eq!(
+        sync_status_line(&status),
+        "2 leaves up to block 42, healthy at block 40 \
+         (1 of 2 leaves, 1 refetched)"
+    );
+}
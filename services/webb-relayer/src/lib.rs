@@ -88,5 +88,9 @@
 
 /// A module for starting long-running tasks for event watching.
 pub mod service;
+/// A module for exporting and importing leaves cache snapshots.
+pub mod snapshot;
+/// A module for serving the relayer API over TLS.
+pub mod tls;
 
 pub use webb_relayer_utils::{Error, Result};
@@ -21,8 +21,18 @@ use std::time::Duration;
 use tokio::signal::unix;
 use tokio::time;
 
-use webb_relayer_config::cli::{create_store, load_config, setup_logger, Opts};
+use anyhow::Context;
+use webb_relayer_config::cli::{
+    create_store, load_config, setup_logger, Command, DeadLetterCommand, Opts,
+    SnapshotCommand,
+};
 use webb_relayer_context::RelayerContext;
+use webb_relayer_store::dead_letter::{
+    discard_dead_letter, list_dead_letters, retry_dead_letter,
+};
+use webb_relayer_store::job::{fail_unfinished_jobs, prune_jobs};
+use webb_relayer_store::sled::SledQueueKey;
+use webb_relayer_store::EventHashStore;
 
 /// The main entry point for the relayer.
 ///
@@ -51,7 +61,11 @@ async fn main(args: Opts) -> anyhow::Result<()> {
     let ctx = RelayerContext::new(config);
 
     // persistent storage for the relayer
-    let store = create_store(&args).await?;
+    let store = create_store(&args, &ctx.config.store).await?;
+    // run the maintenance command, if any, instead of the relayer.
+    if let Some(command) = &args.command {
+        return run_command(&ctx, &store, command).await;
+    }
     let cloned_store = store.clone();
     let cloned_ctx = ctx.clone();
     // metric for data stored which is determined every 1 hour
@@ -64,15 +78,89 @@ async fn main(args: Opts) -> anyhow::Result<()> {
             cloned_ctx
                 .metrics
                 .total_amount_of_data_stored
-                .set(cloned_store.get_data_stored_size() as f64)
+                .set(cloned_store.get_data_stored_size() as f64);
+            // and the size of every tree
+            match cloned_store.get_tree_sizes() {
+                Ok(sizes) => {
+                    for (tree, entries) in sizes {
+                        cloned_ctx
+                            .metrics
+                            .store_tree_entries
+                            .with_label_values(&[tree.as_str()])
+                            .set(entries as f64);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to get the store tree sizes: {}", e)
+                }
+            }
         }
     });
+    // prune the old event hashes, if enabled.
+    let prune_task_handle = ctx.config.store.event_hashes_depth.map(|depth| {
+        let store = store.clone();
+        let metrics = ctx.metrics.clone();
+        let period = ctx.config.store.event_hashes_prune_interval.max(1);
+        tokio::task::spawn(async move {
+            let mut prune_interval =
+                time::interval(Duration::from_secs(period));
+            loop {
+                prune_interval.tick().await;
+                match store.prune_events(depth) {
+                    Ok(pruned) => {
+                        tracing::debug!(pruned, "Pruned the old event hashes");
+                        metrics.pruned_event_hashes.inc_by(pruned as f64);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to prune the event hashes: {}",
+                            e
+                        )
+                    }
+                }
+            }
+        })
+    });
+
+    // the jobs of a previous run are not relayed anymore, fail them so their
+    // clients stop waiting for them.
+    if ctx.config.features.private_tx_relay {
+        let now = webb_relayer_utils::unix_timestamp();
+        let failed = fail_unfinished_jobs(&store, now)?;
+        if failed > 0 {
+            tracing::warn!(failed, "Failed the relay jobs of the previous run");
+        }
+    }
+    // prune the finished relay jobs once they are old enough.
+    let jobs_prune_task_handle = {
+        let store = store.clone();
+        let jobs = ctx.config.jobs;
+        tokio::task::spawn(async move {
+            let mut prune_interval =
+                time::interval(Duration::from_secs(jobs.prune_interval.max(1)));
+            loop {
+                prune_interval.tick().await;
+                let now = webb_relayer_utils::unix_timestamp();
+                let before = now.saturating_sub(jobs.retention);
+                match prune_jobs(&store, before) {
+                    Ok(pruned) => {
+                        tracing::debug!(pruned, "Pruned the old relay jobs");
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to prune the relay jobs: {}", e)
+                    }
+                }
+            }
+        })
+    };
 
     // the build_web_relayer command sets up routing (endpoint queries / requests mapped to handled code)
     // so clients can interact with the relayer
-    let (addr, server) =
+    let (addrs, server) =
         webb_relayer::service::build_web_services(ctx.clone(), store.clone())?;
-    tracing::info!("Starting the server on {}", addr);
+    for addr in &addrs {
+        tracing::info!("Starting the server on {}", addr);
+    }
     // start the server.
     let server_handle = tokio::spawn(server);
     // start all background services.
@@ -103,6 +191,12 @@ async fn main(args: Opts) -> anyhow::Result<()> {
         server_handle.abort();
         // abort get sled storage data task
         sled_metric_task_handle.abort();
+        // and the event hashes pruning task
+        if let Some(handle) = &prune_task_handle {
+            handle.abort();
+        }
+        // and the relay jobs pruning task
+        jobs_prune_task_handle.abort();
         std::thread::sleep(std::time::Duration::from_millis(300));
         tracing::info!("Clean Exit ..");
     };
@@ -122,3 +216,84 @@ async fn main(args: Opts) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// Runs a maintenance command of the relayer.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store (the backend is selected in the configuration)
+/// * `command` - The command to run.
+async fn run_command(
+    ctx: &RelayerContext,
+    store: &webb_relayer::service::Store,
+    command: &Command,
+) -> anyhow::Result<()> {
+    match command {
+        Command::Snapshot(SnapshotCommand::Export {
+            resource_ids,
+            output,
+        }) => {
+            let resource_ids = resource_ids
+                .iter()
+                .map(|r| webb_relayer::snapshot::parse_resource_id(r))
+                .collect::<Result<Vec<_>, _>>()?;
+            webb_relayer::snapshot::export_snapshot(
+                ctx,
+                store,
+                &resource_ids,
+                output,
+            )
+            .await?;
+            tracing::info!("Snapshot written to {}", output.display());
+        }
+        Command::Snapshot(SnapshotCommand::Import { input }) => {
+            webb_relayer::snapshot::import_snapshot(ctx, store, input).await?;
+            tracing::info!("Snapshot imported from {}", input.display());
+        }
+        Command::DeadLetters(DeadLetterCommand::List { queue }) => {
+            let letters = list_dead_letters(store, parse_queue(queue)?)?;
+            let letters = letters
+                .into_iter()
+                .map(|l| {
+                    serde_json::json!({
+                        "id": format!("0x{}", hex::encode(l.id)),
+                        "item": l.item,
+                        "reason": l.reason,
+                        "attempts": l.attempts,
+                        "firstFailedAt": l.first_failed_at,
+                        "lastFailedAt": l.last_failed_at,
+                        "retriedAt": l.retried_at,
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&letters)?);
+        }
+        Command::DeadLetters(DeadLetterCommand::Retry { queue, id }) => {
+            let id = parse_dead_letter_id(id)?;
+            if !retry_dead_letter(store, parse_queue(queue)?, &id)? {
+                anyhow::bail!("Dead letter not found");
+            }
+            tracing::info!("Dead letter sent back to {}", queue);
+        }
+        Command::DeadLetters(DeadLetterCommand::Discard { queue, id }) => {
+            let id = parse_dead_letter_id(id)?;
+            if !discard_dead_letter(store, parse_queue(queue)?, &id)? {
+                anyhow::bail!("Dead letter not found");
+            }
+            tracing::info!("Dead letter discarded from {}", queue);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a queue name, like `evm_tx_5001`.
+fn parse_queue(queue: &str) -> anyhow::Result<SledQueueKey> {
+    SledQueueKey::from_queue_name(queue)
+        .with_context(|| format!("Unknown queue: {queue}"))
+}
+
+/// Parses the hex encoded id of a dead letter.
+fn parse_dead_letter_id(id: &str) -> anyhow::Result<Vec<u8>> {
+    hex::decode(id.trim_start_matches("0x")).context("Invalid dead letter id")
+}
@@ -22,15 +22,23 @@
 //! Services handle keeping up to date with the configured chains.
 
 use std::collections::HashSet;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use webb::evm::ethers::providers;
 
 use webb::substrate::subxt::config::{PolkadotConfig, SubstrateConfig};
-use webb::substrate::subxt::{tx::PairSigner, OnlineClient};
-use webb_event_watcher_traits::evm::{BridgeWatcher, EventWatcher};
+use webb::substrate::subxt::OnlineClient;
+use webb_event_watcher_traits::evm::{
+    BridgeWatcher, EventHandlerFor, EventWatcher,
+};
 use webb_event_watcher_traits::substrate::SubstrateBridgeWatcher;
 use webb_event_watcher_traits::SubstrateEventWatcher;
 use webb_ew_dkg::{DKGGovernorWatcher, ProposalHandlerWatcher};
+use webb_ew_evm::anchor_handler::AnchorHandlerProposalHandler;
+use webb_ew_evm::leaves_checker::{run_leaves_checker, LeavesSource};
 use webb_ew_evm::open_vanchor::{
     OpenVAnchorDepositHandler, OpenVAnchorLeavesHandler,
 };
@@ -38,30 +46,41 @@ use webb_ew_evm::signature_bridge_watcher::{
     SignatureBridgeContractWatcher, SignatureBridgeContractWrapper,
     SignatureBridgeGovernanceOwnershipTransferredHandler,
 };
+use webb_ew_evm::token_wrapper::TokenWrapperProposalHandler;
 use webb_ew_evm::{
-    vanchor::*, OpenVAnchorContractWatcher, OpenVAnchorContractWrapper,
+    vanchor::*, AnchorHandlerContractWatcher, AnchorHandlerContractWrapper,
+    OpenVAnchorContractWatcher, OpenVAnchorContractWrapper,
+    TokenWrapperContractWatcher, TokenWrapperContractWrapper,
     VAnchorContractWatcher, VAnchorContractWrapper,
 };
 use webb_ew_substrate::{
+    InkBridgeEventWatcher, ParachainBridgeEventWatcher,
     SubstrateBridgeEventWatcher, SubstrateVAnchorLeavesWatcher,
     SubstrateVAnchorWatcher,
 };
 use webb_relayer_config::anchor::LinkedAnchorConfig;
 use webb_relayer_config::evm::{
-    Contract, SignatureBridgeContractConfig, VAnchorContractConfig,
+    Contract, GovernanceContractConfig, SignatureBridgeContractConfig,
+    VAnchorContractConfig,
 };
+use webb_relayer_config::server::CorsConfig;
 use webb_relayer_config::signing_backend::ProposalSigningBackendConfig;
 use webb_relayer_config::substrate::{
-    DKGPalletConfig, DKGProposalHandlerPalletConfig, Pallet,
-    SignatureBridgePalletConfig, SubstrateRuntime, VAnchorBn254PalletConfig,
+    DKGPalletConfig, DKGProposalHandlerPalletConfig,
+    InkSignatureBridgeContractConfig, Pallet, SignatureBridgePalletConfig,
+    SubstrateChainType, SubstrateRuntime, VAnchorBn254PalletConfig,
 };
 
 use webb_ew_evm::vanchor::vanchor_encrypted_outputs_handler::VAnchorEncryptedOutputHandler;
 use webb_proposal_signing_backends::*;
+use webb_relayer_context::rate_limit::{RouteGroup, WsConnectionGuard};
 use webb_relayer_context::RelayerContext;
-use webb_relayer_store::SledStore;
+use webb_relayer_handler_utils::WebSocketQuery;
+use webb_relayer_store::AnyStore;
 use webb_relayer_tx_queue::{evm::TxQueue, substrate::SubstrateTxQueue};
 
+use crate::tls;
+
 /// Type alias for providers
 pub type Client = providers::Provider<providers::Http>;
 /// Type alias for the DKG DefaultConfig
@@ -69,12 +88,18 @@ pub type DkgClient = OnlineClient<PolkadotConfig>;
 /// Type alias for the WebbProtocol DefaultConfig
 pub type WebbProtocolClient = OnlineClient<SubstrateConfig>;
 /// Type alias for [Sled](https://sled.rs)-based database store
-pub type Store = webb_relayer_store::sled::SledStore;
+pub type Store = webb_relayer_store::AnyStore;
+
+/// The maximum size (in bytes) of a relay command submitted over HTTP.
+const MAX_COMMAND_BODY_SIZE: u64 = 256 * 1024;
+/// The address of the reverse proxy whose `X-Forwarded-For` header is trusted
+/// to identify the clients.
+const PROXY_ADDR: [u8; 4] = [127, 0, 0, 1];
 
 /// Sets up the web socket server for the relayer,  routing (endpoint queries / requests mapped to handled code) and
 /// instantiates the database store. Allows clients to interact with the relayer.
 ///
-/// Returns `Ok((addr, server))` on success, or `Err(anyhow::Error)` on failure.
+/// Returns `Ok((addrs, server))` on success, or `Err(anyhow::Error)` on failure.
 ///
 /// # Arguments
 ///
@@ -82,45 +107,163 @@ pub type Store = webb_relayer_store::sled::SledStore;
 /// * `store` - [Sled](https://sled.rs)-based database store
 pub fn build_web_services(
     ctx: RelayerContext,
-    store: webb_relayer_store::sled::SledStore,
+    store: webb_relayer_store::AnyStore,
 ) -> crate::Result<(
-    std::net::SocketAddr,
+    Vec<SocketAddr>,
     impl core::future::Future<Output = ()> + 'static,
 )> {
     use warp::Filter;
 
     let port = ctx.config.port;
+    let service = build_routes(&ctx, store.clone())?;
+    let default_listen = [SocketAddr::from(([0, 0, 0, 0], port))];
+    let listen = if ctx.config.server.listen.is_empty() {
+        &default_listen[..]
+    } else {
+        &ctx.config.server.listen[..]
+    };
+    let mut addrs = Vec::with_capacity(listen.len());
+    let mut servers = Vec::new();
+    for listen_addr in listen {
+        let (addr, server) = serve(service.clone(), *listen_addr, &ctx)?;
+        addrs.push(addr);
+        servers.push(server);
+    }
+    if let Some(admin_port) = ctx.config.admin.port {
+        if ctx.config.admin.enabled() {
+            // the admin API listens on the same interfaces, with its own port.
+            let admin_service = warp::path("api")
+                .and(warp::path("v1"))
+                .and(build_admin_routes(ctx.clone(), store))
+                .recover(webb_relayer_handlers::handle_rejection)
+                .with(warp::trace::request());
+            let admin_listen: HashSet<_> = listen
+                .iter()
+                .map(|addr| SocketAddr::new(addr.ip(), admin_port))
+                .collect();
+            for listen_addr in admin_listen {
+                let (admin_addr, admin_server) =
+                    serve(admin_service.clone(), listen_addr, &ctx)?;
+                tracing::info!("Admin API listening on {}", admin_addr);
+                servers.push(admin_server);
+            }
+        }
+    }
+    let server = async move {
+        let handles: Vec<_> = servers.into_iter().map(tokio::spawn).collect();
+        for handle in handles {
+            if let Err(e) = handle.await {
+                tracing::error!("Relayer API server failed: {}", e);
+            }
+        }
+    };
+    Ok((addrs, server))
+}
+
+/// Builds the routes of the relayer API and of its websocket, with the
+/// admin API when it is served on the same port.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - [Sled](https://sled.rs)-based database store
+fn build_routes(
+    ctx: &RelayerContext,
+    store: webb_relayer_store::AnyStore,
+) -> crate::Result<
+    impl warp::Filter<Extract = (impl warp::Reply,), Error = warp::Rejection>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+> {
+    use warp::Filter;
+
     let ctx_arc = Arc::new(ctx.clone());
     let ctx_filter = warp::any().map(move || Arc::clone(&ctx_arc)).boxed();
 
+    // the rate limits of every group of routes, they are checked once the path of
+    // a request is matched, so that only the requests to the group are counted.
+    let data_limit = rate_limit(Arc::new(ctx.clone()), RouteGroup::Data);
+    let relay_limit = rate_limit(Arc::new(ctx.clone()), RouteGroup::Relay);
+    let general_limit = rate_limit(Arc::new(ctx.clone()), RouteGroup::General);
+    let ws_connection_limit = ctx_filter
+        .clone()
+        .and(real_ip(vec![PROXY_ADDR.into()]))
+        .and_then(webb_relayer_handlers::check_ws_connection_limit)
+        .boxed();
+
     // the websocket server for users to submit relay transaction requests
+    let ws_store = Arc::new(store.clone());
+    let ws_store_filter =
+        warp::any().map(move || Arc::clone(&ws_store)).boxed();
     let ws_filter = warp::path("ws")
         .and(warp::ws())
+        .and(relay_limit.clone())
+        .and(ws_connection_limit)
         .and(ctx_filter.clone())
-        .map(|ws: warp::ws::Ws, ctx: Arc<RelayerContext>| {
-            ws.on_upgrade(|socket| async move {
-                let _ = webb_relayer_handlers::accept_connection(
-                    ctx.as_ref(),
-                    socket,
-                )
-                .await;
-            })
-        })
+        .and(ws_store_filter)
+        .and(warp::query::<WebSocketQuery>())
+        .map(
+            |ws: warp::ws::Ws,
+             connection: WsConnectionGuard,
+             ctx: Arc<RelayerContext>,
+             store: Arc<webb_relayer_store::AnyStore>,
+             query: WebSocketQuery| {
+                ws.on_upgrade(move |socket| async move {
+                    // the connection is counted until it is closed.
+                    let _connection = connection;
+                    let _ = webb_relayer_handlers::accept_connection(
+                        ctx.as_ref(),
+                        store,
+                        query.version,
+                        socket,
+                    )
+                    .await;
+                })
+            },
+        )
+        .boxed();
+
+    // Define the handling of the relay transaction requests submitted over HTTP, they are
+    // relayed in the background as jobs, whose latest status is kept in the store.
+    let jobs_store = Arc::new(store.clone());
+    let store_filter = warp::any().map(move || Arc::clone(&jobs_store)).boxed();
+    let send_filter = warp::path("send")
+        .and(warp::post())
+        .and(warp::path::end())
+        .and(relay_limit.clone())
+        .and(ctx_filter.clone())
+        .and(store_filter.clone())
+        .and(warp::body::content_length_limit(MAX_COMMAND_BODY_SIZE))
+        .and(warp::body::json())
+        .and_then(webb_relayer_handlers::handle_relay_job_submission)
+        .boxed();
+    let job_status_filter = warp::path("jobs")
+        .and(warp::get())
+        .and(relay_limit)
+        .and(store_filter)
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_relay_job_status)
         .boxed();
+    let jobs_filter = send_filter.or(job_status_filter).boxed();
 
     // get the ip of the caller.
-    let proxy_addr = [127, 0, 0, 1].into();
+    let proxy_addr = PROXY_ADDR.into();
 
     // First check the x-forwarded-for with 'real_ip' for reverse proxy setups
     // This code identifies the client's ip address and sends it back to them
     // TODO: PUT THE URL FOR THIS ENDPOINT HERE.
     let ip_filter = warp::path("ip")
         .and(warp::get())
-        .and(warp_real_ip::real_ip(vec![proxy_addr]))
+        .and(general_limit.clone())
+        .and(real_ip(vec![proxy_addr]))
         .and_then(webb_relayer_handlers::handle_ip_info)
         .or(warp::path("ip")
             .and(warp::get())
-            .and(warp::addr::remote())
+            .and(general_limit.clone())
+            .and(remote_addr())
             .and_then(webb_relayer_handlers::handle_socket_info))
         .boxed();
 
@@ -128,6 +271,7 @@ pub fn build_web_services(
     // TODO: PUT THE URL FOR THIS ENDPOINT HERE.
     let info_filter = warp::path("info")
         .and(warp::get())
+        .and(general_limit.clone())
         .and(ctx_filter)
         .and_then(webb_relayer_handlers::handle_relayer_info)
         .boxed();
@@ -140,6 +284,7 @@ pub fn build_web_services(
     let ctx_arc = Arc::new(ctx.clone());
     let leaves_cache_filter_evm = warp::path("leaves")
         .and(warp::path("evm"))
+        .and(data_limit.clone())
         .and(store_filter)
         .and(warp::path::param())
         .and(warp::path::param())
@@ -152,6 +297,31 @@ pub fn build_web_services(
             )
         })
         .boxed();
+    // the state of the leaves cache, without the leaves, for the clients
+    // that follow the sync of an anchor.
+    let status_store = Arc::new(store.clone());
+    let store_filter =
+        warp::any().map(move || Arc::clone(&status_store)).boxed();
+    let ctx_arc = Arc::new(ctx.clone());
+    let leaves_status_filter_evm = warp::path("leaves")
+        .and(warp::path("evm"))
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        // limited once the route matched, so that the leaves requests are
+        // not counted twice.
+        .and(data_limit.clone())
+        .and(store_filter)
+        .and_then(move |chain_id, contract, store| {
+            webb_relayer_handlers::handle_leaves_status_evm(
+                store,
+                chain_id,
+                contract,
+                Arc::clone(&ctx_arc),
+            )
+        })
+        .boxed();
     // leaf api handler for substrate
     let substrate_store = Arc::new(store.clone());
     let store_filter = warp::any()
@@ -162,6 +332,7 @@ pub fn build_web_services(
     // TODO: PUT THE URL FOR THIS ENDPOINT HERE.
     let leaves_cache_filter_substrate = warp::path("leaves")
         .and(warp::path("substrate"))
+        .and(data_limit.clone())
         .and(store_filter)
         .and(warp::path::param())
         .and(warp::path::param())
@@ -177,11 +348,67 @@ pub fn build_web_services(
         })
         .boxed();
 
+    let status_store = Arc::new(store.clone());
+    let store_filter =
+        warp::any().map(move || Arc::clone(&status_store)).boxed();
+    let ctx_arc = Arc::new(ctx.clone());
+    let leaves_status_filter_substrate = warp::path("leaves")
+        .and(warp::path("substrate"))
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        // limited once the route matched, so that the leaves requests are
+        // not counted twice.
+        .and(data_limit.clone())
+        .and(store_filter)
+        .and_then(move |chain_id, tree_id, pallet_id, store| {
+            webb_relayer_handlers::handle_leaves_status_substrate(
+                store,
+                chain_id,
+                tree_id,
+                pallet_id,
+                Arc::clone(&ctx_arc),
+            )
+        })
+        .boxed();
+
+    // Define the handling of a request for the proposals this relayer submitted to the DKG for signing.
+    let proposals_store = Arc::new(store.clone());
+    let store_filter = warp::any()
+        .map(move || Arc::clone(&proposals_store))
+        .boxed();
+    let proposals_filter = warp::path("proposals")
+        .and(warp::get())
+        .and(general_limit.clone())
+        .and(store_filter)
+        .and(warp::query::<webb_relayer_handlers::ProposalsQuery>())
+        .and_then(webb_relayer_handlers::handle_proposals)
+        .boxed();
+
+    // the admin API is served on its own port if one is configured,
+    // otherwise with the other routes, as long as an admin token is set.
+    let admin_on_main_port =
+        ctx.config.admin.enabled() && ctx.config.admin.port.is_none();
+    let admin_filter = warp::any()
+        .and_then(move || async move {
+            if admin_on_main_port {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(build_admin_routes(ctx.clone(), store.clone()))
+        .boxed();
+
     let evm_store = Arc::new(store);
     let store_filter = warp::any().map(move || Arc::clone(&evm_store)).boxed();
     let ctx_arc = Arc::new(ctx.clone());
     let encrypted_output_cache_filter_evm = warp::path("encrypted_outputs")
         .and(warp::path("evm"))
+        .and(data_limit)
         .and(store_filter)
         .and(warp::path::param())
         .and(warp::path::param())
@@ -197,32 +424,361 @@ pub fn build_web_services(
 
     let relayer_metrics_info = warp::path("metrics")
         .and(warp::get())
+        .and(general_limit.clone())
         .and_then(webb_relayer_handlers::handle_metric_info)
         .boxed();
 
+    // Define the handling of a request for the OpenAPI document of the API.
+    let openapi_filter = warp::path("openapi.json")
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(general_limit)
+        .and_then(webb_relayer_handlers::handle_openapi)
+        .boxed();
+
     // Code that will map the request handlers above to a defined http endpoint.
     let routes = ip_filter
         .or(info_filter)
+        // before the leaves, whose routes do not end after their params.
+        .or(leaves_status_filter_evm)
+        .or(leaves_status_filter_substrate)
         .or(leaves_cache_filter_evm)
         .or(leaves_cache_filter_substrate)
         .or(encrypted_output_cache_filter_evm)
         .or(relayer_metrics_info)
+        .or(openapi_filter)
+        .or(proposals_filter)
+        .or(jobs_filter)
+        .or(admin_filter)
         .boxed(); // will add more routes here.
     let http_filter =
         warp::path("api").and(warp::path("v1")).and(routes).boxed();
 
-    let cors = warp::cors().allow_any_origin();
+    let cors = build_cors(&ctx.config.server.cors)?;
     let service = http_filter
         .or(ws_filter)
+        .recover(webb_relayer_handlers::handle_rejection)
         .with(cors)
         .with(warp::trace::request());
+    Ok(service)
+}
+
+/// A server listening on one address, until the relayer shuts down.
+type ServerFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Serves the given routes on the given address, over TLS if it is configured.
+///
+/// The TLS certificate is reloaded whenever the certificate or key files
+/// change, the new handshakes are made with it while the connections open at
+/// that time keep the previous one.
+///
+/// Returns the address the server listens on, and the server itself.
+///
+/// # Arguments
+///
+/// * `filter` - The routes to serve
+/// * `addr` - The address to listen on
+/// * `ctx` - RelayContext reference that holds the configuration
+fn serve<F, R>(
+    filter: F,
+    addr: SocketAddr,
+    ctx: &RelayerContext,
+) -> crate::Result<(SocketAddr, ServerFuture)>
+where
+    F: warp::Filter<Extract = (R,), Error = warp::Rejection>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    R: warp::Reply,
+{
     let mut shutdown_signal = ctx.shutdown_signal();
     let shutdown_signal = async move {
         shutdown_signal.recv().await;
     };
-    warp::serve(service)
-        .try_bind_with_graceful_shutdown(([0, 0, 0, 0], port), shutdown_signal)
-        .map_err(Into::into)
+    let tls = match ctx.config.server.tls.clone() {
+        Some(tls) => tls,
+        None => {
+            let (addr, server) = warp::serve(filter)
+                .try_bind_with_graceful_shutdown(addr, shutdown_signal)?;
+            return Ok((addr, Box::pin(server)));
+        }
+    };
+    let resolver =
+        Arc::new(tls::CertResolver::new(tls::load_certified_key(&tls)?));
+    let (addr, server) =
+        tls::serve(filter, addr, resolver.clone(), shutdown_signal)?;
+    let server = async move {
+        let reload = tokio::spawn(tls::reload_certificate(tls, resolver));
+        server.await;
+        reload.abort();
+    };
+    Ok((addr, Box::pin(server)))
+}
+
+/// Builds the CORS policy of the relayer API from its configuration.
+fn build_cors(config: &CorsConfig) -> crate::Result<warp::cors::Builder> {
+    // warp panics on invalid origins, methods and headers, so they are checked first.
+    let mut cors = warp::cors();
+    if config.allows_any_origin() {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &config.allowed_origins {
+            let valid = url::Url::parse(origin)
+                .map(|url| url.has_host() && url.path() == "/")
+                .unwrap_or(false);
+            if !valid {
+                return Err(crate::Error::Generic(
+                    "CORS origins must be in the form `scheme://host[:port]`",
+                ));
+            }
+        }
+        cors = cors.allow_origins(
+            config
+                .allowed_origins
+                .iter()
+                .map(|origin| origin.trim_end_matches('/')),
+        );
+    }
+    if !config.allowed_methods.is_empty() {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| warp::http::Method::from_bytes(method.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| crate::Error::Generic("Invalid CORS method"))?;
+        cors = cors.allow_methods(methods);
+    }
+    if !config.allowed_headers.is_empty() {
+        let headers = config
+            .allowed_headers
+            .iter()
+            .map(|header| {
+                warp::http::header::HeaderName::from_bytes(header.as_bytes())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| crate::Error::Generic("Invalid CORS header"))?;
+        cors = cors.allow_headers(headers);
+    }
+    Ok(cors)
+}
+
+/// Builds a filter extracting the address of the client of a request, from
+/// both the plain and the TLS listeners.
+fn remote_addr() -> impl warp::Filter<
+    Extract = (Option<SocketAddr>,),
+    Error = std::convert::Infallible,
+> + Clone {
+    use warp::Filter;
+
+    warp::addr::remote()
+        .and(warp::ext::optional::<tls::RemoteAddr>())
+        .map(
+            |addr: Option<SocketAddr>, tls_addr: Option<tls::RemoteAddr>| {
+                addr.or(tls_addr.map(|tls_addr| tls_addr.0))
+            },
+        )
+}
+
+/// Builds a filter extracting the IP address of the client of a request, read
+/// from its forwarding headers when it is sent by one of the trusted proxies,
+/// the same way as [`warp_real_ip::real_ip`] (which only knows the clients of
+/// the plain listeners).
+fn real_ip(
+    trusted_proxies: Vec<IpAddr>,
+) -> impl warp::Filter<
+    Extract = (Option<IpAddr>,),
+    Error = std::convert::Infallible,
+> + Clone {
+    use warp::Filter;
+
+    remote_addr().and(warp_real_ip::get_forwarded_for()).map(
+        move |addr: Option<SocketAddr>, forwarded_for: Vec<IpAddr>| {
+            addr.map(|addr| {
+                let hops = forwarded_for
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(addr.ip()));
+                for hop in hops.rev() {
+                    if !trusted_proxies.contains(&hop) {
+                        return hop;
+                    }
+                }
+                // all the hops are trusted, the first one is the client.
+                forwarded_for.first().copied().unwrap_or(addr.ip())
+            })
+        },
+    )
+}
+
+/// Builds a filter that rejects the requests over the rate limit of their
+/// client for the given group of routes.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `group` - The group of routes limited by the filter
+fn rate_limit(
+    ctx: Arc<RelayerContext>,
+    group: RouteGroup,
+) -> warp::filters::BoxedFilter<()> {
+    use warp::Filter;
+
+    warp::any()
+        .map(move || (Arc::clone(&ctx), group))
+        .untuple_one()
+        .and(real_ip(vec![PROXY_ADDR.into()]))
+        .and_then(webb_relayer_handlers::check_rate_limit)
+        .untuple_one()
+        .boxed()
+}
+
+/// Builds the routes of the admin API, under `/admin`.
+///
+/// Every request must carry the admin token in its `Authorization: Bearer <token>` header.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `store` - database store (the backend is selected in the configuration)
+fn build_admin_routes(
+    ctx: RelayerContext,
+    store: webb_relayer_store::AnyStore,
+) -> impl warp::Filter<Extract = (impl warp::Reply,), Error = warp::Rejection>
+       + Clone
+       + Send
+       + Sync
+       + 'static {
+    use warp::Filter;
+
+    let ctx_arc = Arc::new(ctx);
+    let ctx_filter = warp::any().map(move || Arc::clone(&ctx_arc)).boxed();
+    let store_arc = Arc::new(store);
+    let store_filter = warp::any().map(move || Arc::clone(&store_arc)).boxed();
+    let auth_filter = ctx_filter
+        .clone()
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(webb_relayer_handlers::check_admin_token)
+        .untuple_one()
+        .boxed();
+
+    // watchers are identified by the key they keep their last synced block under,
+    // all the watchers of a substrate chain share the same key.
+    let evm_watcher = warp::path("evm")
+        .and(warp::path::param::<u32>())
+        .and(warp::path::param::<ethereum_types::Address>())
+        .map(|chain_id, address: ethereum_types::Address| {
+            webb_proposals::ResourceId::new(
+                webb_proposals::TargetSystem::new_contract_address(
+                    address.to_fixed_bytes(),
+                ),
+                webb_proposals::TypedChainId::Evm(chain_id),
+            )
+        });
+    let substrate_watcher = warp::path("substrate")
+        .and(warp::path::param::<u32>())
+        .map(webb_event_watcher_traits::substrate::history_store_key);
+    let watcher_filter = warp::path("watchers")
+        .and(evm_watcher.or(substrate_watcher).unify())
+        .boxed();
+
+    let status_filter = warp::path("status")
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(ctx_filter.clone())
+        .and_then(webb_relayer_handlers::handle_admin_status)
+        .boxed();
+    let pause_watcher_filter = warp::post()
+        .and(ctx_filter.clone())
+        .and(watcher_filter.clone())
+        .and(
+            warp::path("pause")
+                .map(|| true)
+                .or(warp::path("resume").map(|| false))
+                .unify(),
+        )
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_admin_pause_watcher)
+        .boxed();
+    let reset_cursor_filter = warp::post()
+        .and(ctx_filter.clone())
+        .and(store_filter.clone())
+        .and(watcher_filter)
+        .and(warp::path("cursor"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and_then(webb_relayer_handlers::handle_admin_reset_cursor)
+        .boxed();
+    let pause_queue_filter = warp::path("queues")
+        .and(warp::post())
+        .and(ctx_filter)
+        .and(warp::path::param())
+        .and(
+            warp::path("pause")
+                .map(|| true)
+                .or(warp::path("resume").map(|| false))
+                .unify(),
+        )
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_admin_pause_queue)
+        .boxed();
+    let queue_items_filter = warp::path("queues")
+        .and(warp::get())
+        .and(store_filter.clone())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_admin_queue_items)
+        .boxed();
+    let purge_queue_filter = warp::path("queues")
+        .and(warp::delete())
+        .and(store_filter.clone())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_admin_purge_queue)
+        .boxed();
+
+    // the dead letters: transactions and bridge commands that failed and were
+    // taken out of their queues, they can be listed, sent back to their queue
+    // or discarded.
+    let list_dead_letters_filter = warp::path("dead-letters")
+        .and(warp::get())
+        .and(store_filter.clone())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_dead_letters)
+        .boxed();
+    let retry_dead_letter_filter = warp::path("dead-letters")
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path("retry"))
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_retry_dead_letter)
+        .boxed();
+    let discard_dead_letter_filter = warp::path("dead-letters")
+        .and(warp::delete())
+        .and(store_filter)
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and_then(webb_relayer_handlers::handle_discard_dead_letter)
+        .boxed();
+
+    warp::path("admin")
+        .and(auth_filter)
+        .and(
+            status_filter
+                .or(pause_watcher_filter)
+                .or(reset_cursor_filter)
+                .or(pause_queue_filter)
+                .or(queue_items_filter)
+                .or(purge_queue_filter)
+                .or(list_dead_letters_filter)
+                .or(retry_dead_letter_filter)
+                .or(discard_dead_letter_filter),
+        )
+        .recover(webb_relayer_handlers::handle_admin_rejection)
 }
 
 /// Starts all background services for all chains configured in the config file.
@@ -232,7 +788,7 @@ pub fn build_web_services(
 /// # Arguments
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub async fn ignite(
     ctx: &RelayerContext,
     store: Arc<Store>,
@@ -287,6 +843,70 @@ pub async fn ignite(
                     )
                     .await?;
                 }
+                Contract::TokenWrapper(config) => {
+                    let wrapper = TokenWrapperContractWrapper::new(
+                        config.clone(),
+                        ctx.config.clone(), // the original config to access all networks.
+                        client.clone(),
+                    );
+                    start_governance_events_watcher::<
+                        TokenWrapperContractWatcher,
+                        _,
+                    >(
+                        ctx,
+                        config,
+                        chain_id,
+                        client.clone(),
+                        store.clone(),
+                        wrapper,
+                        |backend| match backend {
+                            ProposalSigningBackendSelector::Dkg(backend) => {
+                                Some(Box::new(
+                                    TokenWrapperProposalHandler::new(backend),
+                                ))
+                            }
+                            ProposalSigningBackendSelector::Mocked(backend) => {
+                                Some(Box::new(
+                                    TokenWrapperProposalHandler::new(backend),
+                                ))
+                            }
+                            ProposalSigningBackendSelector::None => None,
+                        },
+                    )
+                    .await?;
+                }
+                Contract::AnchorHandler(config) => {
+                    let wrapper = AnchorHandlerContractWrapper::new(
+                        config.clone(),
+                        ctx.config.clone(), // the original config to access all networks.
+                        client.clone(),
+                    );
+                    start_governance_events_watcher::<
+                        AnchorHandlerContractWatcher,
+                        _,
+                    >(
+                        ctx,
+                        config,
+                        chain_id,
+                        client.clone(),
+                        store.clone(),
+                        wrapper,
+                        |backend| match backend {
+                            ProposalSigningBackendSelector::Dkg(backend) => {
+                                Some(Box::new(
+                                    AnchorHandlerProposalHandler::new(backend),
+                                ))
+                            }
+                            ProposalSigningBackendSelector::Mocked(backend) => {
+                                Some(Box::new(
+                                    AnchorHandlerProposalHandler::new(backend),
+                                ))
+                            }
+                            ProposalSigningBackendSelector::None => None,
+                        },
+                    )
+                    .await?;
+                }
             }
         }
         // start the transaction queue after starting other tasks.
@@ -336,6 +956,9 @@ pub async fn ignite(
                         Pallet::SignatureBridge(_) => {
                             unreachable!()
                         }
+                        Pallet::InkSignatureBridge(_) => {
+                            unreachable!()
+                        }
                         Pallet::VAnchorBn254(_) => {
                             unreachable!()
                         }
@@ -365,6 +988,20 @@ pub async fn ignite(
                                 store.clone(),
                             )?;
                         }
+                        Pallet::SignatureBridge(config)
+                            if node_config.chain_type.is_parachain() =>
+                        {
+                            start_parachain_signature_bridge_events_watcher(
+                                ctx.clone(),
+                                config,
+                                client.clone(),
+                                node_name.to_owned(),
+                                chain_id,
+                                node_config.chain_type,
+                                store.clone(),
+                            )
+                            .await?;
+                        }
                         Pallet::SignatureBridge(config) => {
                             start_substrate_signature_bridge_events_watcher(
                                 ctx.clone(),
@@ -376,6 +1013,17 @@ pub async fn ignite(
                             )
                             .await?;
                         }
+                        Pallet::InkSignatureBridge(config) => {
+                            start_ink_signature_bridge_events_watcher(
+                                ctx.clone(),
+                                config,
+                                client.clone(),
+                                node_name.to_owned(),
+                                chain_id,
+                                store.clone(),
+                            )
+                            .await?;
+                        }
                         Pallet::DKGProposals(_) => {
                             unreachable!()
                         }
@@ -411,7 +1059,7 @@ pub async fn ignite(
 /// * `client` - WebbProtocol client
 /// * `node_name` - Name of the node
 /// * `chain_id` - An u32 representing the chain id of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_substrate_vanchor_event_watcher(
     ctx: &RelayerContext,
     config: &VAnchorBn254PalletConfig,
@@ -435,7 +1083,6 @@ pub fn start_substrate_vanchor_event_watcher(
     let my_ctx = ctx.clone();
     let my_config = config.clone();
     let mut shutdown_signal = ctx.shutdown_signal();
-    let metrics = ctx.metrics.clone();
     let task = async move {
         let watcher = SubstrateVAnchorLeavesWatcher::default();
         let substrate_leaves_watcher_task = watcher.run(
@@ -443,7 +1090,7 @@ pub fn start_substrate_vanchor_event_watcher(
             chain_id,
             client.clone().into(),
             store.clone(),
-            metrics.clone(),
+            &my_ctx,
         );
         let proposal_signing_backend = make_substrate_proposal_signing_backend(
             &my_ctx,
@@ -467,7 +1114,7 @@ pub fn start_substrate_vanchor_event_watcher(
                     chain_id,
                     client.clone().into(),
                     store.clone(),
-                    metrics.clone(),
+                    &my_ctx,
                 );
                 tokio::select! {
                     _ = substrate_vanchor_watcher_task => {
@@ -502,7 +1149,7 @@ pub fn start_substrate_vanchor_event_watcher(
                     chain_id,
                     client.into(),
                     store.clone(),
-                    metrics.clone(),
+                    &my_ctx,
                 );
                 tokio::select! {
                     _ = substrate_vanchor_watcher_task => {
@@ -560,7 +1207,7 @@ pub fn start_substrate_vanchor_event_watcher(
 /// * `client` - DKG client
 /// * `node_name` - Name of the node
 /// * `chain_id` - An u32 representing the chain id of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_dkg_proposal_handler(
     ctx: &RelayerContext,
     config: &DKGProposalHandlerPalletConfig,
@@ -583,15 +1230,25 @@ pub fn start_dkg_proposal_handler(
     );
     let node_name2 = node_name.clone();
     let mut shutdown_signal = ctx.shutdown_signal();
-    let metrics = ctx.metrics.clone();
+    let my_ctx = ctx.clone();
+    let proposal_handler = ProposalHandlerWatcher::new(&ctx.config);
+    let proposal_timeout = config.proposal_timeout;
+    let proposal_max_resubmissions = config.proposal_max_resubmissions;
+    let proposal_retention = config.proposal_retention;
     let task = async move {
-        let proposal_handler = ProposalHandlerWatcher::default();
+        let submitted_proposals_watcher = watch_submitted_proposals(
+            store.clone(),
+            chain_id,
+            proposal_timeout,
+            proposal_max_resubmissions,
+            proposal_retention,
+        );
         let watcher = proposal_handler.run(
             node_name,
             chain_id,
             client.into(),
             store,
-            metrics,
+            &my_ctx,
         );
         tokio::select! {
             _ = watcher => {
@@ -600,6 +1257,12 @@ pub fn start_dkg_proposal_handler(
                     node_name2,
                 );
             },
+            _ = submitted_proposals_watcher => {
+                tracing::warn!(
+                    "DKG submitted proposals watcher stopped for ({})",
+                    node_name2,
+                );
+            },
             _ = shutdown_signal.recv() => {
                 tracing::trace!(
                     "Stopping DKG Proposal Handler events watcher for ({})",
@@ -624,7 +1287,7 @@ pub fn start_dkg_proposal_handler(
 /// * `client` - DKG client
 /// * `node_name` - Name of the node
 /// * `chain_id` - An u32 representing the chain id of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_dkg_pallet_watcher(
     ctx: &RelayerContext,
     config: &DKGPalletConfig,
@@ -645,7 +1308,7 @@ pub fn start_dkg_pallet_watcher(
     let node_name2 = node_name.clone();
     let mut shutdown_signal = ctx.shutdown_signal();
     let webb_config = ctx.config.clone();
-    let metrics = ctx.metrics.clone();
+    let my_ctx = ctx.clone();
     let task = async move {
         let governor_watcher = DKGGovernorWatcher::new(webb_config);
         let watcher = governor_watcher.run(
@@ -653,7 +1316,7 @@ pub fn start_dkg_pallet_watcher(
             chain_id,
             client.into(),
             store,
-            metrics,
+            &my_ctx,
         );
         tokio::select! {
             _ = watcher => {
@@ -675,6 +1338,53 @@ pub fn start_dkg_pallet_watcher(
     Ok(())
 }
 
+/// Starts the checker of the leaves cache of an EVM VAnchor, which fetches the
+/// missing leaves again and reports the sync status of the cache.
+///
+/// Does nothing if the `interval` (in milliseconds) is zero.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `contract` - The VAnchor contract (wrapper) to check the leaves of
+/// * `chain_id` - An u32 representing the chain id of the chain
+/// * `store` - database store (the backend is selected in the configuration)
+/// * `interval` - How often the leaves are checked, in milliseconds
+fn start_leaves_checker<C>(
+    ctx: &RelayerContext,
+    contract: C,
+    chain_id: u32,
+    store: Arc<Store>,
+    interval: u64,
+) where
+    C: LeavesSource + 'static,
+{
+    if interval == 0 {
+        return;
+    }
+    let mut shutdown_signal = ctx.shutdown_signal();
+    let contract_address = contract.address();
+    let checker_task = run_leaves_checker(
+        contract,
+        chain_id,
+        store,
+        ctx.clone(),
+        Duration::from_millis(interval),
+    );
+    let task = async move {
+        tokio::select! {
+            _ = checker_task => {},
+            _ = shutdown_signal.recv() => {
+                tracing::trace!(
+                    "Stopping leaves checker for ({})",
+                    contract_address,
+                );
+            },
+        }
+    };
+    tokio::task::spawn(task);
+}
+
 /// Starts the event watcher for EVM VAnchor events.
 ///
 /// Returns Ok(()) if successful, or an error if not.
@@ -684,7 +1394,7 @@ pub fn start_dkg_pallet_watcher(
 /// * `ctx` - RelayContext reference that holds the configuration
 /// * `config` - VAnchor contract configuration
 /// * `client` - EVM Chain api client
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 async fn start_evm_vanchor_events_watcher(
     ctx: &RelayerContext,
     config: &VAnchorContractConfig,
@@ -704,6 +1414,15 @@ async fn start_evm_vanchor_events_watcher(
         ctx.config.clone(), // the original config to access all networks.
         client.clone(),
     );
+    if config.events_watcher.enable_data_query {
+        start_leaves_checker(
+            ctx,
+            wrapper.clone(),
+            chain_id,
+            store.clone(),
+            config.events_watcher.leaves_check_interval,
+        );
+    }
     let mut shutdown_signal = ctx.shutdown_signal();
     let contract_address = config.common.address;
     let my_ctx = ctx.clone();
@@ -823,6 +1542,102 @@ async fn start_evm_vanchor_events_watcher(
     Ok(())
 }
 
+/// Starts the event watcher of a contract whose events are turned into
+/// governance proposals for its linked contracts (i.e. the Token Wrapper and
+/// the Anchor Handler).
+///
+/// Returns Ok(()) if successful, or an error if not.
+///
+/// # Arguments
+///
+/// * `ctx` - RelayContext reference that holds the configuration
+/// * `config` - The governance contract configuration
+/// * `chain_id` - An u32 representing the chain id of the chain
+/// * `client` - EVM Chain api client
+/// * `store` - database store (the backend is selected in the configuration)
+/// * `wrapper` - The contract to watch
+/// * `make_handler` - Builds the proposal handler for the selected signing
+///   backend, if any
+pub async fn start_governance_events_watcher<W, F>(
+    ctx: &RelayerContext,
+    config: &GovernanceContractConfig,
+    chain_id: u32,
+    client: Arc<Client>,
+    store: Arc<Store>,
+    wrapper: W::Contract,
+    make_handler: F,
+) -> crate::Result<()>
+where
+    W: EventWatcher<Store = Store> + Default + Send + Sync + 'static,
+    W::Contract: Send + Sync + 'static,
+    F: FnOnce(ProposalSigningBackendSelector) -> Option<EventHandlerFor<W>>
+        + Send
+        + 'static,
+{
+    if !config.events_watcher.enabled {
+        tracing::warn!(
+            "{} is disabled for ({}).",
+            W::TAG,
+            config.common.address,
+        );
+        return Ok(());
+    }
+    let mut shutdown_signal = ctx.shutdown_signal();
+    let contract_address = config.common.address;
+    let my_ctx = ctx.clone();
+    let my_config = config.clone();
+    let task = async move {
+        tracing::debug!("{} for ({}) Started.", W::TAG, contract_address);
+        let contract_watcher = W::default();
+        let proposal_signing_backend = make_proposal_signing_backend(
+            &my_ctx,
+            store.clone(),
+            chain_id,
+            my_config.linked_contracts,
+            my_config.proposal_signing_backend,
+        )
+        .await?;
+        let proposal_handler = match make_handler(proposal_signing_backend) {
+            Some(handler) => handler,
+            None => {
+                tracing::warn!(
+                    "{} for ({}) has nothing to do without a proposal signing backend.",
+                    W::TAG,
+                    contract_address,
+                );
+                return crate::Result::Ok(());
+            }
+        };
+        let watcher_task = contract_watcher.run(
+            client,
+            store,
+            wrapper,
+            vec![proposal_handler],
+            &my_ctx,
+        );
+        tokio::select! {
+            _ = watcher_task => {
+                tracing::warn!(
+                    "{} task stopped for ({})",
+                    W::TAG,
+                    contract_address,
+                );
+            },
+            _ = shutdown_signal.recv() => {
+                tracing::trace!(
+                    "Stopping {} for ({})",
+                    W::TAG,
+                    contract_address,
+                );
+            },
+        }
+        crate::Result::Ok(())
+    };
+    // kick off the watcher.
+    tokio::task::spawn(task);
+    Ok(())
+}
+
 /// Starts the event watcher for EVM VAnchor events.
 ///
 /// Returns Ok(()) if successful, or an error if not.
@@ -832,7 +1647,7 @@ async fn start_evm_vanchor_events_watcher(
 /// * `ctx` - RelayContext reference that holds the configuration
 /// * `config` - VAnchor contract configuration
 /// * `client` - EVM Chain api client
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub async fn start_evm_open_vanchor_events_watcher(
     ctx: &RelayerContext,
     config: &VAnchorContractConfig,
@@ -852,6 +1667,15 @@ pub async fn start_evm_open_vanchor_events_watcher(
         ctx.config.clone(), // the original config to access all networks.
         client.clone(),
     );
+    if config.events_watcher.enable_data_query {
+        start_leaves_checker(
+            ctx,
+            wrapper.clone(),
+            chain_id,
+            store.clone(),
+            config.events_watcher.leaves_check_interval,
+        );
+    }
     let mut shutdown_signal = ctx.shutdown_signal();
     let contract_address = config.common.address;
     let my_ctx = ctx.clone();
@@ -1053,7 +1877,7 @@ pub async fn start_substrate_signature_bridge_events_watcher(
             chain_id,
             client.clone().into(),
             store.clone(),
-            ctx.metrics.clone(),
+            &ctx,
         );
         let cmd_handler_task = SubstrateBridgeWatcher::run(
             &substrate_bridge_watcher,
@@ -1087,6 +1911,136 @@ pub async fn start_substrate_signature_bridge_events_watcher(
     Ok(())
 }
 
+/// Starts the event watcher for Signature Bridge Pallet on a parachain.
+pub async fn start_parachain_signature_bridge_events_watcher(
+    ctx: RelayerContext,
+    config: &SignatureBridgePalletConfig,
+    client: WebbProtocolClient,
+    node_name: String,
+    chain_id: u32,
+    chain_type: SubstrateChainType,
+    store: Arc<Store>,
+) -> crate::Result<()> {
+    if !config.events_watcher.enabled {
+        tracing::warn!(
+            "Parachain Signature Bridge events watcher is disabled for ({}).",
+            node_name,
+        );
+        return Ok(());
+    }
+    let mut shutdown_signal = ctx.shutdown_signal();
+    let task = async move {
+        tracing::debug!(
+            "Parachain Signature Bridge watcher for ({}) Started.",
+            node_name
+        );
+        let parachain_bridge_watcher =
+            ParachainBridgeEventWatcher::new(chain_type);
+        let events_watcher_task = SubstrateEventWatcher::run(
+            &parachain_bridge_watcher,
+            node_name.to_owned(),
+            chain_id,
+            client.clone().into(),
+            store.clone(),
+            &ctx,
+        );
+        let cmd_handler_task = SubstrateBridgeWatcher::run(
+            &parachain_bridge_watcher,
+            chain_id,
+            client.into(),
+            store.clone(),
+        );
+        tokio::select! {
+            _ = events_watcher_task => {
+                tracing::warn!(
+                    "Parachain signature bridge events watcher task stopped for ({})",
+                    node_name
+                );
+            },
+            _ = cmd_handler_task => {
+                tracing::warn!(
+                    "Parachain signature bridge cmd handler task stopped for ({})",
+                    node_name
+                );
+            },
+            _ = shutdown_signal.recv() => {
+                tracing::trace!(
+                    "Stopping Parachain Signature Bridge watcher for ({})",
+                    node_name,
+                );
+            },
+        }
+    };
+    // kick off the watcher.
+    tokio::task::spawn(task);
+    Ok(())
+}
+
+/// Starts the event watcher for an Ink! Signature Bridge contract.
+pub async fn start_ink_signature_bridge_events_watcher(
+    ctx: RelayerContext,
+    config: &InkSignatureBridgeContractConfig,
+    client: WebbProtocolClient,
+    node_name: String,
+    chain_id: u32,
+    store: Arc<Store>,
+) -> crate::Result<()> {
+    if !config.events_watcher.enabled {
+        tracing::warn!(
+            "Ink Signature Bridge events watcher is disabled for ({}).",
+            config.address,
+        );
+        return Ok(());
+    }
+    let mut shutdown_signal = ctx.shutdown_signal();
+    let contract_address = config.address.clone();
+    let task = async move {
+        tracing::debug!(
+            "Ink Signature Bridge watcher for ({}) Started.",
+            contract_address
+        );
+        let ink_bridge_watcher =
+            InkBridgeEventWatcher::new(contract_address.clone());
+        let events_watcher_task = SubstrateEventWatcher::run(
+            &ink_bridge_watcher,
+            node_name.to_owned(),
+            chain_id,
+            client.clone().into(),
+            store.clone(),
+            &ctx,
+        );
+        let cmd_handler_task = SubstrateBridgeWatcher::run(
+            &ink_bridge_watcher,
+            chain_id,
+            client.into(),
+            store.clone(),
+        );
+        tokio::select! {
+            _ = events_watcher_task => {
+                tracing::warn!(
+                    "Ink signature bridge events watcher task stopped for ({})",
+                    contract_address
+                );
+            },
+            _ = cmd_handler_task => {
+                tracing::warn!(
+                    "Ink signature bridge cmd handler task stopped for ({})",
+                    contract_address
+                );
+            },
+            _ = shutdown_signal.recv() => {
+                tracing::trace!(
+                    "Stopping Ink Signature Bridge watcher for ({})",
+                    contract_address,
+                );
+            },
+        }
+    };
+    // kick off the watcher.
+    tokio::task::spawn(task);
+    Ok(())
+}
+
 /// Starts the transaction queue task
 ///
 /// Returns Ok(()) if successful, or an error if not.
@@ -1095,7 +2049,7 @@ pub async fn start_substrate_signature_bridge_events_watcher(
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
 /// * `chain_name` - Name of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_tx_queue(
     ctx: RelayerContext,
     chain_id: String,
@@ -1134,7 +2088,7 @@ pub fn start_tx_queue(
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
 /// * `chain_name` - Name of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_protocol_substrate_tx_queue(
     ctx: RelayerContext,
     chain_id: u32,
@@ -1177,7 +2131,7 @@ pub fn start_protocol_substrate_tx_queue(
 ///
 /// * `ctx` - RelayContext reference that holds the configuration
 /// * `chain_name` - Name of the chain
-/// * `store` -[Sled](https://sled.rs)-based database store
+/// * `store` - database store (the backend is selected in the configuration)
 pub fn start_dkg_substrate_tx_queue(
     ctx: RelayerContext,
     chain_id: u32,
@@ -1218,10 +2172,21 @@ pub enum ProposalSigningBackendSelector {
     /// None
     None,
     /// Mocked
-    Mocked(MockedProposalSigningBackend<SledStore>),
+    Mocked(MockedProposalSigningBackend<AnyStore>),
     /// Dkg
-    Dkg(DkgProposalSigningBackend),
+    Dkg(DkgProposalSigningBackend<AnyStore>),
 }
+
+/// Returns the chain id of the configured DKG node, which is where its transaction queue runs.
+fn dkg_node_chain_id(ctx: &RelayerContext, node: &str) -> crate::Result<u32> {
+    let node_config = ctx.config.substrate.get(node).ok_or_else(|| {
+        webb_relayer_utils::Error::NodeNotFound {
+            chain_id: node.to_string(),
+        }
+    })?;
+    Ok(node_config.chain_id)
+}
+
 /// utility to configure proposal signing backend
 pub async fn make_proposal_signing_backend(
     ctx: &RelayerContext,
@@ -1260,10 +2225,11 @@ pub async fn make_proposal_signing_backend(
             let typed_chain_id = webb_proposals::TypedChainId::Evm(chain_id);
             let dkg_client =
                 ctx.substrate_provider::<PolkadotConfig>(&c.node).await?;
-            let pair = ctx.substrate_wallet(&c.node).await?;
+            let dkg_chain_id = dkg_node_chain_id(ctx, &c.node)?;
             let backend = DkgProposalSigningBackend::new(
                 dkg_client,
-                PairSigner::new(pair),
+                store.clone(),
+                dkg_chain_id,
                 typed_chain_id,
             );
             Ok(ProposalSigningBackendSelector::Dkg(backend))
@@ -1336,10 +2302,11 @@ pub async fn make_substrate_proposal_signing_backend(
                 webb_proposals::TypedChainId::Substrate(chain_id);
             let dkg_client =
                 ctx.substrate_provider::<PolkadotConfig>(&c.node).await?;
-            let pair = ctx.substrate_wallet(&c.node).await?;
+            let dkg_chain_id = dkg_node_chain_id(ctx, &c.node)?;
             let backend = DkgProposalSigningBackend::new(
                 dkg_client,
-                PairSigner::new(pair),
+                store.clone(),
+                dkg_chain_id,
                 typed_chain_id,
             );
             Ok(ProposalSigningBackendSelector::Dkg(backend))
@@ -1375,3 +2342,258 @@ pub async fn make_substrate_proposal_signing_backend(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use warp::Filter;
+    use webb_relayer_config::WebbRelayerConfig;
+    use webb_relayer_store::InMemoryStore;
+
+    use super::*;
+
+    /// Creates a context with the given configuration, the metrics can only
+    /// be registered once so every test shares them.
+    fn context(config: WebbRelayerConfig) -> RelayerContext {
+        static CONTEXT: OnceLock<RelayerContext> = OnceLock::new();
+        let mut ctx = CONTEXT
+            .get_or_init(|| RelayerContext::new(Default::default()))
+            .clone();
+        ctx.rate_limiter =
+            webb_relayer_context::RateLimiter::new(config.rate_limit.clone());
+        ctx.config = config;
+        ctx
+    }
+
+    fn cors(origins: &[&str]) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allowed_methods: vec![String::from("GET")],
+            allowed_headers: vec![String::from("content-type")],
+        }
+    }
+
+    async fn preflight(config: &CorsConfig, origin: &str) -> u16 {
+        let route = warp::any()
+            .map(warp::reply)
+            .with(build_cors(config).unwrap());
+        warp::test::request()
+            .method("OPTIONS")
+            .header("origin", origin)
+            .header("access-control-request-method", "GET")
+            .reply(&route)
+            .await
+            .status()
+            .as_u16()
+    }
+
+    #[tokio::test]
+    async fn cors_should_only_allow_the_configured_origins() {
+        let config = cors(&["https://app.webb.tools/"]);
+        assert_eq!(preflight(&config, "https://app.webb.tools").await, 200);
+        assert_eq!(preflight(&config, "https://evil.example").await, 403);
+
+        let config = cors(&["*"]);
+        assert_eq!(preflight(&config, "https://evil.example").await, 200);
+    }
+
+    #[test]
+    fn invalid_cors_policies_should_be_refused() {
+        assert!(build_cors(&cors(&["https://app.webb.tools/path"])).is_err());
+        assert!(build_cors(&cors(&["app.webb.tools"])).is_err());
+
+        let mut config = cors(&[]);
+        config.allowed_methods = vec![String::from("GE T")];
+        assert!(build_cors(&config).is_err());
+
+        let mut config = cors(&[]);
+        config.allowed_headers = vec![String::from("content type")];
+        assert!(build_cors(&config).is_err());
+    }
+
+    /// Fills the parameters of a path of the OpenAPI document.
+    fn sample_path(path: &str) -> String {
+        path.replace("{chainId}", "5001")
+            .replace("{contract}", "0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df")
+            .replace("{treeId}", "5")
+            .replace("{palletId}", "44")
+            .replace("{queue}", "evm_tx_5001")
+            .replace("{id}", "0011")
+    }
+
+    /// Whether the request is described by an operation of the OpenAPI
+    /// document.
+    fn is_documented(
+        doc: &serde_json::Value,
+        method: &str,
+        path: &str,
+    ) -> bool {
+        let segments: Vec<_> = path.split('/').collect();
+        doc["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .any(|(template, ops)| {
+                let parts: Vec<_> = template.split('/').collect();
+                parts.len() == segments.len()
+                    && parts.iter().zip(&segments).all(|(part, segment)| {
+                        part.starts_with('{') || part == segment
+                    })
+                    && ops.get(method.to_lowercase()).is_some()
+            })
+    }
+
+    #[tokio::test]
+    async fn openapi_paths_should_be_routed() {
+        let mut config = WebbRelayerConfig::default();
+        config.admin.token = Some(String::from("secret"));
+        let ctx = context(config);
+        let store = AnyStore::from(InMemoryStore::default());
+        let routes = build_routes(&ctx, store).unwrap();
+        let request = |method: &str, path: &str| {
+            warp::test::request()
+                .method(method)
+                .path(path)
+                .header("authorization", "Bearer secret")
+                .json(&serde_json::json!({ "block": 1 }))
+                .reply(&routes)
+        };
+        let is_routed =
+            |response: warp::http::Response<warp::hyper::body::Bytes>| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(response.body()).unwrap_or_default();
+                // the handlers can reply with a 404 too, but not with the
+                // message of the requests no route matched.
+                let unmatched =
+                    response.status() == 404 && body["message"] == "Not found";
+                !unmatched && response.status() != 405
+            };
+
+        let doc = webb_relayer_handlers::openapi::openapi();
+        for (path, operations) in doc["paths"].as_object().unwrap() {
+            for method in operations.as_object().unwrap().keys() {
+                let response =
+                    request(&method.to_uppercase(), &sample_path(path)).await;
+                assert!(is_routed(response), "{method} {path} is not routed");
+            }
+        }
+
+        // every route served, which must all be in the document.
+        let served = [
+            ("GET", "/api/v1/ip"),
+            ("GET", "/api/v1/info"),
+            ("GET", "/api/v1/leaves/evm/{chainId}/{contract}"),
+            ("GET", "/api/v1/leaves/evm/{chainId}/{contract}/status"),
+            (
+                "GET",
+                "/api/v1/leaves/substrate/{chainId}/{treeId}/{palletId}",
+            ),
+            (
+                "GET",
+                "/api/v1/leaves/substrate/{chainId}/{treeId}/{palletId}/status",
+            ),
+            ("GET", "/api/v1/encrypted_outputs/evm/{chainId}/{contract}"),
+            ("GET", "/api/v1/metrics"),
+            ("GET", "/api/v1/openapi.json"),
+            ("GET", "/api/v1/proposals"),
+            ("POST", "/api/v1/send"),
+            ("GET", "/api/v1/jobs/{id}"),
+            ("GET", "/ws"),
+            ("GET", "/api/v1/admin/status"),
+            (
+                "POST",
+                "/api/v1/admin/watchers/evm/{chainId}/{contract}/pause",
+            ),
+            (
+                "POST",
+                "/api/v1/admin/watchers/evm/{chainId}/{contract}/resume",
+            ),
+            (
+                "POST",
+                "/api/v1/admin/watchers/evm/{chainId}/{contract}/cursor",
+            ),
+            ("POST", "/api/v1/admin/watchers/substrate/{chainId}/pause"),
+            ("POST", "/api/v1/admin/watchers/substrate/{chainId}/resume"),
+            ("POST", "/api/v1/admin/watchers/substrate/{chainId}/cursor"),
+            ("GET", "/api/v1/admin/queues/{queue}"),
+            ("DELETE", "/api/v1/admin/queues/{queue}"),
+            ("POST", "/api/v1/admin/queues/{queue}/pause"),
+            ("POST", "/api/v1/admin/queues/{queue}/resume"),
+            ("GET", "/api/v1/admin/dead-letters/{queue}"),
+            ("POST", "/api/v1/admin/dead-letters/{queue}/{id}/retry"),
+            ("DELETE", "/api/v1/admin/dead-letters/{queue}/{id}"),
+        ];
+        for (method, path) in served {
+            let path = sample_path(path);
+            assert!(is_routed(request(method, &path).await));
+            assert!(
+                is_documented(&doc, method, &path),
+                "{method} {path} is not documented"
+            );
+        }
+
+        let response = request("GET", "/api/v1/leaves/evm/5001").await;
+        assert!(!is_routed(response));
+        assert!(!is_documented(&doc, "GET", "/api/v1/leaves/evm/5001"));
+        assert!(!is_documented(&doc, "POST", "/api/v1/ip"));
+    }
+
+    #[tokio::test]
+    async fn dead_letters_should_only_be_served_to_admins() {
+        let status = |config: WebbRelayerConfig, path, token: Option<&str>| {
+            let routes = build_routes(
+                &context(config),
+                AnyStore::from(InMemoryStore::default()),
+            )
+            .unwrap();
+            let mut request = warp::test::request().path(path);
+            if let Some(token) = token {
+                request =
+                    request.header("authorization", format!("Bearer {token}"));
+            }
+            async move { request.reply(&routes).await.status() }
+        };
+        let path = "/api/v1/admin/dead-letters/evm_tx_5001";
+        let disabled = WebbRelayerConfig::default();
+        assert_eq!(status(disabled.clone(), path, None).await, 404);
+        assert_eq!(status(disabled, path, Some("")).await, 404);
+
+        let mut enabled = WebbRelayerConfig::default();
+        enabled.admin.token = Some(String::from("secret"));
+        assert_eq!(status(enabled.clone(), path, None).await, 401);
+        assert_eq!(status(enabled.clone(), path, Some("guess")).await, 401);
+        assert_eq!(status(enabled.clone(), path, Some("secret")).await, 200);
+        // they are not served outside of the admin API anymore.
+        let public = "/api/v1/dead-letters/evm_tx_5001";
+        assert_eq!(status(enabled, public, Some("secret")).await, 404);
+    }
+
+    #[tokio::test]
+    async fn leaves_requests_should_take_a_single_token() {
+        use webb_relayer_config::rate_limit::TokenBucketConfig;
+        let mut config = WebbRelayerConfig::default();
+        config.rate_limit.data = Some(TokenBucketConfig {
+            burst: 1,
+            per_second: 0.001,
+        });
+        let ctx = context(config);
+        let store = AnyStore::from(InMemoryStore::default());
+        let routes = build_routes(&ctx, store).unwrap();
+        let status = |path: &'static str| {
+            let routes = routes.clone();
+            async move {
+                warp::test::request()
+                    .path(path)
+                    .reply(&routes)
+                    .await
+                    .status()
+                    .as_u16()
+            }
+        };
+        let leaves = "/api/v1/leaves/evm/5001/\
+                      0xbfce6b877ebff977bb6e80b24fbbb7bc4ebca4df";
+        assert_ne!(status(leaves).await, 429);
+        assert_eq!(status(leaves).await, 429);
+    }
+}
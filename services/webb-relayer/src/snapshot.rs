@@ -0,0 +1,342 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export and import of leaves cache snapshots.
+//!
+//! Only the cached data lives in the store, so on export the last root of every anchor is
+//! read from the contract at the snapshot block. On import, the last root and the number of
+//! leaves are checked against the contract at that block, and the last leaves, with their
+//! encrypted outputs, against the events of the contract, before anything gets written to
+//! the store. The older leaves, encrypted outputs and the nullifiers are imported as they
+//! were exported: the root of the leaves is not computed again, since the contract can only
+//! hash them one node per call.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use webb::evm::contract::protocol_solidity::{
+    VAnchorContract, VAnchorContractEvents,
+};
+use webb::evm::ethers::types;
+use webb_proposals::{ResourceId, TargetSystem, TypedChainId};
+use webb_relayer_context::RelayerContext;
+use webb_relayer_store::snapshot::{self, AnchorSnapshot, Snapshot};
+
+use crate::service::Store;
+
+/// Parses a hex encoded (optionally `0x` prefixed) resource id.
+pub fn parse_resource_id(value: &str) -> crate::Result<ResourceId> {
+    let value = value.trim_start_matches("0x");
+    let bytes = hex::decode(value)
+        .map_err(|_| crate::Error::Generic("invalid hex resource id"))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| crate::Error::Generic("resource id must be 32 bytes"))?;
+    Ok(ResourceId::from(bytes))
+}
+
+/// Creates a VAnchor contract instance for the given (EVM) resource id.
+async fn vanchor_contract(
+    ctx: &RelayerContext,
+    resource_id: ResourceId,
+) -> crate::Result<VAnchorContract<crate::service::Client>> {
+    let chain_id = match resource_id.typed_chain_id() {
+        TypedChainId::Evm(chain_id) => chain_id,
+        _ => {
+            return Err(crate::Error::Generic(
+                "snapshots are only supported for EVM anchors",
+            ))
+        }
+    };
+    let address = match resource_id.target_system() {
+        TargetSystem::ContractAddress(address) => types::Address::from(address),
+        _ => {
+            return Err(crate::Error::Generic(
+                "snapshots are only supported for EVM anchors",
+            ))
+        }
+    };
+    let client = ctx.evm_provider(&chain_id.to_string()).await?;
+    Ok(VAnchorContract::new(address, Arc::new(client)))
+}
+
+/// Exports the leaves, encrypted outputs and last synced block of the given anchors
+/// into a snapshot file.
+pub async fn export_snapshot(
+    ctx: &RelayerContext,
+    store: &Store,
+    resource_ids: &[ResourceId],
+    path: &Path,
+) -> crate::Result<()> {
+    let mut anchors = Vec::with_capacity(resource_ids.len());
+    for resource_id in resource_ids {
+        let mut anchor = snapshot::export_anchor(store, *resource_id)?;
+        if anchor.last_block_number == 0 {
+            tracing::warn!(
+                resource_id = %hex::encode(resource_id.into_bytes()),
+                "Anchor was never synced, skipping it",
+            );
+            continue;
+        }
+        let contract = vanchor_contract(ctx, *resource_id).await?;
+        let root = contract
+            .get_last_root()
+            .block(anchor.last_block_number)
+            .call()
+            .await?;
+        anchor.last_root = Some(root);
+        tracing::info!(
+            resource_id = %hex::encode(resource_id.into_bytes()),
+            leaves = anchor.leaves.len(),
+            block_number = anchor.last_block_number,
+            "Exported anchor",
+        );
+        anchors.push(anchor);
+    }
+    let snapshot = Snapshot::new(anchors)?;
+    let file = std::fs::File::create(path)?;
+    snapshot.write_to(std::io::BufWriter::new(file))?;
+    Ok(())
+}
+
+/// The number of last leaves of every anchor checked against the events of its
+/// contract on import.
+const CHECKED_LEAVES: usize = 16;
+
+/// The number of blocks queried at once for the events of the last leaves.
+const LOGS_BLOCK_STEP: u64 = 1_000;
+
+/// A leaf as logged by the `NewCommitment` event of the contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LoggedLeaf {
+    index: u32,
+    commitment: [u8; 32],
+    encrypted_output: Vec<u8>,
+}
+
+/// Reads the leaves inserted in the contract up to `block` from its
+/// `NewCommitment` events, from the one at `first` on. The events are queried
+/// back from `block` and only their logs are read, so any node can serve it.
+async fn logged_leaves(
+    contract: &VAnchorContract<crate::service::Client>,
+    first: u32,
+    block: u64,
+) -> crate::Result<Vec<LoggedLeaf>> {
+    let mut leaves = Vec::new();
+    let mut to = block;
+    loop {
+        let from = to.saturating_sub(LOGS_BLOCK_STEP - 1);
+        let events = contract
+            .event_with_filter::<VAnchorContractEvents>(Default::default())
+            .from_block(from)
+            .to_block(to)
+            .query()
+            .await?;
+        leaves.extend(events.into_iter().filter_map(|event| match event {
+            VAnchorContractEvents::NewCommitmentFilter(e) => Some(LoggedLeaf {
+                index: e.index.as_u32(),
+                commitment: e.commitment,
+                encrypted_output: e.encrypted_output.to_vec(),
+            }),
+            _ => None,
+        }));
+        // the leaves are inserted in order, so the older blocks only have
+        // lower indices.
+        if leaves.iter().any(|leaf| leaf.index <= first) || from == 0 {
+            break;
+        }
+        to = from - 1;
+    }
+    leaves.retain(|leaf| leaf.index >= first);
+    Ok(leaves)
+}
+
+/// Checks the last [`CHECKED_LEAVES`] leaves of the snapshot, and the encrypted
+/// outputs it has for them, against the leaves logged by the contract.
+fn check_last_leaves(
+    anchor: &AnchorSnapshot,
+    leaves: &[[u8; 32]],
+    logged: &[LoggedLeaf],
+) -> crate::Result<()> {
+    let logged: HashMap<_, _> =
+        logged.iter().map(|leaf| (leaf.index, leaf)).collect();
+    let encrypted_outputs: HashMap<_, _> = anchor
+        .encrypted_outputs
+        .iter()
+        .map(|(index, output)| (*index, output))
+        .collect();
+    let first = leaves.len().saturating_sub(CHECKED_LEAVES);
+    for (index, leaf) in leaves.iter().enumerate().skip(first) {
+        let index = index as u32;
+        let logged = logged.get(&index).ok_or(crate::Error::Generic(
+            "snapshot leaf is missing from the contract events",
+        ))?;
+        if logged.commitment != *leaf {
+            return Err(crate::Error::Generic(
+                "snapshot leaves do not match the contract events",
+            ));
+        }
+        match encrypted_outputs.get(&index) {
+            Some(output) if **output != logged.encrypted_output => {
+                return Err(crate::Error::Generic(
+                    "snapshot encrypted outputs do not match the contract events",
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Returns the leaves of the snapshot as 32 bytes values, checking that their
+/// indices go from 0 without gaps.
+fn contiguous_leaves(anchor: &AnchorSnapshot) -> crate::Result<Vec<[u8; 32]>> {
+    anchor
+        .leaves
+        .iter()
+        .enumerate()
+        .map(|(i, (index, leaf))| {
+            if *index as usize != i {
+                return Err(crate::Error::Generic(
+                    "snapshot leaves indices are not contiguous",
+                ));
+            }
+            leaf.as_slice().try_into().map_err(|_| {
+                crate::Error::Generic("snapshot leaf is not 32 bytes")
+            })
+        })
+        .collect()
+}
+
+/// Checks the snapshot of an anchor against the contract state at the snapshot block.
+async fn verify_anchor(
+    ctx: &RelayerContext,
+    anchor: &AnchorSnapshot,
+) -> crate::Result<()> {
+    let expected_root = anchor
+        .last_root
+        .ok_or(crate::Error::Generic("snapshot anchor has no last root"))?;
+    let contract = vanchor_contract(ctx, anchor.resource_id()).await?;
+    let root = contract
+        .get_last_root()
+        .block(anchor.last_block_number)
+        .call()
+        .await?;
+    if root != expected_root {
+        return Err(crate::Error::Generic(
+            "snapshot last root does not match the contract",
+        ));
+    }
+    let next_index = contract
+        .next_index()
+        .block(anchor.last_block_number)
+        .call()
+        .await?;
+    if next_index as usize != anchor.leaves.len() {
+        return Err(crate::Error::Generic(
+            "snapshot leaves count does not match the contract",
+        ));
+    }
+    let leaves = contiguous_leaves(anchor)?;
+    // an anchor without deposits has nothing more to check.
+    if leaves.is_empty() {
+        return Ok(());
+    }
+    let first = leaves.len().saturating_sub(CHECKED_LEAVES) as u32;
+    let logged =
+        logged_leaves(&contract, first, anchor.last_block_number).await?;
+    check_last_leaves(anchor, &leaves, &logged)
+}
+
+/// Imports a snapshot file into an empty store, every anchor is verified against
+/// its contract first, then the events watchers resume from the snapshot block.
+pub async fn import_snapshot(
+    ctx: &RelayerContext,
+    store: &Store,
+    path: &Path,
+) -> crate::Result<()> {
+    let file = std::fs::File::open(path)?;
+    let snapshot = Snapshot::read_from(std::io::BufReader::new(file))?;
+    for anchor in &snapshot.anchors {
+        verify_anchor(ctx, anchor).await?;
+    }
+    for anchor in &snapshot.anchors {
+        snapshot::import_anchor(store, anchor)?;
+        tracing::info!(
+            resource_id = %hex::encode(anchor.resource_id),
+            leaves = anchor.leaves.len(),
+            block_number = anchor.last_block_number,
+            "Imported anchor",
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor(leaves: u32) -> (AnchorSnapshot, Vec<[u8; 32]>, Vec<LoggedLeaf>) {
+        let leaf = |i: u32| [i as u8 + 1; 32];
+        let anchor = AnchorSnapshot {
+            resource_id: [0; 32],
+            last_block_number: 10,
+            last_deposit_block_number: 10,
+            last_root: Some([0; 32]),
+            leaves: (0..leaves).map(|i| (i, leaf(i).to_vec())).collect(),
+            encrypted_outputs: (0..leaves)
+                .map(|i| (i, vec![i as u8]))
+                .collect(),
+            nullifiers: vec![],
+        };
+        let logged = (0..leaves)
+            .map(|i| LoggedLeaf {
+                index: i,
+                commitment: leaf(i),
+                encrypted_output: vec![i as u8],
+            })
+            .collect();
+        let leaves = contiguous_leaves(&anchor).unwrap();
+        (anchor, leaves, logged)
+    }
+
+    #[test]
+    fn last_leaves_should_match_the_contract_events() {
+        let (anchor, leaves, logged) = anchor(20);
+        assert!(check_last_leaves(&anchor, &leaves, &logged).is_ok());
+        // only the last leaves are checked.
+        assert!(check_last_leaves(&anchor, &leaves, &logged[4..]).is_ok());
+        assert!(check_last_leaves(&anchor, &leaves, &logged[5..]).is_err());
+
+        let mut tampered = leaves.clone();
+        tampered[19] = [0; 32];
+        assert!(check_last_leaves(&anchor, &tampered, &logged).is_err());
+        // the older leaves are imported unchecked.
+        let mut tampered = leaves;
+        tampered[3] = [0; 32];
+        assert!(check_last_leaves(&anchor, &tampered, &logged).is_ok());
+    }
+
+    #[test]
+    fn last_encrypted_outputs_should_match_the_contract_events() {
+        let (mut anchor, leaves, logged) = anchor(20);
+        anchor.encrypted_outputs[19].1 = vec![0xff];
+        assert!(check_last_leaves(&anchor, &leaves, &logged).is_err());
+
+        // encrypted outputs missing from the snapshot are not refused.
+        anchor.encrypted_outputs.truncate(18);
+        assert!(check_last_leaves(&anchor, &leaves, &logged).is_ok());
+    }
+}
@@ -0,0 +1,553 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # TLS Module 🔒
+//!
+//! Serves the relayer API over TLS with a single listener per address. The
+//! certificate is read through a [`CertResolver`], so a renewed certificate
+//! is swapped in place for the next handshakes, while the open connections
+//! keep the one they were made with.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use futures::ready;
+use hyper::server::accept::Accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use hyper::service::{make_service_fn, service_fn, Service};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::{self, CertifiedKey};
+use tokio_rustls::rustls::{
+    Certificate, PrivateKey, ServerConfig, SignatureScheme,
+};
+use tokio_rustls::TlsAcceptor;
+use webb_relayer_config::server::TlsConfig;
+
+/// The address of the client of a TLS connection, added to the extensions
+/// of its requests since warp only knows the address of its own listeners.
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// Resolves every handshake to the current certificate, which is replaced
+/// when the certificate files change.
+pub struct CertResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertResolver {
+    /// Creates a resolver serving the given certificate.
+    pub fn new(certified_key: CertifiedKey) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(certified_key)),
+        }
+    }
+
+    /// Replaces the certificate served to the next handshakes.
+    pub fn set(&self, certified_key: CertifiedKey) {
+        let mut current =
+            self.current.write().unwrap_or_else(|e| e.into_inner());
+        *current = Arc::new(certified_key);
+    }
+
+    /// The certificate served to the next handshakes.
+    pub fn current(&self) -> Arc<CertifiedKey> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+/// Reads the certificate chain and private key of the TLS configuration.
+///
+/// The certificate must be a valid X.509 certificate and the key must be a
+/// supported PKCS#8, PKCS#1 (RSA) or SEC1 (EC) key matching it, so a
+/// broken pair of files is refused before it is served.
+pub fn load_certified_key(tls: &TlsConfig) -> crate::Result<CertifiedKey> {
+    let certs = {
+        let mut reader =
+            io::BufReader::new(std::fs::File::open(&tls.cert_path)?);
+        rustls_pemfile::certs(&mut reader)?
+    };
+    let end_entity = certs.first().ok_or(crate::Error::Generic(
+        "The TLS certificate file contains no certificate",
+    ))?;
+    let end_entity = webpki::EndEntityCert::try_from(end_entity.as_slice())
+        .map_err(|_| {
+            crate::Error::Generic(
+                "The TLS certificate is not a valid X.509 certificate",
+            )
+        })?;
+
+    let mut reader = io::BufReader::new(std::fs::File::open(&tls.key_path)?);
+    let key = loop {
+        match rustls_pemfile::read_one(&mut reader)? {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => break key,
+            Some(_) => continue,
+            None => {
+                return Err(crate::Error::Generic(
+                    "The TLS key file contains no private key",
+                ))
+            }
+        }
+    };
+    let key = sign::any_supported_type(&PrivateKey(key))
+        .map_err(|_| crate::Error::Generic("The TLS key is not supported"))?;
+    check_key_matches(&end_entity, key.as_ref())?;
+    Ok(CertifiedKey::new(
+        certs.into_iter().map(Certificate).collect(),
+        key,
+    ))
+}
+
+/// Signs a message with the key and verifies it with the certificate.
+fn check_key_matches(
+    cert: &webpki::EndEntityCert,
+    key: &dyn sign::SigningKey,
+) -> crate::Result<()> {
+    let schemes: [(SignatureScheme, &webpki::SignatureAlgorithm); 4] = [
+        (
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            &webpki::ECDSA_P256_SHA256,
+        ),
+        (
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            &webpki::ECDSA_P384_SHA384,
+        ),
+        (SignatureScheme::ED25519, &webpki::ED25519),
+        (
+            SignatureScheme::RSA_PKCS1_SHA256,
+            &webpki::RSA_PKCS1_2048_8192_SHA256,
+        ),
+    ];
+    let mismatch =
+        || crate::Error::Generic("The TLS key does not match the certificate");
+    let offered = schemes.map(|(scheme, _)| scheme);
+    let signer = key.choose_scheme(&offered).ok_or_else(mismatch)?;
+    let (_, algorithm) = schemes
+        .iter()
+        .find(|(scheme, _)| *scheme == signer.scheme())
+        .ok_or_else(mismatch)?;
+    let message = b"webb relayer tls key check";
+    let signature = signer.sign(message).map_err(|_| mismatch())?;
+    cert.verify_signature(algorithm, message, &signature)
+        .map_err(|_| mismatch())
+}
+
+/// Returns the last modification times of the TLS certificate and key files.
+fn files_modified(tls: &TlsConfig) -> [Option<SystemTime>; 2] {
+    [&tls.cert_path, &tls.key_path]
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+}
+
+/// Watches the certificate files, and swaps the certificate of the resolver
+/// once they changed.
+///
+/// The new files are loaded before they are served, a broken pair is logged
+/// and the current certificate is kept.
+pub async fn reload_certificate(tls: TlsConfig, resolver: Arc<CertResolver>) {
+    let mut reload_interval =
+        tokio::time::interval(Duration::from_secs(tls.reload_interval.max(1)));
+    let mut reloader = CertReloader::new(tls, resolver);
+    loop {
+        reload_interval.tick().await;
+        reloader.check();
+    }
+}
+
+/// Reloads the certificate of a resolver when its files change.
+struct CertReloader {
+    tls: TlsConfig,
+    resolver: Arc<CertResolver>,
+    /// The modification times of the files of the served certificate.
+    modified: [Option<SystemTime>; 2],
+    /// The modification times of the files seen on the last check, if they
+    /// changed since the certificate was loaded.
+    changed: Option<[Option<SystemTime>; 2]>,
+}
+
+impl CertReloader {
+    fn new(tls: TlsConfig, resolver: Arc<CertResolver>) -> Self {
+        Self {
+            modified: files_modified(&tls),
+            changed: None,
+            tls,
+            resolver,
+        }
+    }
+
+    /// Reloads the certificate if its files changed, and were not modified
+    /// since the last check (the certificate and its key are usually written
+    /// one after the other).
+    fn check(&mut self) {
+        let latest = files_modified(&self.tls);
+        if latest == self.modified {
+            self.changed = None;
+            return;
+        }
+        if self.changed != Some(latest) {
+            self.changed = Some(latest);
+            return;
+        }
+        self.modified = latest;
+        match load_certified_key(&self.tls) {
+            Ok(certified_key) => {
+                self.resolver.set(certified_key);
+                tracing::info!("TLS certificate changed, serving the new one");
+            }
+            Err(e) => {
+                tracing::warn!("Not reloading the TLS certificate: {}", e);
+            }
+        }
+    }
+}
+
+/// Serves the given routes over TLS on the given address, until the
+/// shutdown signal completes.
+///
+/// Returns the address the server listens on, and the server itself.
+pub fn serve<F, R>(
+    filter: F,
+    addr: SocketAddr,
+    resolver: Arc<CertResolver>,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> crate::Result<(SocketAddr, impl Future<Output = ()> + Send + 'static)>
+where
+    F: warp::Filter<Extract = (R,), Error = warp::Rejection>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    R: warp::Reply,
+{
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let incoming = AddrIncoming::bind(&addr).map_err(|e| {
+        io::Error::new(io::ErrorKind::AddrNotAvailable, e.to_string())
+    })?;
+    let addr = incoming.local_addr();
+    let incoming = TlsIncoming {
+        acceptor: TlsAcceptor::from(Arc::new(config)),
+        incoming,
+    };
+    let make_service = make_service_fn(move |stream: &TlsStream| {
+        let remote_addr = RemoteAddr(stream.remote_addr);
+        let mut service = warp::service(filter.clone());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut request| {
+                request.extensions_mut().insert(remote_addr);
+                service.call(request)
+            }))
+        }
+    });
+    let server = hyper::Server::builder(incoming)
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown_signal);
+    let server = async move {
+        if let Err(e) = server.await {
+            tracing::error!("TLS server on {} failed: {}", addr, e);
+        }
+    };
+    Ok((addr, server))
+}
+
+/// Accepts the TCP connections of a listener as TLS connections.
+struct TlsIncoming {
+    acceptor: TlsAcceptor,
+    incoming: AddrIncoming,
+}
+
+impl Accept for TlsIncoming {
+    type Conn = TlsStream;
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.get_mut();
+        match ready!(Pin::new(&mut this.incoming).poll_accept(cx)) {
+            Some(Ok(stream)) => {
+                let remote_addr = stream.remote_addr();
+                Poll::Ready(Some(Ok(TlsStream {
+                    state: TlsState::Handshaking(this.acceptor.accept(stream)),
+                    remote_addr,
+                })))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+enum TlsState {
+    Handshaking(tokio_rustls::Accept<AddrStream>),
+    Streaming(tokio_rustls::server::TlsStream<AddrStream>),
+}
+
+/// A TLS connection, whose handshake is done on its first read or write so
+/// a slow client does not hold the listener.
+struct TlsStream {
+    state: TlsState,
+    remote_addr: SocketAddr,
+}
+
+impl TlsStream {
+    /// Polls the handshake until it is done, then the connection itself.
+    fn poll_stream<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        f: impl FnOnce(
+            Pin<&mut tokio_rustls::server::TlsStream<AddrStream>>,
+            &mut Context<'_>,
+        ) -> Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        if let TlsState::Handshaking(accept) = &mut self.state {
+            let stream = ready!(Pin::new(accept).poll(cx))?;
+            self.state = TlsState::Streaming(stream);
+        }
+        match &mut self.state {
+            TlsState::Streaming(stream) => f(Pin::new(stream), cx),
+            TlsState::Handshaking(_) => unreachable!("the handshake is done"),
+        }
+    }
+}
+
+impl AsyncRead for TlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.get_mut()
+            .poll_stream(cx, |stream, cx| stream.poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut()
+            .poll_stream(cx, |stream, cx| stream.poll_write(cx, buf))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().state {
+            TlsState::Handshaking(_) => Poll::Ready(Ok(())),
+            TlsState::Streaming(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().state {
+            TlsState::Handshaking(_) => Poll::Ready(Ok(())),
+            TlsState::Streaming(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/tls")
+            .join(name)
+    }
+
+    fn tls_config(dir: &Path, name: &str) -> TlsConfig {
+        let tls = TlsConfig {
+            cert_path: dir.join("relayer.crt"),
+            key_path: dir.join("relayer.key"),
+            reload_interval: 1,
+        };
+        write_pair(&tls, name);
+        tls
+    }
+
+    fn write_pair(tls: &TlsConfig, name: &str) {
+        std::fs::copy(fixture(&format!("{name}.crt")), &tls.cert_path).unwrap();
+        std::fs::copy(fixture(&format!("{name}.key")), &tls.key_path).unwrap();
+    }
+
+    fn served_cert(resolver: &CertResolver) -> Vec<u8> {
+        resolver.current().cert[0].0.clone()
+    }
+
+    #[test]
+    fn certificates_should_be_loaded_with_their_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = tls_config(dir.path(), "relayer");
+        let certified_key = load_certified_key(&tls).unwrap();
+        assert_eq!(certified_key.cert.len(), 1);
+    }
+
+    #[test]
+    fn broken_certificates_should_be_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = tls_config(dir.path(), "relayer");
+
+        // a key of another certificate.
+        std::fs::copy(fixture("renewed.key"), &tls.key_path).unwrap();
+        assert!(load_certified_key(&tls).is_err());
+
+        // a PEM file that is not a certificate.
+        write_pair(&tls, "relayer");
+        std::fs::write(
+            &tls.cert_path,
+            "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        assert!(load_certified_key(&tls).is_err());
+
+        // a certificate without its key.
+        write_pair(&tls, "relayer");
+        std::fs::copy(fixture("relayer.crt"), &tls.key_path).unwrap();
+        assert!(load_certified_key(&tls).is_err());
+    }
+
+    #[test]
+    fn certificates_should_be_reloaded_once_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = tls_config(dir.path(), "relayer");
+        let resolver =
+            Arc::new(CertResolver::new(load_certified_key(&tls).unwrap()));
+        let first = served_cert(&resolver);
+        let mut reloader = CertReloader::new(tls.clone(), resolver.clone());
+        reloader.check();
+        assert_eq!(served_cert(&resolver), first);
+
+        // the files are only read once they stopped changing, and a broken
+        // pair is never served.
+        std::fs::copy(fixture("renewed.crt"), &tls.cert_path).unwrap();
+        reloader.check();
+        reloader.check();
+        assert_eq!(served_cert(&resolver), first);
+
+        // the renewed pair is served once its key is written too.
+        std::fs::copy(fixture("renewed.key"), &tls.key_path).unwrap();
+        reloader.check();
+        assert_eq!(served_cert(&resolver), first);
+        reloader.check();
+        let renewed = served_cert(&resolver);
+        assert_ne!(renewed, first);
+        assert_eq!(renewed, load_certified_key(&tls).unwrap().cert[0].0);
+    }
+
+    /// Sends a request to the server, returning the certificate it was
+    /// served with and the response.
+    async fn get_over_tls(addr: SocketAddr, path: &str) -> (Vec<u8>, String) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerName};
+
+        let mut roots = RootCertStore::empty();
+        for name in ["relayer.crt", "renewed.crt"] {
+            let file = std::fs::File::open(fixture(name)).unwrap();
+            let certs =
+                rustls_pemfile::certs(&mut io::BufReader::new(file)).unwrap();
+            roots.add(&Certificate(certs[0].clone())).unwrap();
+        }
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut stream = tokio_rustls::TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        let request = format!(
+            "GET {path} HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let served =
+            stream.get_ref().1.peer_certificates().unwrap()[0].0.clone();
+        (served, response)
+    }
+
+    #[tokio::test]
+    async fn new_connections_should_use_the_reloaded_certificate() {
+        use warp::Filter;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tls = tls_config(dir.path(), "relayer");
+        let resolver =
+            Arc::new(CertResolver::new(load_certified_key(&tls).unwrap()));
+        let first = served_cert(&resolver);
+        let route = warp::path("ip")
+            .and(warp::ext::optional::<RemoteAddr>())
+            .map(|addr: Option<RemoteAddr>| match addr {
+                Some(RemoteAddr(addr)) => addr.ip().to_string(),
+                None => String::from("unknown"),
+            });
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let (addr, server) = serve(
+            route,
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            resolver.clone(),
+            async move {
+                let _ = stopped.await;
+            },
+        )
+        .unwrap();
+        let server = tokio::spawn(server);
+
+        let (served, response) = get_over_tls(addr, "/ip").await;
+        assert_eq!(served, first);
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("127.0.0.1"));
+
+        // the same listener serves the renewed certificate.
+        write_pair(&tls, "renewed");
+        resolver.set(load_certified_key(&tls).unwrap());
+        let (served, _) = get_over_tls(addr, "/ip").await;
+        assert_ne!(served, first);
+        assert_eq!(served, served_cert(&resolver));
+
+        stop.send(()).unwrap();
+        server.await.unwrap();
+    }
+}